@@ -0,0 +1,39 @@
+//! Generates `ENC_TABLE` and `DEC_TABLE` from the ranges in `base2048.ranges`, and the table of
+//! `Alphabet::ltr` from `base2048-ltr.ranges`.
+mod ranges;
+
+use std::{env, fs, path::Path};
+
+const SPEC: &str = "base2048.ranges";
+const LTR_SPEC: &str = "base2048-ltr.ranges";
+const TAIL: [char; 8] = ['0', '1', '2', '3', '4', '5', '6', '7'];
+
+fn main() {
+    println!("cargo:rerun-if-changed={}", SPEC);
+    println!("cargo:rerun-if-changed={}", LTR_SPEC);
+    println!("cargo:rerun-if-changed=build");
+
+    let spec = fs::read_to_string(SPEC).unwrap_or_else(|e| panic!("reading {}: {}", SPEC, e));
+    let table = ranges::parse(&spec, &TAIL).unwrap_or_else(|e| panic!("{}: {}", SPEC, e));
+    let ltr_spec =
+        fs::read_to_string(LTR_SPEC).unwrap_or_else(|e| panic!("reading {}: {}", LTR_SPEC, e));
+    let ltr_table =
+        ranges::parse(&ltr_spec, &TAIL).unwrap_or_else(|e| panic!("{}: {}", LTR_SPEC, e));
+
+    let out = env::var("OUT_DIR").expect("cargo sets OUT_DIR");
+    fs::write(
+        Path::new(&out).join("enc_table.src"),
+        ranges::enc_src(&table),
+    )
+    .unwrap();
+    fs::write(
+        Path::new(&out).join("dec_table.src"),
+        ranges::dec_src(&table),
+    )
+    .unwrap();
+    fs::write(
+        Path::new(&out).join("ltr_table.src"),
+        ranges::enc_src(&ltr_table),
+    )
+    .unwrap();
+}
@@ -0,0 +1,70 @@
+//! Reading a table from a list of code point ranges, and writing it out as Rust.
+//!
+//! Shared by the build script and `cargo xtask gen-tables`.
+
+/// The characters `spec` lists, checked to make a valid table with the tail characters `tail`.
+///
+/// Each line of `spec` is a code point or an inclusive range `first..last`, in hex, and anything
+/// after `#` is a comment.
+pub fn parse(spec: &str, tail: &[char]) -> Result<Vec<char>, String> {
+    let mut table = Vec::new();
+    for (n, line) in spec.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        if line.is_empty() {
+            continue;
+        }
+        let code_point = |s: &str| {
+            u32::from_str_radix(s.trim(), 16)
+                .ok()
+                .and_then(char::from_u32)
+                .ok_or_else(|| format!("line {}: {:?} is not a code point", n + 1, s))
+        };
+        let (first, last) = match line.find("..") {
+            Some(i) => (code_point(&line[..i])?, code_point(&line[i + 2..])?),
+            None => (code_point(line)?, code_point(line)?),
+        };
+        if last < first {
+            return Err(format!("line {}: the range is backwards", n + 1));
+        }
+        table.extend(first..=last);
+    }
+
+    if table.len() != 2048 {
+        return Err(format!("{} characters instead of 2048", table.len()));
+    }
+    // in order, which also rules out duplicates and lets the decoding table be built by position
+    if let Some(pair) = table.windows(2).find(|pair| pair[0] >= pair[1]) {
+        return Err(format!("{:?} comes after {:?}", pair[1], pair[0]));
+    }
+    if let Some(c) = table.iter().find(|c| tail.contains(c)) {
+        return Err(format!("{:?} is a tail character", c));
+    }
+    Ok(table)
+}
+
+/// The position of each code point up to the highest in `table`, or `0xFFFF` for those not in it.
+pub fn dec_table(table: &[char]) -> Vec<u16> {
+    let mut dec = vec![0xFFFF; table.iter().max().map_or(0, |c| *c as usize + 1)];
+    for (index, c) in table.iter().enumerate() {
+        dec[*c as usize] = index as u16;
+    }
+    dec
+}
+
+/// `table` as a Rust array expression, one character to a line.
+pub fn enc_src(table: &[char]) -> String {
+    let mut src = String::from("[");
+    for c in table {
+        src += &format!("{:?},\n", c);
+    }
+    src + "]"
+}
+
+/// [`dec_table`] of `table` as a Rust array expression, one entry to a line.
+pub fn dec_src(table: &[char]) -> String {
+    let mut src = String::from("[");
+    for index in dec_table(table) {
+        src += &format!("{:#X},\n", index);
+    }
+    src + "]"
+}
@@ -0,0 +1,11 @@
+#!/usr/bin/env bash
+# Cargo runner: boots the test binary in QEMU and decodes the defmt frames it writes to the
+# semihosting console. QEMU's exit status is the test result.
+set -euo pipefail
+elf="$1"
+qemu-system-arm \
+    -cpu cortex-m4 \
+    -machine mps2-an386 \
+    -nographic \
+    -semihosting-config enable=on,target=native \
+    -kernel "$elf" | defmt-print -e "$elf"
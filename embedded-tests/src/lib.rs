@@ -0,0 +1,146 @@
+//! Runtime support shared by the on-target tests: a defmt logger, a panic handler that fails the
+//! QEMU run, an allocation-counting heap and a stack high-water-mark probe.
+#![no_std]
+
+use core::{
+    alloc::{GlobalAlloc, Layout},
+    fmt,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+use defmt_semihosting as _;
+use embedded_alloc::LlffHeap as Heap;
+
+const HEAP_SIZE: usize = 64 * 1024;
+
+struct CountingHeap {
+    heap: Heap,
+    allocations: AtomicUsize,
+}
+
+unsafe impl GlobalAlloc for CountingHeap {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        self.allocations.fetch_add(1, Ordering::Relaxed);
+        self.heap.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        self.heap.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static HEAP: CountingHeap = CountingHeap {
+    heap: Heap::empty(),
+    allocations: AtomicUsize::new(0),
+};
+
+/// Hands the heap its memory. Call once before anything allocates.
+pub fn init_heap() {
+    static mut MEMORY: [u8; HEAP_SIZE] = [0; HEAP_SIZE];
+    // SAFETY: called once at start up, before the first allocation
+    unsafe { HEAP.heap.init(core::ptr::addr_of_mut!(MEMORY) as usize, HEAP_SIZE) }
+}
+
+/// The number of allocations made since start up.
+pub fn allocations() -> usize {
+    HEAP.allocations.load(Ordering::Relaxed)
+}
+
+const STACK_PAINT: u32 = 0xDEAD_BEEF;
+const STACK_PROBE_DEPTH: usize = 16 * 1024;
+
+/// Runs `f` and reports (roughly) how many bytes of stack it used.
+///
+/// The stack below the caller is painted with a pattern first and scanned for the deepest
+/// overwritten word afterwards.
+#[inline(never)]
+pub fn stack_usage<R>(f: impl FnOnce() -> R) -> (R, usize) {
+    let top = cortex_m::register::msp::read() as usize - 64;
+    let bottom = top - STACK_PROBE_DEPTH;
+    for addr in (bottom..top).step_by(4) {
+        // SAFETY: this is unused stack in RAM below the current frame
+        unsafe { core::ptr::write_volatile(addr as *mut u32, STACK_PAINT) }
+    }
+
+    let ret = f();
+
+    let deepest = (bottom..top)
+        .step_by(4)
+        // SAFETY: as above
+        .find(|addr| unsafe { core::ptr::read_volatile(*addr as *const u32) } != STACK_PAINT)
+        .unwrap_or(top);
+    (ret, top - deepest)
+}
+
+/// A `fmt::Write` into a fixed buffer, standing in for a UART or a `heapless::String`.
+pub struct FixedWriter<const N: usize> {
+    buf: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> FixedWriter<N> {
+    pub const fn new() -> Self {
+        FixedWriter { buf: [0; N], len: 0 }
+    }
+
+    pub fn as_str(&self) -> &str {
+        // only whole `str`s are ever written
+        core::str::from_utf8(&self.buf[..self.len]).unwrap()
+    }
+}
+
+impl<const N: usize> fmt::Write for FixedWriter<N> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let end = self.len + s.len();
+        self.buf
+            .get_mut(self.len..end)
+            .ok_or(fmt::Error)?
+            .copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+}
+
+impl<const N: usize> Extend<char> for FixedWriter<N> {
+    fn extend<I: IntoIterator<Item = char>>(&mut self, chars: I) {
+        for c in chars {
+            fmt::Write::write_char(self, c).expect("FixedWriter is full");
+        }
+    }
+}
+
+/// An `Extend<u8>` into a fixed buffer, standing in for a `heapless::Vec`.
+pub struct FixedBytes<const N: usize> {
+    buf: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> FixedBytes<N> {
+    pub const fn new() -> Self {
+        FixedBytes { buf: [0; N], len: 0 }
+    }
+
+    pub fn as_slice(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+}
+
+impl<const N: usize> Extend<u8> for FixedBytes<N> {
+    fn extend<I: IntoIterator<Item = u8>>(&mut self, bytes: I) {
+        for byte in bytes {
+            *self.buf.get_mut(self.len).expect("FixedBytes is full") = byte;
+            self.len += 1;
+        }
+    }
+}
+
+#[defmt::panic_handler]
+fn defmt_panic() -> ! {
+    semihosting::process::exit(1)
+}
+
+#[panic_handler]
+fn panic(info: &core::panic::PanicInfo) -> ! {
+    defmt::error!("{}", defmt::Display2Format(info));
+    semihosting::process::exit(1)
+}
@@ -0,0 +1,148 @@
+#![no_std]
+#![no_main]
+
+use base2048_embedded_tests as _;
+
+#[defmt_test::tests]
+mod tests {
+    use base2048::{Base2048Display, DecodeError, DecodeSliceError, Decoder, Encoder};
+    use base2048_embedded_tests::{allocations, init_heap, stack_usage, FixedBytes, FixedWriter};
+    use core::fmt::Write;
+    use defmt::{assert, assert_eq};
+
+    const PAYLOAD: &[u8] = b"some utf8 bytes to encode more compactly";
+    const ENCODED: &str = "ݙޙצҭזЬශƕމਦعҭӿचॳಽܜͳԈඌཥШߣۿ۹ࠄעแಐ7";
+
+    #[init]
+    fn init() {
+        init_heap();
+    }
+
+    #[test]
+    fn display_does_not_allocate() {
+        let mut out = FixedWriter::<256>::new();
+        let before = allocations();
+        write!(out, "{}", Base2048Display::new(PAYLOAD)).unwrap();
+        assert_eq!(allocations(), before);
+        assert_eq!(out.as_str(), ENCODED);
+    }
+
+    #[test]
+    fn display_truncates_on_char_boundaries() {
+        let mut out = FixedWriter::<64>::new();
+        write!(out, "{:.4}", Base2048Display::new(PAYLOAD)).unwrap();
+        assert_eq!(out.as_str(), "ݙޙצ…");
+    }
+
+    #[test]
+    fn display_stack_usage() {
+        let (_, used) = stack_usage(|| {
+            let mut out = FixedWriter::<256>::new();
+            write!(out, "{}", Base2048Display::new(PAYLOAD)).unwrap();
+        });
+        defmt::info!("Base2048Display used {} bytes of stack", used);
+        assert!(used < 2048);
+    }
+
+    #[test]
+    fn roundtrip_on_the_heap() {
+        for len in 0..=64 {
+            let mut bytes = [0u8; 64];
+            for (i, byte) in bytes.iter_mut().enumerate() {
+                *byte = (i * 151 + len) as u8;
+            }
+            let encoded = base2048::encode(&bytes[..len]);
+            let decoded = base2048::decode(&encoded).unwrap();
+            assert_eq!(&decoded[..], &bytes[..len]);
+        }
+    }
+
+    #[test]
+    fn slices_roundtrip_without_allocating() {
+        let mut text = [0u8; 256];
+        let mut decoded = [0u8; 64];
+        let before = allocations();
+        for len in 0..=PAYLOAD.len() {
+            let n = base2048::encode_to_slice(&PAYLOAD[..len], &mut text).unwrap();
+            let encoded = core::str::from_utf8(&text[..n]).unwrap();
+            let n = base2048::decode_to_slice(encoded, &mut decoded).unwrap();
+            assert_eq!(&decoded[..n], &PAYLOAD[..len]);
+        }
+        assert_eq!(allocations(), before);
+        let n = base2048::encode_to_slice(PAYLOAD, &mut text).unwrap();
+        assert_eq!(&text[..n], ENCODED.as_bytes());
+    }
+
+    #[test]
+    fn slices_report_errors() {
+        let mut text = [0u8; 8];
+        let e = base2048::encode_to_slice(PAYLOAD, &mut text).unwrap_err();
+        assert_eq!(e.needed, ENCODED.len());
+
+        let mut decoded = [0u8; 8];
+        assert!(matches!(
+            base2048::decode_to_slice(ENCODED, &mut decoded),
+            Err(DecodeSliceError::BufferTooSmall(e)) if e.needed == PAYLOAD.len()
+        ));
+        // invalid text is reported as such even when the buffer is too small as well
+        assert!(matches!(
+            base2048::decode_to_slice("ետћζы༎X", &mut decoded[..1]),
+            Err(DecodeSliceError::Decode(DecodeError::InvalidChar {
+                char_index: 5,
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn streaming_in_chunks_without_allocating() {
+        let before = allocations();
+        for chunk_len in 1..8 {
+            let mut encoder = Encoder::new();
+            let mut encoded = FixedWriter::<256>::new();
+            for chunk in PAYLOAD.chunks(chunk_len) {
+                encoder.push(chunk, &mut encoded);
+            }
+            encoder.finish(&mut encoded);
+            assert_eq!(encoded.as_str(), ENCODED);
+
+            let mut decoder = Decoder::new();
+            let mut decoded = FixedBytes::<64>::new();
+            let mut rest = ENCODED;
+            while !rest.is_empty() {
+                let at = rest
+                    .char_indices()
+                    .nth(chunk_len)
+                    .map_or(rest.len(), |(i, _)| i);
+                decoder.push(&rest[..at], &mut decoded).unwrap();
+                rest = &rest[at..];
+            }
+            decoder.finish(&mut decoded).unwrap();
+            assert_eq!(decoded.as_slice(), PAYLOAD);
+        }
+        assert_eq!(allocations(), before);
+    }
+
+    #[test]
+    fn streaming_errors_stick() {
+        let mut decoder = Decoder::new();
+        let mut decoded = FixedBytes::<64>::new();
+        decoder.push("ետћζы", &mut decoded).unwrap();
+        let e = decoder.push("༎X", &mut decoded);
+        assert!(matches!(
+            e,
+            Err(DecodeError::InvalidChar { char_index: 5, .. })
+        ));
+        assert!(decoder.push("ե", &mut decoded) == e);
+        assert!(decoder.finish(&mut decoded) == e);
+        // the bytes before the error were still decoded
+        assert_eq!(decoded.as_slice().len(), 5 * 11 / 8);
+    }
+
+    #[test]
+    fn tables_are_intact() {
+        for (i, c) in base2048::ENC_TABLE.iter().enumerate() {
+            assert_eq!(base2048::DEC_TABLE[*c as usize] as usize, i);
+        }
+    }
+}
@@ -0,0 +1,57 @@
+/*
+ * Encodes stdin to stdout, or decodes it with -d, a chunk at a time.
+ *
+ *     cargo build --release --manifest-path ffi/Cargo.toml
+ *     cc -Iffi/include ffi/examples/stream.c ffi/target/release/libbase2048.a -lpthread -ldl -lm \
+ *         -o b2048-stream
+ */
+#include <base2048.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#define CHUNK 4096
+
+static int write_all(const uint8_t *buf, size_t len) {
+    return fwrite(buf, 1, len, stdout) == len ? 0 : -1;
+}
+
+static int encode(void) {
+    static uint8_t in[CHUNK], out[CHUNK * 3];
+    base2048_encoder *encoder = base2048_encoder_new();
+    size_t len, written;
+    int code = BASE2048_OK;
+    while (code == BASE2048_OK && (len = fread(in, 1, CHUNK, stdin)) > 0) {
+        code = base2048_encoder_update(encoder, in, len, out, sizeof out, &written);
+        if (code == BASE2048_OK && write_all(out, written)) return 1;
+    }
+    if (code == BASE2048_OK)
+        code = base2048_encoder_finish(encoder, out, sizeof out, &written);
+    if (code == BASE2048_OK && write_all(out, written)) return 1;
+    base2048_encoder_free(encoder);
+    return code != BASE2048_OK;
+}
+
+static int decode(void) {
+    static uint8_t in[CHUNK], out[CHUNK * 2 + 2];
+    base2048_decoder *decoder = base2048_decoder_new();
+    size_t len, written;
+    int code = BASE2048_OK;
+    while (code == BASE2048_OK && (len = fread(in, 1, CHUNK, stdin)) > 0) {
+        /* skip the newline that usually ends a text file */
+        if (feof(stdin) && len > 0 && in[len - 1] == '\n') len--;
+        code = base2048_decoder_update(decoder, in, len, out, sizeof out, &written);
+        if (code == BASE2048_OK && write_all(out, written)) return 1;
+    }
+    if (code == BASE2048_OK)
+        code = base2048_decoder_finish(decoder, out, sizeof out, &written);
+    if (code == BASE2048_OK && write_all(out, written)) return 1;
+    base2048_decoder_free(decoder);
+    if (code != BASE2048_OK) fprintf(stderr, "invalid input (%d)\n", code);
+    return code != BASE2048_OK;
+}
+
+int main(int argc, char **argv) {
+    if (argc > 1 && strcmp(argv[1], "-d") == 0) return decode();
+    return encode();
+}
@@ -0,0 +1,72 @@
+/*
+ * C bindings for base2048.
+ *
+ * Encoders and decoders take their input in chunks of any size and write their output to buffers
+ * owned by the caller. Each update needs an output buffer of at least the size given by the
+ * matching `_update_size` function, and each finish one of at least BASE2048_FINISH_SIZE bytes.
+ * Encoded text is UTF-8 and is not NUL terminated.
+ *
+ * base2048_encode and base2048_decode convert data that is all in memory in one call. Their output
+ * buffers need at most base2048_encode_size and base2048_decode_size bytes; if one is too small they
+ * return BASE2048_ERR_BUFFER_TOO_SMALL and set *out_len to the size needed. The size functions
+ * return SIZE_MAX when the size doesn't fit in a size_t, which no buffer can hold.
+ *
+ * No function keeps a pointer it is given after returning. Encoders and decoders are owned by the
+ * caller from _new until they are passed to _free.
+ *
+ * Every function returning int returns BASE2048_OK or one of the BASE2048_ERR_ codes. After a
+ * decoder returns BASE2048_ERR_INVALID or BASE2048_ERR_UTF8 it can only be freed. A successful
+ * finish resets the encoder or decoder so it can be used again.
+ */
+#ifndef BASE2048_H
+#define BASE2048_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define BASE2048_OK 0
+/* A required pointer was null. */
+#define BASE2048_ERR_NULL -1
+/* The output buffer is smaller than the size function for the call asks for. */
+#define BASE2048_ERR_BUFFER_TOO_SMALL -2
+/* The input isn't valid base2048. */
+#define BASE2048_ERR_INVALID -3
+/* The input isn't valid UTF-8. */
+#define BASE2048_ERR_UTF8 -4
+
+/* The output buffer size base2048_encoder_finish and base2048_decoder_finish need. */
+#define BASE2048_FINISH_SIZE 4
+
+size_t base2048_encode_size(size_t len);
+int base2048_encode(const uint8_t *data, size_t len, uint8_t *out, size_t out_cap, size_t *out_len);
+size_t base2048_decode_size(size_t len);
+int base2048_decode(const uint8_t *data, size_t len, uint8_t *out, size_t out_cap, size_t *out_len);
+
+typedef struct base2048_encoder base2048_encoder;
+typedef struct base2048_decoder base2048_decoder;
+
+base2048_encoder *base2048_encoder_new(void);
+size_t base2048_encoder_update_size(size_t len);
+int base2048_encoder_update(base2048_encoder *encoder, const uint8_t *data, size_t len,
+                            uint8_t *out, size_t out_cap, size_t *out_len);
+int base2048_encoder_finish(base2048_encoder *encoder, uint8_t *out, size_t out_cap,
+                            size_t *out_len);
+void base2048_encoder_free(base2048_encoder *encoder);
+
+base2048_decoder *base2048_decoder_new(void);
+size_t base2048_decoder_update_size(size_t len);
+int base2048_decoder_update(base2048_decoder *decoder, const uint8_t *data, size_t len,
+                            uint8_t *out, size_t out_cap, size_t *out_len);
+int base2048_decoder_finish(base2048_decoder *decoder, uint8_t *out, size_t out_cap,
+                            size_t *out_len);
+void base2048_decoder_free(base2048_decoder *decoder);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* BASE2048_H */
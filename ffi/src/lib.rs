@@ -0,0 +1,2 @@
+//! Links the bindings from `base2048::ffi` into a library C can link against.
+pub use codec::ffi::*;
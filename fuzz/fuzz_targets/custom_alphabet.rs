@@ -0,0 +1,71 @@
+//! Round trips through random valid alphabets.
+//!
+//! The alphabet starts out as the standard one, is shuffled and then has some characters replaced
+//! by arbitrary ones. Encoding with it must be the standard encoding with each character swapped
+//! for the one at the same position.
+#![no_main]
+use arbitrary::{Arbitrary, Result, Unstructured};
+use base2048::{Alphabet, AlphabetError, ENC_TABLE, TAIL};
+use libfuzzer_sys::fuzz_target;
+use std::collections::HashSet;
+
+#[derive(Debug)]
+struct Input {
+    table: Vec<char>,
+    tail: Vec<char>,
+    data: Vec<u8>,
+}
+
+impl<'a> Arbitrary<'a> for Input {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let mut chars = ENC_TABLE.iter().chain(TAIL).copied().collect::<Vec<_>>();
+        for i in (1..chars.len()).rev() {
+            if u.is_empty() {
+                break;
+            }
+            chars.swap(i, u.int_in_range(0..=i)?);
+        }
+        let mut seen = chars.iter().copied().collect::<HashSet<_>>();
+        for _ in 0..u.int_in_range(0..=64)? {
+            let at = u.choose_index(chars.len())?;
+            // alphabets only take characters up to U+FFFF
+            let c = match char::from_u32(u.int_in_range(0..=0xFFFF)?) {
+                Some(c) => c,
+                None => continue,
+            };
+            if seen.insert(c) {
+                seen.remove(&chars[at]);
+                chars[at] = c;
+            }
+        }
+        let tail = chars.split_off(2048);
+        Ok(Input {
+            table: chars,
+            tail,
+            data: u.arbitrary()?,
+        })
+    }
+}
+
+fuzz_target!(|input: Input| {
+    let alphabet = Alphabet::from_chars(&input.table, &input.tail).unwrap();
+    let encoded = alphabet.encode(&input.data);
+    assert_eq!(alphabet.decode(&encoded), Ok(input.data.clone()));
+
+    let expected = base2048::encode(&input.data)
+        .chars()
+        .map(|c| match ENC_TABLE.iter().position(|t| *t == c) {
+            Some(i) => input.table[i],
+            None => input.tail[TAIL.iter().position(|t| *t == c).unwrap()],
+        })
+        .collect::<String>();
+    assert_eq!(encoded, expected);
+
+    // a repeated character must always be rejected
+    let mut tail = input.tail.clone();
+    tail[7] = input.table[input.data.len() % 2048];
+    assert_eq!(
+        Alphabet::from_chars(&input.table, &tail),
+        Err(AlphabetError::Duplicate(tail[7]))
+    );
+});
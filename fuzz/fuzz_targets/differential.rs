@@ -0,0 +1,59 @@
+#![no_main]
+use base2048::{Alphabet, Base2048String, Codec, CodecError, DecodeError, IterEncodeExt};
+use libfuzzer_sys::fuzz_target;
+
+// Every way of encoding must give the same text, and every way of decoding the same bytes or the
+// same error, so that an optimized path can't quietly drift from the others.
+fuzz_target!(|data: &[u8]| {
+    let alphabet = Alphabet::standard();
+    let codec = Codec::default();
+
+    let expected = base2048::encode(data);
+    let encoders: [(&str, String); 5] = [
+        (
+            "encode_to_vec",
+            String::from_utf8(base2048::encode_to_vec(data)).unwrap(),
+        ),
+        ("Alphabet::encode", alphabet.encode(data)),
+        ("Codec::encode", codec.encode(data)),
+        (
+            "base2048_chars",
+            data.iter().copied().base2048_chars().collect(),
+        ),
+        (
+            "Base2048String",
+            data.iter().collect::<Base2048String>().into_string(),
+        ),
+    ];
+    for (name, encoded) in &encoders {
+        assert_eq!(*encoded, expected, "{} disagrees with encode", name);
+    }
+
+    let string = match core::str::from_utf8(data) {
+        Ok(string) => string,
+        Err(_) => return,
+    };
+    let mut extended = Vec::new();
+    let expected = base2048::decode_extend(string, &mut extended).map(|()| extended);
+    let decoders: [(&str, Result<Vec<u8>, DecodeError>); 4] = [
+        ("Alphabet::decode", alphabet.decode(string)),
+        (
+            "Codec::decode",
+            codec.decode(string).map_err(|e| match e {
+                CodecError::Decode(e) => e,
+                CodecError::TooLong { .. } => unreachable!("the default codec has no limit"),
+            }),
+        ),
+        ("base2048_bytes", string.chars().base2048_bytes().collect()),
+        ("decode_owned", base2048::decode_owned(string.to_owned())),
+    ];
+    for (name, decoded) in &decoders {
+        assert_eq!(*decoded, expected, "{} disagrees with decode_extend", name);
+    }
+    let expected = expected.ok();
+    assert_eq!(base2048::decode(string), expected);
+    assert_eq!(
+        base2048::decode_aligned::<base2048::Align8>(string).map(|b| b.to_vec()),
+        expected
+    );
+});
@@ -0,0 +1,15 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let encoded = base2048::encode(data);
+    assert_eq!(base2048::decode(&encoded).as_deref(), Some(data));
+
+    // arbitrary text must never panic, and whatever decodes must encode back to something that
+    // decodes the same way
+    if let Ok(string) = core::str::from_utf8(data) {
+        if let Some(decoded) = base2048::decode(string) {
+            assert_eq!(base2048::decode(base2048::encode(&decoded)), Some(decoded));
+        }
+    }
+});
@@ -0,0 +1,2 @@
+//! Links the bindings from `base2048::mobile` into a library UniFFI can generate bindings from.
+pub use codec::mobile::*;
@@ -0,0 +1,90 @@
+//! Python bindings for base2048, built with [maturin](https://www.maturin.rs).
+//!
+//! `encode` takes anything supporting the buffer protocol (`bytes`, `bytearray`, `memoryview`,
+//! numpy arrays, ...) and both functions let go of the GIL while working on large inputs, so other
+//! Python threads keep running.
+use pyo3::{
+    buffer::PyBuffer,
+    create_exception,
+    exceptions::PyValueError,
+    prelude::*,
+    types::{PyBytes, PyMemoryView},
+};
+
+/// Inputs at least this big are encoded or decoded without holding the GIL.
+const DETACH_THRESHOLD: usize = 64 * 1024;
+
+create_exception!(
+    base2048,
+    DecodeError,
+    PyValueError,
+    "The string isn't valid base2048.\n\n\
+     `code` says what is wrong (1 for an invalid character, 2 for trailing data, 3 for an unexpected\n\
+     tail and 4 for invalid padding) and `char_index` where, counted in characters."
+);
+
+fn decode_error(py: Python<'_>, e: codec::DecodeError) -> PyErr {
+    let err = DecodeError::new_err(e.to_string());
+    let value = err.value(py);
+    match value
+        .setattr("code", e.code())
+        .and_then(|()| value.setattr("char_index", e.char_index()))
+    {
+        Ok(()) => err,
+        Err(setattr_err) => setattr_err,
+    }
+}
+
+/// The bytes of any object supporting the buffer protocol, in C order.
+fn buffer_bytes(data: &Bound<'_, PyAny>) -> PyResult<Vec<u8>> {
+    let buffer = match PyBuffer::<u8>::get(data) {
+        Ok(buffer) => buffer,
+        // not a buffer of unsigned bytes, such as a numpy array of floats: view it as bytes
+        Err(_) => {
+            let bytes = PyMemoryView::from(data)?.call_method1("cast", ("B",))?;
+            PyBuffer::<u8>::get(&bytes)?
+        }
+    };
+    // Copying lets the GIL go without another thread being able to change the bytes underneath us.
+    buffer.to_vec(data.py())
+}
+
+/// encode(data, /)
+/// --
+///
+/// Encodes the bytes of any object supporting the buffer protocol as a base2048 string.
+#[pyfunction]
+fn encode(py: Python<'_>, data: &Bound<'_, PyAny>) -> PyResult<String> {
+    let bytes = buffer_bytes(data)?;
+    if bytes.len() >= DETACH_THRESHOLD {
+        Ok(py.detach(|| codec::encode(&bytes)))
+    } else {
+        Ok(codec::encode(&bytes))
+    }
+}
+
+/// decode(string, /)
+/// --
+///
+/// Decodes a base2048 string into bytes, raising DecodeError if it isn't valid base2048.
+#[pyfunction]
+fn decode<'py>(py: Python<'py>, string: &str) -> PyResult<Bound<'py, PyBytes>> {
+    let mut bytes = Vec::new();
+    let decoded = if string.len() >= DETACH_THRESHOLD {
+        py.detach(|| codec::decode_extend(string, &mut bytes))
+    } else {
+        codec::decode_extend(string, &mut bytes)
+    };
+    match decoded {
+        Ok(()) => Ok(PyBytes::new(py, &bytes)),
+        Err(e) => Err(decode_error(py, e)),
+    }
+}
+
+#[pymodule(gil_used = false)]
+fn base2048(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(encode, m)?)?;
+    m.add_function(wrap_pyfunction!(decode, m)?)?;
+    m.add("DecodeError", m.py().get_type::<DecodeError>())?;
+    Ok(())
+}
@@ -0,0 +1,52 @@
+import array
+import threading
+
+import pytest
+
+import base2048
+
+
+def test_roundtrip():
+    for data in [b"", b"\x00", b"hello", bytes(range(256)) * 10]:
+        assert base2048.decode(base2048.encode(data)) == data
+
+
+def test_buffer_protocol():
+    data = b"some utf8 bytes to encode more compactly"
+    expected = base2048.encode(data)
+    assert base2048.encode(bytearray(data)) == expected
+    assert base2048.encode(memoryview(data)) == expected
+    assert base2048.encode(memoryview(b"--" + data)[2:]) == expected
+    interleaved = bytes(b for c in data for b in (c, 0))
+    assert base2048.encode(memoryview(interleaved)[::2]) == expected
+    floats = array.array("d", [1.5, -2.0])
+    assert base2048.decode(base2048.encode(floats)) == floats.tobytes()
+
+
+def test_numpy():
+    np = pytest.importorskip("numpy")
+    values = np.arange(1000, dtype=np.uint32)
+    assert base2048.decode(base2048.encode(values)) == values.tobytes()
+
+
+def test_invalid():
+    with pytest.raises(base2048.DecodeError, match="position 5") as e:
+        base2048.decode("ետћζы༎X")
+    assert (e.value.code, e.value.char_index) == (1, 5)
+    assert issubclass(base2048.DecodeError, ValueError)
+    with pytest.raises(TypeError):
+        base2048.encode("not bytes")
+
+
+def test_threads():
+    data = bytes(range(256)) * 8192
+    results = []
+    threads = [
+        threading.Thread(target=lambda: results.append(base2048.decode(base2048.encode(data))))
+        for _ in range(4)
+    ]
+    for thread in threads:
+        thread.start()
+    for thread in threads:
+        thread.join()
+    assert results == [data] * 4
@@ -0,0 +1,144 @@
+use crate::decode_with;
+use alloc::vec::Vec;
+use core::{fmt, mem, ops::Deref, slice};
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// An alignment that decoded bytes can be placed at.
+///
+/// This is implemented for [`Align2`], [`Align4`], [`Align8`] and [`Align16`] only.
+///
+/// # Safety
+///
+/// Implementors must be plain bytes: no padding, no invalid bit patterns and a size equal to their
+/// alignment.
+pub unsafe trait Alignment: Copy + sealed::Sealed {
+    #[doc(hidden)]
+    const ZERO: Self;
+}
+
+macro_rules! alignments {
+    ($($name:ident => $n:literal),+) => {$(
+        #[doc = concat!("Aligns decoded bytes to ", stringify!($n), " bytes.")]
+        #[derive(Clone, Copy)]
+        #[repr(C, align($n))]
+        pub struct $name([u8; $n]);
+
+        impl sealed::Sealed for $name {}
+
+        unsafe impl Alignment for $name {
+            const ZERO: Self = $name([0; $n]);
+        }
+    )+}
+}
+
+alignments!(Align2 => 2, Align4 => 4, Align8 => 8, Align16 => 16);
+
+/// Decoded bytes whose first byte is aligned to `A`.
+///
+/// Dereferences to `[u8]`, so the bytes can be handed straight to something like `zerocopy` to be
+/// viewed as `&[u32]` or a struct without copying them somewhere aligned first.
+#[derive(Clone)]
+pub struct AlignedBytes<A: Alignment> {
+    buf: Vec<A>,
+    len: usize,
+}
+
+impl<A: Alignment> AlignedBytes<A> {
+    fn new() -> Self {
+        AlignedBytes {
+            buf: Vec::new(),
+            len: 0,
+        }
+    }
+
+    fn push(&mut self, byte: u8) {
+        if self.len == self.buf.len() * mem::size_of::<A>() {
+            self.buf.push(A::ZERO);
+        }
+        let len = self.len;
+        self.all_bytes_mut()[len] = byte;
+        self.len += 1;
+    }
+
+    fn all_bytes_mut(&mut self) -> &mut [u8] {
+        // SAFETY: `A` is plain bytes with no padding (see `Alignment`) so the elements of `buf` can
+        // be viewed as one contiguous byte slice.
+        unsafe {
+            slice::from_raw_parts_mut(
+                self.buf.as_mut_ptr() as *mut u8,
+                self.buf.len() * mem::size_of::<A>(),
+            )
+        }
+    }
+
+    /// The decoded bytes.
+    pub fn as_slice(&self) -> &[u8] {
+        // SAFETY: as in `all_bytes_mut`, and `len` never exceeds the bytes in `buf`.
+        unsafe { slice::from_raw_parts(self.buf.as_ptr() as *const u8, self.len) }
+    }
+}
+
+impl<A: Alignment> Deref for AlignedBytes<A> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+impl<A: Alignment> AsRef<[u8]> for AlignedBytes<A> {
+    fn as_ref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+impl<A: Alignment> fmt::Debug for AlignedBytes<A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_slice().fmt(f)
+    }
+}
+
+impl<A: Alignment> PartialEq for AlignedBytes<A> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl<A: Alignment> Eq for AlignedBytes<A> {}
+
+/// Decode a base2048 encoded string into a buffer aligned to `A`
+///
+/// # Example
+/// ```
+/// use base2048::Align4;
+/// let encoded = base2048::encode(&[1, 0, 0, 0, 2, 0, 0, 0]);
+/// let decoded = base2048::decode_aligned::<Align4>(&encoded).unwrap();
+/// assert_eq!(decoded.as_ptr() as usize % 4, 0);
+/// assert_eq!(&decoded[..], &[1, 0, 0, 0, 2, 0, 0, 0]);
+/// ```
+pub fn decode_aligned<A: Alignment>(string: &str) -> Option<AlignedBytes<A>> {
+    let mut ret = AlignedBytes::new();
+    decode_with(string, |byte| ret.push(byte)).ok()?;
+    Some(ret)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{decode, encode};
+
+    #[test]
+    fn aligned_matches_decode() {
+        for len in 0..40 {
+            let bytes = (0..len).map(|i| (i * 37) as u8).collect::<Vec<_>>();
+            let encoded = encode(&bytes);
+            let decoded = decode_aligned::<Align16>(&encoded).unwrap();
+            assert_eq!(decoded.as_ptr() as usize % 16, 0);
+            assert_eq!(&decoded[..], &decode(&encoded).unwrap()[..]);
+        }
+        assert!(decode_aligned::<Align8>("ետћζы༎X").is_none());
+    }
+}
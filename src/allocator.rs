@@ -0,0 +1,61 @@
+//! Encoding and decoding into buffers from a caller-chosen allocator (the `allocator-api2`
+//! feature).
+use crate::{decode_with, decoded_str_len, encode_with, DecodeError};
+use allocator_api2::{alloc::Allocator, vec::Vec};
+
+/// Encode some bytes using base2048 encoding, allocating the output with `alloc`
+///
+/// `allocator_api2` has no `String`, so this returns the UTF-8 bytes of the encoded text. They are
+/// always valid UTF-8.
+///
+/// # Example
+/// ```
+/// use allocator_api2::alloc::Global;
+/// let encoded = base2048::encode_in(b"hello", Global);
+/// assert_eq!(&encoded[..], base2048::encode(b"hello").as_bytes());
+/// ```
+pub fn encode_in<A: Allocator>(bytes: &[u8], alloc: A) -> Vec<u8, A> {
+    let mut ret = Vec::new_in(alloc);
+    encode_with(bytes, |c| {
+        ret.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+    });
+    ret
+}
+
+/// Decode a base2048 encoded string, allocating the output with `alloc`
+///
+/// # Example
+/// ```
+/// use allocator_api2::alloc::Global;
+/// let encoded = base2048::encode(b"hello");
+/// assert_eq!(&base2048::decode_in(&encoded, Global).unwrap()[..], b"hello");
+/// ```
+pub fn decode_in<A: Allocator>(string: &str, alloc: A) -> Result<Vec<u8, A>, DecodeError> {
+    let mut ret = Vec::with_capacity_in(decoded_str_len(string), alloc);
+    decode_with(string, |byte| ret.push(byte))?;
+    Ok(ret)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{decode, encode};
+    use allocator_api2::alloc::Global;
+
+    #[test]
+    fn matches_global_functions() {
+        for len in 0..40 {
+            let bytes = (0..len)
+                .map(|i| (i * 37) as u8)
+                .collect::<alloc::vec::Vec<_>>();
+            let encoded = encode_in(&bytes, Global);
+            assert_eq!(&encoded[..], encode(&bytes).as_bytes());
+            let encoded = core::str::from_utf8(&encoded).unwrap();
+            assert_eq!(
+                &decode_in(encoded, Global).unwrap()[..],
+                &decode(encoded).unwrap()[..]
+            );
+        }
+        assert!(decode_in("ետћζы༎X", Global).is_err());
+    }
+}
@@ -0,0 +1,406 @@
+use crate::{progress, tables, DecodeError, Symbol, TAIL};
+use alloc::{boxed::Box, string::String, vec::Vec};
+use core::{fmt, ops::RangeInclusive};
+
+/// Marks tail characters in `Alphabet::decode_table`, the low bits hold the tail index.
+const TAIL_FLAG: u16 = 0x8000;
+const INVALID: u16 = 0xFFFF;
+
+/// The highest code point an alphabet may use, the last one that takes three bytes in UTF-8.
+const MAX_CHAR: char = '\u{FFFF}';
+
+// generated by build/main.rs from base2048-ltr.ranges
+const LTR_TABLE: [char; 2048] = include!(concat!(env!("OUT_DIR"), "/ltr_table.src"));
+
+/// The characters an encoding uses: 2048 for the table plus 8 tail characters.
+///
+/// The free functions like [`encode`](crate::encode) always use the standard alphabet (see
+/// [`ENC_TABLE`](crate::ENC_TABLE) and [`TAIL`]). An `Alphabet` lets you encode with different
+/// characters, with the same bit layout and tail rules.
+///
+/// # Example
+/// ```
+/// use base2048::Alphabet;
+/// let mut table = base2048::ENC_TABLE.to_vec();
+/// table.reverse();
+/// let alphabet = Alphabet::from_chars(&table, base2048::TAIL).unwrap();
+/// let encoded = alphabet.encode(b"hello");
+/// assert_ne!(encoded, base2048::encode(b"hello"));
+/// assert_eq!(alphabet.decode(&encoded), Ok(b"hello".to_vec()));
+/// ```
+#[derive(Clone, PartialEq, Eq)]
+pub struct Alphabet {
+    table: Box<[char; 2048]>,
+    tail: [char; 8],
+    /// Indexed by code point: the position in `table`, `TAIL_FLAG | i` for `tail[i]` or `INVALID`.
+    decode_table: Box<[u16]>,
+}
+
+/// Why a set of characters can't be used as an [`Alphabet`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AlphabetError {
+    /// The table must have exactly 2048 characters.
+    TableLength(usize),
+    /// There must be exactly 8 tail characters.
+    TailLength(usize),
+    /// The character appears more than once across the table and tail.
+    Duplicate(char),
+    /// The character is above U+FFFF, so it takes four bytes in UTF-8 (and twice the room in the
+    /// decoding table of everything below it).
+    OutOfRange(char),
+}
+
+#[cfg(not(feature = "compact-errors"))]
+impl fmt::Display for AlphabetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AlphabetError::TableLength(n) => {
+                write!(f, "an alphabet needs 2048 table characters, not {}", n)
+            }
+            AlphabetError::TailLength(n) => {
+                write!(f, "an alphabet needs 8 tail characters, not {}", n)
+            }
+            AlphabetError::Duplicate(c) => write!(f, "{:?} appears more than once", c),
+            AlphabetError::OutOfRange(c) => {
+                write!(f, "{:?} is above U+FFFF and takes four bytes in UTF-8", c)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "compact-errors")]
+impl fmt::Display for AlphabetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            AlphabetError::TableLength(_) => "base2048 alphabet error 1: table length",
+            AlphabetError::TailLength(_) => "base2048 alphabet error 2: tail length",
+            AlphabetError::Duplicate(_) => "base2048 alphabet error 3: duplicate character",
+            AlphabetError::OutOfRange(_) => "base2048 alphabet error 4: character out of range",
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for AlphabetError {}
+
+impl Alphabet {
+    /// The standard base2048 alphabet, the one [`encode`](crate::encode) uses.
+    pub fn standard() -> Self {
+        Alphabet::from_chars(tables::enc_table(), TAIL).expect("the standard alphabet is valid")
+    }
+
+    /// The standard alphabet without its Hebrew, Arabic and other right-to-left characters, which
+    /// can reorder the text around them. Myanmar, Georgian and Ethiopic letters take their place;
+    /// the Ethiopic ones are above U+10FF, so Twitter counts them as two characters.
+    pub fn ltr() -> Self {
+        Alphabet::from_chars(&LTR_TABLE, TAIL).expect("the left-to-right alphabet is valid")
+    }
+
+    /// Starts building an alphabet from the standard one, see [`AlphabetBuilder`].
+    pub fn builder() -> AlphabetBuilder {
+        AlphabetBuilder::new()
+    }
+
+    /// Makes an alphabet out of 2048 `table` characters and 8 `tail` characters.
+    ///
+    /// Every character must be distinct and no higher than U+FFFF, so none takes more than three
+    /// bytes in UTF-8.
+    pub fn from_chars(table: &[char], tail: &[char]) -> Result<Self, AlphabetError> {
+        if table.len() != 2048 {
+            return Err(AlphabetError::TableLength(table.len()));
+        }
+        if tail.len() != 8 {
+            return Err(AlphabetError::TailLength(tail.len()));
+        }
+        if let Some(c) = table.iter().chain(tail).find(|c| **c > MAX_CHAR) {
+            return Err(AlphabetError::OutOfRange(*c));
+        }
+
+        let max = table.iter().chain(tail).max().copied().unwrap_or('\0');
+        let mut decode_table = vec![INVALID; max as usize + 1].into_boxed_slice();
+        let entries = table.iter().enumerate().map(|(i, c)| (*c, i as u16)).chain(
+            tail.iter()
+                .enumerate()
+                .map(|(i, c)| (*c, TAIL_FLAG | i as u16)),
+        );
+        for (c, value) in entries {
+            let entry = &mut decode_table[c as usize];
+            if *entry != INVALID {
+                return Err(AlphabetError::Duplicate(c));
+            }
+            *entry = value;
+        }
+
+        let mut table_array = Box::new(['\0'; 2048]);
+        table_array.copy_from_slice(table);
+        let mut tail_array = ['\0'; 8];
+        tail_array.copy_from_slice(tail);
+
+        Ok(Alphabet {
+            table: table_array,
+            tail: tail_array,
+            decode_table,
+        })
+    }
+
+    /// The 2048 characters that each encode 11 bits.
+    pub fn table(&self) -> &[char; 2048] {
+        &self.table
+    }
+
+    /// The 8 characters that may end an encoding, carrying up to 3 bits.
+    pub fn tail(&self) -> &[char; 8] {
+        &self.tail
+    }
+
+    pub(crate) fn char(&self, symbol: Symbol) -> char {
+        match symbol {
+            Symbol::Table(index) => self.table[index as usize],
+            Symbol::Tail(index) => self.tail[index as usize],
+        }
+    }
+
+    pub(crate) fn symbol(&self, c: char) -> Option<Symbol> {
+        match self.decode_table.get(c as usize).copied() {
+            None | Some(INVALID) => None,
+            Some(value) if value & TAIL_FLAG != 0 => Some(Symbol::Tail((value & !TAIL_FLAG) as u8)),
+            Some(value) => Some(Symbol::Table(value)),
+        }
+    }
+
+    /// Encode some bytes using this alphabet
+    pub fn encode(&self, bytes: &[u8]) -> String {
+        self.encode_with_progress(bytes, |_| {})
+    }
+
+    /// Like [`encode`](Self::encode) but reports progress, see
+    /// [`encode_with_progress`](crate::encode_with_progress).
+    pub fn encode_with_progress(&self, bytes: &[u8], progress: impl FnMut(usize)) -> String {
+        progress::encode(bytes, |symbol| self.char(symbol), progress)
+    }
+
+    /// Encode some bytes using this alphabet, adding the characters to `out`
+    ///
+    /// See [`encode_extend`](crate::encode_extend).
+    pub fn encode_extend(&self, bytes: &[u8], out: &mut impl Extend<char>) {
+        progress::encode_with(
+            bytes,
+            |symbol| self.char(symbol),
+            |_| {},
+            |c| out.extend(core::iter::once(c)),
+        )
+    }
+
+    /// Decode a string encoded with this alphabet
+    pub fn decode(&self, string: &str) -> Result<Vec<u8>, DecodeError> {
+        self.decode_with_progress(string, |_| {})
+    }
+
+    /// Decode a string encoded with this alphabet, appending the bytes to `out`
+    ///
+    /// See [`decode_extend`](crate::decode_extend).
+    pub fn decode_extend(
+        &self,
+        string: &str,
+        out: &mut impl Extend<u8>,
+    ) -> Result<(), DecodeError> {
+        progress::decode_with(
+            string,
+            |c| self.symbol(c),
+            |_| {},
+            |byte| out.extend(core::iter::once(byte)),
+        )
+    }
+
+    /// Like [`decode`](Self::decode) but reports progress, see
+    /// [`decode_with_progress`](crate::decode_with_progress).
+    pub fn decode_with_progress(
+        &self,
+        string: &str,
+        progress: impl FnMut(usize),
+    ) -> Result<Vec<u8>, DecodeError> {
+        progress::decode(string, |c| self.symbol(c), progress)
+    }
+}
+
+/// Puts together the characters for an [`Alphabet`], checking them when it is built.
+///
+/// It starts out with the standard table and tail, so only what differs needs to be given.
+///
+/// # Example
+/// ```
+/// use base2048::{Alphabet, AlphabetError, Engine};
+/// // the standard ASCII characters and then CJK ideographs, for a terminal that shows nothing else
+/// let alphabet = Alphabet::builder()
+///     .with_table_ranges(['8'..='9', 'A'..='Z', 'a'..='z', '\u{4E00}'..='\u{55C9}'])
+///     .build()
+///     .unwrap();
+/// let encoded = alphabet.encode(b"hello");
+/// assert_eq!(alphabet.decode(&encoded), Ok(b"hello".to_vec()));
+///
+/// let too_wide = Alphabet::builder().with_tail(['0', '1', '2', '3', '4', '5', '6', '😀']);
+/// assert_eq!(too_wide.build(), Err(AlphabetError::OutOfRange('😀')));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AlphabetBuilder {
+    table: Vec<char>,
+    tail: Vec<char>,
+}
+
+impl AlphabetBuilder {
+    /// The standard table and tail.
+    pub fn new() -> Self {
+        AlphabetBuilder {
+            table: tables::enc_table().to_vec(),
+            tail: TAIL.to_vec(),
+        }
+    }
+
+    /// Uses `table` for the 2048 table characters, in order.
+    pub fn with_table(mut self, table: impl IntoIterator<Item = char>) -> Self {
+        self.table = table.into_iter().collect();
+        self
+    }
+
+    /// Uses the characters in `ranges`, one after another, for the table.
+    pub fn with_table_ranges(self, ranges: impl IntoIterator<Item = RangeInclusive<char>>) -> Self {
+        self.with_table(ranges.into_iter().flatten())
+    }
+
+    /// Uses `tail` for the 8 tail characters, in order.
+    pub fn with_tail(mut self, tail: impl IntoIterator<Item = char>) -> Self {
+        self.tail = tail.into_iter().collect();
+        self
+    }
+
+    /// The alphabet, or why the characters can't make one (see [`Alphabet::from_chars`]).
+    pub fn build(&self) -> Result<Alphabet, AlphabetError> {
+        Alphabet::from_chars(&self.table, &self.tail)
+    }
+}
+
+impl Default for AlphabetBuilder {
+    fn default() -> Self {
+        AlphabetBuilder::new()
+    }
+}
+
+impl Default for Alphabet {
+    fn default() -> Self {
+        Alphabet::standard()
+    }
+}
+
+impl fmt::Debug for Alphabet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let table = self.table.iter().take(8).chain(&['…']).collect::<String>();
+        f.debug_struct("Alphabet")
+            .field("table", &table)
+            .field("tail", &self.tail.iter().collect::<String>())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{decode, encode, ENC_TABLE};
+
+    #[test]
+    fn standard_matches_free_functions() {
+        let alphabet = Alphabet::standard();
+        for len in 0..30 {
+            let bytes = (0..len).map(|i| (i * 43 + 1) as u8).collect::<Vec<_>>();
+            let encoded = alphabet.encode(&bytes);
+            assert_eq!(encoded, encode(&bytes));
+            assert_eq!(alphabet.decode(&encoded).ok(), decode(&encoded));
+
+            let mut extended = String::from(">");
+            alphabet.encode_extend(&bytes, &mut extended);
+            assert_eq!(extended[1..], encoded);
+            let mut decoded = vec![0];
+            alphabet.decode_extend(&encoded, &mut decoded).unwrap();
+            assert_eq!(decoded[1..], bytes[..]);
+        }
+        assert_eq!(
+            alphabet.decode("8€"),
+            Err(DecodeError::InvalidChar {
+                c: '€',
+                char_index: 1
+            })
+        );
+    }
+
+    #[test]
+    fn ltr() {
+        let alphabet = Alphabet::ltr();
+        // Hebrew, Arabic, Syriac, Thaana, N'Ko, Samaritan and Mandaic
+        let rtl = '\u{0590}'..='\u{08FF}';
+        assert!(ENC_TABLE.iter().any(|c| rtl.contains(c)));
+        assert!(!alphabet.table().iter().any(|c| rtl.contains(c)));
+        // the rest of the standard table is kept in place
+        assert_eq!(alphabet.table()[..200], ENC_TABLE[..200]);
+        let bytes = (0..=255).collect::<Vec<u8>>();
+        assert_eq!(alphabet.decode(&alphabet.encode(&bytes)), Ok(bytes));
+    }
+
+    #[test]
+    fn validation() {
+        assert_eq!(
+            Alphabet::from_chars(&ENC_TABLE[1..], TAIL),
+            Err(AlphabetError::TableLength(2047))
+        );
+        assert_eq!(
+            Alphabet::from_chars(ENC_TABLE, &TAIL[1..]),
+            Err(AlphabetError::TailLength(7))
+        );
+        let mut tail = *TAIL;
+        tail[7] = ENC_TABLE[100];
+        assert_eq!(
+            Alphabet::from_chars(ENC_TABLE, &tail),
+            Err(AlphabetError::Duplicate(ENC_TABLE[100]))
+        );
+    }
+
+    #[test]
+    fn out_of_range() {
+        let mut table = ENC_TABLE.to_vec();
+        table[5] = '\u{FFFF}';
+        assert!(Alphabet::from_chars(&table, TAIL).is_ok());
+        table[5] = '\u{10000}';
+        assert_eq!(
+            Alphabet::from_chars(&table, TAIL),
+            Err(AlphabetError::OutOfRange('\u{10000}'))
+        );
+    }
+
+    #[test]
+    fn builder() {
+        assert_eq!(Alphabet::builder().build(), Ok(Alphabet::standard()));
+        let ranges = Alphabet::builder()
+            .with_table_ranges(['\u{4E00}'..='\u{55FF}'])
+            .build()
+            .unwrap();
+        assert_eq!(ranges.table()[2047], '\u{55FF}');
+        assert_eq!(
+            Alphabet::builder().with_table("abc".chars()).build(),
+            Err(AlphabetError::TableLength(3))
+        );
+        assert_eq!(
+            Alphabet::builder().with_tail("01234567".chars()).build(),
+            Ok(Alphabet::standard())
+        );
+    }
+
+    #[test]
+    fn distant_code_points() {
+        let table = (0..2048u32)
+            .map(|i| char::from_u32(0x4E00 + i).unwrap())
+            .collect::<Vec<_>>();
+        let alphabet = Alphabet::from_chars(&table, TAIL).unwrap();
+        for len in 0..30 {
+            let bytes = (0..len).map(|i| (i * 97) as u8).collect::<Vec<_>>();
+            assert_eq!(alphabet.decode(&alphabet.encode(&bytes)), Ok(bytes));
+        }
+    }
+}
@@ -0,0 +1,154 @@
+use crate::{encode_with, tables::table_index, BITS_PER_CHAR, TAIL, TAIL_BITS};
+use alloc::vec::Vec;
+use core::{fmt, ops::Range};
+
+/// One character of an encoding along with the input bits it carries.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AnnotatedChar {
+    /// The output character.
+    pub c: char,
+    /// Its index into [`ENC_TABLE`](crate::ENC_TABLE), or into [`TAIL`] for a tail character.
+    pub value: u16,
+    /// Whether this is a tail character.
+    pub tail: bool,
+    /// The input bits carried by the character, counting from the most significant bit of the
+    /// first byte.
+    pub bits: Range<usize>,
+    /// How many (always set) padding bits follow the input bits in `value`.
+    pub padding: u32,
+}
+
+impl AnnotatedChar {
+    fn width(&self) -> u32 {
+        if self.tail {
+            TAIL_BITS
+        } else {
+            BITS_PER_CHAR
+        }
+    }
+}
+
+/// The bit-by-bit mapping from some input to its encoding, see [`annotate`].
+///
+/// The `Display` implementation renders it as a table with one row per character.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Annotation {
+    chars: Vec<AnnotatedChar>,
+}
+
+impl Annotation {
+    /// The annotated characters in output order.
+    pub fn chars(&self) -> &[AnnotatedChar] {
+        &self.chars
+    }
+}
+
+impl fmt::Display for Annotation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:>5}  char  {:>6}  {:<12}  input bits",
+            "#", "value", "bits"
+        )?;
+        for (i, annotated) in self.chars.iter().enumerate() {
+            let width = annotated.width();
+            let data_bits = width - annotated.padding;
+            let data = annotated.value >> annotated.padding;
+            let mut bits = [b' '; 12];
+            for bit in 0..data_bits {
+                bits[bit as usize] = b'0' + (data >> (data_bits - 1 - bit) & 1) as u8;
+            }
+            if annotated.padding > 0 {
+                bits[data_bits as usize] = b'|';
+                for bit in 0..annotated.padding {
+                    bits[(data_bits + 1 + bit) as usize] = b'1';
+                }
+            }
+            writeln!(
+                f,
+                "{:>5}  {:<4}  {:>6}  {}  {}..{}{}",
+                i,
+                annotated.c,
+                annotated.value,
+                // only ascii was written to `bits`
+                core::str::from_utf8(&bits).unwrap(),
+                annotated.bits.start,
+                annotated.bits.end,
+                if annotated.tail { " (tail)" } else { "" },
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Show which bits of `bytes` end up in which character of its encoding
+///
+/// This is meant for documentation and for debugging disagreements with other implementations.
+/// Padding bits are shown after a `|`.
+///
+/// # Example
+/// ```
+/// let annotation = base2048::annotate(&[0xFF, 0xFF]);
+/// assert_eq!(annotation.chars()[1].bits, 11..16);
+/// assert_eq!(annotation.chars()[1].padding, 6);
+/// print!("{}", annotation);
+/// ```
+pub fn annotate(bytes: &[u8]) -> Annotation {
+    let total_bits = bytes.len() * 8;
+    let mut chars = Vec::new();
+    let mut position = 0;
+
+    encode_with(bytes, |c| {
+        let (value, tail, width) = match table_index(c as u32) {
+            Some(value) => (value, false, BITS_PER_CHAR as usize),
+            None => {
+                let value = TAIL.iter().position(|t| *t == c).unwrap() as u16;
+                (value, true, TAIL_BITS as usize)
+            }
+        };
+        let end = (position + width).min(total_bits);
+        chars.push(AnnotatedChar {
+            c,
+            value,
+            tail,
+            bits: position..end,
+            padding: (width - (end - position)) as u32,
+        });
+        position = end;
+    });
+
+    Annotation { chars }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::string::ToString;
+
+    #[test]
+    fn annotations_cover_every_bit() {
+        for len in 0..23 {
+            let bytes = (0..len).map(|i| (i * 13) as u8).collect::<Vec<_>>();
+            let annotation = annotate(&bytes);
+            let mut position = 0;
+            for annotated in annotation.chars() {
+                assert_eq!(annotated.bits.start, position);
+                assert_eq!(
+                    annotated.value & !(!0 << annotated.padding),
+                    !(!0 << annotated.padding)
+                );
+                position = annotated.bits.end;
+            }
+            assert_eq!(position, len * 8);
+        }
+    }
+
+    #[test]
+    fn table() {
+        assert_eq!(
+            annotate(&[0xFF]).to_string(),
+            "    #  char   value  bits          input bits\n    \
+                 0  ၕ       2047  11111111|111  0..8\n"
+        );
+    }
+}
@@ -0,0 +1,618 @@
+//! base1024, a smaller variant of base2048 for firmware where the full tables don't fit.
+//!
+//! Each character carries 10 bits and is a letter from the two-byte range of UTF-8 (U+0080 to
+//! U+07FF) that neither NFC nor NFKC normalization changes, so the text takes two bytes per
+//! character and survives the same handling as base2048. Its tables take about 5 KiB, against about
+//! 24 KiB for base2048's. The bits are taken from the most significant end; when only 2 are left over at
+//! the end they go in a tail character (`0` to `3`), and otherwise the final character is padded
+//! with ones.
+//!
+//! The functions and adapters mirror the base2048 ones at the top of the crate and report the same
+//! [`DecodeError`]s.
+//!
+//! # Example
+//! ```
+//! use base2048::base1024;
+//! let encoded = base1024::encode(b"hello");
+//! assert_eq!(encoded.chars().count(), base1024::encoded_len(5));
+//! assert_eq!(encoded.len(), 2 * encoded.chars().count());
+//! assert_eq!(base1024::decode(&encoded).as_deref(), Some(&b"hello"[..]));
+//! ```
+use crate::{diagnostics, DecodeError};
+use alloc::{string::String, vec::Vec};
+
+/// The table characters, as the first and last of each run in table order.
+const REPERTOIRE: [(char, char); 29] = [
+    ('\u{00C0}', '\u{00D6}'),
+    ('\u{00D8}', '\u{00F6}'),
+    ('\u{00F8}', '\u{0131}'),
+    ('\u{0134}', '\u{013E}'),
+    ('\u{0141}', '\u{0148}'),
+    ('\u{014A}', '\u{017E}'),
+    ('\u{0180}', '\u{01C3}'),
+    ('\u{01CD}', '\u{01F0}'),
+    ('\u{01F4}', '\u{02AF}'),
+    ('\u{0370}', '\u{0373}'),
+    ('\u{0376}', '\u{0377}'),
+    ('\u{037B}', '\u{037D}'),
+    ('\u{037F}', '\u{037F}'),
+    ('\u{0386}', '\u{0386}'),
+    ('\u{0388}', '\u{038A}'),
+    ('\u{038C}', '\u{038C}'),
+    ('\u{038E}', '\u{03A1}'),
+    ('\u{03A3}', '\u{03CF}'),
+    ('\u{03D7}', '\u{03EF}'),
+    ('\u{03F3}', '\u{03F3}'),
+    ('\u{03F7}', '\u{03F8}'),
+    ('\u{03FA}', '\u{0481}'),
+    ('\u{048A}', '\u{052F}'),
+    ('\u{0531}', '\u{0556}'),
+    ('\u{0560}', '\u{0586}'),
+    ('\u{0588}', '\u{0588}'),
+    ('\u{05D0}', '\u{05EA}'),
+    ('\u{05EF}', '\u{05F2}'),
+    ('\u{0620}', '\u{063C}'),
+];
+
+/// The characters for the last 2 bits, when that is all that is left.
+pub const TAIL: &[char; 4] = &['0', '1', '2', '3'];
+
+/// The number of bits encoded per character.
+pub const BITS_PER_CHAR: u32 = 10;
+
+/// The number of bits encoded in a tail character.
+pub const TAIL_BITS: u32 = 2;
+
+/// The table characters, in order.
+static ENC: [u16; 1 << BITS_PER_CHAR] = enc();
+
+/// The table position of each code point up to the last table character, or [`NONE`].
+static DEC: [u16; N_DEC] = dec();
+
+const N_DEC: usize = REPERTOIRE[REPERTOIRE.len() - 1].1 as usize + 1;
+const NONE: u16 = 0xFFFF;
+
+const fn enc() -> [u16; 1 << BITS_PER_CHAR] {
+    let mut enc = [0; 1 << BITS_PER_CHAR];
+    let mut n = 0;
+    let mut i = 0;
+    while i < REPERTOIRE.len() {
+        let (first, last) = REPERTOIRE[i];
+        let mut c = first as u32;
+        while c <= last as u32 {
+            enc[n] = c as u16;
+            n += 1;
+            c += 1;
+        }
+        i += 1;
+    }
+    assert!(
+        n == enc.len(),
+        "the repertoire has the wrong number of characters"
+    );
+    enc
+}
+
+const fn dec() -> [u16; N_DEC] {
+    let mut dec = [NONE; N_DEC];
+    let mut i = 0;
+    while i < ENC.len() {
+        dec[ENC[i] as usize] = i as u16;
+        i += 1;
+    }
+    dec
+}
+
+/// The table character for `index`.
+fn char_for(index: u32) -> char {
+    // SAFETY: the table only holds characters below the surrogates
+    unsafe { char::from_u32_unchecked(ENC[index as usize] as u32) }
+}
+
+/// The table position of `c`, if it is a table character.
+fn index_of(c: char) -> Option<u32> {
+    match *DEC.get(c as usize)? {
+        NONE => None,
+        index => Some(index as u32),
+    }
+}
+
+/// Encodes bytes one at a time, with up to 9 bits held back between characters.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct EncodeState {
+    bits: u32,
+    n_bits: u32,
+}
+
+impl EncodeState {
+    /// Adds a byte, returning the character it completes (if any).
+    pub(crate) fn push(&mut self, byte: u8) -> Option<char> {
+        self.bits = (self.bits << 8) | byte as u32;
+        self.n_bits += 8;
+        if self.n_bits < BITS_PER_CHAR {
+            return None;
+        }
+        self.n_bits -= BITS_PER_CHAR;
+        let c = char_for(self.bits >> self.n_bits);
+        self.bits &= (1 << self.n_bits) - 1;
+        Some(c)
+    }
+
+    /// The final character, if there are bits left over.
+    pub(crate) fn finish(self) -> Option<char> {
+        match self.n_bits {
+            0 => None,
+            // the input is whole bytes, so this is exactly `TAIL_BITS`
+            1..=TAIL_BITS => Some(TAIL[self.bits as usize]),
+            _ => {
+                let padding = BITS_PER_CHAR - self.n_bits;
+                Some(char_for((self.bits << padding) | ((1 << padding) - 1)))
+            }
+        }
+    }
+}
+
+/// Decodes characters one at a time.
+///
+/// Tail characters aren't table characters, so nothing needs holding back.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct DecodeState {
+    bits: u32,
+    n_bits: u32,
+    /// How many characters have been decoded.
+    chars: usize,
+    /// The position of the tail character, once there has been one.
+    ended: Option<usize>,
+}
+
+impl DecodeState {
+    pub(crate) fn push(&mut self, c: char, mut push: impl FnMut(u8)) -> Result<(), DecodeError> {
+        let char_index = self.chars;
+        if let Some(char_index) = self.ended {
+            return Err(DecodeError::TrailingData { char_index });
+        }
+        let (value, width) = match (index_of(c), TAIL.iter().position(|&t| t == c)) {
+            (Some(index), _) => (index, BITS_PER_CHAR),
+            // a tail character has to complete a byte exactly
+            (None, Some(_)) if self.n_bits + TAIL_BITS != 8 => {
+                return Err(DecodeError::UnexpectedTail { char_index })
+            }
+            (None, Some(tail)) => {
+                self.ended = Some(char_index);
+                (tail as u32, TAIL_BITS)
+            }
+            (None, None) => return Err(DecodeError::InvalidChar { c, char_index }),
+        };
+        self.bits = (self.bits << width) | value;
+        self.n_bits += width;
+        while self.n_bits >= 8 {
+            self.n_bits -= 8;
+            push((self.bits >> self.n_bits) as u8);
+        }
+        self.bits &= (1 << self.n_bits) - 1;
+        self.chars += 1;
+        Ok(())
+    }
+
+    /// Checks the bits left over are padding.
+    pub(crate) fn finish(self) -> Result<(), DecodeError> {
+        match self.bits == (1 << self.n_bits) - 1 {
+            true => Ok(()),
+            false => Err(DecodeError::InvalidPadding {
+                char_index: self.chars - 1,
+            }),
+        }
+    }
+}
+
+/// The number of characters `n_bytes` bytes encode to.
+///
+/// Every character takes two bytes of UTF-8 except a tail character, which takes one.
+///
+/// # Example
+/// ```
+/// assert_eq!(base2048::base1024::encoded_len(5), 4);
+/// ```
+pub const fn encoded_len(n_bytes: usize) -> usize {
+    (n_bytes / 5) * 4 + (n_bytes % 5 * 8).div_ceil(BITS_PER_CHAR as usize)
+}
+
+/// Encode some bytes using base1024 encoding
+///
+/// # Example
+/// ```
+/// let encoded = base2048::base1024::encode(b"hello");
+/// assert_eq!(encoded.chars().count(), 4);
+/// ```
+pub fn encode(bytes: impl AsRef<[u8]>) -> String {
+    let mut ret = String::new();
+    encode_into(bytes.as_ref(), &mut ret);
+    ret
+}
+
+/// Encode some bytes using base1024 encoding, appending the text to `out`
+pub fn encode_into(bytes: &[u8], out: &mut String) {
+    out.reserve(encoded_len(bytes.len()) * 2);
+    let mut state = EncodeState::default();
+    out.extend(bytes.iter().filter_map(|byte| state.push(*byte)));
+    out.extend(state.finish());
+}
+
+/// Decode a base1024 encoded string
+///
+/// # Example
+/// ```
+/// use base2048::base1024;
+/// assert_eq!(base1024::decode(base1024::encode(b"hello")), Some(b"hello".to_vec()));
+/// assert_eq!(base1024::decode("hello"), None);
+/// ```
+pub fn decode(string: impl AsRef<str>) -> Option<Vec<u8>> {
+    try_decode(string).ok()
+}
+
+/// Decode a base1024 encoded string, saying what is wrong with it if it is invalid
+///
+/// # Example
+/// ```
+/// use base2048::{base1024, DecodeError};
+/// assert_eq!(
+///     base1024::try_decode("ÀX"),
+///     Err(DecodeError::InvalidChar { c: 'X', char_index: 1 })
+/// );
+/// ```
+pub fn try_decode(string: impl AsRef<str>) -> Result<Vec<u8>, DecodeError> {
+    let string = string.as_ref();
+    // every table character is two bytes of UTF-8 and gives 10 bits
+    let mut out = Vec::with_capacity(string.len() * 5 / 8);
+    let mut state = DecodeState::default();
+    for c in string.chars() {
+        state
+            .push(c, |byte| out.push(byte))
+            .map_err(diagnostics::decode_error)?;
+    }
+    state.finish().map_err(diagnostics::decode_error)?;
+    Ok(out)
+}
+
+/// Encodes base1024 from bytes that arrive in chunks, like [`crate::Encoder`].
+///
+/// # Example
+/// ```
+/// use base2048::base1024;
+/// let mut encoder = base1024::Encoder::new();
+/// let mut encoded = String::new();
+/// for chunk in [&b"hel"[..], b"lo"] {
+///     encoder.push(chunk, &mut encoded);
+/// }
+/// encoder.finish(&mut encoded);
+/// assert_eq!(encoded, base1024::encode(b"hello"));
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Encoder {
+    state: EncodeState,
+}
+
+impl Encoder {
+    /// An encoder that hasn't been given anything yet.
+    pub fn new() -> Self {
+        Encoder::default()
+    }
+
+    /// Encodes `bytes`, adding the characters they complete to `out`.
+    pub fn push(&mut self, bytes: &[u8], out: &mut impl Extend<char>) {
+        let state = &mut self.state;
+        out.extend(bytes.iter().filter_map(|byte| state.push(*byte)));
+    }
+
+    /// Adds the final character to `out`, if the input so far doesn't fill a whole number of
+    /// characters.
+    pub fn finish(self, out: &mut impl Extend<char>) {
+        out.extend(self.state.finish());
+    }
+}
+
+/// Decodes base1024 text that arrives in chunks, like [`crate::Decoder`].
+///
+/// # Example
+/// ```
+/// use base2048::base1024;
+/// let encoded = base1024::encode(b"hello");
+/// let (first, second) = encoded.split_at(encoded.char_indices().nth(2).unwrap().0);
+/// let mut decoder = base1024::Decoder::new();
+/// let mut decoded = Vec::new();
+/// decoder.push(first, &mut decoded).unwrap();
+/// decoder.push(second, &mut decoded).unwrap();
+/// decoder.finish().unwrap();
+/// assert_eq!(decoded, b"hello");
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Decoder {
+    state: DecodeState,
+    /// Set once the input turns out to be invalid.
+    error: Option<DecodeError>,
+}
+
+impl Decoder {
+    /// A decoder that hasn't been given anything yet.
+    pub fn new() -> Self {
+        Decoder::default()
+    }
+
+    /// Decodes `text`, adding the bytes it completes to `out`.
+    ///
+    /// Once an error has been returned, every later call returns it again and adds nothing.
+    pub fn push(&mut self, text: &str, out: &mut impl Extend<u8>) -> Result<(), DecodeError> {
+        if let Some(e) = self.error {
+            return Err(e);
+        }
+        for c in text.chars() {
+            if let Err(e) = self
+                .state
+                .push(c, |byte| out.extend(core::iter::once(byte)))
+            {
+                self.error = Some(e);
+                return Err(diagnostics::decode_error(e));
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks the text ended properly.
+    ///
+    /// Every byte has already been added by [`push`](Self::push), since no character is held back.
+    pub fn finish(self) -> Result<(), DecodeError> {
+        if let Some(e) = self.error {
+            return Err(e);
+        }
+        self.state.finish().map_err(diagnostics::decode_error)
+    }
+}
+
+#[cfg(feature = "std")]
+pub use self::io::{DecodeReader, EncodeWriter};
+
+#[cfg(feature = "std")]
+mod io {
+    use super::{DecodeState, EncodeState};
+    use crate::{
+        std_io::{Reader, Scheme, Writer},
+        DecodeError,
+    };
+    use std::io::{self, Read, Write};
+
+    /// The base1024 [`Scheme`].
+    #[derive(Clone, Copy, Debug, Default)]
+    pub(crate) struct Base1024;
+
+    impl Scheme for Base1024 {
+        type Encode = EncodeState;
+        type Decode = DecodeState;
+
+        fn push_byte(state: &mut EncodeState, byte: u8) -> Option<char> {
+            state.push(byte)
+        }
+
+        fn finish_bytes(state: EncodeState) -> Option<char> {
+            state.finish()
+        }
+
+        fn push_char(
+            state: &mut DecodeState,
+            c: char,
+            push: impl FnMut(u8),
+        ) -> Result<(), DecodeError> {
+            state.push(c, push)
+        }
+
+        fn finish_chars(state: DecodeState, _: impl FnMut(u8)) -> Result<(), DecodeError> {
+            state.finish()
+        }
+    }
+
+    /// Encodes everything written to it, writing the UTF-8 base1024 text to an inner writer, like
+    /// [`crate::EncodeWriter`].
+    ///
+    /// # Example
+    /// ```
+    /// use base2048::base1024;
+    /// use std::io::Write;
+    /// let mut writer = base1024::EncodeWriter::new(Vec::new());
+    /// writer.write_all(b"hel")?;
+    /// writer.write_all(b"lo")?;
+    /// let encoded = writer.finish()?;
+    /// assert_eq!(encoded, base1024::encode(b"hello").into_bytes());
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[derive(Debug)]
+    pub struct EncodeWriter<W: Write>(Writer<W, Base1024>);
+
+    impl<W: Write> EncodeWriter<W> {
+        /// Writes the encoded text to `inner`.
+        pub fn new(inner: W) -> Self {
+            EncodeWriter(Writer::new(inner))
+        }
+
+        /// The underlying writer.
+        pub fn get_ref(&self) -> &W {
+            self.0.get_ref()
+        }
+
+        /// The underlying writer.
+        ///
+        /// Writing to it directly puts the text out of order with what is still buffered.
+        pub fn get_mut(&mut self) -> &mut W {
+            self.0.get_mut()
+        }
+
+        /// Writes out the final character and everything still buffered, then gives back the
+        /// underlying writer.
+        pub fn finish(self) -> io::Result<W> {
+            self.0.finish()
+        }
+    }
+
+    impl<W: Write> Write for EncodeWriter<W> {
+        fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+            self.0.write(bytes)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            self.0.flush()
+        }
+    }
+
+    /// Decodes UTF-8 base1024 text read from an inner reader, giving the decoded bytes, like
+    /// [`crate::DecodeReader`].
+    ///
+    /// # Example
+    /// ```
+    /// use base2048::base1024;
+    /// use std::io::Read;
+    /// let encoded = base1024::encode(b"hello");
+    /// let mut decoded = Vec::new();
+    /// base1024::DecodeReader::new(encoded.as_bytes()).read_to_end(&mut decoded)?;
+    /// assert_eq!(decoded, b"hello");
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[derive(Debug)]
+    pub struct DecodeReader<R: Read>(Reader<R, Base1024>);
+
+    impl<R: Read> DecodeReader<R> {
+        /// Reads the encoded text from `inner`.
+        pub fn new(inner: R) -> Self {
+            DecodeReader(Reader::new(inner))
+        }
+
+        /// The underlying reader.
+        pub fn get_ref(&self) -> &R {
+            self.0.get_ref()
+        }
+
+        /// The underlying reader.
+        ///
+        /// Reading from it directly skips text this reader would have decoded.
+        pub fn get_mut(&mut self) -> &mut R {
+            self.0.get_mut()
+        }
+
+        /// Gives back the underlying reader, dropping anything read from it but not yet decoded.
+        pub fn into_inner(self) -> R {
+            self.0.into_inner()
+        }
+    }
+
+    impl<R: Read> Read for DecodeReader<R> {
+        fn read(&mut self, dest: &mut [u8]) -> io::Result<usize> {
+            self.0.read(dest)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn table() {
+        for index in 0..1 << BITS_PER_CHAR {
+            let c = char_for(index);
+            assert_eq!(c.len_utf8(), 2);
+            assert_eq!(index_of(c), Some(index));
+            assert!(c.is_alphabetic());
+        }
+        for c in TAIL {
+            assert_eq!(index_of(*c), None);
+        }
+        assert_eq!(index_of('\u{10FFFF}'), None);
+        assert!(core::mem::size_of_val(&ENC) + core::mem::size_of_val(&DEC) < 6 * 1024);
+    }
+
+    #[test]
+    fn round_trips() {
+        for len in 0..40 {
+            let bytes = (0..len).map(|i| (i * 37 + 11) as u8).collect::<Vec<_>>();
+            let encoded = encode(&bytes);
+            assert_eq!(encoded.chars().count(), encoded_len(len));
+            assert_eq!(try_decode(&encoded), Ok(bytes));
+        }
+    }
+
+    #[test]
+    fn final_characters() {
+        // 1 byte leaves 2 bits of padding
+        assert_eq!(encode([0]), String::from(char_for(0b11)));
+        // 2 bytes leave 6 bits for a second character, padded with 4 ones
+        assert_eq!(encode([0xFF, 0xFF]).chars().nth(1), Some(char_for(0x3FF)));
+        // 4 bytes leave 2 bits, which need a tail character
+        assert_eq!(encode([0, 0, 0, 0xFF]).chars().last(), Some('3'));
+        assert_eq!(encoded_len(4), 4);
+    }
+
+    #[test]
+    fn errors() {
+        let zero = String::from(char_for(0));
+        assert_eq!(
+            try_decode(zero.repeat(3) + "0" + &zero),
+            Err(DecodeError::TrailingData { char_index: 3 })
+        );
+        assert_eq!(
+            try_decode(zero.clone() + "0"),
+            Err(DecodeError::UnexpectedTail { char_index: 1 })
+        );
+        assert_eq!(
+            try_decode(&zero),
+            Err(DecodeError::InvalidPadding { char_index: 0 })
+        );
+        assert_eq!(
+            try_decode(zero + "a"),
+            Err(DecodeError::InvalidChar {
+                c: 'a',
+                char_index: 1
+            })
+        );
+    }
+
+    #[test]
+    fn chunks() {
+        let bytes = (0..100).map(|i| (i * 37) as u8).collect::<Vec<_>>();
+        let encoded = encode(&bytes);
+        let chars = encoded.chars().collect::<Vec<_>>();
+        for chunk_len in 1..20 {
+            let mut encoder = Encoder::new();
+            let mut text = String::new();
+            for chunk in bytes.chunks(chunk_len) {
+                encoder.push(chunk, &mut text);
+            }
+            encoder.finish(&mut text);
+            assert_eq!(text, encoded);
+
+            let mut decoder = Decoder::new();
+            let mut decoded = Vec::new();
+            for chunk in chars.chunks(chunk_len) {
+                let chunk = chunk.iter().collect::<String>();
+                decoder.push(&chunk, &mut decoded).unwrap();
+            }
+            decoder.finish().unwrap();
+            assert_eq!(decoded, bytes);
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn io() {
+        use std::io::{Read, Write};
+        let bytes = (0..20_003).map(|i| (i * 37) as u8).collect::<Vec<_>>();
+        let mut writer = EncodeWriter::new(Vec::new());
+        for chunk in bytes.chunks(777) {
+            writer.write_all(chunk).unwrap();
+        }
+        let encoded = writer.finish().unwrap();
+        assert_eq!(encoded, encode(&bytes).into_bytes());
+
+        let mut decoded = Vec::new();
+        DecodeReader::new(&encoded[..])
+            .read_to_end(&mut decoded)
+            .unwrap();
+        assert_eq!(decoded, bytes);
+    }
+}
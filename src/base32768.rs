@@ -0,0 +1,672 @@
+//! base32768, base2048's denser sibling from the same family of encodings.
+//!
+//! Each character carries 15 bits and is a single UTF-16 code unit, so on platforms that count
+//! text in UTF-16 this packs about twice as much into a length limit as base2048 does, at the cost
+//! of three UTF-8 bytes for most characters. The output is the same as qntm's `base32768`: bits are
+//! taken 15 at a time from the most significant end, and if 7 or fewer are left over at the end
+//! they go in a character from a separate repertoire of 128, with the padding bits set to one.
+//!
+//! The functions and adapters here mirror the base2048 ones at the top of the crate and report the
+//! same [`DecodeError`]s; a final 7-bit character plays the part of a tail character.
+//!
+//! # Example
+//! ```
+//! use base2048::base32768;
+//! let encoded = base32768::encode(b"hello");
+//! assert_eq!(encoded.chars().count(), base32768::encoded_len(5));
+//! assert_eq!(encoded.encode_utf16().count(), encoded.chars().count());
+//! assert_eq!(base32768::decode(&encoded).as_deref(), Some(&b"hello"[..]));
+//! ```
+use crate::{diagnostics, DecodeError};
+use alloc::{string::String, vec::Vec};
+
+/// The characters for 15 bits, as the first and last of each block in order.
+const REPERTOIRE_15: [(char, char); 52] = [
+    ('\u{04A0}', '\u{04BF}'),
+    ('\u{0500}', '\u{051F}'),
+    ('\u{0680}', '\u{06BF}'),
+    ('\u{0760}', '\u{079F}'),
+    ('\u{07C0}', '\u{07DF}'),
+    ('\u{1000}', '\u{101F}'),
+    ('\u{10A0}', '\u{10BF}'),
+    ('\u{1100}', '\u{115F}'),
+    ('\u{1180}', '\u{119F}'),
+    ('\u{11E0}', '\u{123F}'),
+    ('\u{1260}', '\u{127F}'),
+    ('\u{12E0}', '\u{12FF}'),
+    ('\u{1320}', '\u{133F}'),
+    ('\u{13A0}', '\u{13DF}'),
+    ('\u{1420}', '\u{165F}'),
+    ('\u{16A0}', '\u{16DF}'),
+    ('\u{1780}', '\u{179F}'),
+    ('\u{1820}', '\u{185F}'),
+    ('\u{18C0}', '\u{18DF}'),
+    ('\u{1980}', '\u{199F}'),
+    ('\u{19E0}', '\u{19FF}'),
+    ('\u{1A20}', '\u{1A3F}'),
+    ('\u{1BC0}', '\u{1BDF}'),
+    ('\u{1C00}', '\u{1C1F}'),
+    ('\u{1D00}', '\u{1D1F}'),
+    ('\u{21E0}', '\u{21FF}'),
+    ('\u{22C0}', '\u{22DF}'),
+    ('\u{2340}', '\u{23DF}'),
+    ('\u{2400}', '\u{241F}'),
+    ('\u{2500}', '\u{275F}'),
+    ('\u{2780}', '\u{27BF}'),
+    ('\u{2800}', '\u{297F}'),
+    ('\u{29A0}', '\u{29BF}'),
+    ('\u{2A20}', '\u{2A5F}'),
+    ('\u{2A80}', '\u{2ABF}'),
+    ('\u{2AE0}', '\u{2B5F}'),
+    ('\u{2C00}', '\u{2C1F}'),
+    ('\u{2C80}', '\u{2CDF}'),
+    ('\u{2D00}', '\u{2D1F}'),
+    ('\u{2D40}', '\u{2D5F}'),
+    ('\u{2EA0}', '\u{2EDF}'),
+    ('\u{31C0}', '\u{31DF}'),
+    ('\u{3400}', '\u{4D9F}'),
+    ('\u{4DC0}', '\u{9FBF}'),
+    ('\u{A000}', '\u{A47F}'),
+    ('\u{A4A0}', '\u{A4BF}'),
+    ('\u{A500}', '\u{A5FF}'),
+    ('\u{A640}', '\u{A65F}'),
+    ('\u{A6A0}', '\u{A6DF}'),
+    ('\u{A700}', '\u{A75F}'),
+    ('\u{A780}', '\u{A79F}'),
+    ('\u{A840}', '\u{A85F}'),
+];
+
+/// The characters for a final 7 bits, as for [`REPERTOIRE_15`].
+const REPERTOIRE_7: [(char, char); 2] = [('\u{0180}', '\u{019F}'), ('\u{0240}', '\u{029F}')];
+
+/// Every block in the repertoires is this many bits' worth of aligned code points.
+const BLOCK_BITS: u32 = 5;
+
+/// The first code point of each block of 32 characters for 15 bits.
+static ENC_15: [u16; 1 << (15 - BLOCK_BITS)] = blocks(&REPERTOIRE_15);
+
+/// The first code point of each block of 32 characters for 7 bits.
+static ENC_7: [u16; 1 << (7 - BLOCK_BITS)] = blocks(&REPERTOIRE_7);
+
+/// For each block of 32 code points, its position in [`ENC_15`], or in [`ENC_7`] with
+/// [`FINAL`] set, or [`NONE`].
+static DEC: [u16; N_DEC] = dec();
+
+const N_DEC: usize = (REPERTOIRE_15[REPERTOIRE_15.len() - 1].1 as usize >> BLOCK_BITS) + 1;
+const FINAL: u16 = 0x8000;
+const NONE: u16 = 0xFFFF;
+
+const fn blocks<const N: usize>(repertoire: &[(char, char)]) -> [u16; N] {
+    let mut blocks = [0; N];
+    let mut n = 0;
+    let mut i = 0;
+    while i < repertoire.len() {
+        let (first, last) = repertoire[i];
+        let mut start = first as u32;
+        while start < last as u32 {
+            blocks[n] = start as u16;
+            n += 1;
+            start += 1 << BLOCK_BITS;
+        }
+        i += 1;
+    }
+    assert!(n == N, "the repertoire has the wrong number of characters");
+    blocks
+}
+
+const fn dec() -> [u16; N_DEC] {
+    let mut dec = [NONE; N_DEC];
+    let mut i = 0;
+    while i < ENC_15.len() {
+        dec[ENC_15[i] as usize >> BLOCK_BITS] = i as u16;
+        i += 1;
+    }
+    i = 0;
+    while i < ENC_7.len() {
+        dec[ENC_7[i] as usize >> BLOCK_BITS] = FINAL | i as u16;
+        i += 1;
+    }
+    dec
+}
+
+/// The character for `value`, taken from `blocks`.
+fn char_for(blocks: &[u16], value: u32) -> char {
+    let c = blocks[(value >> BLOCK_BITS) as usize] as u32 + (value & ((1 << BLOCK_BITS) - 1));
+    // SAFETY: every block is made of characters below the surrogates
+    unsafe { char::from_u32_unchecked(c) }
+}
+
+/// The value of `c` and whether it is a final 7-bit character, if it is in either repertoire.
+fn value_of(c: char) -> Option<(u32, bool)> {
+    let c = c as u32;
+    let block = *DEC.get((c >> BLOCK_BITS) as usize)?;
+    let low = c & ((1 << BLOCK_BITS) - 1);
+    match block {
+        NONE => None,
+        _ => Some((
+            (((block & !FINAL) as u32) << BLOCK_BITS) | low,
+            block & FINAL != 0,
+        )),
+    }
+}
+
+/// Encodes bytes one at a time, with up to 14 bits held back between characters.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct EncodeState {
+    bits: u32,
+    n_bits: u32,
+}
+
+impl EncodeState {
+    /// Adds a byte, returning the character it completes (if any).
+    pub(crate) fn push(&mut self, byte: u8) -> Option<char> {
+        self.bits = (self.bits << 8) | byte as u32;
+        self.n_bits += 8;
+        if self.n_bits < 15 {
+            return None;
+        }
+        self.n_bits -= 15;
+        let c = char_for(&ENC_15, self.bits >> self.n_bits);
+        self.bits &= (1 << self.n_bits) - 1;
+        Some(c)
+    }
+
+    /// The final character, padded with ones, if there are bits left over.
+    pub(crate) fn finish(self) -> Option<char> {
+        let (blocks, width): (&[u16], u32) = match self.n_bits {
+            0 => return None,
+            1..=7 => (&ENC_7, 7),
+            _ => (&ENC_15, 15),
+        };
+        let padding = width - self.n_bits;
+        Some(char_for(
+            blocks,
+            (self.bits << padding) | ((1 << padding) - 1),
+        ))
+    }
+}
+
+/// Decodes characters one at a time.
+///
+/// Unlike base2048's tail characters, a final 7-bit character can be told apart on sight, so
+/// nothing is held back.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct DecodeState {
+    bits: u32,
+    n_bits: u32,
+    /// How many characters have been decoded.
+    chars: usize,
+    /// The position of the final 7-bit character, once there has been one.
+    ended: Option<usize>,
+}
+
+impl DecodeState {
+    pub(crate) fn push(&mut self, c: char, mut push: impl FnMut(u8)) -> Result<(), DecodeError> {
+        let char_index = self.chars;
+        if let Some(char_index) = self.ended {
+            return Err(DecodeError::TrailingData { char_index });
+        }
+        let (value, last) = value_of(c).ok_or(DecodeError::InvalidChar { c, char_index })?;
+        if last {
+            // 7 bits on their own would all be padding
+            if self.n_bits == 0 {
+                return Err(DecodeError::UnexpectedTail { char_index });
+            }
+            self.ended = Some(char_index);
+        }
+        let width = if last { 7 } else { 15 };
+        self.bits = (self.bits << width) | value;
+        self.n_bits += width;
+        while self.n_bits >= 8 {
+            self.n_bits -= 8;
+            push((self.bits >> self.n_bits) as u8);
+        }
+        self.bits &= (1 << self.n_bits) - 1;
+        self.chars += 1;
+        Ok(())
+    }
+
+    /// Checks the bits left over are padding.
+    pub(crate) fn finish(self) -> Result<(), DecodeError> {
+        match self.bits == (1 << self.n_bits) - 1 {
+            true => Ok(()),
+            false => Err(DecodeError::InvalidPadding {
+                char_index: self.chars - 1,
+            }),
+        }
+    }
+}
+
+/// The number of characters `n_bytes` bytes encode to.
+///
+/// Each character is one UTF-16 code unit, so this is also the length in UTF-16.
+///
+/// # Example
+/// ```
+/// assert_eq!(base2048::base32768::encoded_len(15), 8);
+/// assert_eq!(base2048::base32768::encoded_len(16), 9);
+/// ```
+pub const fn encoded_len(n_bytes: usize) -> usize {
+    (n_bytes / 15) * 8 + (n_bytes % 15 * 8).div_ceil(15)
+}
+
+/// Encode some bytes using base32768 encoding
+///
+/// # Example
+/// ```
+/// let encoded = base2048::base32768::encode(b"hello");
+/// assert_eq!(encoded.chars().count(), 3);
+/// ```
+pub fn encode(bytes: impl AsRef<[u8]>) -> String {
+    let mut ret = String::new();
+    encode_into(bytes.as_ref(), &mut ret);
+    ret
+}
+
+/// Encode some bytes using base32768 encoding, appending the text to `out`
+pub fn encode_into(bytes: &[u8], out: &mut String) {
+    // most characters take three bytes
+    out.reserve(encoded_len(bytes.len()) * 3);
+    let mut state = EncodeState::default();
+    out.extend(bytes.iter().filter_map(|byte| state.push(*byte)));
+    out.extend(state.finish());
+}
+
+/// Decode a base32768 encoded string
+///
+/// # Example
+/// ```
+/// use base2048::base32768;
+/// assert_eq!(base32768::decode(base32768::encode(b"hello")), Some(b"hello".to_vec()));
+/// assert_eq!(base32768::decode("hello"), None);
+/// ```
+pub fn decode(string: impl AsRef<str>) -> Option<Vec<u8>> {
+    try_decode(string).ok()
+}
+
+/// Decode a base32768 encoded string, saying what is wrong with it if it is invalid
+///
+/// # Example
+/// ```
+/// use base2048::{base32768, DecodeError};
+/// assert_eq!(
+///     base32768::try_decode("ҠX"),
+///     Err(DecodeError::InvalidChar { c: 'X', char_index: 1 })
+/// );
+/// ```
+pub fn try_decode(string: impl AsRef<str>) -> Result<Vec<u8>, DecodeError> {
+    let string = string.as_ref();
+    let mut out = Vec::with_capacity(string.len() * 15 / 24);
+    let mut state = DecodeState::default();
+    for c in string.chars() {
+        state
+            .push(c, |byte| out.push(byte))
+            .map_err(diagnostics::decode_error)?;
+    }
+    state.finish().map_err(diagnostics::decode_error)?;
+    Ok(out)
+}
+
+/// Encodes base32768 from bytes that arrive in chunks, like [`crate::Encoder`].
+///
+/// # Example
+/// ```
+/// use base2048::base32768;
+/// let mut encoder = base32768::Encoder::new();
+/// let mut encoded = String::new();
+/// for chunk in [&b"hel"[..], b"lo"] {
+///     encoder.push(chunk, &mut encoded);
+/// }
+/// encoder.finish(&mut encoded);
+/// assert_eq!(encoded, base32768::encode(b"hello"));
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Encoder {
+    state: EncodeState,
+}
+
+impl Encoder {
+    /// An encoder that hasn't been given anything yet.
+    pub fn new() -> Self {
+        Encoder::default()
+    }
+
+    /// Encodes `bytes`, adding the characters they complete to `out`.
+    pub fn push(&mut self, bytes: &[u8], out: &mut impl Extend<char>) {
+        let state = &mut self.state;
+        out.extend(bytes.iter().filter_map(|byte| state.push(*byte)));
+    }
+
+    /// Adds the final character to `out`, if the input so far doesn't fill a whole number of
+    /// characters.
+    pub fn finish(self, out: &mut impl Extend<char>) {
+        out.extend(self.state.finish());
+    }
+}
+
+/// Decodes base32768 text that arrives in chunks, like [`crate::Decoder`].
+///
+/// # Example
+/// ```
+/// use base2048::base32768;
+/// let encoded = base32768::encode(b"hello");
+/// let (first, second) = encoded.split_at(encoded.char_indices().nth(1).unwrap().0);
+/// let mut decoder = base32768::Decoder::new();
+/// let mut decoded = Vec::new();
+/// decoder.push(first, &mut decoded).unwrap();
+/// decoder.push(second, &mut decoded).unwrap();
+/// decoder.finish().unwrap();
+/// assert_eq!(decoded, b"hello");
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Decoder {
+    state: DecodeState,
+    /// Set once the input turns out to be invalid.
+    error: Option<DecodeError>,
+}
+
+impl Decoder {
+    /// A decoder that hasn't been given anything yet.
+    pub fn new() -> Self {
+        Decoder::default()
+    }
+
+    /// Decodes `text`, adding the bytes it completes to `out`.
+    ///
+    /// Once an error has been returned, every later call returns it again and adds nothing.
+    pub fn push(&mut self, text: &str, out: &mut impl Extend<u8>) -> Result<(), DecodeError> {
+        if let Some(e) = self.error {
+            return Err(e);
+        }
+        for c in text.chars() {
+            if let Err(e) = self
+                .state
+                .push(c, |byte| out.extend(core::iter::once(byte)))
+            {
+                self.error = Some(e);
+                return Err(diagnostics::decode_error(e));
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks the text ended properly.
+    ///
+    /// Every byte has already been added by [`push`](Self::push), since no character is held back.
+    pub fn finish(self) -> Result<(), DecodeError> {
+        if let Some(e) = self.error {
+            return Err(e);
+        }
+        self.state.finish().map_err(diagnostics::decode_error)
+    }
+}
+
+#[cfg(feature = "std")]
+pub use self::io::{DecodeReader, EncodeWriter};
+
+#[cfg(feature = "std")]
+mod io {
+    use super::{DecodeState, EncodeState};
+    use crate::{
+        std_io::{Reader, Scheme, Writer},
+        DecodeError,
+    };
+    use std::io::{self, Read, Write};
+
+    /// The base32768 [`Scheme`].
+    #[derive(Clone, Copy, Debug, Default)]
+    pub(crate) struct Base32768;
+
+    impl Scheme for Base32768 {
+        type Encode = EncodeState;
+        type Decode = DecodeState;
+
+        fn push_byte(state: &mut EncodeState, byte: u8) -> Option<char> {
+            state.push(byte)
+        }
+
+        fn finish_bytes(state: EncodeState) -> Option<char> {
+            state.finish()
+        }
+
+        fn push_char(
+            state: &mut DecodeState,
+            c: char,
+            push: impl FnMut(u8),
+        ) -> Result<(), DecodeError> {
+            state.push(c, push)
+        }
+
+        fn finish_chars(state: DecodeState, _: impl FnMut(u8)) -> Result<(), DecodeError> {
+            state.finish()
+        }
+    }
+
+    /// Encodes everything written to it, writing the UTF-8 base32768 text to an inner writer, like
+    /// [`crate::EncodeWriter`].
+    ///
+    /// # Example
+    /// ```
+    /// use base2048::base32768;
+    /// use std::io::Write;
+    /// let mut writer = base32768::EncodeWriter::new(Vec::new());
+    /// writer.write_all(b"hel")?;
+    /// writer.write_all(b"lo")?;
+    /// let encoded = writer.finish()?;
+    /// assert_eq!(encoded, base32768::encode(b"hello").into_bytes());
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[derive(Debug)]
+    pub struct EncodeWriter<W: Write>(Writer<W, Base32768>);
+
+    impl<W: Write> EncodeWriter<W> {
+        /// Writes the encoded text to `inner`.
+        pub fn new(inner: W) -> Self {
+            EncodeWriter(Writer::new(inner))
+        }
+
+        /// The underlying writer.
+        pub fn get_ref(&self) -> &W {
+            self.0.get_ref()
+        }
+
+        /// The underlying writer.
+        ///
+        /// Writing to it directly puts the text out of order with what is still buffered.
+        pub fn get_mut(&mut self) -> &mut W {
+            self.0.get_mut()
+        }
+
+        /// Writes out the final character and everything still buffered, then gives back the
+        /// underlying writer.
+        pub fn finish(self) -> io::Result<W> {
+            self.0.finish()
+        }
+    }
+
+    impl<W: Write> Write for EncodeWriter<W> {
+        fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+            self.0.write(bytes)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            self.0.flush()
+        }
+    }
+
+    /// Decodes UTF-8 base32768 text read from an inner reader, giving the decoded bytes, like
+    /// [`crate::DecodeReader`].
+    ///
+    /// # Example
+    /// ```
+    /// use base2048::base32768;
+    /// use std::io::Read;
+    /// let encoded = base32768::encode(b"hello");
+    /// let mut decoded = Vec::new();
+    /// base32768::DecodeReader::new(encoded.as_bytes()).read_to_end(&mut decoded)?;
+    /// assert_eq!(decoded, b"hello");
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[derive(Debug)]
+    pub struct DecodeReader<R: Read>(Reader<R, Base32768>);
+
+    impl<R: Read> DecodeReader<R> {
+        /// Reads the encoded text from `inner`.
+        pub fn new(inner: R) -> Self {
+            DecodeReader(Reader::new(inner))
+        }
+
+        /// The underlying reader.
+        pub fn get_ref(&self) -> &R {
+            self.0.get_ref()
+        }
+
+        /// The underlying reader.
+        ///
+        /// Reading from it directly skips text this reader would have decoded.
+        pub fn get_mut(&mut self) -> &mut R {
+            self.0.get_mut()
+        }
+
+        /// Gives back the underlying reader, dropping anything read from it but not yet decoded.
+        pub fn into_inner(self) -> R {
+            self.0.into_inner()
+        }
+    }
+
+    impl<R: Read> Read for DecodeReader<R> {
+        fn read(&mut self, dest: &mut [u8]) -> io::Result<usize> {
+            self.0.read(dest)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn size(repertoire: &[(char, char)]) -> u32 {
+        repertoire
+            .iter()
+            .map(|&(first, last)| last as u32 - first as u32 + 1)
+            .sum()
+    }
+
+    #[test]
+    fn repertoires() {
+        assert_eq!(size(&REPERTOIRE_15), 1 << 15);
+        assert_eq!(size(&REPERTOIRE_7), 1 << 7);
+        for value in 0..1 << 15 {
+            let c = char_for(&ENC_15, value);
+            assert_eq!(c.len_utf16(), 1);
+            assert_eq!(value_of(c), Some((value, false)));
+        }
+        for value in 0..1 << 7 {
+            assert_eq!(value_of(char_for(&ENC_7, value)), Some((value, true)));
+        }
+        assert_eq!(value_of('a'), None);
+        assert_eq!(value_of('\u{10FFFF}'), None);
+    }
+
+    #[test]
+    fn round_trips() {
+        for len in 0..60 {
+            let bytes = (0..len).map(|i| (i * 37 + 11) as u8).collect::<Vec<_>>();
+            let encoded = encode(&bytes);
+            assert_eq!(encoded.chars().count(), encoded_len(len));
+            assert_eq!(encoded.encode_utf16().count(), encoded_len(len));
+            assert_eq!(try_decode(&encoded), Ok(bytes));
+        }
+    }
+
+    #[test]
+    fn final_characters() {
+        // 1 byte leaves 7 bits of padding in a 15-bit character
+        assert_eq!(encode([0]), "\u{6BF}");
+        let one = encode([0xFF]);
+        assert_eq!(value_of(one.chars().next().unwrap()), Some((0x7FFF, false)));
+        // 2 bytes leave 1 bit for a 7-bit character
+        let two = encode([0xFF, 0xFF]);
+        let last = two.chars().nth(1).unwrap();
+        assert_eq!(value_of(last), Some((0x7F, true)));
+        assert_eq!(last, '\u{29F}');
+    }
+
+    #[test]
+    fn errors() {
+        let seven = char_for(&ENC_7, 0x7F);
+        let fifteen = char_for(&ENC_15, 0);
+        assert_eq!(
+            try_decode([fifteen, seven, fifteen].iter().collect::<String>()),
+            Err(DecodeError::TrailingData { char_index: 1 })
+        );
+        assert_eq!(
+            try_decode([seven].iter().collect::<String>()),
+            Err(DecodeError::UnexpectedTail { char_index: 0 })
+        );
+        assert_eq!(
+            try_decode([fifteen].iter().collect::<String>()),
+            Err(DecodeError::InvalidPadding { char_index: 0 })
+        );
+        assert_eq!(
+            try_decode(encode(b"hello") + "\u{4A0}"),
+            Err(DecodeError::InvalidPadding { char_index: 3 })
+        );
+        assert_eq!(
+            try_decode("Ҡé"),
+            Err(DecodeError::InvalidChar {
+                c: 'é',
+                char_index: 1
+            })
+        );
+    }
+
+    #[test]
+    fn chunks() {
+        let bytes = (0..100).map(|i| (i * 37) as u8).collect::<Vec<_>>();
+        let encoded = encode(&bytes);
+        let chars = encoded.chars().collect::<Vec<_>>();
+        for chunk_len in 1..20 {
+            let mut encoder = Encoder::new();
+            let mut text = String::new();
+            for chunk in bytes.chunks(chunk_len) {
+                encoder.push(chunk, &mut text);
+            }
+            encoder.finish(&mut text);
+            assert_eq!(text, encoded);
+
+            let mut decoder = Decoder::new();
+            let mut decoded = Vec::new();
+            for chunk in chars.chunks(chunk_len) {
+                let chunk = chunk.iter().collect::<String>();
+                decoder.push(&chunk, &mut decoded).unwrap();
+            }
+            decoder.finish().unwrap();
+            assert_eq!(decoded, bytes);
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn io() {
+        use std::io::{Read, Write};
+        let bytes = (0..20_000).map(|i| (i * 37) as u8).collect::<Vec<_>>();
+        let mut writer = EncodeWriter::new(Vec::new());
+        for chunk in bytes.chunks(777) {
+            writer.write_all(chunk).unwrap();
+        }
+        let encoded = writer.finish().unwrap();
+        assert_eq!(encoded, encode(&bytes).into_bytes());
+
+        let mut decoded = Vec::new();
+        DecodeReader::new(&encoded[..])
+            .read_to_end(&mut decoded)
+            .unwrap();
+        assert_eq!(decoded, bytes);
+
+        let err = DecodeReader::new("Ҡé".as_bytes())
+            .read_to_end(&mut Vec::new())
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+}
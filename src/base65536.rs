@@ -0,0 +1,543 @@
+//! base65536, the densest of the family by code points, compatible with qntm's `base65536`.
+//!
+//! Each character carries two bytes: the second byte picks one of 256 blocks of 256 code points
+//! and the first byte the character within it. An odd byte at the end goes in a character from a
+//! separate block on its own. Most characters are outside the Basic Multilingual Plane, so this
+//! only pays off where length is counted in code points; [`base32768`](crate::base32768) is denser
+//! in UTF-16 and base2048 where length is weighted.
+//!
+//! The functions and adapters mirror the base2048 ones at the top of the crate and report the same
+//! [`DecodeError`]s; the final one-byte character plays the part of a tail character.
+//!
+//! # Example
+//! ```
+//! use base2048::base65536;
+//! let encoded = base65536::encode(b"hello");
+//! assert_eq!(encoded.chars().count(), base65536::encoded_len(5));
+//! assert_eq!(base65536::decode(&encoded).as_deref(), Some(&b"hello"[..]));
+//! ```
+use crate::{diagnostics, DecodeError};
+use alloc::{string::String, vec::Vec};
+
+/// The characters for two bytes, as the first and last of each run of blocks in order.
+const REPERTOIRE_16: [(char, char); 10] = [
+    ('\u{3400}', '\u{4CFF}'),
+    ('\u{4E00}', '\u{9EFF}'),
+    ('\u{A100}', '\u{A3FF}'),
+    ('\u{A500}', '\u{A5FF}'),
+    ('\u{10600}', '\u{106FF}'),
+    ('\u{12000}', '\u{122FF}'),
+    ('\u{13000}', '\u{133FF}'),
+    ('\u{14400}', '\u{145FF}'),
+    ('\u{16800}', '\u{169FF}'),
+    ('\u{20000}', '\u{285FF}'),
+];
+
+/// The characters for a final single byte.
+const REPERTOIRE_8: (char, char) = ('\u{1500}', '\u{15FF}');
+
+/// The first code point of the block for each value of the second byte.
+static ENC: [u32; 256] = blocks();
+
+/// For each block of 256 code points, the second byte it stands for, or [`FINAL`], or [`NONE`].
+static DEC: [u16; N_DEC] = dec();
+
+const N_DEC: usize = (REPERTOIRE_16[REPERTOIRE_16.len() - 1].1 as usize >> 8) + 1;
+const FINAL: u16 = 0x100;
+const NONE: u16 = 0xFFFF;
+
+const fn blocks() -> [u32; 256] {
+    let mut blocks = [0; 256];
+    let mut n = 0;
+    let mut i = 0;
+    while i < REPERTOIRE_16.len() {
+        let (first, last) = REPERTOIRE_16[i];
+        let mut start = first as u32;
+        while start < last as u32 {
+            blocks[n] = start;
+            n += 1;
+            start += 0x100;
+        }
+        i += 1;
+    }
+    assert!(
+        n == 256,
+        "the repertoire has the wrong number of characters"
+    );
+    blocks
+}
+
+const fn dec() -> [u16; N_DEC] {
+    let mut dec = [NONE; N_DEC];
+    let mut i = 0;
+    while i < ENC.len() {
+        dec[ENC[i] as usize >> 8] = i as u16;
+        i += 1;
+    }
+    dec[REPERTOIRE_8.0 as usize >> 8] = FINAL;
+    dec
+}
+
+/// The character for `first`, followed by `second` if there is one.
+fn char_for(first: u8, second: Option<u8>) -> char {
+    let block = match second {
+        Some(second) => ENC[second as usize],
+        None => REPERTOIRE_8.0 as u32,
+    };
+    // SAFETY: every block is made of characters outside the surrogates
+    unsafe { char::from_u32_unchecked(block + first as u32) }
+}
+
+/// The bytes `c` stands for, the second `None` for a final one-byte character, if it is in the
+/// repertoire.
+fn bytes_of(c: char) -> Option<(u8, Option<u8>)> {
+    let c = c as u32;
+    let first = c as u8;
+    match *DEC.get((c >> 8) as usize)? {
+        NONE => None,
+        FINAL => Some((first, None)),
+        second => Some((first, Some(second as u8))),
+    }
+}
+
+/// Encodes bytes one at a time, holding back every other one.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct EncodeState {
+    pending: Option<u8>,
+}
+
+impl EncodeState {
+    /// Adds a byte, returning the character it completes (if any).
+    pub(crate) fn push(&mut self, byte: u8) -> Option<char> {
+        match self.pending.take() {
+            Some(first) => Some(char_for(first, Some(byte))),
+            None => {
+                self.pending = Some(byte);
+                None
+            }
+        }
+    }
+
+    /// The final one-byte character, if there is a byte left over.
+    pub(crate) fn finish(self) -> Option<char> {
+        self.pending.map(|first| char_for(first, None))
+    }
+}
+
+/// Decodes characters one at a time.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct DecodeState {
+    /// How many characters have been decoded.
+    chars: usize,
+    /// The position of the final one-byte character, once there has been one.
+    ended: Option<usize>,
+}
+
+impl DecodeState {
+    pub(crate) fn push(&mut self, c: char, mut push: impl FnMut(u8)) -> Result<(), DecodeError> {
+        let char_index = self.chars;
+        if let Some(char_index) = self.ended {
+            return Err(DecodeError::TrailingData { char_index });
+        }
+        let (first, second) = bytes_of(c).ok_or(DecodeError::InvalidChar { c, char_index })?;
+        push(first);
+        match second {
+            Some(second) => push(second),
+            None => self.ended = Some(char_index),
+        }
+        self.chars += 1;
+        Ok(())
+    }
+}
+
+/// The number of characters `n_bytes` bytes encode to.
+///
+/// # Example
+/// ```
+/// assert_eq!(base2048::base65536::encoded_len(5), 3);
+/// ```
+pub const fn encoded_len(n_bytes: usize) -> usize {
+    n_bytes.div_ceil(2)
+}
+
+/// Encode some bytes using base65536 encoding
+///
+/// # Example
+/// ```
+/// let encoded = base2048::base65536::encode(b"hello");
+/// assert_eq!(encoded.chars().count(), 3);
+/// ```
+pub fn encode(bytes: impl AsRef<[u8]>) -> String {
+    let mut ret = String::new();
+    encode_into(bytes.as_ref(), &mut ret);
+    ret
+}
+
+/// Encode some bytes using base65536 encoding, appending the text to `out`
+pub fn encode_into(bytes: &[u8], out: &mut String) {
+    // most characters take four bytes
+    out.reserve(encoded_len(bytes.len()) * 4);
+    for pair in bytes.chunks(2) {
+        out.push(char_for(pair[0], pair.get(1).copied()));
+    }
+}
+
+/// Decode a base65536 encoded string
+///
+/// # Example
+/// ```
+/// use base2048::base65536;
+/// assert_eq!(base65536::decode(base65536::encode(b"hello")), Some(b"hello".to_vec()));
+/// assert_eq!(base65536::decode("hello"), None);
+/// ```
+pub fn decode(string: impl AsRef<str>) -> Option<Vec<u8>> {
+    try_decode(string).ok()
+}
+
+/// Decode a base65536 encoded string, saying what is wrong with it if it is invalid
+///
+/// # Example
+/// ```
+/// use base2048::{base65536, DecodeError};
+/// assert_eq!(
+///     base65536::try_decode("㐀X"),
+///     Err(DecodeError::InvalidChar { c: 'X', char_index: 1 })
+/// );
+/// ```
+pub fn try_decode(string: impl AsRef<str>) -> Result<Vec<u8>, DecodeError> {
+    let string = string.as_ref();
+    // every character takes at least three bytes and gives at most two
+    let mut out = Vec::with_capacity(string.len() / 3 * 2);
+    let mut state = DecodeState::default();
+    for c in string.chars() {
+        state
+            .push(c, |byte| out.push(byte))
+            .map_err(diagnostics::decode_error)?;
+    }
+    Ok(out)
+}
+
+/// Encodes base65536 from bytes that arrive in chunks, like [`crate::Encoder`].
+///
+/// # Example
+/// ```
+/// use base2048::base65536;
+/// let mut encoder = base65536::Encoder::new();
+/// let mut encoded = String::new();
+/// for chunk in [&b"hel"[..], b"lo"] {
+///     encoder.push(chunk, &mut encoded);
+/// }
+/// encoder.finish(&mut encoded);
+/// assert_eq!(encoded, base65536::encode(b"hello"));
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Encoder {
+    state: EncodeState,
+}
+
+impl Encoder {
+    /// An encoder that hasn't been given anything yet.
+    pub fn new() -> Self {
+        Encoder::default()
+    }
+
+    /// Encodes `bytes`, adding the characters they complete to `out`.
+    pub fn push(&mut self, bytes: &[u8], out: &mut impl Extend<char>) {
+        let state = &mut self.state;
+        out.extend(bytes.iter().filter_map(|byte| state.push(*byte)));
+    }
+
+    /// Adds the final character to `out`, if the input so far has an odd number of bytes.
+    pub fn finish(self, out: &mut impl Extend<char>) {
+        out.extend(self.state.finish());
+    }
+}
+
+/// Decodes base65536 text that arrives in chunks, like [`crate::Decoder`].
+///
+/// # Example
+/// ```
+/// use base2048::base65536;
+/// let encoded = base65536::encode(b"hello");
+/// let (first, second) = encoded.split_at(encoded.char_indices().nth(1).unwrap().0);
+/// let mut decoder = base65536::Decoder::new();
+/// let mut decoded = Vec::new();
+/// decoder.push(first, &mut decoded).unwrap();
+/// decoder.push(second, &mut decoded).unwrap();
+/// assert_eq!(decoded, b"hello");
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Decoder {
+    state: DecodeState,
+    /// Set once the input turns out to be invalid.
+    error: Option<DecodeError>,
+}
+
+impl Decoder {
+    /// A decoder that hasn't been given anything yet.
+    pub fn new() -> Self {
+        Decoder::default()
+    }
+
+    /// Decodes `text`, adding the bytes it completes to `out`.
+    ///
+    /// Every character stands for whole bytes, so there is nothing to finish. Once an error has
+    /// been returned, every later call returns it again and adds nothing.
+    pub fn push(&mut self, text: &str, out: &mut impl Extend<u8>) -> Result<(), DecodeError> {
+        if let Some(e) = self.error {
+            return Err(e);
+        }
+        for c in text.chars() {
+            if let Err(e) = self
+                .state
+                .push(c, |byte| out.extend(core::iter::once(byte)))
+            {
+                self.error = Some(e);
+                return Err(diagnostics::decode_error(e));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+pub use self::io::{DecodeReader, EncodeWriter};
+
+#[cfg(feature = "std")]
+mod io {
+    use super::{DecodeState, EncodeState};
+    use crate::{
+        std_io::{Reader, Scheme, Writer},
+        DecodeError,
+    };
+    use std::io::{self, Read, Write};
+
+    /// The base65536 [`Scheme`].
+    #[derive(Clone, Copy, Debug, Default)]
+    pub(crate) struct Base65536;
+
+    impl Scheme for Base65536 {
+        type Encode = EncodeState;
+        type Decode = DecodeState;
+
+        fn push_byte(state: &mut EncodeState, byte: u8) -> Option<char> {
+            state.push(byte)
+        }
+
+        fn finish_bytes(state: EncodeState) -> Option<char> {
+            state.finish()
+        }
+
+        fn push_char(
+            state: &mut DecodeState,
+            c: char,
+            push: impl FnMut(u8),
+        ) -> Result<(), DecodeError> {
+            state.push(c, push)
+        }
+
+        fn finish_chars(_: DecodeState, _: impl FnMut(u8)) -> Result<(), DecodeError> {
+            Ok(())
+        }
+    }
+
+    /// Encodes everything written to it, writing the UTF-8 base65536 text to an inner writer, like
+    /// [`crate::EncodeWriter`].
+    ///
+    /// # Example
+    /// ```
+    /// use base2048::base65536;
+    /// use std::io::Write;
+    /// let mut writer = base65536::EncodeWriter::new(Vec::new());
+    /// writer.write_all(b"hel")?;
+    /// writer.write_all(b"lo")?;
+    /// let encoded = writer.finish()?;
+    /// assert_eq!(encoded, base65536::encode(b"hello").into_bytes());
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[derive(Debug)]
+    pub struct EncodeWriter<W: Write>(Writer<W, Base65536>);
+
+    impl<W: Write> EncodeWriter<W> {
+        /// Writes the encoded text to `inner`.
+        pub fn new(inner: W) -> Self {
+            EncodeWriter(Writer::new(inner))
+        }
+
+        /// The underlying writer.
+        pub fn get_ref(&self) -> &W {
+            self.0.get_ref()
+        }
+
+        /// The underlying writer.
+        ///
+        /// Writing to it directly puts the text out of order with what is still buffered.
+        pub fn get_mut(&mut self) -> &mut W {
+            self.0.get_mut()
+        }
+
+        /// Writes out the final character and everything still buffered, then gives back the
+        /// underlying writer.
+        pub fn finish(self) -> io::Result<W> {
+            self.0.finish()
+        }
+    }
+
+    impl<W: Write> Write for EncodeWriter<W> {
+        fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+            self.0.write(bytes)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            self.0.flush()
+        }
+    }
+
+    /// Decodes UTF-8 base65536 text read from an inner reader, giving the decoded bytes, like
+    /// [`crate::DecodeReader`].
+    ///
+    /// # Example
+    /// ```
+    /// use base2048::base65536;
+    /// use std::io::Read;
+    /// let encoded = base65536::encode(b"hello");
+    /// let mut decoded = Vec::new();
+    /// base65536::DecodeReader::new(encoded.as_bytes()).read_to_end(&mut decoded)?;
+    /// assert_eq!(decoded, b"hello");
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[derive(Debug)]
+    pub struct DecodeReader<R: Read>(Reader<R, Base65536>);
+
+    impl<R: Read> DecodeReader<R> {
+        /// Reads the encoded text from `inner`.
+        pub fn new(inner: R) -> Self {
+            DecodeReader(Reader::new(inner))
+        }
+
+        /// The underlying reader.
+        pub fn get_ref(&self) -> &R {
+            self.0.get_ref()
+        }
+
+        /// The underlying reader.
+        ///
+        /// Reading from it directly skips text this reader would have decoded.
+        pub fn get_mut(&mut self) -> &mut R {
+            self.0.get_mut()
+        }
+
+        /// Gives back the underlying reader, dropping anything read from it but not yet decoded.
+        pub fn into_inner(self) -> R {
+            self.0.into_inner()
+        }
+    }
+
+    impl<R: Read> Read for DecodeReader<R> {
+        fn read(&mut self, dest: &mut [u8]) -> io::Result<usize> {
+            self.0.read(dest)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn repertoire() {
+        let size: u32 = REPERTOIRE_16
+            .iter()
+            .map(|&(first, last)| last as u32 - first as u32 + 1)
+            .sum();
+        assert_eq!(size, 1 << 16);
+        for second in 0..=255 {
+            for first in 0..=255 {
+                let c = char_for(first, Some(second));
+                assert_eq!(bytes_of(c), Some((first, Some(second))));
+            }
+        }
+        for first in 0..=255 {
+            assert_eq!(bytes_of(char_for(first, None)), Some((first, None)));
+        }
+        assert_eq!(bytes_of('a'), None);
+        assert_eq!(bytes_of('\u{10FFFF}'), None);
+    }
+
+    #[test]
+    fn characters() {
+        // the second byte picks the block
+        assert_eq!(encode([0, 0]), "\u{3400}");
+        assert_eq!(encode([0xFF, 0]), "\u{34FF}");
+        assert_eq!(encode([0, 0xFF]), "\u{28500}");
+        assert_eq!(encode([0x41]), "\u{1541}");
+    }
+
+    #[test]
+    fn round_trips() {
+        for len in 0..40 {
+            let bytes = (0..len).map(|i| (i * 37 + 11) as u8).collect::<Vec<_>>();
+            let encoded = encode(&bytes);
+            assert_eq!(encoded.chars().count(), encoded_len(len));
+            assert_eq!(try_decode(&encoded), Ok(bytes));
+        }
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            try_decode("\u{1541}\u{3400}"),
+            Err(DecodeError::TrailingData { char_index: 0 })
+        );
+        assert_eq!(
+            try_decode("\u{3400}\u{4D00}"),
+            Err(DecodeError::InvalidChar {
+                c: '\u{4D00}',
+                char_index: 1
+            })
+        );
+    }
+
+    #[test]
+    fn chunks() {
+        let bytes = (0..101).map(|i| (i * 37) as u8).collect::<Vec<_>>();
+        let encoded = encode(&bytes);
+        let chars = encoded.chars().collect::<Vec<_>>();
+        for chunk_len in 1..10 {
+            let mut encoder = Encoder::new();
+            let mut text = String::new();
+            for chunk in bytes.chunks(chunk_len) {
+                encoder.push(chunk, &mut text);
+            }
+            encoder.finish(&mut text);
+            assert_eq!(text, encoded);
+
+            let mut decoder = Decoder::new();
+            let mut decoded = Vec::new();
+            for chunk in chars.chunks(chunk_len) {
+                let chunk = chunk.iter().collect::<String>();
+                decoder.push(&chunk, &mut decoded).unwrap();
+            }
+            assert_eq!(decoded, bytes);
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn io() {
+        use std::io::{Read, Write};
+        let bytes = (0..20_001).map(|i| (i * 37) as u8).collect::<Vec<_>>();
+        let mut writer = EncodeWriter::new(Vec::new());
+        for chunk in bytes.chunks(777) {
+            writer.write_all(chunk).unwrap();
+        }
+        let encoded = writer.finish().unwrap();
+        assert_eq!(encoded, encode(&bytes).into_bytes());
+
+        let mut decoded = Vec::new();
+        DecodeReader::new(&encoded[..])
+            .read_to_end(&mut decoded)
+            .unwrap();
+        assert_eq!(decoded, bytes);
+    }
+}
@@ -0,0 +1,69 @@
+//! `b2048 bench`: a quick throughput check that doesn't need a benchmarking setup.
+use base64::{engine::general_purpose::STANDARD, Engine};
+use std::{
+    hint::black_box,
+    io,
+    time::{Duration, Instant},
+};
+
+/// Each measurement repeats until at least this much time has passed.
+const MIN_DURATION: Duration = Duration::from_millis(500);
+
+/// Runs `f` repeatedly and returns the throughput in MiB/s of `n_bytes` per run.
+fn throughput(n_bytes: usize, mut f: impl FnMut()) -> f64 {
+    let start = Instant::now();
+    let mut runs = 0u32;
+    while runs == 0 || start.elapsed() < MIN_DURATION {
+        f();
+        runs += 1;
+    }
+    (n_bytes as f64 * runs as f64) / (1024.0 * 1024.0) / start.elapsed().as_secs_f64()
+}
+
+fn payload(size: usize) -> Vec<u8> {
+    let mut x = 0x2545_f491_u32;
+    (0..size)
+        .map(|_| {
+            x ^= x << 13;
+            x ^= x >> 17;
+            x ^= x << 5;
+            x as u8
+        })
+        .collect()
+}
+
+pub fn run(size: usize) -> io::Result<()> {
+    let bytes = payload(size);
+    let encoded = base2048::encode(&bytes);
+    let encoded64 = STANDARD.encode(&bytes);
+
+    println!("payload: {} bytes", size);
+    println!(
+        "{:<10} {:>12} {:>12} {:>14}",
+        "", "encode MiB/s", "decode MiB/s", "output bytes"
+    );
+    let rows = [
+        (
+            "base2048",
+            throughput(size, || {
+                drop(black_box(base2048::encode(black_box(&bytes))))
+            }),
+            throughput(size, || {
+                drop(black_box(base2048::decode(black_box(&encoded))))
+            }),
+            encoded.len(),
+        ),
+        (
+            "base64",
+            throughput(size, || drop(black_box(STANDARD.encode(black_box(&bytes))))),
+            throughput(size, || {
+                drop(black_box(STANDARD.decode(black_box(&encoded64))))
+            }),
+            encoded64.len(),
+        ),
+    ];
+    for (name, encode, decode, len) in &rows {
+        println!("{:<10} {:>12.1} {:>12.1} {:>14}", name, encode, decode, len);
+    }
+    Ok(())
+}
@@ -0,0 +1,143 @@
+//! Compressing before encoding and decompressing after decoding, a piece at a time.
+use std::io::{self, Read, Write};
+
+/// How to compress the data under the text.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Compression {
+    Gzip,
+    Zstd,
+}
+
+impl Compression {
+    /// Which of `--gzip` and `--zstd` was given, if either.
+    pub fn from_flags(gzip: bool, zstd: bool) -> Option<Self> {
+        match (gzip, zstd) {
+            (true, _) => Some(Compression::Gzip),
+            (_, true) => Some(Compression::Zstd),
+            _ => None,
+        }
+    }
+}
+
+/// Compresses what is read from `input`.
+pub fn compress<'a>(
+    input: impl Read + 'a,
+    compression: Option<Compression>,
+) -> io::Result<Box<dyn Read + 'a>> {
+    Ok(match compression {
+        None => Box::new(input),
+        Some(Compression::Gzip) => Box::new(flate2::read::GzEncoder::new(
+            input,
+            flate2::Compression::default(),
+        )),
+        Some(Compression::Zstd) => Box::new(zstd::stream::read::Encoder::new(
+            input,
+            zstd::DEFAULT_COMPRESSION_LEVEL,
+        )?),
+    })
+}
+
+/// Decompresses what is written to it, writing the result to the inner writer.
+pub enum Decompress<W: Write> {
+    Plain(W),
+    Gzip(flate2::write::GzDecoder<W>),
+    // the raw writer, unlike `zstd::stream::write::Decoder`, can tell whether the frame ended
+    Zstd(zstd::stream::zio::Writer<W, zstd::stream::raw::Decoder<'static>>),
+}
+
+impl<W: Write> Decompress<W> {
+    pub fn new(output: W, compression: Option<Compression>) -> io::Result<Self> {
+        Ok(match compression {
+            None => Decompress::Plain(output),
+            Some(Compression::Gzip) => Decompress::Gzip(flate2::write::GzDecoder::new(output)),
+            Some(Compression::Zstd) => Decompress::Zstd(zstd::stream::zio::Writer::new(
+                output,
+                zstd::stream::raw::Decoder::new()?,
+            )),
+        })
+    }
+
+    /// Writes out the rest of the decompressed data, failing if the compressed data stops short.
+    pub fn finish(self) -> io::Result<W> {
+        match self {
+            Decompress::Plain(output) => Ok(output),
+            Decompress::Gzip(decoder) => decoder.finish(),
+            Decompress::Zstd(mut decoder) => {
+                decoder.finish()?;
+                Ok(decoder.into_inner().0)
+            }
+        }
+    }
+}
+
+impl<W: Write> Write for Decompress<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Decompress::Plain(output) => output.write(buf),
+            Decompress::Gzip(decoder) => decoder.write(buf),
+            Decompress::Zstd(decoder) => decoder.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Decompress::Plain(output) => output.flush(),
+            Decompress::Gzip(decoder) => decoder.flush(),
+            Decompress::Zstd(decoder) => decoder.flush(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let bytes = b"hello ".repeat(10_000);
+        for compression in [None, Some(Compression::Gzip), Some(Compression::Zstd)] {
+            let mut compressed = vec![];
+            compress(&bytes[..], compression)
+                .unwrap()
+                .read_to_end(&mut compressed)
+                .unwrap();
+            if compression.is_some() {
+                assert!(compressed.len() < bytes.len() / 100);
+            }
+
+            let mut decompress = Decompress::new(vec![], compression).unwrap();
+            for chunk in compressed.chunks(7) {
+                decompress.write_all(chunk).unwrap();
+            }
+            assert_eq!(decompress.finish().unwrap(), bytes);
+        }
+    }
+
+    #[test]
+    fn garbage() {
+        for compression in [Compression::Gzip, Compression::Zstd] {
+            let mut decompress = Decompress::new(vec![], Some(compression)).unwrap();
+            let result = decompress
+                .write_all(b"not compressed at all")
+                .and_then(|()| decompress.finish().map(drop));
+            assert!(result.is_err(), "{:?}", compression);
+        }
+    }
+
+    #[test]
+    fn truncated() {
+        let bytes = b"hello ".repeat(10_000);
+        for compression in [Compression::Gzip, Compression::Zstd] {
+            let mut compressed = vec![];
+            compress(&bytes[..], Some(compression))
+                .unwrap()
+                .read_to_end(&mut compressed)
+                .unwrap();
+            let mut decompress = Decompress::new(vec![], Some(compression)).unwrap();
+            let result = decompress
+                .write_all(&compressed[..compressed.len() - 4])
+                .and_then(|()| decompress.finish().map(drop));
+            assert!(result.is_err(), "{:?}", compression);
+        }
+    }
+}
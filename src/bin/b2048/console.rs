@@ -0,0 +1,59 @@
+//! Making the Windows console show the encoded characters.
+//!
+//! When writing straight to a console, std already uses the wide-character console API. Redirected
+//! output is different: PowerShell and cmd decode it with the console's code page, which defaults
+//! to a legacy OEM one, so piping `b2048 encode` anywhere turns the characters into mojibake.
+//! Switching the console to UTF-8 for the duration of the run avoids that.
+
+/// Keeps the console in UTF-8 mode until dropped, then restores the previous code pages.
+pub struct Utf8Console {
+    #[cfg(windows)]
+    previous: Option<(u32, u32)>,
+}
+
+#[cfg(windows)]
+impl Utf8Console {
+    pub fn enable() -> Self {
+        use windows_sys::Win32::System::Console::{
+            GetConsoleCP, GetConsoleOutputCP, SetConsoleCP, SetConsoleOutputCP,
+        };
+        const CP_UTF8: u32 = 65001;
+
+        // SAFETY: these only read and set process-wide console state; without a console the
+        // getters return 0 and we leave things alone
+        let previous = unsafe {
+            let input = GetConsoleCP();
+            let output = GetConsoleOutputCP();
+            if input == 0 || output == 0 {
+                None
+            } else {
+                SetConsoleCP(CP_UTF8);
+                SetConsoleOutputCP(CP_UTF8);
+                Some((input, output))
+            }
+        };
+        Utf8Console { previous }
+    }
+}
+
+#[cfg(windows)]
+impl Drop for Utf8Console {
+    fn drop(&mut self) {
+        use windows_sys::Win32::System::Console::{SetConsoleCP, SetConsoleOutputCP};
+
+        if let Some((input, output)) = self.previous {
+            // SAFETY: as in `enable`
+            unsafe {
+                SetConsoleCP(input);
+                SetConsoleOutputCP(output);
+            }
+        }
+    }
+}
+
+#[cfg(not(windows))]
+impl Utf8Console {
+    pub fn enable() -> Self {
+        Utf8Console {}
+    }
+}
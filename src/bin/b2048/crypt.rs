@@ -0,0 +1,95 @@
+//! Passphrase encryption for `--encrypt` and `--decrypt`.
+//!
+//! The key is derived from the passphrase with Argon2id and a random salt, and the data sealed
+//! with ChaCha20-Poly1305, so a wrong passphrase or a changed character is detected rather than
+//! producing garbage. The sealed data is a version byte, the salt, the nonce and the ciphertext
+//! with its tag.
+use argon2::Argon2;
+use chacha20poly1305::{
+    aead::{rand_core::RngCore, Aead, AeadCore, KeyInit, OsRng},
+    ChaCha20Poly1305, Key, Nonce,
+};
+use std::{env, io};
+
+const VERSION: u8 = 1;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const HEADER_LEN: usize = 1 + SALT_LEN + NONCE_LEN;
+
+/// Where the passphrase is taken from instead of asking for it, for scripts.
+const PASSPHRASE_VAR: &str = "B2048_PASSPHRASE";
+
+/// The passphrase from `B2048_PASSPHRASE`, or else asked for on the terminal; `confirm` asks
+/// twice, for encrypting.
+pub fn passphrase(confirm: bool) -> io::Result<String> {
+    if let Ok(passphrase) = env::var(PASSPHRASE_VAR) {
+        return Ok(passphrase);
+    }
+    let passphrase = rpassword::prompt_password("passphrase: ")?;
+    if confirm && rpassword::prompt_password("passphrase again: ")? != passphrase {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "the passphrases don't match",
+        ));
+    }
+    Ok(passphrase)
+}
+
+fn cipher(passphrase: &str, salt: &[u8]) -> io::Result<ChaCha20Poly1305> {
+    let mut key = Key::default();
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| io::Error::other(e.to_string()))?;
+    Ok(ChaCha20Poly1305::new(&key))
+}
+
+pub fn encrypt(passphrase: &str, plaintext: &[u8]) -> io::Result<Vec<u8>> {
+    let mut salt = [0; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher(passphrase, &salt)?
+        .encrypt(&nonce, plaintext)
+        .map_err(|_| io::Error::other("encryption failed"))?;
+
+    let mut sealed = Vec::with_capacity(HEADER_LEN + ciphertext.len());
+    sealed.push(VERSION);
+    sealed.extend_from_slice(&salt);
+    sealed.extend_from_slice(&nonce);
+    sealed.extend_from_slice(&ciphertext);
+    Ok(sealed)
+}
+
+pub fn decrypt(passphrase: &str, sealed: &[u8]) -> io::Result<Vec<u8>> {
+    let invalid = |msg| io::Error::new(io::ErrorKind::InvalidData, msg);
+    if sealed.len() < HEADER_LEN {
+        return Err(invalid("the input is too short to be encrypted"));
+    }
+    if sealed[0] != VERSION {
+        return Err(invalid(
+            "the input wasn't encrypted by this version of b2048",
+        ));
+    }
+    let (salt, rest) = sealed[1..].split_at(SALT_LEN);
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+    cipher(passphrase, salt)?
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| invalid("wrong passphrase, or the input has been changed"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let sealed = encrypt("hunter2", b"hello").unwrap();
+        assert_eq!(sealed.len(), HEADER_LEN + 5 + 16);
+        assert_eq!(decrypt("hunter2", &sealed).unwrap(), b"hello");
+        assert!(decrypt("hunter3", &sealed).is_err());
+
+        let mut changed = sealed.clone();
+        *changed.last_mut().unwrap() ^= 1;
+        assert!(decrypt("hunter2", &changed).is_err());
+        assert!(decrypt("hunter2", &sealed[..HEADER_LEN]).is_err());
+    }
+}
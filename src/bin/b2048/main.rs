@@ -0,0 +1,473 @@
+//! `b2048`: base2048 encode or decode files and standard streams.
+//!
+//! Like `base64(1)`, `b2048 [-d] [FILE]` encodes (or decodes) a file or standard input to standard
+//! output; the subcommands give the other options.
+use base2048::{Alphabet, DecodeDiagnostic};
+use clap::{Args, Parser, Subcommand};
+use indicatif::{ProgressBar, ProgressStyle};
+use std::{
+    env, fs,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+mod bench;
+mod compress;
+mod console;
+mod crypt;
+mod output;
+mod stats;
+mod stream;
+
+use compress::{Compression, Decompress};
+use output::Output;
+
+#[derive(Parser)]
+#[command(
+    version,
+    about = "base2048 encode or decode data",
+    args_conflicts_with_subcommands = true
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+    /// Decode instead of encoding, when no command is given
+    #[arg(short, long)]
+    decode: bool,
+    /// The file to encode or decode when no command is given, standard input if omitted or `-`
+    file: Option<PathBuf>,
+    /// Don't show a progress bar for large inputs
+    #[arg(short, long, global = true)]
+    quiet: bool,
+}
+
+#[derive(Debug, PartialEq, Subcommand)]
+enum Command {
+    /// Encode binary data as base2048 text
+    Encode {
+        /// The file to encode, standard input if omitted or `-`
+        input: Option<PathBuf>,
+        /// Where to write the result, standard output if omitted or `-`
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        #[command(flatten)]
+        alphabet: AlphabetArg,
+        /// Encrypt with a passphrase first, taken from `B2048_PASSPHRASE` or asked for
+        #[arg(long)]
+        encrypt: bool,
+        /// Compress with gzip first, for shorter text
+        #[arg(long, conflicts_with = "zstd")]
+        gzip: bool,
+        /// Compress with zstd first, for shorter text
+        #[arg(long)]
+        zstd: bool,
+        /// Instead of the text, show how long it is next to base32768 and base64
+        #[arg(long, conflicts_with = "output")]
+        stats: bool,
+        /// Add Twitter's weighted character count to `--stats`
+        #[arg(long, requires = "stats")]
+        twitter: bool,
+    },
+    /// Decode base2048 text back into binary data
+    Decode {
+        /// The file to decode, standard input if omitted or `-`
+        input: Option<PathBuf>,
+        /// Where to write the result, standard output if omitted or `-`
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        #[command(flatten)]
+        alphabet: AlphabetArg,
+        /// Decrypt what `encode --encrypt` produced, with the passphrase from `B2048_PASSPHRASE`
+        /// or asked for
+        #[arg(long)]
+        decrypt: bool,
+        /// Decompress what `encode --gzip` produced
+        #[arg(long, conflicts_with = "zstd")]
+        gzip: bool,
+        /// Decompress what `encode --zstd` produced
+        #[arg(long)]
+        zstd: bool,
+    },
+    /// Measure encode and decode throughput on this machine
+    Bench {
+        /// The size of the payload in bytes
+        #[arg(long, default_value_t = 16 * 1024 * 1024)]
+        size: usize,
+    },
+}
+
+#[derive(Debug, PartialEq, Args)]
+struct AlphabetArg {
+    /// The alphabet to use: `standard`; `ltr`, the standard one without its right-to-left
+    /// characters; `cjk`, ASCII letters and digits then CJK ideographs; a file listing 2048 table
+    /// characters optionally followed by 8 tail characters, separated by whitespace (like
+    /// base2048.txt); or the name of such a file installed as `NAME.txt` in `$B2048_ALPHABETS`
+    /// or `b2048/alphabets` in the config directory. The legacy v1 table isn't built in: to read
+    /// old text, install it as a profile such as `v1`
+    #[arg(long, default_value = "standard")]
+    alphabet: String,
+}
+
+impl Cli {
+    /// The command to run, with `[-d] [FILE]` standing for `encode` or `decode` of `FILE`.
+    fn command(self) -> Command {
+        let alphabet = AlphabetArg {
+            alphabet: "standard".into(),
+        };
+        match self.command {
+            Some(command) => command,
+            None if self.decode => Command::Decode {
+                input: self.file,
+                output: None,
+                alphabet,
+                decrypt: false,
+                gzip: false,
+                zstd: false,
+            },
+            None => Command::Encode {
+                input: self.file,
+                output: None,
+                alphabet,
+                encrypt: false,
+                gzip: false,
+                zstd: false,
+                stats: false,
+                twitter: false,
+            },
+        }
+    }
+}
+
+impl AlphabetArg {
+    fn load(&self) -> io::Result<Alphabet> {
+        self.load_from(profile_dir().as_deref())
+    }
+
+    /// Loads the alphabet, looking for installed profiles in `profiles`.
+    fn load_from(&self, profiles: Option<&Path>) -> io::Result<Alphabet> {
+        match self.alphabet.as_str() {
+            "standard" => return Ok(Alphabet::standard()),
+            "ltr" => return Ok(Alphabet::ltr()),
+            "cjk" => {
+                return Ok(Alphabet::builder()
+                    .with_table_ranges(['8'..='9', 'A'..='Z', 'a'..='z', '\u{4E00}'..='\u{55C9}'])
+                    .build()
+                    .expect("the CJK alphabet is valid"))
+            }
+            _ => {}
+        }
+        let path = Path::new(&self.alphabet);
+        if path.is_file() {
+            return read_alphabet(path);
+        }
+        let is_name = path.components().count() == 1 && path.extension().is_none();
+        match profiles {
+            Some(profiles) if is_name => {
+                let profile = profiles.join(path).with_extension("txt");
+                if profile.is_file() {
+                    return read_alphabet(&profile);
+                }
+                Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!(
+                        "unknown alphabet `{}`: not built in, a file or a profile in {}",
+                        self.alphabet,
+                        profiles.display()
+                    ),
+                ))
+            }
+            // let the error name the missing file
+            _ => read_alphabet(path),
+        }
+    }
+}
+
+/// Where `--alphabet NAME` finds `NAME.txt`: `$B2048_ALPHABETS`, or `b2048/alphabets` in the
+/// user's config directory.
+fn profile_dir() -> Option<PathBuf> {
+    if let Some(dir) = env::var_os("B2048_ALPHABETS") {
+        return Some(dir.into());
+    }
+    let config = env::var_os("XDG_CONFIG_HOME")
+        .or_else(|| env::var_os("APPDATA"))
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".config")))?;
+    Some(config.join("b2048").join("alphabets"))
+}
+
+/// Reads an alphabet listed like base2048.txt.
+fn read_alphabet(path: &Path) -> io::Result<Alphabet> {
+    let chars = fs::read_to_string(path)
+        .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path.display(), e)))?
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<Vec<_>>();
+    let (table, tail) = if chars.len() > 2048 {
+        chars.split_at(2048)
+    } else {
+        (&chars[..], &base2048::TAIL[..])
+    };
+    Alphabet::from_chars(table, tail).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: {}", path.display(), e),
+        )
+    })
+}
+
+/// Opens the input, returning its length if known in advance.
+fn open_input(input: Option<PathBuf>) -> io::Result<(Box<dyn Read>, usize)> {
+    match input {
+        Some(path) if path.as_os_str() != "-" => {
+            let file = fs::File::open(path)?;
+            let len = file.metadata()?.len() as usize;
+            Ok((Box::new(file), len))
+        }
+        _ => Ok((Box::new(io::stdin().lock()), 0)),
+    }
+}
+
+/// Inputs smaller than this finish too quickly for a progress bar to be useful.
+const PROGRESS_THRESHOLD: usize = 4 * 1024 * 1024;
+
+fn progress_bar(len: usize, quiet: bool) -> ProgressBar {
+    if quiet || len < PROGRESS_THRESHOLD {
+        return ProgressBar::hidden();
+    }
+    ProgressBar::new(len as u64).with_style(
+        ProgressStyle::with_template("{bar:40} {bytes}/{total_bytes} ({bytes_per_sec}, {eta})")
+            .expect("the template is valid"),
+    )
+}
+
+/// Why `b2048` failed.
+enum Failure {
+    Io(io::Error),
+    /// Shown with the offending part of the input.
+    Decode(DecodeDiagnostic),
+}
+
+impl From<io::Error> for Failure {
+    fn from(e: io::Error) -> Self {
+        Failure::Io(e)
+    }
+}
+
+fn run(cli: Cli) -> Result<(), Failure> {
+    let quiet = cli.quiet;
+    match cli.command() {
+        Command::Encode {
+            input,
+            output,
+            alphabet,
+            encrypt,
+            gzip,
+            zstd,
+            stats,
+            twitter,
+        } => {
+            let alphabet = alphabet.load()?;
+            let (input, len) = open_input(input)?;
+            let bar = progress_bar(len, quiet);
+            let mut input =
+                compress::compress(bar.wrap_read(input), Compression::from_flags(gzip, zstd))?;
+            if encrypt {
+                // the whole message is sealed at once
+                let mut bytes = vec![];
+                input.read_to_end(&mut bytes)?;
+                let bytes = crypt::encrypt(&crypt::passphrase(true)?, &bytes)?;
+                input = Box::new(io::Cursor::new(bytes));
+            }
+            if stats {
+                stats::run(&alphabet, input, twitter, io::stdout().lock())?;
+                bar.finish_and_clear();
+                return Ok(());
+            }
+            let mut output = Output::open(output.as_deref())?;
+            stream::encode(&alphabet, input, &mut output)?;
+            bar.finish_and_clear();
+            Ok(output.commit()?)
+        }
+        Command::Decode {
+            input,
+            output,
+            alphabet,
+            decrypt,
+            gzip,
+            zstd,
+        } => {
+            let alphabet = alphabet.load()?;
+            let (input, len) = open_input(input)?;
+            let bar = progress_bar(len, quiet);
+            let input = bar.wrap_read(input);
+            let mut output = Output::open(output.as_deref())?;
+            let mut decompress = Decompress::new(&mut output, Compression::from_flags(gzip, zstd))?;
+            if decrypt {
+                // nothing may be written before the whole message is authenticated
+                let mut bytes = vec![];
+                let result = stream::decode(&alphabet, input, &mut bytes);
+                bar.finish_and_clear();
+                result?;
+                decompress.write_all(&crypt::decrypt(&crypt::passphrase(false)?, &bytes)?)?;
+            } else {
+                let result = stream::decode(&alphabet, input, &mut decompress);
+                bar.finish_and_clear();
+                result?;
+            }
+            decompress.finish()?;
+            Ok(output.commit()?)
+        }
+        Command::Bench { size } => Ok(bench::run(size)?),
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let _console = console::Utf8Console::enable();
+    match run(cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(Failure::Io(e)) => {
+            eprintln!("b2048: {}", e);
+            ExitCode::FAILURE
+        }
+        Err(Failure::Decode(e)) => {
+            eprintln!("{:?}", miette::Report::new(e));
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn command(args: &[&str]) -> Command {
+        Cli::try_parse_from(args).unwrap().command()
+    }
+
+    #[test]
+    fn base64_style_arguments() {
+        let standard = || AlphabetArg {
+            alphabet: "standard".into(),
+        };
+        assert_eq!(
+            command(&["b2048"]),
+            Command::Encode {
+                input: None,
+                output: None,
+                alphabet: standard(),
+                encrypt: false,
+                gzip: false,
+                zstd: false,
+                stats: false,
+                twitter: false,
+            }
+        );
+        assert_eq!(
+            command(&["b2048", "-d", "in.txt"]),
+            Command::Decode {
+                input: Some("in.txt".into()),
+                output: None,
+                alphabet: standard(),
+                decrypt: false,
+                gzip: false,
+                zstd: false,
+            }
+        );
+        assert_eq!(
+            command(&["b2048", "decode", "-o", "out.bin"]),
+            Command::Decode {
+                input: None,
+                output: Some("out.bin".into()),
+                alphabet: standard(),
+                decrypt: false,
+                gzip: false,
+                zstd: false,
+            }
+        );
+        assert!(Cli::try_parse_from(["b2048", "encode", "--gzip", "--zstd"]).is_err());
+        // once there are arguments, a file called `decode` is just a file
+        assert_eq!(
+            command(&["b2048", "-d", "decode"]),
+            Command::Decode {
+                input: Some("decode".into()),
+                output: None,
+                alphabet: standard(),
+                decrypt: false,
+                gzip: false,
+                zstd: false,
+            }
+        );
+    }
+
+    #[test]
+    fn alphabet_profiles() {
+        let load = |name: &str, profiles: Option<&Path>| {
+            AlphabetArg {
+                alphabet: name.into(),
+            }
+            .load_from(profiles)
+        };
+        assert_eq!(load("standard", None).unwrap(), Alphabet::standard());
+        assert_eq!(load("ltr", None).unwrap(), Alphabet::ltr());
+        let cjk = load("cjk", None).unwrap();
+        assert_eq!(cjk.table()[2047], '\u{55C9}');
+        assert_eq!(cjk.decode(&cjk.encode(b"hello")), Ok(b"hello".to_vec()));
+
+        let profiles = env::temp_dir().join(format!("b2048-alphabets-{}", std::process::id()));
+        fs::create_dir_all(&profiles).unwrap();
+        let mut rotated = Alphabet::standard().table().to_vec();
+        rotated.rotate_left(1);
+        let listing = rotated.iter().map(|c| format!("{}\n", c));
+        let listing = listing.collect::<String>();
+        fs::write(profiles.join("rotated.txt"), listing).unwrap();
+        let alphabet = load("rotated", Some(&profiles)).unwrap();
+        assert_eq!(alphabet.table()[..], rotated[..]);
+        assert_eq!(alphabet.tail(), Alphabet::standard().tail());
+        // a file given by its path works without installing it
+        let path = profiles.join("rotated.txt");
+        assert_eq!(load(path.to_str().unwrap(), None).unwrap(), alphabet);
+
+        let e = load("missing", Some(&profiles)).unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::NotFound);
+        assert!(
+            e.to_string().starts_with("unknown alphabet `missing`"),
+            "{}",
+            e
+        );
+        fs::remove_dir_all(&profiles).unwrap();
+    }
+
+    #[test]
+    fn truncated_compression_fails() {
+        let dir = env::temp_dir().join(format!("b2048-truncated-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let bytes = b"hello ".repeat(10_000);
+        for (flag, compression) in [("--gzip", Compression::Gzip), ("--zstd", Compression::Zstd)] {
+            let mut compressed = vec![];
+            compress::compress(&bytes[..], Some(compression))
+                .unwrap()
+                .read_to_end(&mut compressed)
+                .unwrap();
+            let input = dir.join("in.txt");
+            let output = dir.join("out.bin");
+            let decode = || {
+                let args = ["b2048", "decode", "-q", flag, "-o"];
+                let paths = [output.to_str().unwrap(), input.to_str().unwrap()];
+                run(Cli::try_parse_from(args.iter().chain(&paths)).unwrap())
+            };
+
+            fs::write(&input, base2048::encode(&compressed)).unwrap();
+            assert!(decode().is_ok(), "{}", flag);
+            assert_eq!(fs::read(&output).unwrap(), bytes);
+            fs::remove_file(&output).unwrap();
+
+            let truncated = &compressed[..compressed.len() - 4];
+            fs::write(&input, base2048::encode(truncated)).unwrap();
+            assert!(decode().is_err(), "{}", flag);
+            assert!(!output.exists(), "{}", flag);
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
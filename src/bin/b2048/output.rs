@@ -0,0 +1,82 @@
+//! Output that never leaves a half-written file behind.
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+/// Standard output, or a temporary file next to the destination that replaces it on
+/// [`commit`](Output::commit). If the run fails part way the temporary file is removed and the
+/// destination is left untouched.
+pub enum Output {
+    Stdout(io::StdoutLock<'static>),
+    File {
+        file: BufWriter<File>,
+        tmp: PathBuf,
+        dest: PathBuf,
+    },
+}
+
+impl Output {
+    /// Opens `path` for writing; `None` or `-` mean standard output.
+    pub fn open(path: Option<&Path>) -> io::Result<Self> {
+        let dest = match path {
+            Some(path) if path != Path::new("-") => path,
+            _ => return Ok(Output::Stdout(io::stdout().lock())),
+        };
+        let name = dest
+            .file_name()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "output is not a file"))?;
+        let mut tmp_name = std::ffi::OsString::from(".");
+        tmp_name.push(name);
+        tmp_name.push(format!(".b2048-{}.tmp", std::process::id()));
+        let tmp = dest.with_file_name(tmp_name);
+
+        Ok(Output::File {
+            file: BufWriter::new(File::create(&tmp)?),
+            tmp,
+            dest: dest.to_owned(),
+        })
+    }
+
+    /// Makes everything written so far visible at the destination.
+    pub fn commit(mut self) -> io::Result<()> {
+        match &mut self {
+            Output::Stdout(stdout) => stdout.flush(),
+            Output::File { file, tmp, dest } => {
+                file.flush()?;
+                file.get_ref().sync_all()?;
+                fs::rename(&*tmp, &*dest)?;
+                // nothing left to clean up
+                tmp.clear();
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Output::Stdout(stdout) => stdout.write(buf),
+            Output::File { file, .. } => file.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Output::Stdout(stdout) => stdout.flush(),
+            Output::File { file, .. } => file.flush(),
+        }
+    }
+}
+
+impl Drop for Output {
+    fn drop(&mut self) {
+        if let Output::File { tmp, .. } = self {
+            if !tmp.as_os_str().is_empty() {
+                let _ = fs::remove_file(tmp);
+            }
+        }
+    }
+}
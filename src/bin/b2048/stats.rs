@@ -0,0 +1,137 @@
+//! `b2048 encode --stats`: how long the text would be, next to other encodings.
+use crate::stream::{read_full, ENCODE_CHUNK};
+use base2048::{base32768, Alphabet};
+use std::io::{self, Read, Write};
+
+/// The characters Twitter counts once; everything else counts twice.
+const TWITTER_LIGHT: [(char, char); 4] = [
+    ('\u{0}', '\u{10FF}'),
+    ('\u{2000}', '\u{200D}'),
+    ('\u{2010}', '\u{201F}'),
+    ('\u{2032}', '\u{2037}'),
+];
+
+/// How long some text is, by each measure.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct Tally {
+    chars: u64,
+    bytes: u64,
+    twitter: u64,
+}
+
+impl Tally {
+    fn add(&mut self, c: char) {
+        self.chars += 1;
+        self.bytes += c.len_utf8() as u64;
+        let light = TWITTER_LIGHT
+            .iter()
+            .any(|&(first, last)| (first..=last).contains(&c));
+        self.twitter += if light { 1 } else { 2 };
+    }
+}
+
+impl Extend<char> for Tally {
+    fn extend<I: IntoIterator<Item = char>>(&mut self, chars: I) {
+        chars.into_iter().for_each(|c| self.add(c))
+    }
+}
+
+/// Reads `input` and writes out how long its encoding is with `alphabet`, base32768 and padded
+/// base64, adding Twitter's weighted length if `twitter`.
+pub fn run(
+    alphabet: &Alphabet,
+    mut input: impl Read,
+    twitter: bool,
+    mut out: impl Write,
+) -> io::Result<()> {
+    let mut base2048 = Tally::default();
+    let mut base32768 = Tally::default();
+    let mut encoder = base32768::Encoder::new();
+    let mut buf = vec![0; ENCODE_CHUNK];
+    let mut len = 0;
+    loop {
+        let n = read_full(&mut input, &mut buf)?;
+        alphabet.encode_extend(&buf[..n], &mut base2048);
+        encoder.push(&buf[..n], &mut base32768);
+        len += n as u64;
+        if n < buf.len() {
+            break;
+        }
+    }
+    encoder.finish(&mut base32768);
+    // base64 only uses ASCII
+    let chars = len.div_ceil(3) * 4;
+    let base64 = Tally {
+        chars,
+        bytes: chars,
+        twitter: chars,
+    };
+
+    writeln!(out, "input: {} bytes", len)?;
+    write!(out, "{:<10} {:>12} {:>12}", "", "characters", "UTF-8 bytes")?;
+    if twitter {
+        write!(out, " {:>14}", "Twitter weight")?;
+    }
+    writeln!(out)?;
+    for (name, tally) in [
+        ("base2048", base2048),
+        ("base32768", base32768),
+        ("base64", base64),
+    ] {
+        write!(out, "{:<10} {:>12} {:>12}", name, tally.chars, tally.bytes)?;
+        if twitter {
+            write!(out, " {:>14}", tally.twitter)?;
+        }
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn tallies(bytes: &[u8]) -> (Tally, Tally) {
+        let mut base2048 = Tally::default();
+        base2048.extend(base2048::encode(bytes).chars());
+        let mut base32768 = Tally::default();
+        let mut encoder = base32768::Encoder::new();
+        encoder.push(bytes, &mut base32768);
+        encoder.finish(&mut base32768);
+        (base2048, base32768)
+    }
+
+    #[test]
+    fn lengths() {
+        for len in 0..40 {
+            let (base2048, base32768) = tallies(&vec![0xA5; len]);
+            assert_eq!(base2048.chars, base2048::encoded_len(len) as u64);
+            // base2048 only uses characters Twitter counts once
+            assert_eq!(base2048.twitter, base2048.chars);
+            assert_eq!(base32768.chars, (len as u64 * 8).div_ceil(15));
+        }
+        // the first character of each repertoire, and the last with padding
+        let (_, tally) = tallies(&[0, 0]);
+        assert_eq!((tally.chars, tally.bytes, tally.twitter), (2, 4, 2));
+        let (_, tally) = tallies(&[0xFF, 0xFF]);
+        assert_eq!((tally.chars, tally.bytes, tally.twitter), (2, 5, 3));
+    }
+
+    #[test]
+    fn report() {
+        let mut out = vec![];
+        run(&Alphabet::standard(), &b"hello"[..], true, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let lines = out.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], "input: 5 bytes");
+        assert!(lines[1].ends_with("Twitter weight"));
+        assert_eq!(
+            lines[2].split_whitespace().collect::<Vec<_>>(),
+            ["base2048", "4", "9", "4"]
+        );
+        assert_eq!(
+            lines[4].split_whitespace().collect::<Vec<_>>(),
+            ["base64", "8", "8", "8"]
+        );
+    }
+}
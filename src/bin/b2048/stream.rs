@@ -0,0 +1,204 @@
+//! Encoding and decoding a piece at a time, so any size of input takes the same memory.
+use crate::Failure;
+use base2048::{Alphabet, DecodeDiagnostic, DecodeError};
+use std::{
+    io::{self, Read, Write},
+    str,
+};
+
+/// How many bytes are encoded at a time. Every 11 bytes make exactly 8 characters, so encoding
+/// whole groups of 11 and putting the text together gives the same as encoding everything at once.
+pub const ENCODE_CHUNK: usize = 11 * 6 * 1024;
+
+/// How many bytes of text are read at a time.
+const DECODE_CHUNK: usize = 64 * 1024;
+
+/// Reads until `buf` is full or the input ends, returning how much was read.
+pub fn read_full(input: &mut impl Read, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match input.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
+
+/// Encodes `input` to `output`, ending the text with a newline.
+pub fn encode(alphabet: &Alphabet, mut input: impl Read, mut output: impl Write) -> io::Result<()> {
+    let mut buf = vec![0; ENCODE_CHUNK];
+    let mut text = String::new();
+    loop {
+        let n = read_full(&mut input, &mut buf)?;
+        text.clear();
+        alphabet.encode_extend(&buf[..n], &mut text);
+        output.write_all(text.as_bytes())?;
+        if n < buf.len() {
+            return writeln!(output);
+        }
+    }
+}
+
+/// Decodes the text in `input` to `output`, ignoring whitespace at the end.
+///
+/// Characters split between reads are put back together. Whole groups of 8 characters are decoded
+/// as they arrive, keeping back at least the last one, which may be a tail character.
+pub fn decode(
+    alphabet: &Alphabet,
+    mut input: impl Read,
+    mut output: impl Write,
+) -> Result<(), Failure> {
+    let mut buf = vec![0; DECODE_CHUNK];
+    // the start of a character the last read cut off
+    let mut partial = Vec::new();
+    // the characters not decoded yet, which start `offset` characters into the text
+    let mut text = String::new();
+    let mut offset = 0;
+    let mut read = 0;
+    let mut bytes = Vec::new();
+    loop {
+        let n = read_full(&mut input, &mut buf)?;
+        let eof = n < buf.len();
+        partial.extend_from_slice(&buf[..n]);
+        let valid = match str::from_utf8(&partial) {
+            Ok(valid) => valid,
+            Err(e) if e.error_len().is_none() && !eof => {
+                str::from_utf8(&partial[..e.valid_up_to()]).expect("checked by from_utf8")
+            }
+            Err(e) => {
+                let message = format!("invalid UTF-8 at byte {}", read + e.valid_up_to());
+                return Err(io::Error::new(io::ErrorKind::InvalidData, message).into());
+            }
+        };
+        text.push_str(valid);
+        read += valid.len();
+        partial.drain(..valid.len());
+
+        let end = text.trim_end().len();
+        let chars = text[..end].chars().count();
+        let take = if eof {
+            chars
+        } else {
+            chars.saturating_sub(1) / 8 * 8
+        };
+        let split = text
+            .char_indices()
+            .nth(take)
+            .map_or(end, |(i, _)| i.min(end));
+        let group = &text[..split];
+
+        bytes.clear();
+        let result = alphabet.decode_extend(group, &mut bytes);
+        // more text follows, so a tail character ending the group is out of place
+        let last_is_tail = !eof
+            && group
+                .chars()
+                .next_back()
+                .is_some_and(|c| alphabet.tail().contains(&c));
+        let result = match result {
+            Err(e) if !last_is_tail || e.char_index() + 1 < take => Err(e),
+            _ if last_is_tail => Err(DecodeError::TrailingData {
+                char_index: take - 1,
+            }),
+            result => result,
+        };
+        result.map_err(|e| {
+            Failure::Decode(DecodeDiagnostic::with_offset(&text, offset, e, alphabet))
+        })?;
+        output.write_all(&bytes)?;
+        offset += take;
+        text.drain(..split);
+        if eof {
+            return Ok(());
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn bytes(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i * 37 + i / 256) as u8).collect()
+    }
+
+    /// Gives out its input `step` bytes at a time, so reads end in the middle of characters.
+    struct Trickle<'a> {
+        input: &'a [u8],
+        step: usize,
+    }
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let n = buf.len().min(self.step).min(self.input.len());
+            buf[..n].copy_from_slice(&self.input[..n]);
+            self.input = &self.input[n..];
+            Ok(n)
+        }
+    }
+
+    fn decode_text(text: &str, step: usize) -> Result<Vec<u8>, DecodeError> {
+        let mut out = Vec::new();
+        let input = Trickle {
+            input: text.as_bytes(),
+            step,
+        };
+        match decode(&Alphabet::standard(), input, &mut out) {
+            Ok(()) => Ok(out),
+            Err(Failure::Decode(e)) => Err(e.error()),
+            Err(Failure::Io(e)) => panic!("{}", e),
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        for len in [0, 1, 10, 11, ENCODE_CHUNK, 3 * ENCODE_CHUNK + 5] {
+            let bytes = bytes(len);
+            let mut text = Vec::new();
+            encode(&Alphabet::standard(), &bytes[..], &mut text).unwrap();
+            let text = String::from_utf8(text).unwrap();
+            assert_eq!(text, base2048::encode(&bytes) + "\n");
+            for step in [1000, DECODE_CHUNK] {
+                assert_eq!(decode_text(&text, step).unwrap(), bytes);
+            }
+        }
+    }
+
+    #[test]
+    fn errors_count_from_the_start() {
+        let mut text = base2048::encode(bytes(100_000)).chars().collect::<Vec<_>>();
+        text[70_000] = '€';
+        let text = text.into_iter().collect::<String>();
+        assert_eq!(
+            decode_text(&text, 1000),
+            Err(DecodeError::InvalidChar {
+                c: '€',
+                char_index: 70_000
+            })
+        );
+
+        // a tail character that ends the first group decoded, with more text after it
+        let text = base2048::encode(bytes(100_000));
+        let first_read = text
+            .char_indices()
+            .take_while(|(i, c)| i + c.len_utf8() <= DECODE_CHUNK)
+            .count();
+        let mut text = text.chars().collect::<Vec<_>>();
+        let char_index = (first_read - 1) / 8 * 8 - 1;
+        text[char_index] = base2048::TAIL[0];
+        let text = text.into_iter().collect::<String>();
+        assert_eq!(
+            decode_text(&text, DECODE_CHUNK),
+            Err(DecodeError::TrailingData { char_index })
+        );
+
+        let mut text = base2048::encode(bytes(100)).into_bytes();
+        text.insert(50, 0xFF);
+        let mut out = Vec::new();
+        let result = decode(&Alphabet::standard(), &text[..], &mut out);
+        assert!(matches!(result, Err(Failure::Io(e)) if e.to_string().contains("byte 50")));
+    }
+}
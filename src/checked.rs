@@ -0,0 +1,285 @@
+//! Encoding with a checksum, for text that is copied by hand.
+use crate::{encode_into, try_decode, DecodeError, Symbol, ENC_TABLE};
+use alloc::{string::String, vec::Vec};
+use core::fmt;
+
+/// The number of checksum characters [`encode_checked`] appends.
+pub const CHECKSUM_CHARS: usize = 2;
+
+/// The reduction polynomial of GF(2¹¹), x¹¹ + x² + 1, without the x¹¹ term.
+const REDUCE: u32 = 0b101;
+
+/// The generator (x + α)(x + α²) with α = x, as its x¹ and x⁰ coefficients.
+const G1: u16 = 0b110;
+const G0: u16 = 0b1000;
+
+/// The checksum state before any text, derived from the alphabet so text checked with one alphabet
+/// doesn't pass with another.
+const SEED: Checksum = seed();
+
+/// A Reed-Solomon code over GF(2¹¹), one 11-bit symbol per character.
+///
+/// It is a BCH code like bech32's, but over the symbols of the alphabet rather than 5-bit groups,
+/// with a distance of 3: any one or two changed characters in up to 2045 characters of text are
+/// always detected, and other corruption is missed about once in 2²².
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Checksum {
+    /// The remainder so far, highest coefficient first.
+    rem: [u16; 2],
+}
+
+impl Checksum {
+    /// Divides in one more symbol.
+    const fn push(self, symbol: u16) -> Self {
+        let t = self.rem[0] ^ symbol;
+        Checksum {
+            rem: [self.rem[1] ^ mul(t, G1), mul(t, G0)],
+        }
+    }
+
+    /// Adds a character of the text.
+    ///
+    /// A tail character comes after a marker, so it doesn't check the same as the table character
+    /// with the same position.
+    fn push_symbol(self, symbol: Symbol) -> Self {
+        match symbol {
+            Symbol::Table(index) => self.push(index),
+            Symbol::Tail(index) => self.push(0x7FF).push(index as u16),
+        }
+    }
+}
+
+/// The product of `a` and `b` in GF(2¹¹).
+pub(crate) const fn mul(a: u16, b: u16) -> u16 {
+    let (mut a, mut b, mut product) = (a as u32, b as u32, 0);
+    while b != 0 {
+        if b & 1 != 0 {
+            product ^= a;
+        }
+        b >>= 1;
+        a <<= 1;
+        if a & 0x800 != 0 {
+            a ^= 0x800 | REDUCE;
+        }
+    }
+    product as u16
+}
+
+const fn seed() -> Checksum {
+    let mut checksum = Checksum { rem: [0, 1] };
+    let mut i = 0;
+    while i < ENC_TABLE.len() {
+        let c = ENC_TABLE[i] as u32;
+        checksum = checksum.push((c >> 11) as u16).push((c & 0x7FF) as u16);
+        i += 1;
+    }
+    checksum
+}
+
+/// Why a string couldn't be decoded by [`decode_checked`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CheckedDecodeError {
+    /// The string isn't valid base2048.
+    Decode(DecodeError),
+    /// The checksum doesn't match the text, or is missing.
+    ChecksumMismatch,
+}
+
+impl From<DecodeError> for CheckedDecodeError {
+    fn from(e: DecodeError) -> Self {
+        CheckedDecodeError::Decode(e)
+    }
+}
+
+#[cfg(not(feature = "compact-errors"))]
+impl fmt::Display for CheckedDecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckedDecodeError::Decode(e) => e.fmt(f),
+            CheckedDecodeError::ChecksumMismatch => {
+                f.write_str("the checksum doesn't match the text")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "compact-errors")]
+impl fmt::Display for CheckedDecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckedDecodeError::Decode(e) => e.fmt(f),
+            CheckedDecodeError::ChecksumMismatch => {
+                f.write_str("base2048 checksum error 1: mismatch")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CheckedDecodeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CheckedDecodeError::Decode(e) => Some(e),
+            CheckedDecodeError::ChecksumMismatch => None,
+        }
+    }
+}
+
+/// Encode some bytes using base2048 encoding, followed by a checksum of
+/// [`CHECKSUM_CHARS`] characters
+///
+/// The checksum covers the text and the alphabet, so a mistyped, missing or extra character is
+/// caught by [`decode_checked`] even where the text would still decode.
+///
+/// # Example
+/// ```
+/// let encoded = base2048::encode_checked(b"hello");
+/// assert_eq!(encoded.chars().count(), base2048::encoded_len(5) + base2048::CHECKSUM_CHARS);
+/// assert!(encoded.starts_with(&base2048::encode(b"hello")));
+/// ```
+pub fn encode_checked(bytes: impl AsRef<[u8]>) -> String {
+    let mut ret = String::new();
+    encode_into(bytes.as_ref(), &mut ret);
+    let checksum = ret
+        .chars()
+        .filter_map(Symbol::from_char)
+        .fold(SEED, Checksum::push_symbol);
+    ret.extend(
+        checksum
+            .rem
+            .iter()
+            .map(|&index| Symbol::Table(index).to_char()),
+    );
+    ret
+}
+
+/// Decode a string made by [`encode_checked`], verifying its checksum
+///
+/// Invalid characters are reported as [`DecodeError::InvalidChar`] before the checksum is
+/// checked; anything else wrong with the text is most likely a typo, and so a
+/// [`ChecksumMismatch`](CheckedDecodeError::ChecksumMismatch).
+///
+/// # Example
+/// ```
+/// use base2048::{decode_checked, encode_checked, CheckedDecodeError};
+/// let encoded = encode_checked(b"hello");
+/// assert_eq!(decode_checked(&encoded), Ok(b"hello".to_vec()));
+/// let typo = encoded.replacen(encoded.chars().nth(1).unwrap(), "ϡ", 1);
+/// assert_eq!(decode_checked(typo), Err(CheckedDecodeError::ChecksumMismatch));
+/// ```
+pub fn decode_checked(string: impl AsRef<str>) -> Result<Vec<u8>, CheckedDecodeError> {
+    let string = string.as_ref();
+    let split = string
+        .char_indices()
+        .rev()
+        .nth(CHECKSUM_CHARS - 1)
+        .map_or(0, |(i, _)| i);
+    let (text, check) = string.split_at(split);
+    let mut checksum = SEED;
+    let mut n_chars = 0;
+    for c in text.chars() {
+        let symbol = Symbol::from_char(c).ok_or(DecodeError::InvalidChar {
+            c,
+            char_index: n_chars,
+        })?;
+        checksum = checksum.push_symbol(symbol);
+        n_chars += 1;
+    }
+    let mut expected = checksum.rem.iter();
+    let mut matches = true;
+    for c in check.chars() {
+        let symbol = Symbol::from_char(c).ok_or(DecodeError::InvalidChar {
+            c,
+            char_index: n_chars,
+        })?;
+        matches &= expected.next().map(|&index| Symbol::Table(index)) == Some(symbol);
+        n_chars += 1;
+    }
+    if !matches || expected.next().is_some() {
+        return Err(CheckedDecodeError::ChecksumMismatch);
+    }
+    Ok(try_decode(text)?)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{encode, encoded_len, TAIL};
+
+    #[test]
+    fn round_trips() {
+        for len in 0..40 {
+            let bytes = (0..len).map(|i| (i * 37) as u8).collect::<Vec<_>>();
+            let encoded = encode_checked(&bytes);
+            assert_eq!(encoded.chars().count(), encoded_len(len) + CHECKSUM_CHARS);
+            assert!(encoded.starts_with(&encode(&bytes)));
+            assert_eq!(decode_checked(&encoded), Ok(bytes));
+        }
+    }
+
+    #[test]
+    fn field() {
+        // α = x generates every nonzero element, so x¹¹ + x² + 1 is primitive
+        let mut power = 1;
+        for i in 1..2048 {
+            power = mul(power, 2);
+            assert_eq!(power == 1, i == 2047, "{}", i);
+        }
+        // the generator's roots are α and α²
+        for root in [2, 4] {
+            assert_eq!(mul(root, root) ^ mul(G1, root) ^ G0, 0);
+        }
+    }
+
+    #[test]
+    fn substitutions_are_caught() {
+        let encoded = encode_checked((0..40).collect::<Vec<u8>>())
+            .chars()
+            .collect::<Vec<_>>();
+        let replacements = ENC_TABLE.iter().step_by(97).chain(TAIL);
+        for at in 0..encoded.len() {
+            for &c in replacements.clone() {
+                if c == encoded[at] {
+                    continue;
+                }
+                let mut corrupted = encoded.clone();
+                corrupted[at] = c;
+                let corrupted = corrupted.into_iter().collect::<String>();
+                assert!(decode_checked(corrupted).is_err());
+            }
+        }
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            decode_checked(""),
+            Err(CheckedDecodeError::ChecksumMismatch)
+        );
+        let encoded = encode_checked(b"hello");
+        let mut chars = encoded.chars().collect::<Vec<_>>();
+        chars.remove(2);
+        assert_eq!(
+            decode_checked(chars.iter().collect::<String>()),
+            Err(CheckedDecodeError::ChecksumMismatch)
+        );
+        // the tail marker keeps a tail character from checking like the table character
+        let mut chars = encode_checked([0xFF; 3]).chars().collect::<Vec<_>>();
+        let tail = chars.len() - CHECKSUM_CHARS - 1;
+        assert_eq!(chars[tail], TAIL[7]);
+        chars[tail] = ENC_TABLE[7];
+        assert_eq!(
+            decode_checked(chars.iter().collect::<String>()),
+            Err(CheckedDecodeError::ChecksumMismatch)
+        );
+        assert_eq!(
+            decode_checked(encoded + "!"),
+            Err(CheckedDecodeError::Decode(DecodeError::InvalidChar {
+                c: '!',
+                char_index: 6
+            }))
+        );
+        // text checked with the standard alphabet doesn't start from an unseeded state
+        assert_ne!(SEED, Checksum { rem: [0, 1] });
+    }
+}
@@ -0,0 +1,936 @@
+//! A configured encoder and decoder that can be built once and passed around.
+use crate::{
+    diagnostics, Alphabet, BufferTooSmall, DecodeError, DecodeState, DecodeWarning, EncodeState,
+    Symbol,
+};
+use alloc::{string::String, vec::Vec};
+use core::fmt;
+
+/// An [`Alphabet`] together with the options for encoding and decoding with it.
+///
+/// The free functions cover the common case; a `Codec` is for applications that pick their
+/// settings once, keep them in their state and use them everywhere. The default is the standard
+/// alphabet with no options set, which behaves like [`encode`](crate::encode) and
+/// [`decode`](crate::decode).
+///
+/// Its methods mirror the free functions: [`encode`](Self::encode),
+/// [`encode_to_vec`](Self::encode_to_vec), [`encode_into`](Self::encode_into),
+/// [`encode_extend`](Self::encode_extend) and [`encode_to_slice`](Self::encode_to_slice);
+/// [`decode`](Self::decode), [`decode_into`](Self::decode_into),
+/// [`decode_extend`](Self::decode_extend) and [`decode_to_slice`](Self::decode_to_slice); and
+/// [`encoder`](Self::encoder) and [`decoder`](Self::decoder) for input that arrives in pieces.
+///
+/// # Example
+/// ```
+/// use base2048::{Codec, CodecError};
+/// let codec = Codec::new().with_wrap(Some(4)).with_max_decoded_len(Some(16));
+/// let encoded = codec.encode(b"hello world");
+/// assert_eq!(encoded.lines().map(|line| line.chars().count()).collect::<Vec<_>>(), [4, 4]);
+/// assert_eq!(codec.decode(&encoded), Ok(b"hello world".to_vec()));
+/// assert_eq!(
+///     codec.decode(&codec.encode(&[0; 17])),
+///     Err(CodecError::TooLong { limit: 16 })
+/// );
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Codec {
+    alphabet: Alphabet,
+    strict: bool,
+    wrap: Option<usize>,
+    line_ending: LineEnding,
+    ignore_whitespace: bool,
+    ignored: Vec<char>,
+    max_decoded_len: Option<usize>,
+}
+
+/// What ends each line of text wrapped by [`Codec::with_wrap`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum LineEnding {
+    /// `\n`, as on Unix.
+    #[default]
+    Lf,
+    /// `\r\n`, as MIME and Windows expect.
+    CrLf,
+    /// `\r`.
+    Cr,
+}
+
+impl LineEnding {
+    /// The line ending as a string.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+            LineEnding::Cr => "\r",
+        }
+    }
+}
+
+/// Why a [`Codec`] couldn't decode a string.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CodecError {
+    /// The string isn't valid base2048, or isn't canonical in strict mode.
+    Decode(DecodeError),
+    /// The string decodes to more bytes than the codec's limit.
+    TooLong {
+        /// The limit, see [`Codec::with_max_decoded_len`].
+        limit: usize,
+    },
+    /// The string is valid but the buffer given to [`Codec::decode_to_slice`] can't hold what it
+    /// decodes to.
+    BufferTooSmall(BufferTooSmall),
+}
+
+impl From<DecodeError> for CodecError {
+    fn from(e: DecodeError) -> Self {
+        CodecError::Decode(e)
+    }
+}
+
+impl From<BufferTooSmall> for CodecError {
+    fn from(e: BufferTooSmall) -> Self {
+        CodecError::BufferTooSmall(e)
+    }
+}
+
+#[cfg(not(feature = "compact-errors"))]
+impl fmt::Display for CodecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CodecError::Decode(e) => e.fmt(f),
+            CodecError::TooLong { limit } => {
+                write!(f, "the string decodes to more than {} bytes", limit)
+            }
+            CodecError::BufferTooSmall(e) => e.fmt(f),
+        }
+    }
+}
+
+#[cfg(feature = "compact-errors")]
+impl fmt::Display for CodecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CodecError::Decode(e) => e.fmt(f),
+            CodecError::TooLong { .. } => f.write_str("base2048 codec error 1: too long"),
+            CodecError::BufferTooSmall(e) => e.fmt(f),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CodecError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CodecError::Decode(e) => Some(e),
+            CodecError::TooLong { .. } => None,
+            CodecError::BufferTooSmall(e) => Some(e),
+        }
+    }
+}
+
+impl Codec {
+    /// The standard alphabet with no options set.
+    pub fn new() -> Self {
+        Codec::default()
+    }
+
+    /// Encodes and decodes with `alphabet` instead of the standard one.
+    pub fn with_alphabet(mut self, alphabet: Alphabet) -> Self {
+        self.alphabet = alphabet;
+        self
+    }
+
+    /// Whether decoding rejects a final character whose padding bits aren't all set.
+    ///
+    /// The encoder never produces one, but by default it is accepted, so more than one string
+    /// decodes to the same bytes. Strict decoding fails with [`DecodeError::InvalidPadding`]
+    /// instead, so every input has exactly one encoding, like
+    /// [`try_decode_strict`](crate::try_decode_strict).
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Breaks encoded text into lines of `width` characters, or doesn't with `None`.
+    ///
+    /// Decoding with a width set skips line breaks (`\n` and `\r`) anywhere in the input, whatever
+    /// the [line ending](Self::with_line_ending) and width were.
+    ///
+    /// # Panics
+    ///
+    /// If `width` is `Some(0)`.
+    pub fn with_wrap(mut self, width: Option<usize>) -> Self {
+        assert_ne!(width, Some(0), "lines must be at least one character wide");
+        self.wrap = width;
+        self
+    }
+
+    /// Ends lines with `line_ending` when wrapping, instead of `\n`.
+    ///
+    /// # Example
+    /// ```
+    /// use base2048::{Codec, LineEnding};
+    /// let codec = Codec::new().with_wrap(Some(2)).with_line_ending(LineEnding::CrLf);
+    /// let encoded = codec.encode(b"hello");
+    /// assert_eq!(encoded.matches("\r\n").count(), 1);
+    /// assert_eq!(codec.decode(&encoded), Ok(b"hello".to_vec()));
+    /// ```
+    pub fn with_line_ending(mut self, line_ending: LineEnding) -> Self {
+        self.line_ending = line_ending;
+        self
+    }
+
+    /// Whether decoding skips ASCII whitespace between characters.
+    ///
+    /// Text pasted from email or chat often picks up line breaks and spaces; with this set it
+    /// decodes without stripping them first. Whitespace after a tail character is skipped too, but
+    /// a tail character followed by any other character is still
+    /// [`DecodeError::TrailingData`]. Error positions count the skipped characters.
+    pub fn with_ignore_whitespace(mut self, ignore: bool) -> Self {
+        self.ignore_whitespace = ignore;
+        self
+    }
+
+    /// Characters for decoding to skip wherever they appear, such as `-`, `.` or the zero-width
+    /// spaces some messengers insert, replacing any set before.
+    ///
+    /// Characters of the alphabet are decoded as usual even if they are in `chars`. Like
+    /// whitespace, a skipped character after a tail character is allowed.
+    ///
+    /// # Example
+    /// ```
+    /// use base2048::Codec;
+    /// let codec = Codec::new().with_ignored_chars(['-', '\u{200B}']);
+    /// let encoded = base2048::encode(b"hello");
+    /// let (head, tail) = encoded.split_at(encoded.char_indices().nth(2).unwrap().0);
+    /// assert_eq!(codec.decode(&format!("{}-\u{200B}{}", head, tail)), Ok(b"hello".to_vec()));
+    /// ```
+    pub fn with_ignored_chars(mut self, chars: impl IntoIterator<Item = char>) -> Self {
+        self.ignored = chars.into_iter().collect();
+        self.ignored.sort_unstable();
+        self.ignored.dedup();
+        self
+    }
+
+    /// Fails decoding with [`CodecError::TooLong`] once the output would be longer than `limit`,
+    /// or never with `None`.
+    ///
+    /// Decoding stops as soon as the limit is passed, so untrusted input can't make it allocate
+    /// more than that.
+    pub fn with_max_decoded_len(mut self, limit: Option<usize>) -> Self {
+        self.max_decoded_len = limit;
+        self
+    }
+
+    /// The alphabet in use.
+    pub fn alphabet(&self) -> &Alphabet {
+        &self.alphabet
+    }
+
+    /// Whether decoding is strict, see [`with_strict`](Self::with_strict).
+    pub fn is_strict(&self) -> bool {
+        self.strict
+    }
+
+    /// The line width, see [`with_wrap`](Self::with_wrap).
+    pub fn wrap(&self) -> Option<usize> {
+        self.wrap
+    }
+
+    /// The line ending, see [`with_line_ending`](Self::with_line_ending).
+    pub fn line_ending(&self) -> LineEnding {
+        self.line_ending
+    }
+
+    /// Whether decoding skips whitespace, see
+    /// [`with_ignore_whitespace`](Self::with_ignore_whitespace).
+    pub fn ignores_whitespace(&self) -> bool {
+        self.ignore_whitespace
+    }
+
+    /// The characters decoding skips, in order, see
+    /// [`with_ignored_chars`](Self::with_ignored_chars).
+    pub fn ignored_chars(&self) -> &[char] {
+        &self.ignored
+    }
+
+    /// The decoded length limit, see [`with_max_decoded_len`](Self::with_max_decoded_len).
+    pub fn max_decoded_len(&self) -> Option<usize> {
+        self.max_decoded_len
+    }
+
+    /// Encode some bytes
+    pub fn encode(&self, bytes: &[u8]) -> String {
+        let mut ret = String::new();
+        self.encode_with(bytes, |c| ret.push(c));
+        ret
+    }
+
+    /// Encode some bytes, returning the UTF-8 bytes of the encoded text
+    pub fn encode_to_vec(&self, bytes: &[u8]) -> Vec<u8> {
+        let mut ret = Vec::new();
+        self.encode_with(bytes, |c| {
+            ret.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+        });
+        ret
+    }
+
+    /// Encode some bytes, appending the text to `out`
+    pub fn encode_into(&self, bytes: &[u8], out: &mut String) {
+        self.encode_with(bytes, |c| out.push(c));
+    }
+
+    /// Encode some bytes, adding the characters to `out`
+    pub fn encode_extend(&self, bytes: &[u8], out: &mut impl Extend<char>) {
+        self.encode_with(bytes, |c| out.extend(Some(c)));
+    }
+
+    /// Encode some bytes into `out` as UTF-8, returning the length of the text
+    ///
+    /// Nothing is allocated. If `out` is too short the error says how long it needs to be, and
+    /// what has been written to it is unspecified.
+    ///
+    /// # Example
+    /// ```
+    /// use base2048::Codec;
+    /// let codec = Codec::new().with_wrap(Some(2));
+    /// let mut buf = [0; 32];
+    /// let len = codec.encode_to_slice(b"hello", &mut buf).unwrap();
+    /// assert_eq!(&buf[..len], codec.encode(b"hello").as_bytes());
+    /// assert_eq!(codec.encode_to_slice(b"hello", &mut buf[..4]).unwrap_err().needed, len);
+    /// ```
+    pub fn encode_to_slice(&self, bytes: &[u8], out: &mut [u8]) -> Result<usize, BufferTooSmall> {
+        let mut len = 0;
+        self.encode_with(bytes, |c| {
+            // keep counting once it doesn't fit, to find out how much is needed
+            if let Some(dest) = out.get_mut(len..len + c.len_utf8()) {
+                c.encode_utf8(dest);
+            }
+            len += c.len_utf8();
+        });
+        if len <= out.len() {
+            Ok(len)
+        } else {
+            Err(BufferTooSmall { needed: len })
+        }
+    }
+
+    /// An encoder for bytes that arrive in pieces, see [`CodecEncoder`].
+    pub fn encoder(&self) -> CodecEncoder<'_> {
+        CodecEncoder {
+            codec: self,
+            state: EncodeState::default(),
+            line: 0,
+        }
+    }
+
+    fn encode_with(&self, bytes: &[u8], mut push: impl FnMut(char)) {
+        let mut encoder = self.encoder();
+        encoder.push_with(bytes, &mut push);
+        encoder.finish_with(push);
+    }
+
+    /// Decode a string
+    pub fn decode(&self, string: &str) -> Result<Vec<u8>, CodecError> {
+        let mut ret = Vec::new();
+        self.decode_extend(string, &mut ret)?;
+        Ok(ret)
+    }
+
+    /// Decode a string, appending the bytes to `out` and returning how many there are
+    ///
+    /// If `string` is invalid, `out` is left as it was.
+    pub fn decode_into(&self, string: &str, out: &mut Vec<u8>) -> Result<usize, CodecError> {
+        let start = out.len();
+        match self.decode_extend(string, out) {
+            Ok(()) => Ok(out.len() - start),
+            Err(e) => {
+                out.truncate(start);
+                Err(e)
+            }
+        }
+    }
+
+    /// Decode a string, appending the bytes to `out`
+    ///
+    /// If `string` turns out to be invalid, the bytes before the error have already been added.
+    pub fn decode_extend(&self, string: &str, out: &mut impl Extend<u8>) -> Result<(), CodecError> {
+        let mut decoder = self.decoder();
+        decoder.push(string, out)?;
+        decoder.finish(out)
+    }
+
+    /// Decode a string into `out`, returning the number of bytes written
+    ///
+    /// Nothing is allocated. An invalid string, or one over the
+    /// [limit](Self::with_max_decoded_len), is reported as such whatever the size of `out`; if it
+    /// is valid but `out` is too short, the error is [`CodecError::BufferTooSmall`] with the length
+    /// needed. Either way, what has been written to `out` is unspecified.
+    ///
+    /// # Example
+    /// ```
+    /// use base2048::{BufferTooSmall, Codec, CodecError};
+    /// let codec = Codec::new().with_ignore_whitespace(true);
+    /// let mut buf = [0; 8];
+    /// let len = codec.decode_to_slice(&(base2048::encode(b"hello") + "\n"), &mut buf).unwrap();
+    /// assert_eq!(&buf[..len], b"hello");
+    /// assert_eq!(
+    ///     codec.decode_to_slice(&base2048::encode(b"hello world"), &mut buf),
+    ///     Err(CodecError::BufferTooSmall(BufferTooSmall { needed: 11 }))
+    /// );
+    /// ```
+    pub fn decode_to_slice(&self, string: &str, out: &mut [u8]) -> Result<usize, CodecError> {
+        let mut slice = SliceOut { out, len: 0 };
+        self.decode_extend(string, &mut slice)?;
+        if slice.len <= slice.out.len() {
+            Ok(slice.len)
+        } else {
+            Err(BufferTooSmall { needed: slice.len }.into())
+        }
+    }
+
+    /// A decoder for text that arrives in pieces, see [`CodecDecoder`].
+    pub fn decoder(&self) -> CodecDecoder<'_> {
+        CodecDecoder {
+            codec: self,
+            state: DecodeState {
+                strict: self.strict,
+                ..DecodeState::default()
+            },
+            pending: None,
+            chars: 0,
+            written: 0,
+            error: None,
+        }
+    }
+
+    /// Whether decoding passes over `c` without decoding it.
+    fn skips(&self, c: char) -> bool {
+        self.wrap.is_some() && (c == '\n' || c == '\r')
+            || self.ignore_whitespace && c.is_ascii_whitespace()
+            || !self.ignored.is_empty()
+                && self.ignored.binary_search(&c).is_ok()
+                && self.alphabet.symbol(c).is_none()
+    }
+}
+
+/// Encodes bytes that arrive in chunks with a [`Codec`]'s settings, from [`Codec::encoder`].
+///
+/// Like [`Encoder`](crate::Encoder), but in the codec's alphabet and wrapped at its width; the
+/// result is the same as [`Codec::encode`] on all the chunks at once.
+///
+/// # Example
+/// ```
+/// let codec = base2048::Codec::new().with_wrap(Some(2));
+/// let mut encoder = codec.encoder();
+/// let mut encoded = String::new();
+/// for chunk in [&b"hel"[..], b"lo"] {
+///     encoder.push(chunk, &mut encoded);
+/// }
+/// encoder.finish(&mut encoded);
+/// assert_eq!(encoded, codec.encode(b"hello"));
+/// ```
+#[derive(Clone, Debug)]
+pub struct CodecEncoder<'a> {
+    codec: &'a Codec,
+    state: EncodeState,
+    /// How many characters are on the current line.
+    line: usize,
+}
+
+impl CodecEncoder<'_> {
+    /// Encodes `bytes`, adding the characters they complete to `out`.
+    pub fn push(&mut self, bytes: &[u8], out: &mut impl Extend<char>) {
+        self.push_with(bytes, |c| out.extend(Some(c)));
+    }
+
+    /// Adds the final character to `out`, if the input so far doesn't fill a whole number of
+    /// characters.
+    pub fn finish(self, out: &mut impl Extend<char>) {
+        self.finish_with(|c| out.extend(Some(c)));
+    }
+
+    fn push_with(&mut self, bytes: &[u8], mut push: impl FnMut(char)) {
+        let CodecEncoder { codec, state, line } = self;
+        state.push_slice(bytes, |index| {
+            Self::push_char(codec, line, Symbol::Table(index), &mut push)
+        });
+    }
+
+    fn finish_with(mut self, mut push: impl FnMut(char)) {
+        if let Some(symbol) = self.state.finish_symbol() {
+            Self::push_char(self.codec, &mut self.line, symbol, &mut push);
+        }
+    }
+
+    /// Pushes the character for `symbol`, breaking the line first if it is full.
+    fn push_char(codec: &Codec, line: &mut usize, symbol: Symbol, push: &mut impl FnMut(char)) {
+        if codec.wrap == Some(*line) {
+            codec.line_ending.as_str().chars().for_each(&mut *push);
+            *line = 0;
+        }
+        push(codec.alphabet.char(symbol));
+        *line += 1;
+    }
+}
+
+/// Decodes text that arrives in chunks with a [`Codec`]'s settings, from [`Codec::decoder`].
+///
+/// Like [`Decoder`](crate::Decoder), one character is held back until the next chunk or
+/// [`finish`](Self::finish) shows whether it was the last; the characters the codec skips don't
+/// count for that, but do count towards error positions. The result is the same as
+/// [`Codec::decode_extend`] on all the chunks at once.
+///
+/// # Example
+/// ```
+/// let codec = base2048::Codec::new().with_wrap(Some(2));
+/// let encoded = codec.encode(b"hello");
+/// let (first, second) = encoded.split_at(encoded.char_indices().nth(2).unwrap().0);
+/// let mut decoder = codec.decoder();
+/// let mut decoded = Vec::new();
+/// decoder.push(first, &mut decoded).unwrap();
+/// decoder.push(second, &mut decoded).unwrap();
+/// decoder.finish(&mut decoded).unwrap();
+/// assert_eq!(decoded, b"hello");
+/// ```
+#[derive(Clone, Debug)]
+pub struct CodecDecoder<'a> {
+    codec: &'a Codec,
+    state: DecodeState,
+    /// The last character decoding didn't skip, with its position.
+    pending: Option<(usize, Symbol)>,
+    /// How many characters have been pushed, skipped ones included.
+    chars: usize,
+    /// How many bytes have been decoded, including any past the limit.
+    written: usize,
+    /// Set once the input turns out to be invalid.
+    error: Option<CodecError>,
+}
+
+impl CodecDecoder<'_> {
+    /// Decodes `text`, adding the bytes it completes to `out`.
+    ///
+    /// Once an error has been returned, every later call returns it again and adds nothing.
+    pub fn push(&mut self, text: &str, out: &mut impl Extend<u8>) -> Result<(), CodecError> {
+        if let Some(e) = self.error {
+            return Err(e);
+        }
+        for c in text.chars() {
+            let char_index = self.chars;
+            self.chars += 1;
+            if self.codec.skips(c) {
+                continue;
+            }
+            if let Err(e) = self.push_char(c, char_index, out) {
+                self.error = Some(e);
+                return Err(diagnostics::decode_error(e));
+            }
+        }
+        Ok(())
+    }
+
+    /// Decodes the character held back, adding the last bytes to `out`.
+    pub fn finish(mut self, out: &mut impl Extend<u8>) -> Result<(), CodecError> {
+        if let Some(e) = self.error {
+            return Err(e);
+        }
+        self.finish_pending(out).map_err(diagnostics::decode_error)
+    }
+
+    fn push_char(
+        &mut self,
+        c: char,
+        char_index: usize,
+        out: &mut impl Extend<u8>,
+    ) -> Result<(), CodecError> {
+        if let Some((pending_index, symbol)) = self.pending.take() {
+            self.push_symbol(symbol, pending_index, false, out)?;
+        }
+        let symbol = self
+            .codec
+            .alphabet
+            .symbol(c)
+            .ok_or(DecodeError::InvalidChar { c, char_index })?;
+        self.pending = Some((char_index, symbol));
+        Ok(())
+    }
+
+    fn finish_pending(&mut self, out: &mut impl Extend<u8>) -> Result<(), CodecError> {
+        let last_index = match self.pending.take() {
+            Some((char_index, symbol)) => {
+                self.push_symbol(symbol, char_index, true, out)?;
+                char_index
+            }
+            None => 0,
+        };
+        let limit = self.limit();
+        let written = &mut self.written;
+        self.state
+            .finish(|byte| Self::push_byte(limit, written, byte, out));
+        self.check_limit()?;
+
+        if self.state.non_canonical_padding {
+            diagnostics::fixup(DecodeWarning::NonCanonicalPadding {
+                char_index: last_index,
+            });
+        }
+        Ok(())
+    }
+
+    fn push_symbol(
+        &mut self,
+        symbol: Symbol,
+        char_index: usize,
+        last: bool,
+        out: &mut impl Extend<u8>,
+    ) -> Result<(), CodecError> {
+        let limit = self.limit();
+        let written = &mut self.written;
+        self.state
+            .push_symbol(symbol, last, |byte| {
+                Self::push_byte(limit, written, byte, out)
+            })
+            .map_err(|e| e.with_char_index(char_index))?;
+        self.check_limit()
+    }
+
+    /// Stops adding to `out` at the limit, but keeps counting so the caller can tell.
+    fn push_byte(limit: usize, written: &mut usize, byte: u8, out: &mut impl Extend<u8>) {
+        if *written < limit {
+            out.extend(Some(byte));
+        }
+        *written = written.saturating_add(1);
+    }
+
+    fn limit(&self) -> usize {
+        self.codec.max_decoded_len.unwrap_or(usize::MAX)
+    }
+
+    fn check_limit(&self) -> Result<(), CodecError> {
+        match self.written > self.limit() {
+            true => Err(CodecError::TooLong {
+                limit: self.limit(),
+            }),
+            false => Ok(()),
+        }
+    }
+}
+
+/// Writes decoded bytes to a slice, counting past its end to find out how long it needs to be.
+struct SliceOut<'a> {
+    out: &'a mut [u8],
+    len: usize,
+}
+
+impl Extend<u8> for SliceOut<'_> {
+    fn extend<I: IntoIterator<Item = u8>>(&mut self, bytes: I) {
+        for byte in bytes {
+            if let Some(dest) = self.out.get_mut(self.len) {
+                *dest = byte;
+            }
+            self.len += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{decode, encode, ENC_TABLE, TAIL};
+
+    fn bytes(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i * 37) as u8).collect()
+    }
+
+    #[test]
+    fn default_matches_free_functions() {
+        let codec = Codec::default();
+        for len in 0..40 {
+            let bytes = bytes(len);
+            let encoded = codec.encode(&bytes);
+            assert_eq!(encoded, encode(&bytes));
+            assert_eq!(codec.encode_to_vec(&bytes), encoded.as_bytes());
+            assert_eq!(codec.decode(&encoded).ok(), decode(&encoded));
+        }
+        assert_eq!(
+            codec.decode("ետћζы༎X"),
+            Err(CodecError::Decode(DecodeError::InvalidChar {
+                c: '༎',
+                char_index: 5
+            }))
+        );
+    }
+
+    #[test]
+    fn custom_alphabet() {
+        let mut table = ENC_TABLE.to_vec();
+        table.reverse();
+        let alphabet = Alphabet::from_chars(&table, TAIL).unwrap();
+        let codec = Codec::new()
+            .with_alphabet(alphabet.clone())
+            .with_wrap(Some(3));
+        let encoded = codec.encode(&bytes(30));
+        assert_eq!(encoded.replace('\n', ""), alphabet.encode(&bytes(30)));
+        assert_eq!(codec.decode(&encoded), Ok(bytes(30)));
+    }
+
+    #[test]
+    fn wrap() {
+        let codec = Codec::new().with_wrap(Some(5));
+        for len in 0..40 {
+            let encoded = codec.encode(&bytes(len));
+            assert!(encoded.lines().all(|line| line.chars().count() <= 5));
+            assert!(!encoded.ends_with('\n'));
+            assert_eq!(encoded.replace('\n', ""), encode(bytes(len)));
+            assert_eq!(codec.decode(&encoded), Ok(bytes(len)));
+            let crlf = encoded.replace('\n', "\r\n");
+            assert_eq!(codec.decode(&crlf), Ok(bytes(len)));
+        }
+        // errors count the line breaks
+        assert_eq!(
+            codec.decode("ետћ\nζы༎X"),
+            Err(CodecError::Decode(DecodeError::InvalidChar {
+                c: '༎',
+                char_index: 6
+            }))
+        );
+        assert!(Codec::new().decode(&codec.encode(&bytes(20))).is_err());
+    }
+
+    #[test]
+    fn line_ending() {
+        for line_ending in [LineEnding::Lf, LineEnding::CrLf, LineEnding::Cr] {
+            let codec = Codec::new()
+                .with_wrap(Some(4))
+                .with_line_ending(line_ending);
+            assert_eq!(codec.line_ending(), line_ending);
+            for len in 0..40 {
+                let encoded = codec.encode(&bytes(len));
+                let lines = encoded.split(line_ending.as_str()).collect::<Vec<_>>();
+                assert!(lines.iter().all(|line| line.chars().count() <= 4));
+                assert_eq!(lines.concat(), encode(bytes(len)));
+                assert_eq!(codec.encode_to_vec(&bytes(len)), encoded.as_bytes());
+                assert_eq!(codec.decode(&encoded), Ok(bytes(len)));
+                // any wrapping codec reads it
+                assert_eq!(
+                    Codec::new().with_wrap(Some(9)).decode(&encoded),
+                    Ok(bytes(len))
+                );
+            }
+        }
+        assert_eq!(Codec::new().line_ending(), LineEnding::Lf);
+    }
+
+    #[test]
+    fn ignore_whitespace() {
+        let codec = Codec::new().with_ignore_whitespace(true);
+        for len in 0..40 {
+            let encoded = encode(bytes(len));
+            let pasted = encoded
+                .chars()
+                .enumerate()
+                .map(|(i, c)| match i % 3 {
+                    0 => format!(" {}", c),
+                    1 => format!("{}\r\n", c),
+                    _ => format!("{}\t", c),
+                })
+                .collect::<String>();
+            assert_eq!(codec.decode(&pasted), Ok(bytes(len)));
+            assert_eq!(codec.decode(&format!("{}\n", encoded)), Ok(bytes(len)));
+        }
+        assert!(Codec::new().decode(" ").is_err());
+        assert!(codec.ignores_whitespace());
+        // a tail character still has to come last, and positions count the whitespace
+        let tail = format!("ետћ{} ե", TAIL[0]);
+        assert_eq!(
+            codec.decode(&tail),
+            Err(CodecError::Decode(DecodeError::TrailingData {
+                char_index: 3
+            }))
+        );
+        // only ASCII whitespace is skipped
+        assert_eq!(
+            codec.decode("ետ\u{3000}ћ"),
+            Err(CodecError::Decode(DecodeError::InvalidChar {
+                c: '\u{3000}',
+                char_index: 2
+            }))
+        );
+    }
+
+    #[test]
+    fn ignored_chars() {
+        let codec = Codec::new().with_ignored_chars("-.\u{200B}-".chars());
+        assert_eq!(codec.ignored_chars(), ['-', '.', '\u{200B}']);
+        for len in 0..40 {
+            let encoded = encode(bytes(len));
+            let dashed = encoded
+                .chars()
+                .flat_map(|c| [c, "-.\u{200B}".chars().nth(len % 3).unwrap()])
+                .collect::<String>();
+            assert_eq!(codec.decode(&dashed), Ok(bytes(len)));
+            assert!(len == 0 || Codec::new().decode(&dashed).is_err());
+        }
+        // the alphabet comes first
+        let codec = Codec::new().with_ignored_chars([ENC_TABLE[0], TAIL[0]]);
+        assert_eq!(codec.decode(&encode(bytes(30))), Ok(bytes(30)));
+        assert_eq!(
+            codec.decode("ե-"),
+            Err(CodecError::Decode(DecodeError::InvalidChar {
+                c: '-',
+                char_index: 1
+            }))
+        );
+        assert!(Codec::new().ignored_chars().is_empty());
+    }
+
+    #[test]
+    fn strict() {
+        // the first table character has none of its padding bits set
+        let mut lenient = encode(bytes(1));
+        lenient.pop();
+        lenient.push(ENC_TABLE[0]);
+        assert!(Codec::new().decode(&lenient).is_ok());
+        assert_eq!(
+            Codec::new().with_strict(true).decode(&lenient),
+            Err(CodecError::Decode(DecodeError::InvalidPadding {
+                char_index: 0
+            }))
+        );
+        for len in 0..40 {
+            let codec = Codec::new().with_strict(true);
+            assert_eq!(codec.decode(&encode(bytes(len))), Ok(bytes(len)));
+        }
+    }
+
+    #[test]
+    fn limit() {
+        let codec = Codec::new().with_max_decoded_len(Some(10));
+        for len in 0..=10 {
+            assert_eq!(codec.decode(&encode(bytes(len))), Ok(bytes(len)));
+        }
+        for len in 11..40 {
+            assert_eq!(
+                codec.decode(&encode(bytes(len))),
+                Err(CodecError::TooLong { limit: 10 })
+            );
+        }
+        let mut out = Vec::new();
+        assert!(codec.decode_extend(&encode(bytes(1000)), &mut out).is_err());
+        assert!(out.len() <= 10);
+    }
+
+    #[test]
+    fn into_and_extend() {
+        let codec = Codec::new().with_wrap(Some(3));
+        let mut encoded = String::from("x");
+        codec.encode_into(&bytes(20), &mut encoded);
+        assert_eq!(encoded[1..], codec.encode(&bytes(20)));
+        let mut chars = Vec::new();
+        codec.encode_extend(&bytes(20), &mut chars);
+        assert_eq!(chars.into_iter().collect::<String>(), encoded[1..]);
+
+        let mut decoded = vec![1];
+        assert_eq!(codec.decode_into(&encoded[1..], &mut decoded), Ok(20));
+        assert_eq!(decoded[1..], bytes(20));
+        assert!(codec.decode_into("ետ0ζ", &mut decoded).is_err());
+        assert_eq!(decoded.len(), 21);
+    }
+
+    #[test]
+    fn slices() {
+        let codec = Codec::new()
+            .with_wrap(Some(4))
+            .with_max_decoded_len(Some(30));
+        for len in 0..40 {
+            let encoded = codec.encode(&bytes(len));
+            let mut buf = [0; 200];
+            assert_eq!(
+                codec.encode_to_slice(&bytes(len), &mut buf),
+                Ok(encoded.len())
+            );
+            assert_eq!(buf[..encoded.len()], *encoded.as_bytes());
+            if !encoded.is_empty() {
+                assert_eq!(
+                    codec.encode_to_slice(&bytes(len), &mut buf[..encoded.len() - 1]),
+                    Err(BufferTooSmall {
+                        needed: encoded.len()
+                    })
+                );
+            }
+
+            let decoded = codec.decode(&encoded);
+            assert_eq!(
+                codec.decode_to_slice(&encoded, &mut buf),
+                decoded.map(|d| d.len())
+            );
+            if len <= 30 {
+                assert_eq!(buf[..len], bytes(len)[..]);
+            }
+            if len > 0 && len <= 30 {
+                assert_eq!(
+                    codec.decode_to_slice(&encoded, &mut buf[..len - 1]),
+                    Err(CodecError::BufferTooSmall(BufferTooSmall { needed: len }))
+                );
+            }
+        }
+        assert_eq!(
+            codec.decode_to_slice("ետ0ζ", &mut [0; 1]),
+            Err(CodecError::Decode(DecodeError::TrailingData {
+                char_index: 2
+            }))
+        );
+    }
+
+    #[test]
+    fn streaming() {
+        let mut table = ENC_TABLE.to_vec();
+        table.reverse();
+        let codec = Codec::new()
+            .with_alphabet(Alphabet::from_chars(&table, TAIL).unwrap())
+            .with_wrap(Some(3))
+            .with_line_ending(LineEnding::CrLf)
+            .with_strict(true);
+        let bytes = bytes(50);
+        let encoded = codec.encode(&bytes);
+        let chars = encoded.chars().collect::<Vec<_>>();
+        for chunk_len in 1..8 {
+            let mut encoder = codec.encoder();
+            let mut streamed = String::new();
+            for chunk in bytes.chunks(chunk_len) {
+                encoder.push(chunk, &mut streamed);
+            }
+            encoder.finish(&mut streamed);
+            assert_eq!(streamed, encoded);
+
+            let mut decoder = codec.decoder();
+            let mut decoded = Vec::new();
+            for chunk in chars.chunks(chunk_len) {
+                let chunk = chunk.iter().collect::<String>();
+                decoder.push(&chunk, &mut decoded).unwrap();
+            }
+            decoder.finish(&mut decoded).unwrap();
+            assert_eq!(decoded, bytes);
+        }
+
+        // positions count the line breaks, and errors stick
+        let codec = Codec::new().with_wrap(Some(5));
+        let mut decoder = codec.decoder();
+        assert_eq!(decoder.push("ետ\nћζ", &mut Vec::new()), Ok(()));
+        let e = Err(CodecError::Decode(DecodeError::InvalidChar {
+            c: '༎',
+            char_index: 6,
+        }));
+        assert_eq!(decoder.push("ы༎X", &mut Vec::new()), e);
+        assert_eq!(decoder.push("ետ", &mut Vec::new()), e);
+        assert_eq!(decoder.finish(&mut Vec::new()), e);
+
+        // a line break after the last character doesn't stop it being the last
+        let mut decoder = codec.decoder();
+        let mut decoded = Vec::new();
+        decoder.push(&encode(b"hi"), &mut decoded).unwrap();
+        decoder.push("\n", &mut decoded).unwrap();
+        decoder.finish(&mut decoded).unwrap();
+        assert_eq!(decoded, b"hi");
+    }
+}
@@ -0,0 +1,169 @@
+use core::fmt;
+
+/// How much of the input [`compressibility`] looks at, spread over the whole payload.
+const SAMPLE_BUDGET: usize = 16 * 1024;
+/// The sample is taken as this many evenly spaced chunks.
+const SAMPLE_CHUNKS: usize = 16;
+/// Below this size compression headers eat up any savings.
+const MIN_WORTHWHILE_LEN: usize = 64;
+/// Don't bother for less than this saving.
+const MIN_WORTHWHILE_SAVING: f64 = 0.1;
+
+/// An estimate of how well a payload would compress, see [`compressibility`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Compressibility {
+    /// Shannon entropy of the sampled bytes, in bits per byte (0 to 8).
+    pub entropy: f64,
+    /// Fraction of sampled bytes that repeat the byte `distance` positions back for some small
+    /// distance, which catches runs and short patterns that entropy alone misses.
+    pub repetition: f64,
+    /// The estimated compressed size as a fraction of the original.
+    pub estimated_ratio: f64,
+}
+
+impl Compressibility {
+    /// Whether compressing before encoding is likely to be worth it.
+    pub fn worth_compressing(&self) -> bool {
+        1.0 - self.estimated_ratio >= MIN_WORTHWHILE_SAVING
+    }
+
+    /// The estimated number of bytes saved by compressing `len` bytes.
+    pub fn estimated_saving(&self, len: usize) -> usize {
+        (len as f64 * (1.0 - self.estimated_ratio)) as usize
+    }
+}
+
+impl fmt::Display for Compressibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:.2} bits/byte, {:.0}% repetitive, ~{:.0}% of the original size when compressed",
+            self.entropy,
+            self.repetition * 100.0,
+            self.estimated_ratio * 100.0
+        )
+    }
+}
+
+/// Estimate whether compressing `bytes` before encoding them would pay off
+///
+/// This only samples the payload (at most 16KiB of it) so it is cheap enough to call on every
+/// message to decide whether to compress it. The estimate is rough: it is meant to tell text and
+/// sparse binary data apart from data that is already compressed or encrypted, not to predict the
+/// exact output of a compressor.
+///
+/// # Example
+/// ```
+/// let text = b"the quick brown fox jumps over the lazy dog. ".repeat(20);
+/// assert!(base2048::compressibility(&text).worth_compressing());
+///
+/// let noise = (0..1000u32).map(|i| (i.wrapping_mul(2654435761) >> 13) as u8).collect::<Vec<_>>();
+/// assert!(!base2048::compressibility(&noise).worth_compressing());
+/// ```
+pub fn compressibility(bytes: &[u8]) -> Compressibility {
+    let mut histogram = [0u32; 256];
+    let mut repeats = 0u32;
+    let mut sampled = 0u32;
+
+    let chunk_len = (SAMPLE_BUDGET / SAMPLE_CHUNKS).min(bytes.len());
+    let n_chunks = if bytes.len() <= SAMPLE_BUDGET {
+        1
+    } else {
+        SAMPLE_CHUNKS
+    };
+    let chunks = (0..n_chunks).map(|i| {
+        let start = if n_chunks == 1 {
+            0
+        } else {
+            (bytes.len() - chunk_len) / (n_chunks - 1) * i
+        };
+        let len = if n_chunks == 1 {
+            bytes.len()
+        } else {
+            chunk_len
+        };
+        &bytes[start..start + len]
+    });
+
+    for chunk in chunks {
+        for (i, byte) in chunk.iter().enumerate() {
+            histogram[*byte as usize] += 1;
+            sampled += 1;
+            if (1..=4).any(|distance| i >= distance && chunk[i - distance] == *byte) {
+                repeats += 1;
+            }
+        }
+    }
+
+    if sampled == 0 {
+        return Compressibility {
+            entropy: 0.0,
+            repetition: 0.0,
+            estimated_ratio: 1.0,
+        };
+    }
+
+    let n = sampled as f64;
+    let entropy = histogram
+        .iter()
+        .filter(|count| **count > 0)
+        .map(|count| {
+            let p = *count as f64 / n;
+            -p * log2(p)
+        })
+        .sum::<f64>();
+    let repetition = repeats as f64 / n;
+
+    // Entropy coding alone gets close to `entropy / 8`. Repeated bytes are mostly absorbed by
+    // back references on top of that, so they're only charged a little.
+    let mut estimated_ratio = (entropy / 8.0) * (1.0 - repetition * 0.75);
+    if bytes.len() < MIN_WORTHWHILE_LEN {
+        estimated_ratio = 1.0;
+    }
+
+    Compressibility {
+        entropy,
+        repetition,
+        estimated_ratio: estimated_ratio.clamp(0.0, 1.0),
+    }
+}
+
+/// `log2` for `0 < x <= 1` without `std`, accurate to about 1e-4 which is plenty here.
+fn log2(x: f64) -> f64 {
+    let bits = x.to_bits();
+    let exponent = ((bits >> 52) & 0x7ff) as i64 - 1023;
+    // the mantissa as a number in [1, 2)
+    let m = f64::from_bits((bits & !(0x7ff << 52)) | (1023 << 52));
+    // log2(m) = 2 * atanh((m - 1) / (m + 1)) / ln(2)
+    let t = (m - 1.0) / (m + 1.0);
+    let t2 = t * t;
+    let series = t * (1.0 + t2 * (1.0 / 3.0 + t2 * (1.0 / 5.0 + t2 * (1.0 / 7.0 + t2 / 9.0))));
+    exponent as f64 + 2.0 * series / core::f64::consts::LN_2
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn log2_is_close_enough() {
+        for (x, expected) in &[(1.0, 0.0), (0.5, -1.0), (0.25, -2.0), (0.3, -1.736966)] {
+            assert!((log2(*x) - expected).abs() < 1e-4, "log2({})", x);
+        }
+    }
+
+    #[test]
+    fn extremes() {
+        let zeros = compressibility(&[0u8; 4096]);
+        assert_eq!(zeros.entropy, 0.0);
+        assert!(zeros.worth_compressing());
+
+        let every_byte = (0..=255u8).cycle().take(100_000).collect::<Vec<_>>();
+        let estimate = compressibility(&every_byte);
+        assert!((estimate.entropy - 8.0).abs() < 1e-3);
+
+        assert!(!compressibility(b"short").worth_compressing());
+        assert!(!compressibility(&[]).worth_compressing());
+    }
+}
@@ -0,0 +1,295 @@
+//! Encoding and decoding in `const` contexts, for values fixed at compile time.
+//!
+//! These work a character at a time from the position of its bits, since the encoder and decoder
+//! states take `&mut self` and so can't be used from a `const fn`.
+use crate::{encoded_len, BITS_PER_CHAR, DEC_TABLE, ENC_TABLE, TAIL, TAIL_BITS};
+
+/// The `k`th character of the encoding of `bytes`.
+const fn char_at(bytes: &[u8], k: usize) -> char {
+    let total = bytes.len() * 8;
+    let start = k * BITS_PER_CHAR as usize;
+    let n_bits = if total - start < BITS_PER_CHAR as usize {
+        total - start
+    } else {
+        BITS_PER_CHAR as usize
+    };
+
+    // the (at most 18) bits from `start` on lie within the three bytes from its byte
+    let mut window = 0u32;
+    let mut i = 0;
+    while i < 3 {
+        let byte = if start / 8 + i < bytes.len() {
+            bytes[start / 8 + i]
+        } else {
+            0
+        };
+        window = window << 8 | byte as u32;
+        i += 1;
+    }
+    let bits = (window >> (24 - start % 8 - n_bits)) & ((1 << n_bits) - 1);
+
+    // the final character is padded with ones, like `EncodeState::finish_symbol`
+    if n_bits <= TAIL_BITS as usize {
+        let padding = TAIL_BITS as usize - n_bits;
+        TAIL[(bits << padding | ((1 << padding) - 1)) as usize]
+    } else {
+        let padding = BITS_PER_CHAR as usize - n_bits;
+        ENC_TABLE[(bits << padding | ((1 << padding) - 1)) as usize]
+    }
+}
+
+/// The UTF-8 encoding of `c`, and its length.
+const fn utf8(c: char) -> ([u8; 4], usize) {
+    let c = c as u32;
+    match c {
+        0..=0x7F => ([c as u8, 0, 0, 0], 1),
+        0x80..=0x7FF => ([0xC0 | (c >> 6) as u8, 0x80 | (c & 0x3F) as u8, 0, 0], 2),
+        0x800..=0xFFFF => (
+            [
+                0xE0 | (c >> 12) as u8,
+                0x80 | (c >> 6 & 0x3F) as u8,
+                0x80 | (c & 0x3F) as u8,
+                0,
+            ],
+            3,
+        ),
+        _ => (
+            [
+                0xF0 | (c >> 18) as u8,
+                0x80 | (c >> 12 & 0x3F) as u8,
+                0x80 | (c >> 6 & 0x3F) as u8,
+                0x80 | (c & 0x3F) as u8,
+            ],
+            4,
+        ),
+    }
+}
+
+/// The character starting at byte `pos` of valid UTF-8, as a code point, and its length.
+const fn next_char(s: &[u8], pos: usize) -> (u32, usize) {
+    let b0 = s[pos] as u32;
+    match b0 {
+        0..=0x7F => (b0, 1),
+        0x80..=0xDF => ((b0 & 0x1F) << 6 | continuation(s, pos + 1), 2),
+        0xE0..=0xEF => (
+            (b0 & 0x0F) << 12 | continuation(s, pos + 1) << 6 | continuation(s, pos + 2),
+            3,
+        ),
+        _ => (
+            (b0 & 0x07) << 18
+                | continuation(s, pos + 1) << 12
+                | continuation(s, pos + 2) << 6
+                | continuation(s, pos + 3),
+            4,
+        ),
+    }
+}
+
+/// The six bits a UTF-8 continuation byte carries.
+const fn continuation(s: &[u8], pos: usize) -> u32 {
+    (s[pos] & 0x3F) as u32
+}
+
+/// The length in bytes of the UTF-8 encoded text `bytes` encode to
+///
+/// The array length for [`encode_const`].
+pub const fn encoded_utf8_len(bytes: &[u8]) -> usize {
+    let mut len = 0;
+    let mut k = 0;
+    while k < encoded_len(bytes.len()) {
+        len += char_at(bytes, k).len_utf8();
+        k += 1;
+    }
+    len
+}
+
+/// Encode some bytes at compile time, returning the UTF-8 of the encoded text
+///
+/// `N` must be [`encoded_utf8_len`]`(bytes)`. [`encode_str!`](crate::encode_str) does that and
+/// gives a `&'static str`.
+///
+/// # Panics
+///
+/// If `N` is wrong, which fails compilation when evaluated in a `const`.
+///
+/// # Example
+/// ```
+/// const KEY: [u8; 5] = *b"hello";
+/// const ENCODED: [u8; base2048::encoded_utf8_len(&KEY)] = base2048::encode_const(&KEY);
+/// assert_eq!(&ENCODED[..], base2048::encode(KEY).as_bytes());
+/// ```
+pub const fn encode_const<const N: usize>(bytes: &[u8]) -> [u8; N] {
+    let mut out = [0; N];
+    let mut len = 0;
+    let mut k = 0;
+    while k < encoded_len(bytes.len()) {
+        let (utf8, width) = utf8(char_at(bytes, k));
+        let mut i = 0;
+        while i < width {
+            assert!(len < N, "N is shorter than the encoded text");
+            out[len] = utf8[i];
+            len += 1;
+            i += 1;
+        }
+        k += 1;
+    }
+    assert!(len == N, "N is longer than the encoded text");
+    out
+}
+
+/// Decode a base2048 encoded string at compile time
+///
+/// `N` must be the number of bytes `string` decodes to. Final characters with non-canonical
+/// padding are accepted, as by [`decode`](crate::decode).
+///
+/// # Panics
+///
+/// If `string` isn't valid base2048 or `N` is wrong, which fails compilation when evaluated in a
+/// `const`.
+///
+/// # Example
+/// ```
+/// const KEY: [u8; 5] = base2048::decode_const("ڵϠɲඹ");
+/// assert_eq!(base2048::encode(KEY), "ڵϠɲඹ");
+/// ```
+pub const fn decode_const<const N: usize>(string: &str) -> [u8; N] {
+    let s = string.as_bytes();
+    let mut out = [0; N];
+    let mut len = 0;
+    let mut stage = 0u32;
+    let mut remaining = 0;
+    let mut pos = 0;
+    while pos < s.len() {
+        let (c, width) = next_char(s, pos);
+        pos += width;
+        let last = pos == s.len();
+
+        let index = if (c as usize) < DEC_TABLE.len() {
+            DEC_TABLE[c as usize]
+        } else {
+            0xFFFF
+        };
+        let (n_bits, bits) = if index != 0xFFFF {
+            if last {
+                // the final character's padding is dropped so the output ends on a byte boundary
+                let n_bits = BITS_PER_CHAR - (remaining + BITS_PER_CHAR) % 8;
+                (n_bits, index as u32 >> (BITS_PER_CHAR - n_bits))
+            } else {
+                (BITS_PER_CHAR, index as u32)
+            }
+        } else {
+            let mut tail = 0;
+            while tail < TAIL.len() && TAIL[tail] as u32 != c {
+                tail += 1;
+            }
+            assert!(tail < TAIL.len(), "invalid base2048 character");
+            assert!(last, "base2048 tail character before the end");
+            let need = 8 - remaining;
+            assert!(need <= TAIL_BITS, "unexpected base2048 tail character");
+            let padding = TAIL_BITS - need;
+            assert!(
+                (tail as u32).trailing_ones() >= padding,
+                "invalid base2048 padding"
+            );
+            (need, tail as u32 >> padding)
+        };
+
+        stage = stage << n_bits | bits;
+        remaining += n_bits;
+        while remaining >= 8 {
+            remaining -= 8;
+            assert!(len < N, "N is shorter than the decoded bytes");
+            out[len] = (stage >> remaining) as u8;
+            len += 1;
+            stage &= (1 << remaining) - 1;
+        }
+    }
+    assert!(len == N, "N is longer than the decoded bytes");
+    out
+}
+
+/// Encode some bytes at compile time into a `&'static str`
+///
+/// The argument must be a constant expression of a type that coerces to `&[u8]`.
+///
+/// # Example
+/// ```
+/// const KEY: [u8; 5] = *b"hello";
+/// const TOKEN: &str = base2048::encode_str!(&KEY);
+/// assert_eq!(TOKEN, base2048::encode(KEY));
+/// ```
+#[macro_export]
+macro_rules! encode_str {
+    ($bytes:expr) => {{
+        // items aren't hygienic, so these names mustn't clash with what `$bytes` refers to
+        const BASE2048_INPUT: &[u8] = $bytes;
+        const BASE2048_UTF8: [u8; $crate::encoded_utf8_len(BASE2048_INPUT)] =
+            $crate::encode_const(BASE2048_INPUT);
+        const BASE2048_STR: &str = match ::core::str::from_utf8(&BASE2048_UTF8) {
+            Ok(s) => s,
+            Err(_) => panic!("base2048 text is always UTF-8"),
+        };
+        BASE2048_STR
+    }};
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{decode, encode};
+    use alloc::vec::Vec;
+
+    #[test]
+    fn matches_encode_decode() {
+        for len in 0..40 {
+            let bytes = (0..len).map(|i| (i * 37) as u8).collect::<Vec<_>>();
+            let encoded = encode(&bytes);
+            assert_eq!(encoded_utf8_len(&bytes), encoded.len());
+            let chars = (0..encoded_len(len)).map(|k| char_at(&bytes, k));
+            assert!(chars.eq(encoded.chars()));
+            let mut buf = [0; 128];
+            let mut pos = 0;
+            for c in encoded.chars() {
+                let (utf8, width) = utf8(c);
+                buf[pos..pos + width].copy_from_slice(&utf8[..width]);
+                assert_eq!(next_char(&buf, pos), (c as u32, width));
+                pos += width;
+            }
+            assert_eq!(&buf[..pos], encoded.as_bytes());
+        }
+    }
+
+    #[test]
+    fn const_round_trip() {
+        const BYTES: [u8; 12] = *b"hello world!";
+        const ENCODED: &str = encode_str!(&BYTES);
+        const DECODED: [u8; 12] = decode_const(ENCODED);
+        assert_eq!(ENCODED, encode(BYTES));
+        assert_eq!(DECODED, BYTES);
+        assert_eq!(decode_const::<0>(""), [0u8; 0]);
+        for len in 0..12 {
+            let encoded = encode(&BYTES[..len]);
+            let decoded = match len {
+                0 => decode_const::<0>(&encoded).to_vec(),
+                1 => decode_const::<1>(&encoded).to_vec(),
+                2 => decode_const::<2>(&encoded).to_vec(),
+                3 => decode_const::<3>(&encoded).to_vec(),
+                4 => decode_const::<4>(&encoded).to_vec(),
+                5 => decode_const::<5>(&encoded).to_vec(),
+                6 => decode_const::<6>(&encoded).to_vec(),
+                7 => decode_const::<7>(&encoded).to_vec(),
+                8 => decode_const::<8>(&encoded).to_vec(),
+                9 => decode_const::<9>(&encoded).to_vec(),
+                10 => decode_const::<10>(&encoded).to_vec(),
+                _ => decode_const::<11>(&encoded).to_vec(),
+            };
+            assert_eq!(Some(decoded), decode(&encoded));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid base2048 character")]
+    fn invalid() {
+        decode_const::<8>("ետћζы༎X");
+    }
+}
@@ -0,0 +1,57 @@
+//! Timing-safe comparison, for secrets such as auth tokens sent as base2048.
+use crate::Base2048Str;
+use core::hint::black_box;
+
+/// Compares two encoded strings in time that depends only on their lengths
+///
+/// Checking a token with `==` returns at the first differing byte, so the time taken tells an
+/// attacker how much of a guess was right. This looks at every byte whatever the contents.
+///
+/// The text is compared, not the bytes it encodes, so a string with non-canonical padding never
+/// equals the canonical encoding of the same bytes. Use [`constant_time_eq_bytes`] on the decoded
+/// bytes to compare those instead.
+///
+/// # Example
+/// ```
+/// use base2048::{constant_time_eq, Base2048Str};
+/// let expected = base2048::encode(b"secret token");
+/// let expected = Base2048Str::new(&expected).unwrap();
+/// let received = base2048::encode(b"secret token");
+/// assert!(constant_time_eq(expected, Base2048Str::new(&received).unwrap()));
+/// ```
+pub fn constant_time_eq(a: &Base2048Str, b: &Base2048Str) -> bool {
+    constant_time_eq_bytes(a.as_bytes(), b.as_bytes())
+}
+
+/// Compares two byte strings, such as decoded tokens, in time that depends only on their lengths
+pub fn constant_time_eq_bytes(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut diff = 0u8;
+    for (x, y) in a.iter().zip(b) {
+        // black_box keeps the optimiser from turning the loop back into an early exit
+        diff = black_box(diff | (x ^ y));
+    }
+    diff == 0
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::encode;
+
+    #[test]
+    fn compares() {
+        let a = encode(b"secret token");
+        let b = encode(b"secret tokem");
+        let a = Base2048Str::new(&a).unwrap();
+        let b = Base2048Str::new(&b).unwrap();
+        assert!(constant_time_eq(a, a));
+        assert!(!constant_time_eq(a, b));
+        assert!(constant_time_eq_bytes(b"", b""));
+        assert!(constant_time_eq_bytes(b"abc", b"abc"));
+        assert!(!constant_time_eq_bytes(b"abc", b"abd"));
+        assert!(!constant_time_eq_bytes(b"abc", b"ab"));
+    }
+}
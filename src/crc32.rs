@@ -0,0 +1,206 @@
+//! CRC-32 with the IEEE polynomial, as used by zlib, PNG and Ethernet.
+use crate::{encode_into, try_decode, DecodeError};
+use alloc::{string::String, vec::Vec};
+use core::fmt;
+
+const TABLE: [u32; 256] = table();
+
+const fn table() -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+/// A CRC-32 computed a piece at a time.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Crc32(u32);
+
+impl Crc32 {
+    pub(crate) fn new() -> Self {
+        Crc32(!0)
+    }
+
+    pub(crate) fn update(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = (self.0 >> 8) ^ TABLE[((self.0 ^ *byte as u32) & 0xFF) as usize];
+        }
+    }
+
+    pub(crate) fn finish(self) -> u32 {
+        !self.0
+    }
+}
+
+/// The number of bytes of CRC-32 [`encode_with_crc`] appends to the payload.
+const CRC_BYTES: usize = 4;
+
+/// Why a string couldn't be decoded by [`decode_with_crc`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CrcError {
+    /// The string isn't valid base2048.
+    Decode(DecodeError),
+    /// The string decodes to fewer bytes than a CRC-32.
+    Truncated,
+    /// The CRC-32 after the payload isn't the payload's.
+    Mismatch {
+        /// The CRC-32 in the string.
+        expected: u32,
+        /// The CRC-32 of the payload as decoded.
+        actual: u32,
+    },
+}
+
+impl From<DecodeError> for CrcError {
+    fn from(e: DecodeError) -> Self {
+        CrcError::Decode(e)
+    }
+}
+
+#[cfg(not(feature = "compact-errors"))]
+impl fmt::Display for CrcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CrcError::Decode(e) => e.fmt(f),
+            CrcError::Truncated => f.write_str("the string is too short to hold a CRC-32"),
+            CrcError::Mismatch { expected, actual } => write!(
+                f,
+                "the CRC-32 is {:08x} but the payload's is {:08x}",
+                expected, actual
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "compact-errors")]
+impl fmt::Display for CrcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CrcError::Decode(e) => e.fmt(f),
+            CrcError::Truncated => f.write_str("base2048 crc error 1: truncated"),
+            CrcError::Mismatch { .. } => f.write_str("base2048 crc error 2: mismatch"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CrcError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CrcError::Decode(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Encode some bytes using base2048 encoding, followed by their CRC-32
+///
+/// The CRC-32 is appended big-endian to the bytes before they are encoded, so the text is
+/// [`encoded_len`](crate::encoded_len)`(n + 4)` characters and any base2048 decoder can read the
+/// payload back out. It is lighter than the `ecc` module's parity but only detects damage.
+///
+/// # Example
+/// ```
+/// let encoded = base2048::encode_with_crc(b"hello");
+/// assert_eq!(encoded.chars().count(), base2048::encoded_len(5 + 4));
+/// assert_eq!(base2048::decode_with_crc(&encoded), Ok(b"hello".to_vec()));
+/// ```
+pub fn encode_with_crc(bytes: impl AsRef<[u8]>) -> String {
+    let bytes = bytes.as_ref();
+    let mut crc = Crc32::new();
+    crc.update(bytes);
+    let mut framed = Vec::with_capacity(bytes.len() + CRC_BYTES);
+    framed.extend_from_slice(bytes);
+    framed.extend_from_slice(&crc.finish().to_be_bytes());
+    let mut ret = String::new();
+    encode_into(&framed, &mut ret);
+    ret
+}
+
+/// Decode a string made by [`encode_with_crc`], verifying its CRC-32
+///
+/// # Example
+/// ```
+/// use base2048::{decode_with_crc, encode, CrcError};
+/// assert_eq!(decode_with_crc(encode(b"")), Err(CrcError::Truncated));
+/// assert!(matches!(decode_with_crc(encode(b"hello")), Err(CrcError::Mismatch { .. })));
+/// ```
+pub fn decode_with_crc(string: impl AsRef<str>) -> Result<Vec<u8>, CrcError> {
+    let mut bytes = try_decode(string.as_ref())?;
+    let split = bytes
+        .len()
+        .checked_sub(CRC_BYTES)
+        .ok_or(CrcError::Truncated)?;
+    let mut expected = [0; CRC_BYTES];
+    expected.copy_from_slice(&bytes[split..]);
+    let expected = u32::from_be_bytes(expected);
+    bytes.truncate(split);
+    let mut crc = Crc32::new();
+    crc.update(&bytes);
+    match crc.finish() {
+        actual if actual == expected => Ok(bytes),
+        actual => Err(CrcError::Mismatch { expected, actual }),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{encode, encoded_len, ENC_TABLE};
+
+    #[test]
+    fn check_value() {
+        let mut crc = Crc32::new();
+        crc.update(b"1234");
+        crc.update(b"56789");
+        assert_eq!(crc.finish(), 0xCBF4_3926);
+        assert_eq!(Crc32::new().finish(), 0);
+    }
+
+    #[test]
+    fn round_trips() {
+        for len in 0..40 {
+            let bytes = (0..len).map(|i| (i * 37) as u8).collect::<Vec<_>>();
+            let encoded = encode_with_crc(&bytes);
+            assert_eq!(encoded.chars().count(), encoded_len(len + CRC_BYTES));
+            assert!(try_decode(&encoded).unwrap().starts_with(&bytes));
+            assert_eq!(decode_with_crc(&encoded), Ok(bytes));
+        }
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(decode_with_crc(""), Err(CrcError::Truncated));
+        assert_eq!(decode_with_crc(encode(b"abc")), Err(CrcError::Truncated));
+        let mut chars = encode_with_crc(b"hello").chars().collect::<Vec<_>>();
+        chars[0] = if chars[0] == ENC_TABLE[0] {
+            ENC_TABLE[1]
+        } else {
+            ENC_TABLE[0]
+        };
+        assert!(matches!(
+            decode_with_crc(chars.iter().collect::<String>()),
+            Err(CrcError::Mismatch { .. })
+        ));
+        assert_eq!(
+            decode_with_crc("!"),
+            Err(CrcError::Decode(DecodeError::InvalidChar {
+                c: '!',
+                char_index: 0
+            }))
+        );
+    }
+}
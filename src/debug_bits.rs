@@ -0,0 +1,189 @@
+//! Step-by-step traces of the encoder and decoder state (the `debug-bits` feature).
+//!
+//! Every byte or character fed in produces one step showing the staged bits afterwards, which
+//! makes disagreements about tail characters and padding much easier to pin down.
+use crate::{DecodeState, EncodeState};
+use alloc::{string::String, vec::Vec};
+use core::fmt;
+
+/// The encoder state after taking in a byte (or finishing).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EncodeStep {
+    /// The byte taken in, `None` for the final step.
+    pub byte: Option<u8>,
+    /// Bits waiting to be put into a character.
+    pub stage: u16,
+    /// How many bits of `stage` are in use.
+    pub remaining: u32,
+    /// The character this step completed.
+    pub emitted: Option<char>,
+}
+
+impl fmt::Display for EncodeStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.byte {
+            Some(byte) => write!(f, "byte {:#04x}", byte)?,
+            None => write!(f, "finish   ")?,
+        }
+        write!(
+            f,
+            "  stage {:0>11b}  remaining {:>2}",
+            self.stage, self.remaining
+        )?;
+        if let Some(c) = self.emitted {
+            write!(f, "  -> {}", c)?;
+        }
+        Ok(())
+    }
+}
+
+/// The decoder state after taking in a character (or finishing).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DecodeStep {
+    /// The character taken in, `None` for the final step.
+    pub c: Option<char>,
+    /// Whether the character was treated as the last one.
+    pub last: bool,
+    /// Bits waiting to be put into a byte.
+    pub stage: u32,
+    /// How many bits of `stage` are in use.
+    pub remaining: u8,
+    /// The misalignment between characters and bytes so far.
+    pub residue: u8,
+    bytes: [u8; 2],
+    n_bytes: u8,
+}
+
+impl DecodeStep {
+    fn new(c: Option<char>, last: bool) -> Self {
+        DecodeStep {
+            c,
+            last,
+            stage: 0,
+            remaining: 0,
+            residue: 0,
+            bytes: [0; 2],
+            n_bytes: 0,
+        }
+    }
+
+    fn with_state(self, state: &DecodeState) -> Self {
+        DecodeStep {
+            // only the lowest `remaining` bits are ever set
+            stage: state.stage as u32,
+            remaining: state.remaining,
+            residue: state.residue,
+            ..self
+        }
+    }
+
+    fn emit(&mut self, byte: u8) {
+        self.bytes[self.n_bytes as usize] = byte;
+        self.n_bytes += 1;
+    }
+
+    /// The bytes this step completed.
+    pub fn emitted(&self) -> &[u8] {
+        &self.bytes[..self.n_bytes as usize]
+    }
+}
+
+impl fmt::Display for DecodeStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.c {
+            Some(c) if self.last => write!(f, "last {}", c)?,
+            Some(c) => write!(f, "char {}", c)?,
+            None => write!(f, "finish")?,
+        }
+        write!(
+            f,
+            "  stage {:0>8b}  remaining {}  residue {}",
+            self.stage, self.remaining, self.residue
+        )?;
+        if self.n_bytes > 0 {
+            write!(f, "  -> {:02x?}", self.emitted())?;
+        }
+        Ok(())
+    }
+}
+
+/// Like [`encode`](crate::encode) but hands every step of the encoder to `trace`.
+///
+/// # Example
+/// ```
+/// let mut steps = vec![];
+/// let encoded = base2048::trace_encode(&[0xFF, 0xFF], |step| steps.push(step));
+/// assert_eq!(encoded, base2048::encode(&[0xFF, 0xFF]));
+/// assert_eq!(steps.len(), 3);
+/// assert_eq!(steps[2].remaining, 5);
+/// ```
+pub fn trace_encode(bytes: &[u8], mut trace: impl FnMut(EncodeStep)) -> String {
+    let mut ret = String::new();
+    let mut state = EncodeState::default();
+    for byte in bytes {
+        let emitted = state.push(*byte);
+        ret.extend(emitted);
+        trace(EncodeStep {
+            byte: Some(*byte),
+            stage: state.stage as u16,
+            remaining: state.remaining,
+            emitted,
+        });
+    }
+    let before = state;
+    let emitted = state.finish();
+    ret.extend(emitted);
+    trace(EncodeStep {
+        byte: None,
+        stage: before.stage as u16,
+        remaining: before.remaining,
+        emitted,
+    });
+    ret
+}
+
+/// Like [`decode`](crate::decode) but hands every step of the decoder to `trace`.
+///
+/// When decoding fails the character it failed on is the one after the last step.
+pub fn trace_decode(string: &str, mut trace: impl FnMut(DecodeStep)) -> Option<Vec<u8>> {
+    let mut ret = vec![];
+    let mut state = DecodeState::default();
+    let mut chars = string.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        let last = chars.peek().is_none();
+        let mut step = DecodeStep::new(Some(c), last);
+        state.push(c, last, |byte| step.emit(byte)).ok()?;
+        ret.extend_from_slice(step.emitted());
+        trace(step.with_state(&state));
+    }
+
+    let mut step = DecodeStep::new(None, false).with_state(&state);
+    state.finish(|byte| step.emit(byte));
+    ret.extend_from_slice(step.emitted());
+    trace(step);
+
+    Some(ret)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{decode, encode};
+
+    #[test]
+    fn traces_agree_with_plain_functions() {
+        for len in 0..25 {
+            let bytes = (0..len).map(|i| (i * 29 + 3) as u8).collect::<Vec<_>>();
+            let mut encode_steps = 0;
+            let encoded = trace_encode(&bytes, |_| encode_steps += 1);
+            assert_eq!(encoded, encode(&bytes));
+            assert_eq!(encode_steps, len + 1);
+
+            let mut emitted = vec![];
+            let decoded = trace_decode(&encoded, |step| emitted.extend_from_slice(step.emitted()));
+            assert_eq!(decoded, decode(&encoded));
+            assert_eq!(Some(emitted), decoded);
+        }
+    }
+}
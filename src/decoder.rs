@@ -0,0 +1,192 @@
+//! Decoding input that arrives in pieces.
+use crate::{diagnostics, DecodeError, DecodeState};
+
+/// Decodes base2048 text that arrives in chunks, such as WebSocket messages, without collecting it
+/// first.
+///
+/// A chunk may end anywhere, even part way through the encoding of a byte; each chunk passed to
+/// [`push`](Self::push) gives the bytes it completes. The last character is interpreted differently
+/// from the rest, so one character is always held back until the next chunk or
+/// [`finish`](Self::finish) shows whether it was the last. Error positions count characters from
+/// the start of the first chunk.
+///
+/// # Example
+/// ```
+/// let encoded = base2048::encode(b"hello");
+/// let (first, second) = encoded.split_at(encoded.char_indices().nth(2).unwrap().0);
+/// let mut decoder = base2048::Decoder::new();
+/// let mut decoded = Vec::new();
+/// decoder.push(first, &mut decoded).unwrap();
+/// decoder.push(second, &mut decoded).unwrap();
+/// decoder.finish(&mut decoded).unwrap();
+/// assert_eq!(decoded, b"hello");
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Decoder {
+    chars: CharDecoder,
+    /// Set once the input turns out to be invalid.
+    error: Option<DecodeError>,
+}
+
+impl Decoder {
+    /// A decoder that hasn't been given anything yet.
+    pub fn new() -> Self {
+        Decoder::default()
+    }
+
+    /// Whether a final character whose padding bits aren't all set is rejected, as
+    /// [`try_decode_strict`](crate::try_decode_strict) rejects it.
+    ///
+    /// # Example
+    /// ```
+    /// use base2048::DecodeError;
+    /// let loose = base2048::encode(b"hi").replace('Ո', "Ԉ");
+    /// let mut decoder = base2048::Decoder::new().with_strict(true);
+    /// let mut decoded = Vec::new();
+    /// decoder.push(&loose, &mut decoded).unwrap();
+    /// assert_eq!(
+    ///     decoder.finish(&mut decoded),
+    ///     Err(DecodeError::InvalidPadding { char_index: 1 })
+    /// );
+    /// ```
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.chars.set_strict(strict);
+        self
+    }
+
+    /// Decodes `text`, adding the bytes it completes to `out`.
+    ///
+    /// Once an error has been returned, every later call returns it again and adds nothing.
+    pub fn push(&mut self, text: &str, out: &mut impl Extend<u8>) -> Result<(), DecodeError> {
+        if let Some(e) = self.error {
+            return Err(e);
+        }
+        for c in text.chars() {
+            if let Err(e) = self
+                .chars
+                .push(c, |byte| out.extend(core::iter::once(byte)))
+            {
+                self.error = Some(e);
+                return Err(diagnostics::decode_error(e));
+            }
+        }
+        Ok(())
+    }
+
+    /// Decodes the character held back, adding the last bytes to `out`.
+    pub fn finish(self, out: &mut impl Extend<u8>) -> Result<(), DecodeError> {
+        if let Some(e) = self.error {
+            return Err(e);
+        }
+        self.chars
+            .finish(|byte| out.extend(core::iter::once(byte)))
+            .map_err(diagnostics::decode_error)
+    }
+}
+
+/// Decodes characters one at a time.
+///
+/// The last character is interpreted differently so each character is held back until the next
+/// one (or the end) arrives.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct CharDecoder {
+    state: DecodeState,
+    pending: Option<char>,
+}
+
+impl CharDecoder {
+    /// Rejects a last character with non-canonical padding if `strict`.
+    pub(crate) fn set_strict(&mut self, strict: bool) {
+        self.state.strict = strict;
+    }
+
+    pub(crate) fn push(&mut self, c: char, push: impl FnMut(u8)) -> Result<(), DecodeError> {
+        if let Some(pending) = self.pending.replace(c) {
+            self.state.push(pending, false, push)?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn finish(mut self, mut push: impl FnMut(u8)) -> Result<(), DecodeError> {
+        if let Some(pending) = self.pending.take() {
+            self.state.push(pending, true, &mut push)?;
+        }
+        self.state.finish_checked(push);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::encode;
+    use alloc::{string::String, vec::Vec};
+
+    fn decode_in_chunks(text: &str, chunk_len: usize) -> Result<Vec<u8>, DecodeError> {
+        let chars = text.chars().collect::<Vec<_>>();
+        let mut decoder = Decoder::new();
+        let mut ret = Vec::new();
+        for chunk in chars.chunks(chunk_len) {
+            decoder.push(&chunk.iter().collect::<String>(), &mut ret)?;
+        }
+        decoder.push("", &mut ret)?;
+        decoder.finish(&mut ret)?;
+        Ok(ret)
+    }
+
+    #[test]
+    fn chunks_match_decode() {
+        for len in 0..40 {
+            let bytes = (0..len).map(|i| (i * 37) as u8).collect::<Vec<_>>();
+            let encoded = encode(&bytes);
+            for chunk_len in 1..6 {
+                assert_eq!(decode_in_chunks(&encoded, chunk_len), Ok(bytes.clone()));
+            }
+        }
+    }
+
+    #[test]
+    fn errors() {
+        for chunk_len in 1..6 {
+            assert_eq!(
+                decode_in_chunks("ետћζы༎X", chunk_len),
+                Err(DecodeError::InvalidChar {
+                    c: '༎',
+                    char_index: 5
+                })
+            );
+            assert_eq!(
+                decode_in_chunks("ետ0ζ", chunk_len),
+                Err(DecodeError::TrailingData { char_index: 2 })
+            );
+        }
+        let mut decoder = Decoder::new();
+        let e = decoder.push("༎ե", &mut Vec::new());
+        assert!(e.is_err());
+        assert_eq!(decoder.push("ետ", &mut Vec::new()), e);
+        assert_eq!(decoder.finish(&mut Vec::new()), e);
+    }
+
+    #[test]
+    fn strict() {
+        let loose = encode(b"hi").replace('Ո', "Ԉ");
+        let mut decoder = Decoder::new();
+        let mut decoded = Vec::new();
+        decoder.push(&loose, &mut decoded).unwrap();
+        decoder.finish(&mut decoded).unwrap();
+        assert_eq!(decoded, b"hi");
+
+        let mut decoder = Decoder::new().with_strict(true);
+        let mut decoded = Vec::new();
+        decoder.push(&encode(b"hi"), &mut decoded).unwrap();
+        decoder.finish(&mut decoded).unwrap();
+        assert_eq!(decoded, b"hi");
+
+        let mut decoder = Decoder::new().with_strict(true);
+        decoder.push(&loose, &mut Vec::new()).unwrap();
+        assert_eq!(
+            decoder.finish(&mut Vec::new()),
+            Err(DecodeError::InvalidPadding { char_index: 1 })
+        );
+    }
+}
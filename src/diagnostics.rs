@@ -0,0 +1,23 @@
+//! Records for the [`log`](https://docs.rs/log) crate (the `log` feature).
+//!
+//! Without the feature these do nothing and compile away. Everything is logged with the target
+//! `base2048`.
+use crate::DecodeWarning;
+use core::fmt::Display;
+
+/// Logs why a decode failed at debug level, passing the error on so it fits in a `map_err`.
+#[cold]
+pub(crate) fn decode_error<E: Display>(e: E) -> E {
+    #[cfg(feature = "log")]
+    log::debug!(target: "base2048", "decode failed: {}", e);
+    e
+}
+
+/// Logs something odd that a decoder accepted anyway at warn level.
+#[cold]
+pub(crate) fn fixup(warning: DecodeWarning) {
+    #[cfg(feature = "log")]
+    log::warn!(target: "base2048", "{}", warning);
+    #[cfg(not(feature = "log"))]
+    let _ = warning;
+}
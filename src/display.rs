@@ -0,0 +1,127 @@
+use crate::{encode_with, encoded_len, BITS_PER_CHAR};
+use core::fmt::{self, Alignment, Write};
+
+/// Encodes a byte slice as base2048 when formatted, without allocating.
+///
+/// Width, fill and alignment work as they do for `str`. A precision limits the output to that many
+/// characters: if the encoding is longer it is cut short and ends in `…` (which counts towards the
+/// limit). The cut always falls on a character boundary so previews are safe to put in logs.
+///
+/// # Example
+/// ```
+/// use base2048::Base2048Display;
+/// let bytes = b"some utf8 bytes to encode more compactly";
+/// assert_eq!(format!("{}", Base2048Display::new(bytes)), base2048::encode(bytes));
+/// assert_eq!(format!("{:.4}", Base2048Display::new(bytes)), "ݙޙצ…");
+/// assert_eq!(format!("[{:>6.3}]", Base2048Display::new(bytes)), "[   ݙޙ…]");
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct Base2048Display<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Base2048Display<'a> {
+    /// Wraps `bytes` so they are encoded when formatted.
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Base2048Display { bytes }
+    }
+}
+
+/// Wrap some bytes so they are base2048 encoded when formatted, without allocating
+///
+/// Short for [`Base2048Display::new`].
+///
+/// # Example
+/// ```
+/// let digest = [0xAB; 32];
+/// let line = format!("digest {}", base2048::display(&digest));
+/// assert_eq!(line, format!("digest {}", base2048::encode(digest)));
+/// ```
+pub fn display(bytes: &[u8]) -> Base2048Display<'_> {
+    Base2048Display::new(bytes)
+}
+
+impl fmt::Display for Base2048Display<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let total = encoded_len(self.bytes.len());
+        let (shown, ellipsis) = match f.precision() {
+            Some(precision) if precision < total => (precision.saturating_sub(1), precision > 0),
+            _ => (total, false),
+        };
+
+        let padding = f
+            .width()
+            .map_or(0, |width| width.saturating_sub(shown + ellipsis as usize));
+        let (before, after) = match f.align() {
+            None | Some(Alignment::Left) => (0, padding),
+            Some(Alignment::Right) => (padding, 0),
+            Some(Alignment::Center) => (padding / 2, padding - padding / 2),
+        };
+        let fill = f.fill();
+
+        for _ in 0..before {
+            f.write_char(fill)?;
+        }
+
+        // only encode as many bytes as it takes to determine the characters we show
+        let needed = (shown * BITS_PER_CHAR as usize).div_ceil(8);
+        let bytes = &self.bytes[..needed.min(self.bytes.len())];
+        let mut res = Ok(());
+        let mut written = 0;
+        encode_with(bytes, |c| {
+            if written < shown && res.is_ok() {
+                res = f.write_char(c);
+            }
+            written += 1;
+        });
+        res?;
+
+        if ellipsis {
+            f.write_char('…')?;
+        }
+        for _ in 0..after {
+            f.write_char(fill)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::encode;
+    use alloc::{string::String, vec::Vec};
+
+    #[test]
+    fn truncation_is_a_prefix() {
+        for len in 0..30 {
+            let bytes = (0..len).map(|i| (i * 91) as u8).collect::<Vec<_>>();
+            let encoded = encode(&bytes);
+            let n_chars = encoded.chars().count();
+            assert_eq!(format!("{}", Base2048Display::new(&bytes)), encoded);
+            for precision in 0..n_chars + 2 {
+                let shown = format!("{:.*}", precision, Base2048Display::new(&bytes));
+                if precision >= n_chars {
+                    assert_eq!(shown, encoded);
+                } else {
+                    let expected = encoded
+                        .chars()
+                        .take(precision.saturating_sub(1))
+                        .chain(Some('…').filter(|_| precision > 0))
+                        .collect::<String>();
+                    assert_eq!(shown, expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn width_and_fill() {
+        let display = Base2048Display::new(&[0xFF, 0xFF]);
+        assert_eq!(format!("{}", display), "ၕၕ");
+        assert_eq!(format!("{:4}", display), "ၕၕ  ");
+        assert_eq!(format!("{:*^7}", display), "**ၕၕ***");
+        assert_eq!(format!("{:>4.1}", display), "   …");
+        assert_eq!(format!("{:1}", display), "ၕၕ");
+    }
+}
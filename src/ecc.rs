@@ -0,0 +1,510 @@
+//! Error correction: text that decodes even with some characters replaced (the `ecc` feature).
+//!
+//! The encoded characters are taken as symbols of GF(2¹¹) and split evenly into as few blocks of
+//! at most 2047 as will do, each followed by parity characters of a Reed-Solomon code, two for
+//! every character it can correct. Any `corrections` characters of a block can be wrong, including
+//! ones replaced by characters outside the alphabet, and [`decode`] still gives back the original
+//! bytes and says how many it put right. Inserted or deleted characters can't be corrected.
+//!
+//! The blocks are interleaved: the text is the first character of each block, then the second of
+//! each, and so on. Damage tends to come in runs, such as a pasted-over line, and a run of up to
+//! `corrections` times the number of blocks characters touches each block at most `corrections`
+//! times.
+//!
+//! All of the text is table characters: the tail character, if the bytes need one, is folded into
+//! a trailer character after the data so it is protected too.
+//!
+//! ```
+//! use base2048::ecc;
+//! let encoded = ecc::encode(b"hello", 2);
+//! assert_eq!(encoded.chars().count(), ecc::encoded_len(5, 2));
+//! let mut damaged = encoded.chars().collect::<Vec<_>>();
+//! damaged[0] = 'x';
+//! damaged[3] = '?';
+//! let damaged = damaged.into_iter().collect::<String>();
+//! let decoded = ecc::decode(&damaged, 2).unwrap();
+//! assert_eq!((decoded.bytes.as_slice(), decoded.corrected), (&b"hello"[..], 2));
+//! ```
+use crate::{
+    checked::mul, encoded_len as data_len, tables, try_decode, DecodeError, Symbol, TAIL, TAIL_BITS,
+};
+use alloc::{string::String, vec, vec::Vec};
+use core::fmt;
+
+/// The most characters in a block, data and parity: one less than the size of the field.
+pub const MAX_BLOCK_CHARS: usize = 2047;
+
+/// The most characters a block can correct, leaving room for one data character.
+pub const MAX_CORRECTIONS: usize = (MAX_BLOCK_CHARS - 1) / 2;
+
+/// α⁰ to α²⁰⁴⁶, twice over so products of two logarithms need no reduction.
+static EXP: [u16; 2 * MAX_BLOCK_CHARS] = exp();
+
+/// The logarithm to base α of each nonzero element.
+static LOG: [u16; MAX_BLOCK_CHARS + 1] = log();
+
+const fn exp() -> [u16; 2 * MAX_BLOCK_CHARS] {
+    let mut exp = [0; 2 * MAX_BLOCK_CHARS];
+    let mut power = 1;
+    let mut i = 0;
+    while i < exp.len() {
+        exp[i] = power;
+        power = mul(power, 2);
+        i += 1;
+    }
+    exp
+}
+
+const fn log() -> [u16; MAX_BLOCK_CHARS + 1] {
+    let mut log = [0; MAX_BLOCK_CHARS + 1];
+    let mut i = 0;
+    while i < MAX_BLOCK_CHARS {
+        log[EXP[i] as usize] = i as u16;
+        i += 1;
+    }
+    log
+}
+
+fn gf_mul(a: u16, b: u16) -> u16 {
+    match a == 0 || b == 0 {
+        true => 0,
+        false => EXP[LOG[a as usize] as usize + LOG[b as usize] as usize],
+    }
+}
+
+fn gf_div(a: u16, b: u16) -> u16 {
+    match a {
+        0 => 0,
+        _ => EXP[LOG[a as usize] as usize + MAX_BLOCK_CHARS - LOG[b as usize] as usize],
+    }
+}
+
+/// αⁿ.
+fn alpha(n: usize) -> u16 {
+    EXP[n % MAX_BLOCK_CHARS]
+}
+
+/// The value of the polynomial with coefficients `poly`, lowest first, at `x`.
+fn eval(poly: &[u16], x: u16) -> u16 {
+    poly.iter().rev().fold(0, |acc, c| gf_mul(acc, x) ^ c)
+}
+
+/// Why a string couldn't be decoded by [`decode`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EccError {
+    /// The string is too short for its blocks to hold their parity characters, so characters
+    /// were probably deleted or the wrong `corrections` was given.
+    InvalidLength,
+    /// A block has more wrong characters than it can correct.
+    Uncorrectable {
+        /// The block's position among the interleaved blocks, counting from zero.
+        block: usize,
+    },
+    /// The corrected data isn't valid base2048, which also means too many wrong characters.
+    Decode(DecodeError),
+}
+
+#[cfg(not(feature = "compact-errors"))]
+impl fmt::Display for EccError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EccError::InvalidLength => f.write_str("the string is too short for its parity"),
+            EccError::Uncorrectable { block } => {
+                write!(f, "block {} has too many errors to correct", block)
+            }
+            EccError::Decode(e) => e.fmt(f),
+        }
+    }
+}
+
+#[cfg(feature = "compact-errors")]
+impl fmt::Display for EccError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EccError::InvalidLength => f.write_str("base2048 ecc error 1: invalid length"),
+            EccError::Uncorrectable { .. } => f.write_str("base2048 ecc error 2: uncorrectable"),
+            EccError::Decode(e) => e.fmt(f),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for EccError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            EccError::Decode(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// The bytes [`decode`] found, and how many characters it had to correct to find them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Decoded {
+    /// The decoded bytes.
+    pub bytes: Vec<u8>,
+    /// How many characters were wrong, over all the blocks.
+    pub corrected: usize,
+}
+
+/// The data characters in a full block.
+fn block_data(corrections: usize) -> usize {
+    assert!(
+        corrections <= MAX_CORRECTIONS,
+        "a block can correct at most {} characters",
+        MAX_CORRECTIONS
+    );
+    MAX_BLOCK_CHARS - 2 * corrections
+}
+
+/// The number of characters [`encode`] turns `n_bytes` bytes into.
+///
+/// # Panics
+///
+/// If `corrections` is more than [`MAX_CORRECTIONS`].
+///
+/// # Example
+/// ```
+/// // 4 data characters and a trailer, then 4 parity characters
+/// assert_eq!(base2048::ecc::encoded_len(5, 2), 9);
+/// ```
+pub fn encoded_len(n_bytes: usize, corrections: usize) -> usize {
+    // the table characters of the plain encoding, then the trailer
+    let bits = n_bytes * 8;
+    let data = bits / 11 + (bits % 11 > TAIL_BITS as usize) as usize + 1;
+    data + data.div_ceil(block_data(corrections)) * 2 * corrections
+}
+
+/// The length of each of the `n_blocks` blocks that share `n_data` data characters, longest first.
+fn block_lens(n_data: usize, n_blocks: usize, n_parity: usize) -> Vec<usize> {
+    (0..n_blocks)
+        .map(|b| n_data / n_blocks + (b < n_data % n_blocks) as usize + n_parity)
+        .collect()
+}
+
+/// Where each character of the text goes among blocks of lengths `lens`, as (block, index) pairs:
+/// the first of each block, then the second of each, and so on.
+fn interleaved(lens: &[usize]) -> impl Iterator<Item = (usize, usize)> + '_ {
+    let longest = lens.first().copied().unwrap_or(0);
+    (0..longest).flat_map(move |i| {
+        lens.iter()
+            .enumerate()
+            .filter(move |(_, &len)| i < len)
+            .map(move |(b, _)| (b, i))
+    })
+}
+
+/// The generator polynomial (x - α)(x - α²)… with a root for each parity character, highest
+/// coefficient first.
+fn generator(n_parity: usize) -> Vec<u16> {
+    let mut g = vec![1];
+    for i in 1..=n_parity {
+        g.push(0);
+        for j in (1..g.len()).rev() {
+            g[j] ^= gf_mul(g[j - 1], alpha(i));
+        }
+    }
+    g
+}
+
+/// Encode some bytes using base2048 encoding, with parity to correct up to `corrections` wrong
+/// characters in each block
+///
+/// # Panics
+///
+/// If `corrections` is more than [`MAX_CORRECTIONS`].
+pub fn encode(bytes: impl AsRef<[u8]>, corrections: usize) -> String {
+    let bytes = bytes.as_ref();
+    let mut symbols = Vec::with_capacity(data_len(bytes.len()) + 1);
+    let mut trailer = 0;
+    for c in crate::encode(bytes).chars() {
+        match Symbol::from_char(c) {
+            Some(Symbol::Table(index)) => symbols.push(index),
+            Some(Symbol::Tail(index)) => trailer = 1 + index as u16,
+            None => unreachable!("encoding only gives table and tail characters"),
+        }
+    }
+    symbols.push(trailer);
+
+    let n_parity = 2 * corrections;
+    let g = generator(n_parity);
+    let n_blocks = symbols.len().div_ceil(block_data(corrections));
+    let lens = block_lens(symbols.len(), n_blocks, n_parity);
+    let mut data = &symbols[..];
+    let blocks = lens
+        .iter()
+        .map(|len| {
+            let (block, rest) = data.split_at(len - n_parity);
+            data = rest;
+            let mut parity = vec![0; n_parity];
+            for &symbol in block {
+                if let Some(&first) = parity.first() {
+                    let feedback = symbol ^ first;
+                    parity.remove(0);
+                    parity.push(0);
+                    for (p, g) in parity.iter_mut().zip(&g[1..]) {
+                        *p ^= gf_mul(feedback, *g);
+                    }
+                }
+            }
+            [block, &parity].concat()
+        })
+        .collect::<Vec<_>>();
+
+    let enc_table = tables::enc_table();
+    interleaved(&lens)
+        .map(|(b, i)| enc_table[blocks[b][i] as usize])
+        .collect()
+}
+
+/// Decode a string made by [`encode`] with the same `corrections`, correcting wrong characters
+///
+/// # Panics
+///
+/// If `corrections` is more than [`MAX_CORRECTIONS`].
+pub fn decode(string: impl AsRef<str>, corrections: usize) -> Result<Decoded, EccError> {
+    let data_per_block = block_data(corrections);
+    let n_parity = 2 * corrections;
+    // anything outside the table is as wrong as a wrong table character
+    let symbols = string
+        .as_ref()
+        .chars()
+        .map(|c| tables::table_index(c as u32).unwrap_or(0))
+        .collect::<Vec<_>>();
+    // the only lengths `encode` gives: as many blocks as the data needs, each with its parity
+    let n_blocks = symbols.len().div_ceil(MAX_BLOCK_CHARS);
+    let n_data = match symbols.len().checked_sub(n_blocks * n_parity) {
+        Some(n_data) if n_data > 0 && n_data.div_ceil(data_per_block) == n_blocks => n_data,
+        _ => return Err(EccError::InvalidLength),
+    };
+
+    let lens = block_lens(n_data, n_blocks, n_parity);
+    let mut blocks = lens
+        .iter()
+        .map(|&len| Vec::with_capacity(len))
+        .collect::<Vec<_>>();
+    for ((b, _), symbol) in interleaved(&lens).zip(symbols) {
+        blocks[b].push(symbol);
+    }
+
+    let mut corrected = 0;
+    let mut data = Vec::with_capacity(n_data);
+    for (i, block) in blocks.iter_mut().enumerate() {
+        corrected += correct(block, n_parity).ok_or(EccError::Uncorrectable { block: i })?;
+        data.extend_from_slice(&block[..block.len() - n_parity]);
+    }
+
+    let trailer = data.pop().expect("every block has data");
+    let enc_table = tables::enc_table();
+    let mut text = data
+        .iter()
+        .map(|&i| enc_table[i as usize])
+        .collect::<String>();
+    match trailer {
+        0 => {}
+        1..=8 => text.push(TAIL[trailer as usize - 1]),
+        _ => {
+            return Err(EccError::Uncorrectable {
+                block: n_blocks - 1,
+            })
+        }
+    }
+    let bytes = try_decode(text).map_err(EccError::Decode)?;
+    Ok(Decoded { bytes, corrected })
+}
+
+/// The syndromes of `block`, the received polynomial at each root of the generator.
+fn syndromes(block: &[u16], n_parity: usize) -> Vec<u16> {
+    (1..=n_parity)
+        .map(|j| {
+            let x = alpha(j);
+            block.iter().fold(0, |acc, &r| gf_mul(acc, x) ^ r)
+        })
+        .collect()
+}
+
+/// Corrects `block` in place, returning how many symbols were wrong, or `None` if too many were.
+fn correct(block: &mut [u16], n_parity: usize) -> Option<usize> {
+    let s = syndromes(block, n_parity);
+    if s.iter().all(|&s| s == 0) {
+        return Some(0);
+    }
+
+    // Berlekamp-Massey finds the error locator Λ, lowest coefficient first
+    let mut lambda = vec![1];
+    let mut prev = vec![1];
+    let mut n_errors = 0;
+    let mut shift = 1;
+    let mut prev_discrepancy = 1;
+    for n in 0..n_parity {
+        let discrepancy = (1..=n_errors).fold(s[n], |d, i| {
+            d ^ gf_mul(lambda.get(i).copied().unwrap_or(0), s[n - i])
+        });
+        if discrepancy == 0 {
+            shift += 1;
+            continue;
+        }
+        let scale = gf_div(discrepancy, prev_discrepancy);
+        let mut next = lambda.clone();
+        next.resize(next.len().max(prev.len() + shift), 0);
+        for (i, &b) in prev.iter().enumerate() {
+            next[i + shift] ^= gf_mul(scale, b);
+        }
+        if 2 * n_errors <= n {
+            n_errors = n + 1 - n_errors;
+            prev = core::mem::replace(&mut lambda, next);
+            prev_discrepancy = discrepancy;
+            shift = 1;
+        } else {
+            lambda = next;
+            shift += 1;
+        }
+    }
+    if 2 * n_errors > n_parity {
+        return None;
+    }
+
+    // the error at the symbol for xᵖ makes α⁻ᵖ a root of Λ; Forney's formula gives its value
+    // from Ω = SΛ mod x²ᵗ
+    let mut omega = vec![0; n_parity];
+    for (i, &l) in lambda.iter().enumerate() {
+        for (j, &s) in s.iter().enumerate().take(n_parity.saturating_sub(i)) {
+            omega[i + j] ^= gf_mul(l, s);
+        }
+    }
+    let derivative = lambda
+        .iter()
+        .enumerate()
+        .skip(1)
+        .map(|(i, &l)| if i % 2 == 1 { l } else { 0 })
+        .collect::<Vec<_>>();
+    let len = block.len();
+    let mut found = 0;
+    for (i, r) in block.iter_mut().enumerate() {
+        let x_inv = alpha(MAX_BLOCK_CHARS - (len - 1 - i) % MAX_BLOCK_CHARS);
+        if eval(&lambda, x_inv) == 0 {
+            let d = eval(&derivative, x_inv);
+            if d == 0 {
+                return None;
+            }
+            *r ^= gf_div(eval(&omega, x_inv), d);
+            found += 1;
+        }
+    }
+    match found == n_errors && syndromes(block, n_parity).iter().all(|&s| s == 0) {
+        true => Some(found),
+        false => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ENC_TABLE;
+
+    fn payload(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i * 37 + 5) as u8).collect()
+    }
+
+    #[test]
+    fn round_trips() {
+        for corrections in [0, 1, 3] {
+            for len in 0..40 {
+                let bytes = payload(len);
+                let encoded = encode(&bytes, corrections);
+                assert_eq!(encoded.chars().count(), encoded_len(len, corrections));
+                assert!(encoded.chars().all(|c| ENC_TABLE.contains(&c)));
+                let decoded = decode(&encoded, corrections).unwrap();
+                assert_eq!((decoded.bytes, decoded.corrected), (bytes, 0));
+            }
+        }
+        // without parity, the plain encoding's table characters and the trailer
+        assert_eq!(
+            encode(b"hello", 0).chars().count(),
+            crate::encoded_len(5) + 1
+        );
+        assert_eq!(encode([0xFF; 3], 0).chars().count(), crate::encoded_len(3));
+    }
+
+    #[test]
+    fn corrects_up_to_the_limit() {
+        let mut seed = 1u32;
+        let mut next = move || {
+            seed ^= seed << 13;
+            seed ^= seed >> 17;
+            seed ^= seed << 5;
+            seed as usize
+        };
+        let symbol = |c: char| tables::table_index(c as u32).unwrap_or(0);
+        for corrections in 1..6 {
+            for len in [1, 3, 30, 200] {
+                let bytes = payload(len);
+                let encoded = encode(&bytes, corrections).chars().collect::<Vec<_>>();
+                for n_errors in 0..=corrections {
+                    let mut damaged = encoded.clone();
+                    let mut at = Vec::new();
+                    while at.len() < n_errors {
+                        let i = next() % damaged.len();
+                        let c = match next() % 4 {
+                            0 => '\u{FFFD}',
+                            _ => ENC_TABLE[next() % 2048],
+                        };
+                        if !at.contains(&i) && symbol(c) != symbol(damaged[i]) {
+                            at.push(i);
+                            damaged[i] = c;
+                        }
+                    }
+                    let damaged = damaged.into_iter().collect::<String>();
+                    let decoded = decode(&damaged, corrections).unwrap();
+                    assert_eq!(decoded.bytes, bytes);
+                    assert_eq!(decoded.corrected, n_errors);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn long_input_has_several_blocks() {
+        let bytes = payload(6000);
+        let encoded = encode(&bytes, 4);
+        let chars = encoded.chars().collect::<Vec<_>>();
+        assert_eq!(chars.len(), encoded_len(6000, 4));
+        assert!(chars.len() > 2 * MAX_BLOCK_CHARS);
+        assert!(chars.len() <= 3 * MAX_BLOCK_CHARS);
+        let damage = |at: usize, len: usize| {
+            let mut damaged = chars.clone();
+            damaged[at..at + len].fill('x');
+            decode(damaged.iter().collect::<String>(), 4)
+        };
+        // three blocks of four corrections share out a run of twelve wherever it is
+        for at in [0, 1, 2, 1000, 3000, chars.len() - 12] {
+            let decoded = damage(at, 12).unwrap();
+            assert_eq!((&decoded.bytes, decoded.corrected), (&bytes, 12));
+        }
+        assert!(damage(1000, 13).is_err());
+    }
+
+    #[test]
+    fn blocks_are_interleaved() {
+        assert_eq!(block_lens(7, 3, 2), [5, 4, 4]);
+        assert_eq!(
+            interleaved(&[3, 2, 2]).collect::<Vec<_>>(),
+            [(0, 0), (1, 0), (2, 0), (0, 1), (1, 1), (2, 1), (0, 2)]
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let encoded = encode(b"hello", 1);
+        assert_eq!(decode("", 1), Err(EccError::InvalidLength));
+        let short = encoded.chars().take(2).collect::<String>();
+        assert_eq!(decode(short, 1), Err(EccError::InvalidLength));
+        let mut chars = encoded.chars().collect::<Vec<_>>();
+        chars[0] = 'x';
+        chars[1] = 'x';
+        assert!(matches!(
+            decode(chars.iter().collect::<String>(), 1),
+            Err(EccError::Uncorrectable { block: 0 }) | Err(EccError::Decode(_))
+        ));
+    }
+}
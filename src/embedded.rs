@@ -0,0 +1,662 @@
+//! Streaming over [`embedded_io`] readers and writers, for targets without `std::io`.
+//!
+//! The adapters only buffer a fixed, small amount of text, so they suit a UART or USB-CDC link.
+//! With the `embedded-io-async` feature, [`AsyncEncodeWriter`] and [`AsyncDecodeReader`] do the
+//! same over [`embedded_io_async`].
+use crate::{
+    decoder::CharDecoder,
+    stream::{StreamError, Utf8Chunks},
+    DecodeError, EncodeState,
+};
+use core::fmt;
+use embedded_io::{ErrorKind, ErrorType, Read, Write};
+
+/// The most encoded text buffered at a time, in bytes.
+const TEXT_LEN: usize = 64;
+
+/// The most bytes `TEXT_LEN` bytes of text decode to, since each character is at least a byte and
+/// gives at most 11 bits.
+const OUT_LEN: usize = TEXT_LEN * 11 / 8 + 1;
+
+/// Why reading or writing through an adapter failed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error<E> {
+    /// The underlying reader or writer failed.
+    Io(E),
+    /// The text wasn't valid UTF-8.
+    Utf8,
+    /// The text wasn't valid base2048.
+    Decode(DecodeError),
+}
+
+impl<E> From<StreamError> for Error<E> {
+    fn from(e: StreamError) -> Self {
+        match e {
+            StreamError::Utf8 => Error::Utf8,
+            StreamError::Decode(e) => Error::Decode(e),
+        }
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for Error<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => e.fmt(f),
+            Error::Utf8 => f.write_str("stream did not contain valid UTF-8"),
+            Error::Decode(e) => e.fmt(f),
+        }
+    }
+}
+
+impl<E: embedded_io::Error> embedded_io::Error for Error<E> {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Error::Io(e) => e.kind(),
+            Error::Utf8 | Error::Decode(_) => ErrorKind::InvalidData,
+        }
+    }
+}
+
+/// Encoded text waiting to be written.
+#[derive(Clone, Copy, Debug)]
+struct EncodeBuf {
+    state: EncodeState,
+    text: [u8; TEXT_LEN],
+    len: usize,
+    written: usize,
+}
+
+impl Default for EncodeBuf {
+    fn default() -> Self {
+        EncodeBuf {
+            state: EncodeState::default(),
+            text: [0; TEXT_LEN],
+            len: 0,
+            written: 0,
+        }
+    }
+}
+
+impl EncodeBuf {
+    /// Whether the text should be written out before encoding more.
+    fn is_full(&self) -> bool {
+        self.len + crate::MAX_CHAR_LEN > TEXT_LEN
+    }
+
+    /// Encodes as much of `bytes` as fits, returning how much that was.
+    fn encode(&mut self, bytes: &[u8]) -> usize {
+        let mut n = 0;
+        for byte in bytes {
+            if self.is_full() {
+                break;
+            }
+            if let Some(c) = self.state.push(*byte) {
+                self.len += c.encode_utf8(&mut self.text[self.len..]).len();
+            }
+            n += 1;
+        }
+        n
+    }
+
+    /// Encodes the final character, if the input so far needs one.
+    ///
+    /// The buffer must have been written out first, if it's full.
+    fn finish(&mut self) {
+        if let Some(c) = core::mem::take(&mut self.state).finish() {
+            self.len += c.encode_utf8(&mut self.text[self.len..]).len();
+        }
+    }
+
+    /// The text not written yet.
+    fn pending(&self) -> &[u8] {
+        &self.text[self.written..self.len]
+    }
+
+    /// Marks `n` more bytes of the text as written.
+    fn consume(&mut self, n: usize) {
+        self.written += n;
+        if self.written == self.len {
+            self.len = 0;
+            self.written = 0;
+        }
+    }
+}
+
+/// Decoded bytes waiting to be read.
+#[derive(Clone, Copy, Debug)]
+struct DecodeBuf {
+    chunks: Utf8Chunks,
+    // `None` once the end of the text has been decoded
+    decoder: Option<CharDecoder>,
+    error: Option<StreamError>,
+    out: [u8; OUT_LEN],
+    len: usize,
+    pos: usize,
+}
+
+impl Default for DecodeBuf {
+    fn default() -> Self {
+        DecodeBuf {
+            chunks: Utf8Chunks::default(),
+            decoder: Some(CharDecoder::default()),
+            error: None,
+            out: [0; OUT_LEN],
+            len: 0,
+            pos: 0,
+        }
+    }
+}
+
+impl DecodeBuf {
+    /// Rejects a final character with non-canonical padding if `strict`.
+    fn set_strict(&mut self, strict: bool) {
+        if let Some(decoder) = &mut self.decoder {
+            decoder.set_strict(strict);
+        }
+    }
+
+    /// Reads decoded bytes into `dest`, or `None` if more text is needed first.
+    fn read<E>(&mut self, dest: &mut [u8]) -> Option<Result<usize, Error<E>>> {
+        if self.pos < self.len {
+            let n = dest.len().min(self.len - self.pos);
+            dest[..n].copy_from_slice(&self.out[self.pos..self.pos + n]);
+            self.pos += n;
+            Some(Ok(n))
+        } else if let Some(e) = self.error {
+            Some(Err(e.into()))
+        } else if self.decoder.is_none() || dest.is_empty() {
+            Some(Ok(0))
+        } else {
+            None
+        }
+    }
+
+    /// Decodes the next block of at most `TEXT_LEN` bytes of text, where an empty one means the
+    /// end of it.
+    fn push(&mut self, text: &[u8]) {
+        self.len = 0;
+        self.pos = 0;
+        let (out, len) = (&mut self.out, &mut self.len);
+        let mut push = |byte| {
+            out[*len] = byte;
+            *len += 1;
+        };
+        let decoder = match &mut self.decoder {
+            Some(decoder) => decoder,
+            None => return,
+        };
+        let result = if text.is_empty() {
+            let result = self
+                .chunks
+                .finish()
+                .and_then(|()| decoder.finish(&mut push).map_err(StreamError::from));
+            self.decoder = None;
+            result
+        } else {
+            self.chunks.push(text, |c| {
+                decoder.push(c, &mut push).map_err(StreamError::from)
+            })
+        };
+        self.error = result.err();
+    }
+}
+
+/// Encodes everything written to it, writing the UTF-8 base2048 text to an inner writer.
+///
+/// At most 64 bytes of text are buffered. The bits left over at the end of the input are only
+/// written by [`finish`](Self::finish), since whether they need a tail character depends on there
+/// being no more input.
+///
+/// # Example
+/// ```
+/// use embedded_io::Write;
+/// let mut buf = [0; 16];
+/// let mut writer = base2048::embedded::EncodeWriter::new(&mut buf[..]);
+/// writer.write_all(b"hello").unwrap();
+/// let left = writer.finish().unwrap().len();
+/// let len = buf.len() - left;
+/// assert_eq!(&buf[..len], base2048::encode(b"hello").as_bytes());
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct EncodeWriter<W> {
+    inner: W,
+    buf: EncodeBuf,
+}
+
+impl<W: Write> EncodeWriter<W> {
+    /// Writes the encoded text to `inner`.
+    pub fn new(inner: W) -> Self {
+        EncodeWriter {
+            inner,
+            buf: EncodeBuf::default(),
+        }
+    }
+
+    /// The underlying writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// The underlying writer.
+    ///
+    /// Writing to it directly puts the text out of order with what is still buffered.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Writes out the final character and everything still buffered, then gives back the
+    /// underlying writer.
+    pub fn finish(mut self) -> Result<W, Error<W::Error>> {
+        self.write_buf()?;
+        self.buf.finish();
+        self.write_buf()?;
+        self.inner.flush().map_err(Error::Io)?;
+        Ok(self.inner)
+    }
+
+    fn write_buf(&mut self) -> Result<(), Error<W::Error>> {
+        while !self.buf.pending().is_empty() {
+            let n = self.inner.write(self.buf.pending()).map_err(Error::Io)?;
+            self.buf.consume(n);
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> ErrorType for EncodeWriter<W> {
+    type Error = Error<W::Error>;
+}
+
+impl<W: Write> Write for EncodeWriter<W> {
+    fn write(&mut self, bytes: &[u8]) -> Result<usize, Self::Error> {
+        if !bytes.is_empty() && self.buf.is_full() {
+            self.write_buf()?;
+        }
+        Ok(self.buf.encode(bytes))
+    }
+
+    /// Writes out the buffered text and flushes the underlying writer.
+    ///
+    /// The bits left over from the input so far stay pending, since the next write may complete
+    /// them.
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.write_buf()?;
+        self.inner.flush().map_err(Error::Io)
+    }
+}
+
+/// Decodes UTF-8 base2048 text read from an inner reader, giving the decoded bytes.
+///
+/// The text is read 64 bytes at a time, and characters split between reads are put back
+/// together. The bytes decoded before invalid text are read before the error, and then the error
+/// is returned by every read.
+///
+/// # Example
+/// ```
+/// use embedded_io::Read;
+/// let encoded = base2048::encode(b"hello");
+/// let mut reader = base2048::embedded::DecodeReader::new(encoded.as_bytes());
+/// let mut buf = [0; 8];
+/// let mut len = 0;
+/// loop {
+///     match reader.read(&mut buf[len..]).unwrap() {
+///         0 => break,
+///         n => len += n,
+///     }
+/// }
+/// assert_eq!(&buf[..len], b"hello");
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct DecodeReader<R> {
+    inner: R,
+    text: [u8; TEXT_LEN],
+    buf: DecodeBuf,
+}
+
+impl<R: Read> DecodeReader<R> {
+    /// Reads the encoded text from `inner`.
+    pub fn new(inner: R) -> Self {
+        DecodeReader {
+            inner,
+            text: [0; TEXT_LEN],
+            buf: DecodeBuf::default(),
+        }
+    }
+
+    /// Whether a final character whose padding bits aren't all set is rejected, as
+    /// [`try_decode_strict`](crate::try_decode_strict) rejects it.
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.buf.set_strict(strict);
+        self
+    }
+
+    /// The underlying reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// The underlying reader.
+    ///
+    /// Reading from it directly skips text this reader would have decoded.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Gives back the underlying reader, dropping anything read from it but not yet decoded.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> ErrorType for DecodeReader<R> {
+    type Error = Error<R::Error>;
+}
+
+impl<R: Read> Read for DecodeReader<R> {
+    fn read(&mut self, dest: &mut [u8]) -> Result<usize, Self::Error> {
+        loop {
+            if let Some(result) = self.buf.read(dest) {
+                return result;
+            }
+            let n = self.inner.read(&mut self.text).map_err(Error::Io)?;
+            self.buf.push(&self.text[..n]);
+        }
+    }
+}
+
+/// The async twin of [`EncodeWriter`].
+#[cfg(feature = "embedded-io-async")]
+#[derive(Clone, Copy, Debug)]
+pub struct AsyncEncodeWriter<W> {
+    inner: W,
+    buf: EncodeBuf,
+}
+
+#[cfg(feature = "embedded-io-async")]
+impl<W: embedded_io_async::Write> AsyncEncodeWriter<W> {
+    /// Writes the encoded text to `inner`.
+    pub fn new(inner: W) -> Self {
+        AsyncEncodeWriter {
+            inner,
+            buf: EncodeBuf::default(),
+        }
+    }
+
+    /// The underlying writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// The underlying writer.
+    ///
+    /// Writing to it directly puts the text out of order with what is still buffered.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Writes out the final character and everything still buffered, then gives back the
+    /// underlying writer.
+    pub async fn finish(mut self) -> Result<W, Error<W::Error>> {
+        self.write_buf().await?;
+        self.buf.finish();
+        self.write_buf().await?;
+        self.inner.flush().await.map_err(Error::Io)?;
+        Ok(self.inner)
+    }
+
+    async fn write_buf(&mut self) -> Result<(), Error<W::Error>> {
+        while !self.buf.pending().is_empty() {
+            let n = self
+                .inner
+                .write(self.buf.pending())
+                .await
+                .map_err(Error::Io)?;
+            self.buf.consume(n);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "embedded-io-async")]
+impl<W: embedded_io_async::Write> ErrorType for AsyncEncodeWriter<W> {
+    type Error = Error<W::Error>;
+}
+
+#[cfg(feature = "embedded-io-async")]
+impl<W: embedded_io_async::Write> embedded_io_async::Write for AsyncEncodeWriter<W> {
+    async fn write(&mut self, bytes: &[u8]) -> Result<usize, Self::Error> {
+        if !bytes.is_empty() && self.buf.is_full() {
+            self.write_buf().await?;
+        }
+        Ok(self.buf.encode(bytes))
+    }
+
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        self.write_buf().await?;
+        self.inner.flush().await.map_err(Error::Io)
+    }
+}
+
+/// The async twin of [`DecodeReader`].
+#[cfg(feature = "embedded-io-async")]
+#[derive(Clone, Copy, Debug)]
+pub struct AsyncDecodeReader<R> {
+    inner: R,
+    text: [u8; TEXT_LEN],
+    buf: DecodeBuf,
+}
+
+#[cfg(feature = "embedded-io-async")]
+impl<R: embedded_io_async::Read> AsyncDecodeReader<R> {
+    /// Reads the encoded text from `inner`.
+    pub fn new(inner: R) -> Self {
+        AsyncDecodeReader {
+            inner,
+            text: [0; TEXT_LEN],
+            buf: DecodeBuf::default(),
+        }
+    }
+
+    /// Whether a final character whose padding bits aren't all set is rejected, as
+    /// [`try_decode_strict`](crate::try_decode_strict) rejects it.
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.buf.set_strict(strict);
+        self
+    }
+
+    /// The underlying reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// The underlying reader.
+    ///
+    /// Reading from it directly skips text this reader would have decoded.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Gives back the underlying reader, dropping anything read from it but not yet decoded.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+#[cfg(feature = "embedded-io-async")]
+impl<R: embedded_io_async::Read> ErrorType for AsyncDecodeReader<R> {
+    type Error = Error<R::Error>;
+}
+
+#[cfg(feature = "embedded-io-async")]
+impl<R: embedded_io_async::Read> embedded_io_async::Read for AsyncDecodeReader<R> {
+    async fn read(&mut self, dest: &mut [u8]) -> Result<usize, Self::Error> {
+        loop {
+            if let Some(result) = self.buf.read(dest) {
+                return result;
+            }
+            let n = self.inner.read(&mut self.text).await.map_err(Error::Io)?;
+            self.buf.push(&self.text[..n]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::encode;
+    use alloc::vec::Vec;
+    use core::convert::Infallible;
+
+    /// Takes at most three bytes per write, to split characters.
+    #[derive(Default)]
+    struct Sink(Vec<u8>);
+
+    impl ErrorType for Sink {
+        type Error = Infallible;
+    }
+
+    impl Write for Sink {
+        fn write(&mut self, bytes: &[u8]) -> Result<usize, Infallible> {
+            let n = bytes.len().min(3);
+            self.0.extend_from_slice(&bytes[..n]);
+            Ok(n)
+        }
+
+        fn flush(&mut self) -> Result<(), Infallible> {
+            Ok(())
+        }
+    }
+
+    #[cfg(feature = "embedded-io-async")]
+    impl embedded_io_async::Write for Sink {
+        async fn write(&mut self, bytes: &[u8]) -> Result<usize, Infallible> {
+            Write::write(self, bytes)
+        }
+    }
+
+    /// Gives at most `n` bytes per read.
+    struct Trickle<'a>(&'a [u8], usize);
+
+    impl ErrorType for Trickle<'_> {
+        type Error = Infallible;
+    }
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, dest: &mut [u8]) -> Result<usize, Infallible> {
+            let n = dest.len().min(self.0.len()).min(self.1);
+            dest[..n].copy_from_slice(&self.0[..n]);
+            self.0 = &self.0[n..];
+            Ok(n)
+        }
+    }
+
+    #[cfg(feature = "embedded-io-async")]
+    impl embedded_io_async::Read for Trickle<'_> {
+        async fn read(&mut self, dest: &mut [u8]) -> Result<usize, Infallible> {
+            Read::read(self, dest)
+        }
+    }
+
+    fn bytes(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i * 37) as u8).collect()
+    }
+
+    fn read_to_end<R: Read>(mut reader: R) -> Result<Vec<u8>, R::Error> {
+        let mut out = Vec::new();
+        let mut buf = [0; 7];
+        loop {
+            match reader.read(&mut buf)? {
+                0 => return Ok(out),
+                n => out.extend_from_slice(&buf[..n]),
+            }
+        }
+    }
+
+    #[test]
+    fn write_matches_encode() {
+        let bytes = bytes(1000);
+        for chunk_len in [1, 5, 64, 1000] {
+            let mut writer = EncodeWriter::new(Sink::default());
+            for chunk in bytes.chunks(chunk_len) {
+                writer.write_all(chunk).unwrap();
+            }
+            assert_eq!(writer.finish().unwrap().0, encode(&bytes).into_bytes());
+        }
+    }
+
+    #[test]
+    fn read_matches_decode() {
+        let bytes = bytes(1000);
+        let encoded = encode(&bytes);
+        for n in [1, 2, 5, 64] {
+            let reader = DecodeReader::new(Trickle(encoded.as_bytes(), n));
+            assert_eq!(read_to_end(reader), Ok(bytes.clone()));
+        }
+    }
+
+    #[test]
+    fn invalid_input() {
+        let reader = DecodeReader::new(Trickle("ետћζы༎X".as_bytes(), 64));
+        assert_eq!(
+            read_to_end(reader),
+            Err(Error::Decode(DecodeError::InvalidChar {
+                c: '༎',
+                char_index: 5
+            }))
+        );
+        let reader = DecodeReader::new(Trickle(&[0xE0, 0xA4], 64));
+        assert_eq!(read_to_end(reader), Err(Error::Utf8));
+    }
+
+    #[test]
+    fn strict() {
+        let loose = encode(b"hi").replace('Ո', "Ԉ");
+        let reader = DecodeReader::new(Trickle(loose.as_bytes(), 1));
+        assert_eq!(read_to_end(reader), Ok(b"hi".to_vec()));
+        let reader = DecodeReader::new(Trickle(loose.as_bytes(), 1)).with_strict(true);
+        assert_eq!(
+            read_to_end(reader),
+            Err(Error::Decode(DecodeError::InvalidPadding { char_index: 1 }))
+        );
+    }
+
+    #[cfg(feature = "embedded-io-async")]
+    #[test]
+    fn async_round_trip() {
+        use embedded_io_async::{Read as _, Write as _};
+        let bytes = bytes(1000);
+        futures_executor::block_on(async {
+            let mut writer = AsyncEncodeWriter::new(Sink::default());
+            for chunk in bytes.chunks(37) {
+                writer.write_all(chunk).await.unwrap();
+            }
+            let encoded = writer.finish().await.unwrap().0;
+            assert_eq!(encoded, encode(&bytes).into_bytes());
+
+            let mut reader = AsyncDecodeReader::new(Trickle(&encoded, 5));
+            let mut decoded = Vec::new();
+            let mut buf = [0; 7];
+            loop {
+                match reader.read(&mut buf).await.unwrap() {
+                    0 => break,
+                    n => decoded.extend_from_slice(&buf[..n]),
+                }
+            }
+            assert_eq!(decoded, bytes);
+
+            let loose = encode(b"hi").replace('Ո', "Ԉ");
+            let mut reader = AsyncDecodeReader::new(Trickle(loose.as_bytes(), 5)).with_strict(true);
+            let result = loop {
+                match reader.read(&mut buf).await {
+                    Ok(0) => break Ok(()),
+                    Ok(_) => {}
+                    Err(e) => break Err(e),
+                }
+            };
+            assert_eq!(
+                result,
+                Err(Error::Decode(DecodeError::InvalidPadding { char_index: 1 }))
+            );
+        });
+    }
+}
@@ -0,0 +1,70 @@
+//! Encoding input that arrives in pieces.
+use crate::{tables::enc_table, EncodeState};
+
+/// Encodes bytes that arrive in chunks, such as from a socket, without collecting them first.
+///
+/// Each chunk passed to [`push`](Self::push) gives the characters it completes right away; the
+/// bits left over are only written out by [`finish`](Self::finish), since whether they need a
+/// tail character depends on there being no more input. The result is the same as encoding all the
+/// chunks at once.
+///
+/// # Example
+/// ```
+/// let mut encoder = base2048::Encoder::new();
+/// let mut encoded = String::new();
+/// for chunk in [&b"hel"[..], b"lo"] {
+///     encoder.push(chunk, &mut encoded);
+/// }
+/// encoder.finish(&mut encoded);
+/// assert_eq!(encoded, base2048::encode(b"hello"));
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Encoder {
+    state: EncodeState,
+}
+
+impl Encoder {
+    /// An encoder that hasn't been given anything yet.
+    pub fn new() -> Self {
+        Encoder::default()
+    }
+
+    /// Encodes `bytes`, adding the characters they complete to `out`.
+    pub fn push(&mut self, bytes: &[u8], out: &mut impl Extend<char>) {
+        let enc_table = enc_table();
+        self.state.push_slice(bytes, |index| {
+            out.extend(core::iter::once(enc_table[index as usize]))
+        });
+    }
+
+    /// Adds the final character to `out`, if the input so far doesn't fill a whole number of
+    /// characters.
+    pub fn finish(self, out: &mut impl Extend<char>) {
+        out.extend(self.state.finish());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::encode;
+    use alloc::{string::String, vec::Vec};
+
+    #[test]
+    fn chunks_match_encode() {
+        let bytes = (0..100).map(|i| (i * 37) as u8).collect::<Vec<_>>();
+        for chunk_len in 1..25 {
+            let mut encoder = Encoder::new();
+            let mut encoded = String::new();
+            for chunk in bytes.chunks(chunk_len) {
+                encoder.push(chunk, &mut encoded);
+            }
+            encoder.finish(&mut encoded);
+            assert_eq!(encoded, encode(&bytes));
+        }
+
+        let mut empty = String::new();
+        Encoder::new().finish(&mut empty);
+        assert_eq!(empty, "");
+    }
+}
@@ -0,0 +1,157 @@
+//! One interface over every way of encoding and decoding, like the `base64` crate's `Engine`.
+use crate::{Alphabet, Codec, CodecError, DecodeError};
+use alloc::{string::String, vec::Vec};
+
+/// Something that encodes bytes to base2048 text and decodes them back.
+///
+/// Code written against `Engine` works the same with the standard alphabet ([`STANDARD`]), another
+/// [`Alphabet`], or a [`Codec`] with strict decoding, line wrapping or a length limit, and with an
+/// implementation of your own, such as one with a different table backend. Implementations only
+/// need [`encode_into`](Self::encode_into) and [`decode_into`](Self::decode_into).
+///
+/// # Example
+/// ```
+/// use base2048::{Codec, Engine, STANDARD};
+/// fn round_trip(engine: &impl Engine, bytes: &[u8]) -> bool {
+///     engine.decode(engine.encode(bytes)).ok().as_deref() == Some(bytes)
+/// }
+/// assert!(round_trip(&STANDARD, b"hello"));
+/// assert!(round_trip(&Codec::new().with_wrap(Some(2)).with_strict(true), b"hello"));
+/// assert_eq!(STANDARD.encode(b"hello"), base2048::encode(b"hello"));
+/// ```
+pub trait Engine {
+    /// What goes wrong decoding.
+    type Error: From<DecodeError>;
+
+    /// Encode some bytes, appending the text to `out`
+    fn encode_into(&self, bytes: &[u8], out: &mut String);
+
+    /// Decode a string, appending the bytes to `out` and returning how many there were
+    ///
+    /// If `string` is invalid, `out` is left as it was.
+    fn decode_into(&self, string: &str, out: &mut Vec<u8>) -> Result<usize, Self::Error>;
+
+    /// Encode some bytes
+    fn encode(&self, bytes: impl AsRef<[u8]>) -> String {
+        let mut ret = String::new();
+        self.encode_into(bytes.as_ref(), &mut ret);
+        ret
+    }
+
+    /// Decode a string
+    fn decode(&self, string: impl AsRef<str>) -> Result<Vec<u8>, Self::Error> {
+        let mut ret = Vec::new();
+        self.decode_into(string.as_ref(), &mut ret)?;
+        Ok(ret)
+    }
+}
+
+/// The standard alphabet with no options, as used by [`encode`](crate::encode) and
+/// [`try_decode`](crate::try_decode).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StandardEngine;
+
+/// The [`Engine`] the free functions use.
+pub const STANDARD: StandardEngine = StandardEngine;
+
+impl Engine for StandardEngine {
+    type Error = DecodeError;
+
+    fn encode_into(&self, bytes: &[u8], out: &mut String) {
+        crate::encode_into(bytes, out)
+    }
+
+    fn decode_into(&self, string: &str, out: &mut Vec<u8>) -> Result<usize, DecodeError> {
+        crate::decode_into(string, out)
+    }
+}
+
+impl Engine for Alphabet {
+    type Error = DecodeError;
+
+    fn encode_into(&self, bytes: &[u8], out: &mut String) {
+        self.encode_extend(bytes, out)
+    }
+
+    fn decode_into(&self, string: &str, out: &mut Vec<u8>) -> Result<usize, DecodeError> {
+        let start = out.len();
+        match self.decode_extend(string, out) {
+            Ok(()) => Ok(out.len() - start),
+            Err(e) => {
+                out.truncate(start);
+                Err(e)
+            }
+        }
+    }
+}
+
+impl Engine for Codec {
+    type Error = CodecError;
+
+    fn encode_into(&self, bytes: &[u8], out: &mut String) {
+        Codec::encode_into(self, bytes, out)
+    }
+
+    fn decode_into(&self, string: &str, out: &mut Vec<u8>) -> Result<usize, CodecError> {
+        Codec::decode_into(self, string, out)
+    }
+}
+
+impl<E: Engine + ?Sized> Engine for &E {
+    type Error = E::Error;
+
+    fn encode_into(&self, bytes: &[u8], out: &mut String) {
+        (**self).encode_into(bytes, out)
+    }
+
+    fn decode_into(&self, string: &str, out: &mut Vec<u8>) -> Result<usize, E::Error> {
+        (**self).decode_into(string, out)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{encode, try_decode, ENC_TABLE, TAIL};
+
+    fn check<E: Engine>(engine: E, bytes: &[u8]) -> Result<Vec<u8>, E::Error> {
+        let mut out = vec![0xAA];
+        let encoded = engine.encode(bytes);
+        let n = engine.decode_into(&encoded, &mut out)?;
+        assert_eq!(&out[1..], bytes);
+        assert_eq!(n, bytes.len());
+        engine.decode(encoded)
+    }
+
+    #[test]
+    fn engines_round_trip() {
+        let mut table = ENC_TABLE.to_vec();
+        table.reverse();
+        let reversed = Alphabet::from_chars(&table, TAIL).unwrap();
+        let codec = Codec::new().with_wrap(Some(3)).with_strict(true);
+        for len in 0..40 {
+            let bytes = (0..len).map(|i| (i * 37) as u8).collect::<Vec<_>>();
+            assert_eq!(check(STANDARD, &bytes), Ok(bytes.clone()));
+            assert_eq!(check(&reversed, &bytes), Ok(bytes.clone()));
+            assert_eq!(check(&codec, &bytes), Ok(bytes.clone()));
+            assert_eq!(STANDARD.encode(&bytes), encode(&bytes));
+            assert_eq!(Alphabet::standard().encode(&bytes), encode(&bytes));
+        }
+    }
+
+    #[test]
+    fn errors_leave_out_alone() {
+        let invalid = "ետћζы༎X";
+        let mut out = vec![1, 2];
+        assert_eq!(
+            STANDARD.decode_into(invalid, &mut out),
+            Err(try_decode(invalid).unwrap_err())
+        );
+        assert_eq!(
+            Alphabet::standard().decode_into(invalid, &mut out),
+            Err(try_decode(invalid).unwrap_err())
+        );
+        assert!(Codec::new().decode_into(invalid, &mut out).is_err());
+        assert_eq!(out, [1, 2]);
+    }
+}
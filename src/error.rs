@@ -0,0 +1,138 @@
+use core::fmt;
+
+/// Why a string couldn't be decoded.
+///
+/// With the `compact-errors` feature, formatting one only gives its kind and [`code`](Self::code),
+/// which keeps the formatting code for the character and position out of small binaries.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The character is neither in the encoding table nor a tail character.
+    InvalidChar {
+        /// The offending character.
+        c: char,
+        /// Its position, counted in characters.
+        char_index: usize,
+    },
+    /// A tail character was followed by more characters. Tail characters may only come last.
+    TrailingData {
+        /// The position of the tail character, counted in characters.
+        char_index: usize,
+    },
+    /// The string ends in a tail character but the characters before it leave no room for one.
+    UnexpectedTail {
+        /// The position of the tail character, counted in characters.
+        char_index: usize,
+    },
+    /// The padding bits of the final character aren't all set.
+    InvalidPadding {
+        /// The position of the final character, counted in characters.
+        char_index: usize,
+    },
+}
+
+impl DecodeError {
+    /// The position of the character the error is about, counted in characters.
+    pub fn char_index(&self) -> usize {
+        match *self {
+            DecodeError::InvalidChar { char_index, .. }
+            | DecodeError::TrailingData { char_index }
+            | DecodeError::UnexpectedTail { char_index }
+            | DecodeError::InvalidPadding { char_index } => char_index,
+        }
+    }
+
+    /// A number identifying the kind of error: 1 for `InvalidChar`, 2 for `TrailingData`, 3 for
+    /// `UnexpectedTail` and 4 for `InvalidPadding`.
+    pub fn code(&self) -> u8 {
+        match self {
+            DecodeError::InvalidChar { .. } => 1,
+            DecodeError::TrailingData { .. } => 2,
+            DecodeError::UnexpectedTail { .. } => 3,
+            DecodeError::InvalidPadding { .. } => 4,
+        }
+    }
+
+    /// Replaces the position, for decoders that skip some characters before decoding.
+    pub(crate) fn with_char_index(mut self, index: usize) -> Self {
+        match &mut self {
+            DecodeError::InvalidChar { char_index, .. }
+            | DecodeError::TrailingData { char_index }
+            | DecodeError::UnexpectedTail { char_index }
+            | DecodeError::InvalidPadding { char_index } => *char_index = index,
+        }
+        self
+    }
+}
+
+#[cfg(not(feature = "compact-errors"))]
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::InvalidChar { c, char_index } => write!(
+                f,
+                "invalid character {:?} (U+{:04X}) at position {}",
+                c, *c as u32, char_index
+            ),
+            DecodeError::TrailingData { char_index } => write!(
+                f,
+                "tail character at position {} is followed by more characters",
+                char_index
+            ),
+            DecodeError::UnexpectedTail { char_index } => write!(
+                f,
+                "tail character at position {} can't end the string here",
+                char_index
+            ),
+            DecodeError::InvalidPadding { char_index } => write!(
+                f,
+                "the final character at position {} has invalid padding",
+                char_index
+            ),
+        }
+    }
+}
+
+/// Only says what kind of error it is, without formatting the character or position.
+#[cfg(feature = "compact-errors")]
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            DecodeError::InvalidChar { .. } => "base2048 error 1: invalid character",
+            DecodeError::TrailingData { .. } => "base2048 error 2: trailing data",
+            DecodeError::UnexpectedTail { .. } => "base2048 error 3: unexpected tail",
+            DecodeError::InvalidPadding { .. } => "base2048 error 4: invalid padding",
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DecodeError {}
+
+#[cfg(feature = "std")]
+impl From<DecodeError> for std::io::Error {
+    fn from(e: DecodeError) -> Self {
+        std::io::Error::new(std::io::ErrorKind::InvalidData, e)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::string::ToString;
+
+    #[test]
+    fn display() {
+        let e = DecodeError::InvalidChar {
+            c: 'X',
+            char_index: 3,
+        };
+        assert_eq!(e.code(), 1);
+        #[cfg(not(feature = "compact-errors"))]
+        assert_eq!(
+            e.to_string(),
+            "invalid character 'X' (U+0058) at position 3"
+        );
+        #[cfg(feature = "compact-errors")]
+        assert_eq!(e.to_string(), "base2048 error 1: invalid character");
+    }
+}
@@ -0,0 +1,575 @@
+//! C bindings (the `ffi` feature), built into a library with a header by the `ffi` directory.
+//!
+//! Encoding and decoding go through contexts so that C callers can feed data in chunks:
+//! `base2048_encoder_new`, then `base2048_encoder_update` for each chunk, `base2048_encoder_finish`
+//! and `base2048_encoder_free`, and likewise for decoders. Output goes into buffers the caller owns;
+//! `base2048_encoder_update_size` and friends say how big they need to be. Data that is all in
+//! memory can be converted in one call with `base2048_encode` and `base2048_decode` instead.
+use crate::{
+    decode_to_slice,
+    decoder::CharDecoder,
+    encode_to_slice, encoded_len, max_decoded_len,
+    stream::{StreamError, Utf8Chunks},
+    DecodeSliceError, EncodeState,
+};
+use alloc::boxed::Box;
+use core::slice;
+
+/// Success.
+pub const BASE2048_OK: i32 = 0;
+/// A required pointer was null.
+pub const BASE2048_ERR_NULL: i32 = -1;
+/// The output buffer is smaller than the size function for the call asks for.
+pub const BASE2048_ERR_BUFFER_TOO_SMALL: i32 = -2;
+/// The input isn't valid base2048. The decoder can't be used any more, except to free it.
+pub const BASE2048_ERR_INVALID: i32 = -3;
+/// The input isn't valid UTF-8. The decoder can't be used any more, except to free it.
+pub const BASE2048_ERR_UTF8: i32 = -4;
+
+/// The output buffer size `base2048_encoder_finish` and `base2048_decoder_finish` need.
+pub const BASE2048_FINISH_SIZE: usize = 4;
+
+/// Each byte completes at most one character, which takes at most 3 bytes of UTF-8.
+const MAX_CHAR_UTF8: usize = 3;
+
+/// An encoding in progress, made by [`base2048_encoder_new`].
+#[derive(Debug, Default)]
+pub struct Encoder {
+    state: EncodeState,
+}
+
+/// A decoding in progress, made by [`base2048_decoder_new`].
+#[derive(Debug, Default)]
+pub struct Decoder {
+    chars: Utf8Chunks,
+    decoder: CharDecoder,
+    failed: bool,
+}
+
+/// Where output is being written, with the number of bytes written so far.
+struct Out<'a> {
+    buf: &'a mut [u8],
+    len: usize,
+}
+
+impl Out<'_> {
+    /// # Safety
+    ///
+    /// `buf` must be null or valid for writing `cap` bytes.
+    unsafe fn new<'a>(buf: *mut u8, cap: usize, needed: usize) -> Result<Out<'a>, i32> {
+        if cap < needed {
+            return Err(BASE2048_ERR_BUFFER_TOO_SMALL);
+        }
+        if buf.is_null() {
+            return if needed == 0 {
+                Ok(Out {
+                    buf: &mut [],
+                    len: 0,
+                })
+            } else {
+                Err(BASE2048_ERR_NULL)
+            };
+        }
+        Ok(Out {
+            buf: slice::from_raw_parts_mut(buf, cap),
+            len: 0,
+        })
+    }
+
+    fn push(&mut self, byte: u8) {
+        self.buf[self.len] = byte;
+        self.len += 1;
+    }
+
+    fn push_char(&mut self, c: char) {
+        let len = c.encode_utf8(&mut self.buf[self.len..]).len();
+        self.len += len;
+    }
+}
+
+/// # Safety
+///
+/// `data` must be null or valid for reading `len` bytes.
+unsafe fn input<'a>(data: *const u8, len: usize) -> Result<&'a [u8], i32> {
+    match (data.is_null(), len) {
+        (true, 0) => Ok(&[]),
+        (true, _) => Err(BASE2048_ERR_NULL),
+        (false, _) => Ok(slice::from_raw_parts(data, len)),
+    }
+}
+
+/// # Safety
+///
+/// `out_len` must be null or valid for writing.
+unsafe fn written(result: Result<usize, i32>, out_len: *mut usize) -> i32 {
+    match result {
+        Ok(len) => {
+            if !out_len.is_null() {
+                *out_len = len;
+            }
+            BASE2048_OK
+        }
+        Err(code) => code,
+    }
+}
+
+/// Makes an encoder, to be freed with [`base2048_encoder_free`].
+#[no_mangle]
+pub extern "C" fn base2048_encoder_new() -> *mut Encoder {
+    Box::into_raw(Box::default())
+}
+
+/// The output buffer size [`base2048_encoder_update`] needs for `len` bytes of input.
+#[no_mangle]
+pub extern "C" fn base2048_encoder_update_size(len: usize) -> usize {
+    len.saturating_mul(MAX_CHAR_UTF8)
+}
+
+/// Encodes `len` bytes from `data`, writing the UTF-8 of the characters they complete to `out` and
+/// their length to `out_len`.
+///
+/// `out_cap` must be at least [`base2048_encoder_update_size`]`(len)`. Nothing is written (and the
+/// encoder is unchanged) if a code other than `BASE2048_OK` is returned.
+///
+/// # Safety
+///
+/// `encoder` must come from [`base2048_encoder_new`] and not be freed. `data` must be valid for
+/// reading `len` bytes and `out` for writing `out_cap` bytes; either may be null if its length is
+/// 0. `out_len` must be null or valid for writing.
+#[no_mangle]
+pub unsafe extern "C" fn base2048_encoder_update(
+    encoder: *mut Encoder,
+    data: *const u8,
+    len: usize,
+    out: *mut u8,
+    out_cap: usize,
+    out_len: *mut usize,
+) -> i32 {
+    let result = (|| {
+        let encoder = encoder.as_mut().ok_or(BASE2048_ERR_NULL)?;
+        let data = input(data, len)?;
+        let mut out = Out::new(out, out_cap, base2048_encoder_update_size(len))?;
+        for byte in data {
+            if let Some(c) = encoder.state.push(*byte) {
+                out.push_char(c);
+            }
+        }
+        Ok(out.len)
+    })();
+    written(result, out_len)
+}
+
+/// Writes the final character (if any) to `out` and its length to `out_len`, and resets the
+/// encoder so it can start another encoding.
+///
+/// `out_cap` must be at least `BASE2048_FINISH_SIZE`.
+///
+/// # Safety
+///
+/// As for [`base2048_encoder_update`].
+#[no_mangle]
+pub unsafe extern "C" fn base2048_encoder_finish(
+    encoder: *mut Encoder,
+    out: *mut u8,
+    out_cap: usize,
+    out_len: *mut usize,
+) -> i32 {
+    let result = (|| {
+        let encoder = encoder.as_mut().ok_or(BASE2048_ERR_NULL)?;
+        let mut out = Out::new(out, out_cap, BASE2048_FINISH_SIZE)?;
+        if let Some(c) = core::mem::take(&mut encoder.state).finish() {
+            out.push_char(c);
+        }
+        Ok(out.len)
+    })();
+    written(result, out_len)
+}
+
+/// Frees an encoder. Does nothing if `encoder` is null.
+///
+/// # Safety
+///
+/// `encoder` must be null or come from [`base2048_encoder_new`] and not be freed already.
+#[no_mangle]
+pub unsafe extern "C" fn base2048_encoder_free(encoder: *mut Encoder) {
+    if !encoder.is_null() {
+        drop(Box::from_raw(encoder));
+    }
+}
+
+/// Makes a decoder, to be freed with [`base2048_decoder_free`].
+#[no_mangle]
+pub extern "C" fn base2048_decoder_new() -> *mut Decoder {
+    Box::into_raw(Box::default())
+}
+
+/// The output buffer size [`base2048_decoder_update`] needs for `len` bytes of input.
+#[no_mangle]
+pub extern "C" fn base2048_decoder_update_size(len: usize) -> usize {
+    // every character is at least one byte and gives at most two, plus two for the character held
+    // back from the previous chunk
+    len.saturating_mul(2).saturating_add(2)
+}
+
+/// Decodes `len` bytes of UTF-8 base2048 text from `data`, writing the bytes decoded so far to
+/// `out` and how many there are to `out_len`.
+///
+/// The text may be split anywhere, even part way through a character. The last character is only
+/// decoded by [`base2048_decoder_finish`], once it's known to be the last. `out_cap` must be at
+/// least [`base2048_decoder_update_size`]`(len)`.
+///
+/// # Safety
+///
+/// `decoder` must come from [`base2048_decoder_new`] and not be freed. `data` must be valid for
+/// reading `len` bytes and `out` for writing `out_cap` bytes; either may be null if its length is
+/// 0. `out_len` must be null or valid for writing.
+#[no_mangle]
+pub unsafe extern "C" fn base2048_decoder_update(
+    decoder: *mut Decoder,
+    data: *const u8,
+    len: usize,
+    out: *mut u8,
+    out_cap: usize,
+    out_len: *mut usize,
+) -> i32 {
+    let result = (|| {
+        let decoder = decoder.as_mut().ok_or(BASE2048_ERR_NULL)?;
+        if decoder.failed {
+            return Err(BASE2048_ERR_INVALID);
+        }
+        let data = input(data, len)?;
+        let mut out = Out::new(out, out_cap, base2048_decoder_update_size(len))?;
+        let Decoder {
+            chars,
+            decoder: inner,
+            ..
+        } = &mut *decoder;
+        let result = chars.push(data, |c| -> Result<(), StreamError> {
+            Ok(inner.push(c, |byte| out.push(byte))?)
+        });
+        decoder.fail_on(result)?;
+        Ok(out.len)
+    })();
+    written(result, out_len)
+}
+
+/// Decodes the last character, writing the bytes it gives to `out` and how many there are to
+/// `out_len`, and resets the decoder so it can start another decoding.
+///
+/// `out_cap` must be at least `BASE2048_FINISH_SIZE`.
+///
+/// # Safety
+///
+/// As for [`base2048_decoder_update`].
+#[no_mangle]
+pub unsafe extern "C" fn base2048_decoder_finish(
+    decoder: *mut Decoder,
+    out: *mut u8,
+    out_cap: usize,
+    out_len: *mut usize,
+) -> i32 {
+    let result = (|| {
+        let decoder = decoder.as_mut().ok_or(BASE2048_ERR_NULL)?;
+        if decoder.failed {
+            return Err(BASE2048_ERR_INVALID);
+        }
+        let mut out = Out::new(out, out_cap, BASE2048_FINISH_SIZE)?;
+        let Decoder {
+            chars,
+            decoder: inner,
+            ..
+        } = core::mem::take(decoder);
+        let result = chars
+            .finish()
+            .and_then(|()| Ok(inner.finish(|byte| out.push(byte))?));
+        decoder.fail_on(result)?;
+        Ok(out.len)
+    })();
+    written(result, out_len)
+}
+
+/// Frees a decoder. Does nothing if `decoder` is null.
+///
+/// # Safety
+///
+/// `decoder` must be null or come from [`base2048_decoder_new`] and not be freed already.
+#[no_mangle]
+pub unsafe extern "C" fn base2048_decoder_free(decoder: *mut Decoder) {
+    if !decoder.is_null() {
+        drop(Box::from_raw(decoder));
+    }
+}
+
+/// An output buffer size that [`base2048_encode`] can always encode `len` bytes into, or
+/// `usize::MAX` if the size doesn't fit in a `usize`.
+#[no_mangle]
+pub extern "C" fn base2048_encode_size(len: usize) -> usize {
+    // `encoded_len` itself can't overflow
+    encoded_len(len).saturating_mul(MAX_CHAR_UTF8)
+}
+
+/// An output buffer size that [`base2048_decode`] can always decode `len` bytes of text into, or
+/// `usize::MAX` if the size doesn't fit in a `usize`.
+#[no_mangle]
+pub extern "C" fn base2048_decode_size(len: usize) -> usize {
+    // every character is at least one byte; `max_decoded_len` is `usize::MAX` when it overflows
+    max_decoded_len(len)
+}
+
+/// Encodes `len` bytes from `data`, writing the UTF-8 of the text to `out` and its length to
+/// `out_len`.
+///
+/// [`base2048_encode_size`]`(len)` is always enough for `out_cap`, but less may do. If it isn't
+/// enough, `BASE2048_ERR_BUFFER_TOO_SMALL` is returned and the size needed is written to
+/// `out_len`; what has been written to `out` is unspecified.
+///
+/// # Safety
+///
+/// `data` must be valid for reading `len` bytes and `out` for writing `out_cap` bytes; either may
+/// be null if its length is 0. `out_len` must be null or valid for writing.
+#[no_mangle]
+pub unsafe extern "C" fn base2048_encode(
+    data: *const u8,
+    len: usize,
+    out: *mut u8,
+    out_cap: usize,
+    out_len: *mut usize,
+) -> i32 {
+    let result = (|| {
+        let data = input(data, len)?;
+        let out = Out::new(out, out_cap, 0)?;
+        encode_to_slice(data, out.buf).map_err(|e| too_small(e.needed, out_len))
+    })();
+    written(result, out_len)
+}
+
+/// Decodes `len` bytes of UTF-8 base2048 text from `data`, writing the decoded bytes to `out` and
+/// how many there are to `out_len`.
+///
+/// [`base2048_decode_size`]`(len)` is always enough for `out_cap`, but less may do. If it isn't
+/// enough for valid text, `BASE2048_ERR_BUFFER_TOO_SMALL` is returned and the size needed is
+/// written to `out_len`. On any error, what has been written to `out` is unspecified.
+///
+/// # Safety
+///
+/// As for [`base2048_encode`].
+#[no_mangle]
+pub unsafe extern "C" fn base2048_decode(
+    data: *const u8,
+    len: usize,
+    out: *mut u8,
+    out_cap: usize,
+    out_len: *mut usize,
+) -> i32 {
+    let result = (|| {
+        let data = input(data, len)?;
+        let text = core::str::from_utf8(data).map_err(|_| BASE2048_ERR_UTF8)?;
+        let out = Out::new(out, out_cap, 0)?;
+        decode_to_slice(text, out.buf).map_err(|e| match e {
+            DecodeSliceError::BufferTooSmall(e) => too_small(e.needed, out_len),
+            DecodeSliceError::Decode(_) => BASE2048_ERR_INVALID,
+        })
+    })();
+    written(result, out_len)
+}
+
+/// Reports the buffer size needed through `out_len`.
+///
+/// # Safety
+///
+/// `out_len` must be null or valid for writing.
+unsafe fn too_small(needed: usize, out_len: *mut usize) -> i32 {
+    if !out_len.is_null() {
+        *out_len = needed;
+    }
+    BASE2048_ERR_BUFFER_TOO_SMALL
+}
+
+impl Decoder {
+    fn fail_on(&mut self, result: Result<(), StreamError>) -> Result<(), i32> {
+        result.map_err(|e| {
+            self.failed = true;
+            match e {
+                StreamError::Utf8 => BASE2048_ERR_UTF8,
+                StreamError::Decode(_) => BASE2048_ERR_INVALID,
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::encode;
+    use alloc::{vec, vec::Vec};
+    use core::ptr;
+
+    fn encode_in_chunks(bytes: &[u8], chunk_len: usize) -> Vec<u8> {
+        let encoder = base2048_encoder_new();
+        let mut ret = Vec::new();
+        let mut out = vec![0; base2048_encoder_update_size(chunk_len)];
+        let mut len = 0;
+        for chunk in bytes.chunks(chunk_len) {
+            let code = unsafe {
+                base2048_encoder_update(
+                    encoder,
+                    chunk.as_ptr(),
+                    chunk.len(),
+                    out.as_mut_ptr(),
+                    out.len(),
+                    &mut len,
+                )
+            };
+            assert_eq!(code, BASE2048_OK);
+            ret.extend_from_slice(&out[..len]);
+        }
+        let code = unsafe { base2048_encoder_finish(encoder, out.as_mut_ptr(), 4, &mut len) };
+        assert_eq!(code, BASE2048_OK);
+        ret.extend_from_slice(&out[..len]);
+        unsafe { base2048_encoder_free(encoder) };
+        ret
+    }
+
+    fn decode_in_chunks(text: &[u8], chunk_len: usize) -> Result<Vec<u8>, i32> {
+        let decoder = base2048_decoder_new();
+        let mut ret = Vec::new();
+        let mut out = vec![0; base2048_decoder_update_size(chunk_len)];
+        let mut len = 0;
+        let mut result = Ok(());
+        for chunk in text.chunks(chunk_len) {
+            let code = unsafe {
+                base2048_decoder_update(
+                    decoder,
+                    chunk.as_ptr(),
+                    chunk.len(),
+                    out.as_mut_ptr(),
+                    out.len(),
+                    &mut len,
+                )
+            };
+            if code != BASE2048_OK {
+                result = Err(code);
+                break;
+            }
+            ret.extend_from_slice(&out[..len]);
+        }
+        if result.is_ok() {
+            let code = unsafe { base2048_decoder_finish(decoder, out.as_mut_ptr(), 4, &mut len) };
+            if code == BASE2048_OK {
+                ret.extend_from_slice(&out[..len]);
+            } else {
+                result = Err(code);
+            }
+        }
+        unsafe { base2048_decoder_free(decoder) };
+        result.map(|()| ret)
+    }
+
+    #[test]
+    fn chunked_roundtrip() {
+        for len in [0, 1, 2, 10, 100] {
+            let bytes = (0..len).map(|i| (i * 37) as u8).collect::<Vec<_>>();
+            let expected = encode(&bytes);
+            for chunk_len in [1, 2, 3, 7, 64] {
+                assert_eq!(encode_in_chunks(&bytes, chunk_len), expected.as_bytes());
+                assert_eq!(
+                    decode_in_chunks(expected.as_bytes(), chunk_len),
+                    Ok(bytes.clone())
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn errors() {
+        let text = "ետћζы༎X".as_bytes();
+        assert_eq!(decode_in_chunks(text, 3), Err(BASE2048_ERR_INVALID));
+        assert_eq!(decode_in_chunks(&text[..3], 3), Err(BASE2048_ERR_UTF8));
+        assert_eq!(decode_in_chunks(&[0xFF], 1), Err(BASE2048_ERR_UTF8));
+
+        let decoder = base2048_decoder_new();
+        let mut out = [0; 4];
+        let mut len = 0;
+        unsafe {
+            let code =
+                base2048_decoder_update(decoder, text.as_ptr(), 2, out.as_mut_ptr(), 4, &mut len);
+            assert_eq!(code, BASE2048_ERR_BUFFER_TOO_SMALL);
+            let code =
+                base2048_decoder_update(decoder, ptr::null(), 2, out.as_mut_ptr(), 6, &mut len);
+            assert_eq!(code, BASE2048_ERR_NULL);
+            base2048_decoder_free(decoder);
+            assert_eq!(
+                base2048_encoder_finish(ptr::null_mut(), out.as_mut_ptr(), 4, &mut len),
+                BASE2048_ERR_NULL
+            );
+        }
+    }
+
+    #[test]
+    fn one_shot() {
+        for len in [0, 1, 2, 10, 100] {
+            let bytes = (0..len).map(|i| (i * 37) as u8).collect::<Vec<_>>();
+            let expected = encode(&bytes);
+            let mut out = vec![0; base2048_encode_size(len)];
+            let mut out_len = 0;
+            let code = unsafe {
+                base2048_encode(
+                    bytes.as_ptr(),
+                    len,
+                    out.as_mut_ptr(),
+                    out.len(),
+                    &mut out_len,
+                )
+            };
+            assert_eq!(code, BASE2048_OK);
+            assert_eq!(&out[..out_len], expected.as_bytes());
+
+            let mut decoded = vec![0; base2048_decode_size(expected.len())];
+            let code = unsafe {
+                base2048_decode(
+                    expected.as_ptr(),
+                    expected.len(),
+                    decoded.as_mut_ptr(),
+                    decoded.len(),
+                    &mut out_len,
+                )
+            };
+            assert_eq!(code, BASE2048_OK);
+            assert_eq!(&decoded[..out_len], &bytes[..]);
+        }
+    }
+
+    #[test]
+    fn one_shot_sizes_overflow() {
+        assert_eq!(base2048_encode_size(usize::MAX), usize::MAX);
+        assert_eq!(base2048_encode_size(usize::MAX / 2), usize::MAX);
+        assert_eq!(base2048_decode_size(usize::MAX), usize::MAX);
+        assert_eq!(base2048_decode_size(usize::MAX / 8 * 7), usize::MAX);
+    }
+
+    #[test]
+    fn one_shot_errors() {
+        let mut out = [0; 4];
+        let mut out_len = 0;
+        unsafe {
+            let code = base2048_encode(b"hello".as_ptr(), 5, out.as_mut_ptr(), 4, &mut out_len);
+            assert_eq!(code, BASE2048_ERR_BUFFER_TOO_SMALL);
+            assert_eq!(out_len, encode(b"hello").len());
+            let code = base2048_encode(ptr::null(), 0, ptr::null_mut(), 0, &mut out_len);
+            assert_eq!((code, out_len), (BASE2048_OK, 0));
+
+            let text = encode(b"hello");
+            let code =
+                base2048_decode(text.as_ptr(), text.len(), out.as_mut_ptr(), 4, &mut out_len);
+            assert_eq!((code, out_len), (BASE2048_ERR_BUFFER_TOO_SMALL, 5));
+            let text = "ետћζы༎X".as_bytes();
+            let code =
+                base2048_decode(text.as_ptr(), text.len(), out.as_mut_ptr(), 4, &mut out_len);
+            assert_eq!(code, BASE2048_ERR_INVALID);
+            let code = base2048_decode(text.as_ptr(), 3, out.as_mut_ptr(), 4, &mut out_len);
+            assert_eq!(code, BASE2048_ERR_UTF8);
+            let code = base2048_decode(ptr::null(), 3, out.as_mut_ptr(), 4, &mut out_len);
+            assert_eq!(code, BASE2048_ERR_NULL);
+        }
+    }
+}
@@ -0,0 +1,328 @@
+//! Adapters between [`futures_io`] and base2048 text, the async twins of
+//! [`EncodeWriter`](crate::EncodeWriter) and [`DecodeReader`](crate::DecodeReader).
+use crate::std_io::{DecodeBuf, EncodeBuf, BUF_LEN};
+use alloc::vec::Vec;
+use core::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+use futures_io::{AsyncRead, AsyncWrite};
+use std::io;
+
+/// Encodes everything written to it, writing the UTF-8 base2048 text to an inner writer.
+///
+/// The encoded text is buffered and written out in blocks. The bits left over at the end of the
+/// input are only written on [`poll_close`](AsyncWrite::poll_close), since whether they need a tail
+/// character depends on there being no more input, so the writer must be closed.
+///
+/// # Example
+/// ```
+/// use futures_executor::block_on;
+/// use futures_io::AsyncWrite;
+/// use std::{future::poll_fn, pin::Pin};
+/// let mut writer = base2048::AsyncEncodeWriter::new(Vec::new());
+/// block_on(async {
+///     poll_fn(|cx| Pin::new(&mut writer).poll_write(cx, b"hello")).await?;
+///     poll_fn(|cx| Pin::new(&mut writer).poll_close(cx)).await
+/// })?;
+/// assert_eq!(writer.into_inner(), base2048::encode(b"hello").into_bytes());
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct AsyncEncodeWriter<W> {
+    inner: W,
+    buf: EncodeBuf,
+    closing: bool,
+}
+
+impl<W: AsyncWrite + Unpin> AsyncEncodeWriter<W> {
+    /// Writes the encoded text to `inner`.
+    pub fn new(inner: W) -> Self {
+        AsyncEncodeWriter {
+            inner,
+            buf: EncodeBuf::default(),
+            closing: false,
+        }
+    }
+
+    /// The underlying writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// The underlying writer.
+    ///
+    /// Writing to it directly puts the text out of order with what is still buffered.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Gives back the underlying writer, dropping any text not written yet.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+
+    /// Writes out the buffered text, keeping whatever couldn't be written yet.
+    fn poll_write_buf(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        while !self.buf.pending().is_empty() {
+            match Pin::new(&mut self.inner).poll_write(cx, self.buf.pending()) {
+                Poll::Ready(Ok(0)) => {
+                    return Poll::Ready(Err(io::Error::new(
+                        io::ErrorKind::WriteZero,
+                        "failed to write the encoded text",
+                    )))
+                }
+                Poll::Ready(Ok(n)) => self.buf.consume(n),
+                Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<W: AsyncWrite + Unpin> AsyncWrite for AsyncEncodeWriter<W> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bytes: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        if this.buf.is_full() {
+            match this.poll_write_buf(cx) {
+                Poll::Ready(Ok(())) => {}
+                other => return other.map(|result| result.map(|()| 0)),
+            }
+        }
+        Poll::Ready(Ok(this.buf.encode(bytes)))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        match this.poll_write_buf(cx) {
+            Poll::Ready(Ok(())) => Pin::new(&mut this.inner).poll_flush(cx),
+            other => other,
+        }
+    }
+
+    /// Writes out the final character and everything still buffered, then closes the underlying
+    /// writer.
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        if !this.closing {
+            this.buf.finish();
+            this.closing = true;
+        }
+        match this.poll_write_buf(cx) {
+            Poll::Ready(Ok(())) => Pin::new(&mut this.inner).poll_close(cx),
+            other => other,
+        }
+    }
+}
+
+/// Decodes UTF-8 base2048 text read from an inner reader, giving the decoded bytes.
+///
+/// Characters split between reads are put back together. Errors are reported as by
+/// [`DecodeReader`](crate::DecodeReader).
+#[derive(Debug)]
+pub struct AsyncDecodeReader<R> {
+    inner: R,
+    text: Vec<u8>,
+    buf: DecodeBuf,
+}
+
+impl<R: AsyncRead + Unpin> AsyncDecodeReader<R> {
+    /// Reads the encoded text from `inner`.
+    pub fn new(inner: R) -> Self {
+        AsyncDecodeReader {
+            inner,
+            text: vec![0; BUF_LEN],
+            buf: DecodeBuf::default(),
+        }
+    }
+
+    /// Whether a final character whose padding bits aren't all set is rejected, as
+    /// [`try_decode_strict`](crate::try_decode_strict) rejects it.
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.buf.set_strict(strict);
+        self
+    }
+
+    /// The underlying reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// The underlying reader.
+    ///
+    /// Reading from it directly skips text this reader would have decoded.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Gives back the underlying reader, dropping anything read from it but not yet decoded.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for AsyncDecodeReader<R> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        dest: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(result) = this.buf.read(dest) {
+                return Poll::Ready(result);
+            }
+            match Pin::new(&mut this.inner).poll_read(cx, &mut this.text) {
+                Poll::Ready(Ok(n)) => this.buf.push(&this.text[..n]),
+                Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{encode, DecodeError};
+    use futures_executor::block_on;
+    use std::future::poll_fn;
+
+    /// Reads or writes a byte at a time, returning `Pending` before each one.
+    struct Trickle<T> {
+        inner: T,
+        ready: bool,
+    }
+
+    impl<T> Trickle<T> {
+        fn new(inner: T) -> Self {
+            Trickle {
+                inner,
+                ready: false,
+            }
+        }
+
+        fn poll(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+            self.ready = !self.ready;
+            if self.ready {
+                Poll::Ready(())
+            } else {
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        }
+    }
+
+    impl AsyncWrite for Trickle<Vec<u8>> {
+        fn poll_write(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            bytes: &[u8],
+        ) -> Poll<io::Result<usize>> {
+            let this = self.get_mut();
+            this.poll(cx).map(|()| {
+                this.inner.extend(bytes.first());
+                Ok(bytes.len().min(1))
+            })
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    impl AsyncRead for Trickle<&[u8]> {
+        fn poll_read(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            dest: &mut [u8],
+        ) -> Poll<io::Result<usize>> {
+            let this = self.get_mut();
+            this.poll(cx).map(|()| {
+                let n = dest.len().min(this.inner.len()).min(1);
+                dest[..n].copy_from_slice(&this.inner[..n]);
+                this.inner = &this.inner[n..];
+                Ok(n)
+            })
+        }
+    }
+
+    fn bytes(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i * 37) as u8).collect()
+    }
+
+    fn read_to_end(reader: &mut (impl AsyncRead + Unpin)) -> io::Result<Vec<u8>> {
+        let mut out = Vec::new();
+        let mut buf = [0; 7];
+        loop {
+            let n = block_on(poll_fn(|cx| Pin::new(&mut *reader).poll_read(cx, &mut buf)))?;
+            if n == 0 {
+                return Ok(out);
+            }
+            out.extend_from_slice(&buf[..n]);
+        }
+    }
+
+    #[test]
+    fn write_matches_encode() {
+        let bytes = bytes(20_000);
+        let mut writer = AsyncEncodeWriter::new(Trickle::new(Vec::new()));
+        block_on(async {
+            for chunk in bytes.chunks(333) {
+                let mut chunk = chunk;
+                while !chunk.is_empty() {
+                    let n = poll_fn(|cx| Pin::new(&mut writer).poll_write(cx, chunk)).await?;
+                    chunk = &chunk[n..];
+                }
+            }
+            poll_fn(|cx| Pin::new(&mut writer).poll_flush(cx)).await?;
+            poll_fn(|cx| Pin::new(&mut writer).poll_close(cx)).await
+        })
+        .unwrap();
+        assert_eq!(writer.into_inner().inner, encode(&bytes).into_bytes());
+    }
+
+    #[test]
+    fn read_matches_decode() {
+        let bytes = bytes(20_000);
+        let encoded = encode(&bytes);
+        let mut reader = AsyncDecodeReader::new(Trickle::new(encoded.as_bytes()));
+        assert_eq!(read_to_end(&mut reader).unwrap(), bytes);
+    }
+
+    #[test]
+    fn invalid_input() {
+        let mut reader = AsyncDecodeReader::new(Trickle::new("ետћζы༎X".as_bytes()));
+        let e = read_to_end(&mut reader).unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(
+            e.into_inner().unwrap().downcast_ref::<DecodeError>(),
+            Some(&DecodeError::InvalidChar {
+                c: '༎',
+                char_index: 5
+            })
+        );
+    }
+
+    #[test]
+    fn strict() {
+        let loose = encode(b"hi").replace('Ո', "Ԉ");
+        let mut reader = AsyncDecodeReader::new(Trickle::new(loose.as_bytes()));
+        assert_eq!(read_to_end(&mut reader).unwrap(), b"hi");
+        let mut reader = AsyncDecodeReader::new(Trickle::new(loose.as_bytes())).with_strict(true);
+        let e = read_to_end(&mut reader).unwrap_err();
+        assert_eq!(
+            e.into_inner().unwrap().downcast_ref::<DecodeError>(),
+            Some(&DecodeError::InvalidPadding { char_index: 1 })
+        );
+    }
+}
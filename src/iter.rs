@@ -0,0 +1,369 @@
+//! Encoding and decoding as iterator adaptors.
+use crate::{diagnostics, DecodeError, DecodeState, EncodeState, BITS_PER_CHAR};
+use alloc::vec::Vec;
+use core::{
+    fmt,
+    iter::{Copied, FusedIterator, Peekable},
+    slice,
+    str::Chars,
+};
+
+/// Adds base2048 adaptors to iterators.
+///
+/// Implemented for every iterator; [`base2048_chars`](Self::base2048_chars) is available on
+/// iterators of bytes and [`base2048_bytes`](Self::base2048_bytes) on iterators of characters.
+///
+/// # Example
+/// ```
+/// use base2048::IterEncodeExt;
+/// let encoded = b"hello".iter().copied().base2048_chars().collect::<String>();
+/// assert_eq!(encoded, base2048::encode(b"hello"));
+/// let decoded = encoded.chars().base2048_bytes().collect::<Result<Vec<u8>, _>>();
+/// assert_eq!(decoded.unwrap(), b"hello");
+/// ```
+pub trait IterEncodeExt: Iterator {
+    /// Encodes the bytes of this iterator, yielding base2048 characters.
+    fn base2048_chars(self) -> EncodeChars<Self>
+    where
+        Self: Iterator<Item = u8> + Sized,
+    {
+        EncodeChars {
+            bytes: self,
+            state: Some(EncodeState::default()),
+        }
+    }
+
+    /// Decodes the base2048 characters of this iterator, yielding bytes.
+    ///
+    /// After the first error the iterator ends.
+    fn base2048_bytes(self) -> DecodeBytes<Self>
+    where
+        Self: Iterator<Item = char> + Sized,
+    {
+        DecodeBytes {
+            chars: self.peekable(),
+            state: Some(DecodeState::default()),
+            buf: [0; 2],
+            len: 0,
+            pos: 0,
+        }
+    }
+}
+
+impl<I: Iterator> IterEncodeExt for I {}
+
+/// The characters encoding the bytes of `I`, created by
+/// [`IterEncodeExt::base2048_chars`].
+#[derive(Clone, Debug)]
+pub struct EncodeChars<I> {
+    bytes: I,
+    /// `None` once the tail character (if any) has been produced.
+    state: Option<EncodeState>,
+}
+
+impl<I: Iterator<Item = u8>> Iterator for EncodeChars<I> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        let state = self.state.as_mut()?;
+        for byte in &mut self.bytes {
+            if let Some(c) = state.push(byte) {
+                return Some(c);
+            }
+        }
+        self.state.take()?.finish()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let pending = match &self.state {
+            Some(state) => state.remaining as usize,
+            None => return (0, Some(0)),
+        };
+        // every 11 bits make a character, and whatever is left over one more
+        let chars = |bytes: usize| {
+            bytes
+                .checked_mul(8)
+                .and_then(|bits| bits.checked_add(pending))
+                .map(|bits| bits.div_ceil(BITS_PER_CHAR as usize))
+        };
+        let (lower, upper) = self.bytes.size_hint();
+        (chars(lower).unwrap_or(usize::MAX), upper.and_then(chars))
+    }
+}
+
+impl<I: Iterator<Item = u8>> FusedIterator for EncodeChars<I> {}
+
+impl<I: ExactSizeIterator<Item = u8>> ExactSizeIterator for EncodeChars<I> {}
+
+/// Encode some bytes lazily, yielding the characters one at a time
+///
+/// Nothing is allocated, and the number of characters left is always known, so the result can be
+/// fed straight into other formatting without building a `String` first.
+///
+/// # Example
+/// ```
+/// let chars = base2048::encode_iter(b"hello");
+/// assert_eq!(chars.len(), 4);
+/// assert_eq!(chars.collect::<String>(), base2048::encode(b"hello"));
+/// ```
+pub fn encode_iter(bytes: &[u8]) -> EncodeChars<Copied<slice::Iter<'_, u8>>> {
+    bytes.iter().copied().base2048_chars()
+}
+
+/// The bytes decoded from the characters of `I`, created by
+/// [`IterEncodeExt::base2048_bytes`].
+#[derive(Clone, Debug)]
+pub struct DecodeBytes<I: Iterator<Item = char>> {
+    chars: Peekable<I>,
+    /// `None` once the input is exhausted or invalid.
+    state: Option<DecodeState>,
+    buf: [u8; 2],
+    len: usize,
+    pos: usize,
+}
+
+impl<I: Iterator<Item = char>> DecodeBytes<I> {
+    /// Whether a final character whose padding bits aren't all set is rejected, as
+    /// [`try_decode_strict`](crate::try_decode_strict) rejects it.
+    ///
+    /// # Example
+    /// ```
+    /// use base2048::DecodeError;
+    /// let loose = base2048::encode(b"hi").replace('Ո', "Ԉ");
+    /// let bytes = base2048::decode_iter(&loose).with_strict(true);
+    /// assert_eq!(
+    ///     bytes.collect::<Result<Vec<u8>, _>>(),
+    ///     Err(DecodeError::InvalidPadding { char_index: 1 })
+    /// );
+    /// ```
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        if let Some(state) = &mut self.state {
+            state.strict = strict;
+        }
+        self
+    }
+}
+
+impl<I: Iterator<Item = char>> Iterator for DecodeBytes<I> {
+    type Item = Result<u8, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.pos < self.len {
+                self.pos += 1;
+                return Some(Ok(self.buf[self.pos - 1]));
+            }
+            let state = self.state.as_mut()?;
+            self.len = 0;
+            self.pos = 0;
+            let (buf, len) = (&mut self.buf, &mut self.len);
+            let push = |byte| {
+                buf[*len] = byte;
+                *len += 1;
+            };
+            match self.chars.next() {
+                Some(c) => {
+                    let last = self.chars.peek().is_none();
+                    if let Err(e) = state.push(c, last, push) {
+                        self.state = None;
+                        return Some(Err(diagnostics::decode_error(e)));
+                    }
+                }
+                None => self.state.take()?.finish(push),
+            }
+        }
+    }
+}
+
+impl<I: Iterator<Item = char>> FusedIterator for DecodeBytes<I> {}
+
+/// Decode a base2048 encoded string lazily, yielding the bytes one at a time
+///
+/// Nothing is allocated. Characters are checked as they are reached, so an invalid string gives
+/// the bytes before the error, then the error, and then ends; a consumer can stop at the first
+/// error without the rest of the string being looked at.
+///
+/// # Example
+/// ```
+/// let encoded = base2048::encode(b"hello");
+/// let bytes = base2048::decode_iter(&encoded).collect::<Result<Vec<u8>, _>>();
+/// assert_eq!(bytes.unwrap(), b"hello");
+/// assert!(base2048::decode_iter("ետћζы༎X").any(|byte| byte.is_err()));
+/// ```
+pub fn decode_iter(string: &str) -> DecodeBytes<Chars<'_>> {
+    string.chars().base2048_bytes()
+}
+
+/// Why [`decode_try_chars`] failed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TryDecodeError<E> {
+    /// The characters couldn't be read.
+    Source(E),
+    /// The characters aren't valid base2048.
+    Decode(DecodeError),
+}
+
+impl<E: fmt::Display> fmt::Display for TryDecodeError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TryDecodeError::Source(e) => e.fmt(f),
+            TryDecodeError::Decode(e) => e.fmt(f),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E: std::error::Error + 'static> std::error::Error for TryDecodeError<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TryDecodeError::Source(e) => Some(e),
+            TryDecodeError::Decode(e) => Some(e),
+        }
+    }
+}
+
+/// Decode base2048 characters from a source that can fail, such as a UTF-8 decoder reading a
+/// socket
+///
+/// Characters are decoded as they arrive, so the text never has to be buffered. Reading stops at
+/// the first error from either the source or the decoding; a source error is reported before the
+/// character preceding it is decoded, since that character can only be judged once it is known
+/// whether it is the last.
+///
+/// # Example
+/// ```
+/// use base2048::TryDecodeError;
+/// let encoded = base2048::encode(b"hello");
+/// let chars = encoded.chars().map(Ok::<_, &str>);
+/// assert_eq!(base2048::decode_try_chars(chars), Ok(b"hello".to_vec()));
+///
+/// let broken = encoded.chars().map(Ok).chain(Some(Err("connection reset")));
+/// assert_eq!(
+///     base2048::decode_try_chars(broken),
+///     Err(TryDecodeError::Source("connection reset"))
+/// );
+/// ```
+pub fn decode_try_chars<E>(
+    chars: impl IntoIterator<Item = Result<char, E>>,
+) -> Result<Vec<u8>, TryDecodeError<E>> {
+    let mut ret = Vec::new();
+    let mut state = DecodeState::default();
+    let mut chars = chars.into_iter().peekable();
+    while let Some(c) = chars.next() {
+        let c = c.map_err(TryDecodeError::Source)?;
+        let last = match chars.peek() {
+            None => true,
+            Some(Ok(_)) => false,
+            Some(Err(_)) => break,
+        };
+        state
+            .push(c, last, |byte| ret.push(byte))
+            .map_err(|e| TryDecodeError::Decode(diagnostics::decode_error(e)))?;
+    }
+    if let Some(Err(e)) = chars.next() {
+        return Err(TryDecodeError::Source(e));
+    }
+    state.finish_checked(|byte| ret.push(byte));
+    Ok(ret)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{decode, encode};
+    use alloc::{string::String, vec::Vec};
+
+    #[test]
+    fn matches_encode_decode() {
+        for len in 0..40 {
+            let bytes = (0..len).map(|i| (i * 37) as u8).collect::<Vec<_>>();
+            let encoded = bytes.iter().copied().base2048_chars().collect::<String>();
+            assert_eq!(encoded, encode(&bytes));
+            let decoded = encoded
+                .chars()
+                .base2048_bytes()
+                .collect::<Result<Vec<_>, _>>();
+            assert_eq!(decoded.unwrap(), decode(&encoded).unwrap());
+        }
+    }
+
+    #[test]
+    fn exact_size() {
+        for len in 0..40 {
+            let bytes = (0..len).map(|i| (i * 37) as u8).collect::<Vec<_>>();
+            let mut chars = encode_iter(&bytes);
+            let mut expected = encode(&bytes).chars().count();
+            loop {
+                assert_eq!(chars.len(), expected);
+                if chars.next().is_none() {
+                    break;
+                }
+                expected -= 1;
+            }
+            assert_eq!(expected, 0);
+        }
+    }
+
+    #[test]
+    fn try_chars() {
+        for len in 0..40 {
+            let bytes = (0..len).map(|i| (i * 37) as u8).collect::<Vec<_>>();
+            let encoded = encode(&bytes);
+            let chars = encoded.chars().map(Ok::<_, ()>);
+            assert_eq!(decode_try_chars(chars), Ok(bytes));
+        }
+        let chars = "ետћζы༎X".chars().map(Ok::<_, ()>);
+        assert_eq!(
+            decode_try_chars(chars),
+            Err(TryDecodeError::Decode(DecodeError::InvalidChar {
+                c: '༎',
+                char_index: 5
+            }))
+        );
+        // a tail character followed by a failed read is reported as the read failing
+        let chars = [Ok('0'), Err(1), Ok('0')];
+        assert_eq!(decode_try_chars(chars), Err(TryDecodeError::Source(1)));
+        let chars = [Err(1), Err(2)];
+        assert_eq!(decode_try_chars(chars), Err(TryDecodeError::Source(1)));
+    }
+
+    #[test]
+    fn stops_after_error() {
+        let mut bytes = "ետћζы༎X".chars().base2048_bytes();
+        assert!(bytes.by_ref().any(|b| b.is_err()));
+        assert_eq!(bytes.next(), None);
+    }
+
+    #[test]
+    fn decode_iter_yields_bytes_then_error() {
+        let mut encoded = encode(b"hello world");
+        let valid = decode_iter(&encoded).collect::<Result<Vec<_>, _>>();
+        assert_eq!(valid.unwrap(), b"hello world");
+
+        // the characters before the error still give their bytes
+        let at = encoded.char_indices().nth(3).unwrap().0;
+        encoded.insert(at, '༎');
+        let items = decode_iter(&encoded).collect::<Vec<_>>();
+        assert_eq!(items.len(), 5);
+        assert!(items[..4].iter().all(|item| item.is_ok()));
+        assert_eq!(
+            items[4],
+            Err(DecodeError::InvalidChar {
+                c: '༎',
+                char_index: 3
+            })
+        );
+    }
+
+    #[test]
+    fn strict() {
+        let loose = encode(b"hi").replace('Ո', "Ԉ");
+        let bytes = decode_iter(&loose).collect::<Result<Vec<_>, _>>();
+        assert_eq!(bytes, Ok(b"hi".to_vec()));
+        let items = decode_iter(&loose).with_strict(true).collect::<Vec<_>>();
+        assert_eq!(items, [
+            Ok(b'h'),
+            Err(DecodeError::InvalidPadding { char_index: 1 })
+        ]);
+    }
+}
@@ -3,11 +3,136 @@
 
 #[macro_use]
 extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
 use alloc::{string::String, vec::Vec};
+use core::convert::TryInto;
 
-pub const ENC_TABLE: &'static [char; 2048] = &include!("./enc_table.src");
-pub const DEC_TABLE: &'static [u16; 4182] = &include!("./dec_table.src");
-pub const TAIL: &'static [char; 8] = &['0', '1', '2', '3', '4', '5', '6', '7'];
+// the generated code for the bindings in `mobile` must be in the crate root
+#[cfg(feature = "uniffi")]
+uniffi::setup_scaffolding!();
+
+mod aligned;
+#[cfg(feature = "allocator-api2")]
+mod allocator;
+mod alphabet;
+mod annotate;
+#[cfg(feature = "base1024")]
+pub mod base1024;
+pub mod base32768;
+#[cfg(feature = "base65536")]
+pub mod base65536;
+mod checked;
+mod codec;
+mod compressibility;
+mod const_codec;
+mod constant_time;
+mod crc32;
+#[cfg(feature = "debug-bits")]
+mod debug_bits;
+mod decoder;
+mod diagnostics;
+mod display;
+#[cfg(feature = "ecc")]
+pub mod ecc;
+#[cfg(feature = "embedded-io")]
+pub mod embedded;
+mod encoder;
+mod engine;
+mod error;
+#[cfg(feature = "futures")]
+mod futures_io;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod iter;
+#[cfg(feature = "uniffi")]
+pub mod mobile;
+mod multibase;
+mod owned;
+mod progress;
+#[cfg(feature = "miette")]
+mod pretty;
+#[cfg(feature = "std")]
+pub mod record_log;
+pub mod rle;
+mod self_test;
+#[cfg(feature = "simd")]
+mod simd;
+#[cfg(feature = "serde")]
+pub mod serde;
+mod slice;
+mod split;
+#[cfg(feature = "abi_stable")]
+pub mod stable_abi;
+#[cfg(feature = "std")]
+mod std_io;
+mod string;
+mod tables;
+#[cfg(any(feature = "std", feature = "ffi", feature = "embedded-io"))]
+mod stream;
+#[cfg(feature = "tokio-util")]
+mod tokio_codec;
+#[cfg(feature = "tokio")]
+mod tokio_io;
+#[cfg(feature = "ufmt")]
+mod uwrite;
+mod warnings;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+mod whiten;
+pub use aligned::{decode_aligned, Align16, Align2, Align4, Align8, AlignedBytes, Alignment};
+#[cfg(feature = "allocator-api2")]
+pub use allocator::{decode_in, encode_in};
+pub use alphabet::{Alphabet, AlphabetBuilder, AlphabetError};
+pub use annotate::{annotate, AnnotatedChar, Annotation};
+pub use checked::{decode_checked, encode_checked, CheckedDecodeError, CHECKSUM_CHARS};
+pub use codec::{Codec, CodecDecoder, CodecEncoder, CodecError, LineEnding};
+pub use compressibility::{compressibility, Compressibility};
+pub use const_codec::{decode_const, encode_const, encoded_utf8_len};
+pub use constant_time::{constant_time_eq, constant_time_eq_bytes};
+pub use crc32::{decode_with_crc, encode_with_crc, CrcError};
+#[cfg(feature = "debug-bits")]
+pub use debug_bits::{trace_decode, trace_encode, DecodeStep, EncodeStep};
+pub use decoder::Decoder;
+pub use display::{display, Base2048Display};
+pub use encoder::Encoder;
+pub use engine::{Engine, StandardEngine, STANDARD};
+pub use error::DecodeError;
+#[cfg(feature = "futures")]
+pub use futures_io::{AsyncDecodeReader, AsyncEncodeWriter};
+pub use iter::{
+    decode_iter, decode_try_chars, encode_iter, DecodeBytes, EncodeChars, IterEncodeExt,
+    TryDecodeError,
+};
+pub use multibase::{decode_multibase, encode_multibase, MultibaseError, MULTIBASE_PREFIX};
+pub use owned::decode_owned;
+#[cfg(feature = "miette")]
+pub use pretty::{decode_diagnostic, DecodeDiagnostic};
+pub use progress::{decode_with_progress, encode_with_progress};
+pub use rle::{decode_rle, encode_rle};
+pub use self_test::{self_test, SelfTestError};
+#[cfg(feature = "serde")]
+pub use crate::serde::Base2048Bytes;
+pub use slice::{
+    decode_to_slice, decode_to_slice_strict, encode_to_slice, BufferTooSmall, DecodeSliceError,
+};
+pub use split::{join, split, split_with_parity, JoinError, TWEET_CHARS};
+#[cfg(feature = "std")]
+pub use std_io::{DecodeReader, EncodeWriter};
+pub use string::{Base2048Str, Base2048String};
+#[cfg(feature = "tokio-util")]
+pub use tokio_codec::Base2048Codec;
+#[cfg(feature = "tokio")]
+pub use tokio_io::{copy_decode, copy_decode_strict, copy_encode};
+#[cfg(feature = "ufmt")]
+pub use uwrite::encode_to_uwrite;
+pub use warnings::{decode_with_warnings, DecodeReport, DecodeWarning};
+pub use whiten::{decode_whitened, encode_whitened};
+
+// generated by build/main.rs from base2048.ranges
+pub const ENC_TABLE: &[char; 2048] = &include!(concat!(env!("OUT_DIR"), "/enc_table.src"));
+pub const DEC_TABLE: &[u16; 4182] = &include!(concat!(env!("OUT_DIR"), "/dec_table.src"));
+pub const TAIL: &[char; 8] = &['0', '1', '2', '3', '4', '5', '6', '7'];
 
 /// The maximum number of bits encoded in a tail character
 pub const TAIL_BITS: u32 = 3;
@@ -15,135 +140,936 @@ pub const TAIL_BITS: u32 = 3;
 /// The number of bits encoded per char in the output
 pub const BITS_PER_CHAR: u32 = 11;
 
+/// The longest UTF-8 encoding of a character in [`ENC_TABLE`] or [`TAIL`], in bytes.
+///
+/// Sizing encoded text by it never needs to grow, and wastes a fifth at most since most characters
+/// take two or three bytes.
+const MAX_CHAR_LEN: usize = 3;
+
 
 /// Encode some bytes using base2048 encoding
 ///
+/// Anything that is `AsRef<[u8]>` works, such as a `Vec<u8>` or a `&str`; [`encode_bytes`] is the
+/// same function for `&[u8]` only, for when a function pointer is needed.
+///
 /// # Example
-/// -```
+/// ```
 /// let some_bytes = b"some utf8 bytes to encode more compactly";
 /// assert_eq!(
 ///     base2048::encode(some_bytes),
-///     "ݙޙצҭזЬශƕމਦعҭӿचॳಽܜͳԈඌཥШߣۿ۹ࠄעแಐ1"
+///     "ݙޙצҭזЬශƕމਦعҭӿचॳಽܜͳԈඌཥШߣۿ۹ࠄעแಐ7"
 /// );
 /// ```
-pub fn encode(bytes: &[u8]) -> String {
-    let mut ret = String::new();
-    let mut stage = 0x0000u16;
-    let mut remaining = 0;
+pub fn encode(bytes: impl AsRef<[u8]>) -> String {
+    encode_bytes(bytes.as_ref())
+}
 
+/// Encode some bytes using base2048 encoding
+///
+/// The non-generic version of [`encode`].
+///
+/// # Example
+/// ```
+/// let encoders: [fn(&[u8]) -> String; 1] = [base2048::encode_bytes];
+/// assert_eq!(encoders[0](b"hello"), base2048::encode("hello"));
+/// ```
+pub fn encode_bytes(bytes: &[u8]) -> String {
+    STANDARD.encode(bytes)
+}
+
+/// Encode some bytes using base2048 encoding, returning the UTF-8 bytes of the encoded text
+///
+/// Useful when the text is going straight to a socket or file and a `String` would only be turned
+/// back into bytes.
+///
+/// # Example
+/// ```
+/// let encoded = base2048::encode_to_vec(b"hello");
+/// assert_eq!(encoded, base2048::encode(b"hello").into_bytes());
+/// ```
+pub fn encode_to_vec(bytes: &[u8]) -> Vec<u8> {
+    let mut ret = Vec::with_capacity(encoded_len(bytes.len()) * MAX_CHAR_LEN);
+    encode_utf8(bytes, &mut ret);
+    ret
+}
+
+/// Encode some bytes using base2048 encoding, adding the characters to `out`
+///
+/// Works with any character collection, such as a `String` that already holds a prefix, a
+/// `VecDeque<char>` or a custom buffer; each character is added as soon as it is known.
+///
+/// # Example
+/// ```
+/// let mut message = String::from("token: ");
+/// base2048::encode_extend(b"hello", &mut message);
+/// assert_eq!(message, format!("token: {}", base2048::encode(b"hello")));
+/// ```
+pub fn encode_extend(bytes: &[u8], out: &mut impl Extend<char>) {
+    encode_with(bytes, |c| out.extend(core::iter::once(c)))
+}
+
+/// Encode some bytes using base2048 encoding, writing the characters to a [`fmt::Write`] sink
+///
+/// Works with a `Formatter`, a `String` or a fixed-capacity string on `no_std` targets, without an
+/// intermediate `String`. Stops at, and returns, the first error from `w`.
+///
+/// [`fmt::Write`]: core::fmt::Write
+///
+/// # Example
+/// ```
+/// use std::fmt::Write;
+/// let mut line = String::from("digest: ");
+/// base2048::encode_to_fmt(b"hello", &mut line).unwrap();
+/// assert_eq!(line, format!("digest: {}", base2048::encode(b"hello")));
+/// ```
+pub fn encode_to_fmt(bytes: &[u8], w: &mut (impl core::fmt::Write + ?Sized)) -> core::fmt::Result {
+    let mut state = EncodeState::default();
     for byte in bytes {
-        let byte = *byte as u16;
+        if let Some(c) = state.push(*byte) {
+            w.write_char(c)?;
+        }
+    }
+    if let Some(c) = state.finish() {
+        w.write_char(c)?;
+    }
+    Ok(())
+}
+
+/// Encode some bytes using base2048 encoding, appending the text to `out`
+///
+/// Clearing and reusing one `String` across many calls avoids allocating for each of them once it
+/// has grown to fit the largest output.
+///
+/// # Example
+/// ```
+/// let mut buf = String::new();
+/// for payload in [&b"hello"[..], b"world"] {
+///     buf.clear();
+///     base2048::encode_into(payload, &mut buf);
+///     assert_eq!(buf, base2048::encode(payload));
+/// }
+/// ```
+pub fn encode_into(bytes: &[u8], out: &mut String) {
+    // every character takes at least one byte
+    out.reserve(encoded_len(bytes.len()));
+    // SAFETY: `encode_utf8` only appends whole characters
+    encode_utf8(bytes, unsafe { out.as_mut_vec() })
+}
+
+/// Encodes `bytes`, appending the UTF-8 text to `out`.
+///
+/// The characters are copied from [`tables::enc_utf8`] rather than encoded one at a time. With the `simd`
+/// feature, whole blocks of the input are encoded with SIMD instructions where the processor has
+/// them.
+fn encode_utf8(bytes: &[u8], out: &mut Vec<u8>) {
+    #[cfg(feature = "simd")]
+    let bytes = &bytes[simd::encode_blocks(bytes, out)..];
+    out.reserve(encoded_len(bytes.len()) * MAX_CHAR_LEN);
+    let enc_utf8 = tables::enc_utf8();
+    let mut push = |index: u16| {
+        let utf8 = enc_utf8[index as usize];
+        out.extend_from_slice(&utf8.to_le_bytes()[..(utf8 >> 24) as usize])
+    };
+    let mut state = EncodeState::default();
+    state.push_slice(bytes, &mut push);
+    match state.finish_symbol() {
+        Some(Symbol::Table(index)) => push(index),
+        // tail characters are ASCII
+        Some(Symbol::Tail(index)) => out.push(TAIL[index as usize] as u8),
+        None => {}
+    }
+}
+
+/// Encodes `bytes`, handing each character to `push` as soon as it is known.
+///
+/// This is the core of every encoding function; they only differ in where the characters end up.
+pub(crate) fn encode_with(bytes: &[u8], mut push: impl FnMut(char)) {
+    let enc_table = tables::enc_table();
+    let mut state = EncodeState::default();
+    state.push_slice(bytes, |index| push(enc_table[index as usize]));
+    if let Some(c) = state.finish() {
+        push(c);
+    }
+}
+
+/// The table positions of the 8 characters 11 bytes make.
+#[inline(always)]
+fn encode_block(block: &[u8; 11]) -> [u16; 8] {
+    // characters 0 to 4 are in the first 8 bytes and 5 to 7 in the last 8
+    let mut word = [0; 8];
+    word.copy_from_slice(&block[..8]);
+    let hi = u64::from_be_bytes(word);
+    word.copy_from_slice(&block[3..]);
+    let lo = u64::from_be_bytes(word);
+    [
+        (hi >> 53) as u16,
+        (hi >> 42) as u16 & 0x7FF,
+        (hi >> 31) as u16 & 0x7FF,
+        (hi >> 20) as u16 & 0x7FF,
+        (hi >> 9) as u16 & 0x7FF,
+        (lo >> 22) as u16 & 0x7FF,
+        (lo >> 11) as u16 & 0x7FF,
+        lo as u16 & 0x7FF,
+    ]
+}
+
+/// The 11 bytes the 8 characters at table positions `indices` make.
+#[inline(always)]
+fn decode_block(indices: [u16; 8]) -> [u8; 11] {
+    let [i0, i1, i2, i3, i4, i5, i6, i7] = indices.map(u64::from);
+    // the first 8 bytes take all of characters 0 to 4 and the top 9 bits of 5
+    let hi = i0 << 53 | i1 << 42 | i2 << 31 | i3 << 20 | i4 << 9 | i5 >> 2;
+    let lo = (i5 & 0b11) << 22 | i6 << 11 | i7;
+    let mut block = [0; 11];
+    block[..8].copy_from_slice(&hi.to_be_bytes());
+    block[8..].copy_from_slice(&lo.to_be_bytes()[5..]);
+    block
+}
+
+/// The bits of the input that have not been put into a character yet.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct EncodeState {
+    /// The bits, in the lowest `remaining` bits; the rest are clear between calls.
+    pub(crate) stage: u64,
+    pub(crate) remaining: u32,
+}
+
+/// A position in the encoding table or among the tail characters.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Symbol {
+    Table(u16),
+    Tail(u8),
+}
+
+impl Symbol {
+    /// The symbol's character in the standard alphabet.
+    pub(crate) fn to_char(self) -> char {
+        match self {
+            Symbol::Table(index) => tables::enc_table()[index as usize],
+            Symbol::Tail(index) => TAIL[index as usize],
+        }
+    }
+
+    /// The symbol `c` stands for in the standard alphabet.
+    pub(crate) fn from_char(c: char) -> Option<Symbol> {
+        match tables::table_index(c as u32) {
+            Some(index) => Some(Symbol::Table(index)),
+            None => Symbol::tail_from_char(c),
+        }
+    }
+
+    /// The rest of [`from_char`](Self::from_char), for characters outside the table.
+    ///
+    /// Outlined since they can only be the one tail character or an error.
+    #[cold]
+    #[inline(never)]
+    fn tail_from_char(c: char) -> Option<Symbol> {
+        TAIL.iter()
+            .position(|t| *t == c)
+            .map(|index| Symbol::Tail(index as u8))
+    }
+}
+
+impl EncodeState {
+    /// Adds a byte, returning the character it completes (if any).
+    pub(crate) fn push(&mut self, byte: u8) -> Option<char> {
+        self.push_index(byte)
+            .map(|index| tables::enc_table()[index as usize])
+    }
+
+    /// Like [`push`](Self::push) but returns the character's position in the table.
+    pub(crate) fn push_index(&mut self, byte: u8) -> Option<u16> {
+        let byte = byte as u64;
         // how many more bits do we need to complete the next character?
-        let need = 11 - remaining;
+        let need = 11 - self.remaining;
         if need <= 8 {
             // if we need a byte or less then take what we need and push it
-            remaining = 8 - need;
-            let index = (stage << need) | (byte >> remaining);
-            ret.push(ENC_TABLE[index as usize]);
+            self.remaining = 8 - need;
+            let index = (self.stage << need) | (byte >> self.remaining);
             // put what remains in stage
-            stage = byte & ((1 << remaining) - 1);
+            self.stage = byte & ((1 << self.remaining) - 1);
+            Some(index as u16)
         } else {
             // we need more than a byte so just shift it into stage
-            stage = (stage << 8) | byte;
-            remaining += 8;
+            self.stage = (self.stage << 8) | byte;
+            self.remaining += 8;
+            None
         }
     }
 
-    // there are some bits that haven't been put into the string
-    // (happens whenever 8 * bytes.len() is not divisible by 11).
-    if remaining > 0 {
-        // We need to disambiguate between a terminating character conveying =< 3 or > 8 bits.
-        // e.g. is this character just finishing the last byte or is it doing that and adding another byte.
-        if remaining <= TAIL_BITS {
-            let padding = TAIL_BITS - remaining;
-            let index = stage << padding | !(!0 << padding);
+    /// Adds `bytes`, handing the position of each character they complete to `push`.
+    ///
+    /// Six bytes at a time are shifted into the stage before the characters are taken out of it,
+    /// rather than checking for a whole character after every byte.
+    pub(crate) fn push_slice(&mut self, mut bytes: &[u8], mut push: impl FnMut(u16)) {
+        if self.remaining == 0 {
+            // every 11 bytes make exactly 8 characters, leaving the stage empty again
+            let mut blocks = bytes.chunks_exact(11);
+            for block in &mut blocks {
+                for index in encode_block(block.try_into().expect("blocks are 11 bytes")) {
+                    push(index);
+                }
+            }
+            bytes = blocks.remainder();
+        }
+        let mut chunks = bytes.chunks_exact(6);
+        for chunk in &mut chunks {
+            let mut word = [0; 8];
+            word[2..].copy_from_slice(chunk);
+            // at most 10 bits are left over, so 48 more still fit
+            self.stage = (self.stage << 48) | u64::from_be_bytes(word);
+            self.remaining += 48;
+            while self.remaining >= 11 {
+                self.remaining -= 11;
+                push((self.stage >> self.remaining) as u16 & 0x7FF);
+            }
+            self.stage &= (1 << self.remaining) - 1;
+        }
+        for byte in chunks.remainder() {
+            if let Some(index) = self.push_index(*byte) {
+                push(index);
+            }
+        }
+    }
 
-            // we're adding 1-3 bits so add special tail character
-            ret.push(TAIL[index as usize]);
-        } else {
-            let padding = BITS_PER_CHAR - remaining;
-            let index = stage << padding | !(!0 << padding);
+    /// The final character, if there are bits that haven't been put into a character yet.
+    pub(crate) fn finish(self) -> Option<char> {
+        self.finish_symbol().map(Symbol::to_char)
+    }
+
+    /// Like [`finish`](Self::finish) but returns the final character as a [`Symbol`].
+    pub(crate) fn finish_symbol(self) -> Option<Symbol> {
+        // there are some bits that haven't been put into the string
+        // (happens whenever 8 * bytes.len() is not divisible by 11).
+        if self.remaining > 0 {
+            // We need to disambiguate between a terminating character conveying =< 3 or > 8 bits.
+            // e.g. is this character just finishing the last byte or is it doing that and adding another byte.
+            if self.remaining <= TAIL_BITS {
+                let padding = TAIL_BITS - self.remaining;
+                let index = self.stage << padding | !(!0 << padding);
 
-            // we're adding > 3 bits no need for a tail since it's not ambigious
-            ret.push(ENC_TABLE[index as usize])
+                // we're adding 1-3 bits so add special tail character
+                Some(Symbol::Tail(index as u8))
+            } else {
+                let padding = BITS_PER_CHAR - self.remaining;
+                let index = self.stage << padding | !(!0 << padding);
+
+                // we're adding > 3 bits no need for a tail since it's not ambigious
+                Some(Symbol::Table(index as u16))
+            }
+        } else {
+            None
         }
     }
+}
 
-    ret
+/// The number of characters `n_bytes` bytes encode to, including the tail character if any
+///
+/// # Example
+/// ```
+/// assert_eq!(base2048::encoded_len(5), base2048::encode(b"hello").chars().count());
+/// ```
+pub const fn encoded_len(n_bytes: usize) -> usize {
+    // every 11 bytes are exactly 8 characters, so only the rest need rounding up; this never
+    // overflows, unlike `n_bytes * 8`
+    let bits_per_char = BITS_PER_CHAR as usize;
+    n_bytes / bits_per_char * 8 + (n_bytes % bits_per_char * 8).div_ceil(bits_per_char)
+}
+
+/// The number of bytes `n_chars` characters decode to, or `None` if no input encodes to that many
+///
+/// `ends_in_tail` says whether the last character is a tail character, which carries only the last
+/// 1 to 3 bits rather than 11. Useful for checking the length of a message before decoding it.
+///
+/// # Example
+/// ```
+/// let encoded = base2048::encode(b"hello");
+/// let ends_in_tail = encoded.ends_with(|c| base2048::TAIL.contains(&c));
+/// assert_eq!(base2048::decoded_len(encoded.chars().count(), ends_in_tail), Some(5));
+/// // 2 bytes take 2 characters without a tail, and 3 take 3
+/// assert_eq!(base2048::decoded_len(2, true), None);
+/// ```
+pub const fn decoded_len(n_chars: usize, ends_in_tail: bool) -> Option<usize> {
+    let n_bytes = if !ends_in_tail {
+        max_decoded_len(n_chars)
+    } else if n_chars > 0 {
+        max_decoded_len(n_chars - 1).saturating_add(1)
+    } else {
+        return None;
+    };
+    // the encoder ends in a tail exactly when 1 to 3 bits are left over
+    let leftover = n_bytes % BITS_PER_CHAR as usize * 8 % BITS_PER_CHAR as usize;
+    let has_tail = leftover > 0 && leftover <= TAIL_BITS as usize;
+    if encoded_len(n_bytes) == n_chars && has_tail == ends_in_tail {
+        Some(n_bytes)
+    } else {
+        None
+    }
+}
+
+/// The most bytes `n_chars` characters can decode to
+///
+/// This is `usize::MAX` if the bytes would be too many to count, which no string can hold.
+///
+/// # Example
+/// ```
+/// assert_eq!(base2048::max_decoded_len(8), 11);
+/// assert_eq!(base2048::max_decoded_len(usize::MAX), usize::MAX);
+/// ```
+pub const fn max_decoded_len(n_chars: usize) -> usize {
+    // every 8 characters are exactly 11 bytes
+    let bits_per_char = BITS_PER_CHAR as usize;
+    (n_chars / 8)
+        .saturating_mul(bits_per_char)
+        .saturating_add(n_chars % 8 * bits_per_char / 8)
+}
+
+/// The number of bytes `string` decodes to, if it is valid.
+///
+/// Every character but the last carries 11 bits; a tail character at the end completes the final
+/// byte while a table character's padding is dropped.
+pub(crate) fn decoded_str_len(string: &str) -> usize {
+    let n_chars = string.chars().count();
+    match string.chars().next_back() {
+        Some(c) if TAIL.contains(&c) => max_decoded_len(n_chars - 1) + 1,
+        _ => max_decoded_len(n_chars),
+    }
 }
 
 /// Decode a base2048 encoded string
+///
+/// Anything that is `AsRef<str>` works, such as a `String`; [`decode_str`] is the same function for
+/// `&str` only, for when a function pointer is needed.
+///
 /// # Example
-/// -```
-/// let encoded_message = "ݙޙצҭזЬශƕމਦعҭӿचॳಽܜͳԈඌཥШߣۿ۹ࠄעแಐ1";
+/// ```
+/// let encoded_message = "ݙޙצҭזЬශƕމਦعҭӿचॳಽܜͳԈඌཥШߣۿ۹ࠄעแಐ7";
 /// assert_eq!(
 ///     base2048::decode(encoded_message),
 ///     Some(b"some utf8 bytes to encode more compactly".to_vec())
 /// );
 /// ```
-pub fn decode(string: &str) -> Option<Vec<u8>> {
-    let mut ret = vec![];
-    let mut remaining = 0u8;
-    let mut stage = 0x00u32;
-    let mut chars = string.chars().peekable();
-    let mut residue = 0;
+pub fn decode(string: impl AsRef<str>) -> Option<Vec<u8>> {
+    decode_str(string.as_ref())
+}
 
-    while let Some(c) = chars.next() {
-        // keep track of the misalignment between byte boundary.  This is useful when we get to the
-        // last character and it's NOT a tail character.
-        residue = (residue + 11) % 8;
-        let (n_new_bits, new_bits) = match DEC_TABLE[c as usize] {
-            0xFFFF => {
-                if chars.peek().is_some() {
-                    return None;
+/// Decode a base2048 encoded string
+///
+/// The non-generic version of [`decode`].
+///
+/// # Example
+/// ```
+/// let decoders: [fn(&str) -> Option<Vec<u8>>; 1] = [base2048::decode_str];
+/// assert_eq!(decoders[0](&base2048::encode("hello")), Some(b"hello".to_vec()));
+/// ```
+pub fn decode_str(string: &str) -> Option<Vec<u8>> {
+    try_decode(string).ok()
+}
+
+/// Decode a base2048 encoded string, saying what is wrong with it if it is invalid
+///
+/// Like [`decode`], but the [`DecodeError`] tells which character is at fault and why, for error
+/// messages worth showing to whoever supplied the string.
+///
+/// A final character whose padding bits aren't all set is accepted, though the encoder never
+/// produces one; [`try_decode_strict`] rejects it.
+///
+/// # Example
+/// ```
+/// use base2048::DecodeError;
+/// assert_eq!(base2048::try_decode(base2048::encode(b"hello")), Ok(b"hello".to_vec()));
+/// assert_eq!(
+///     base2048::try_decode("ետћζы༎X"),
+///     Err(DecodeError::InvalidChar { c: '༎', char_index: 5 })
+/// );
+/// ```
+pub fn try_decode(string: impl AsRef<str>) -> Result<Vec<u8>, DecodeError> {
+    STANDARD.decode(string)
+}
+
+/// Decode a base2048 encoded string, accepting only the text [`encode`] gives for the bytes
+///
+/// [`try_decode`] lets the padding bits of a final table character take any value, so several
+/// strings decode to the same bytes. This fails with [`DecodeError::InvalidPadding`] unless they
+/// are all set as the encoder sets them, so signatures and hashes over the text can't be
+/// sidestepped with another spelling of the same bytes.
+///
+/// # Example
+/// ```
+/// use base2048::DecodeError;
+/// let encoded = base2048::encode(b"hi");
+/// assert_eq!(base2048::try_decode_strict(&encoded), Ok(b"hi".to_vec()));
+/// // the same two bytes, with the padding bits clear
+/// let loose = encoded.replace('Ո', "Ԉ");
+/// assert_eq!(base2048::try_decode(&loose), Ok(b"hi".to_vec()));
+/// assert_eq!(
+///     base2048::try_decode_strict(&loose),
+///     Err(DecodeError::InvalidPadding { char_index: 1 })
+/// );
+/// ```
+pub fn try_decode_strict(string: impl AsRef<str>) -> Result<Vec<u8>, DecodeError> {
+    let string = string.as_ref();
+    let mut ret = Vec::with_capacity(decoded_str_len(string));
+    decode_to_vec(string, &mut ret, true)?;
+    Ok(ret)
+}
+
+/// Decode a base2048 encoded string, appending the bytes to `out`
+///
+/// Works with any byte collection, such as a `VecDeque` or an arena, so no intermediate `Vec` is
+/// needed. If `string` turns out to be invalid, the bytes before the error have already been added.
+///
+/// # Example
+/// ```
+/// use std::collections::VecDeque;
+/// let mut queue = VecDeque::from(vec![0]);
+/// base2048::decode_extend(&base2048::encode(b"hello"), &mut queue).unwrap();
+/// assert_eq!(queue, b"\0hello");
+/// ```
+pub fn decode_extend(string: &str, out: &mut impl Extend<u8>) -> Result<(), DecodeError> {
+    decode_with(string, |byte| out.extend(core::iter::once(byte)))
+}
+
+/// Like [`decode_extend`], but rejecting non-canonical padding as [`try_decode_strict`] does
+///
+/// # Example
+/// ```
+/// use base2048::DecodeError;
+/// let loose = base2048::encode(b"hi").replace('Ո', "Ԉ");
+/// let mut out = Vec::new();
+/// assert_eq!(
+///     base2048::decode_extend_strict(&loose, &mut out),
+///     Err(DecodeError::InvalidPadding { char_index: 1 })
+/// );
+/// ```
+pub fn decode_extend_strict(string: &str, out: &mut impl Extend<u8>) -> Result<(), DecodeError> {
+    decode_with_strict(string, true, |byte| out.extend(core::iter::once(byte)))
+}
+
+/// Decode a base2048 encoded string, appending the bytes to `out` and returning how many there are
+///
+/// Clearing and reusing one `Vec` across many calls avoids allocating for each of them once it has
+/// grown to fit the largest output. If `string` is invalid, `out` is left as it was.
+///
+/// # Example
+/// ```
+/// let mut buf = vec![0];
+/// assert_eq!(base2048::decode_into(&base2048::encode(b"hello"), &mut buf), Ok(5));
+/// assert_eq!(buf, b"\0hello");
+/// assert!(base2048::decode_into("ետћζы༎X", &mut buf).is_err());
+/// assert_eq!(buf, b"\0hello");
+/// ```
+pub fn decode_into(string: &str, out: &mut Vec<u8>) -> Result<usize, DecodeError> {
+    decode_into_with(string, out, false)
+}
+
+/// Like [`decode_into`], but rejecting non-canonical padding as [`try_decode_strict`] does
+///
+/// # Example
+/// ```
+/// use base2048::DecodeError;
+/// let loose = base2048::encode(b"hi").replace('Ո', "Ԉ");
+/// let mut buf = vec![0];
+/// assert_eq!(
+///     base2048::decode_into_strict(&loose, &mut buf),
+///     Err(DecodeError::InvalidPadding { char_index: 1 })
+/// );
+/// assert_eq!(buf, b"\0");
+/// ```
+pub fn decode_into_strict(string: &str, out: &mut Vec<u8>) -> Result<usize, DecodeError> {
+    decode_into_with(string, out, true)
+}
+
+fn decode_into_with(string: &str, out: &mut Vec<u8>, strict: bool) -> Result<usize, DecodeError> {
+    let start = out.len();
+    out.reserve(decoded_str_len(string));
+    match decode_to_vec(string, out, strict) {
+        Ok(()) => Ok(out.len() - start),
+        Err(e) => {
+            out.truncate(start);
+            Err(e)
+        }
+    }
+}
+
+/// Decodes `string`, handing each decoded byte to `push` as soon as it is complete.
+///
+/// This is the core of every decoding function; they only differ in where the bytes end up.
+pub(crate) fn decode_with(string: &str, push: impl FnMut(u8)) -> Result<(), DecodeError> {
+    decode_with_strict(string, false, push)
+}
+
+/// Like [`decode_with`], rejecting non-canonical padding if `strict`.
+pub(crate) fn decode_with_strict(
+    string: &str,
+    strict: bool,
+    push: impl FnMut(u8),
+) -> Result<(), DecodeError> {
+    let state = DecodeState {
+        strict,
+        ..DecodeState::default()
+    };
+    decode_str_from(state, string, push)
+}
+
+/// Decodes `string`, appending the bytes to `out`, rejecting non-canonical padding if `strict`.
+///
+/// With the `simd` feature, whole blocks of the text are decoded with SIMD instructions where the
+/// processor has them.
+fn decode_to_vec(string: &str, out: &mut Vec<u8>, strict: bool) -> Result<(), DecodeError> {
+    #[cfg(feature = "simd")]
+    let (string, state) = {
+        let (n_bytes, chars) = simd::decode_blocks(string, out);
+        let state = DecodeState {
+            chars,
+            strict,
+            ..DecodeState::default()
+        };
+        (&string[n_bytes..], state)
+    };
+    #[cfg(not(feature = "simd"))]
+    let state = DecodeState {
+        strict,
+        ..DecodeState::default()
+    };
+    decode_str_from(state, string, |byte| out.push(byte))
+}
+
+/// Like [`decode_chars_from`] for a `str`, taking whole blocks of 8 table characters at a time
+/// while `state` is between blocks.
+///
+/// Reads the code points straight from the UTF-8 rather than through `chars().peekable()`; the
+/// last character is the one that ends the string, so it needs no lookahead.
+fn decode_str_from(
+    mut state: DecodeState,
+    string: &str,
+    mut push: impl FnMut(u8),
+) -> Result<(), DecodeError> {
+    let bytes = string.as_bytes();
+    let mut pos = 0;
+    if state.remaining == 0 && state.residue == 0 {
+        'blocks: loop {
+            let mut end = pos;
+            let mut indices = [0; 8];
+            for index in &mut indices {
+                if end == bytes.len() {
+                    break 'blocks;
+                }
+                let (c, len) = next_code_point(bytes, end);
+                match tables::table_index(c) {
+                    Some(i) => *index = i,
+                    None => break 'blocks,
                 }
+                end += len;
+            }
+            // the last character of the string is read differently, so leave it
+            if end == bytes.len() {
+                break;
+            }
+            decode_block(indices).iter().for_each(|byte| push(*byte));
+            state.chars += 8;
+            pos = end;
+        }
+    }
 
-                match TAIL.iter().enumerate().find(|(_, t)| *t == &c) {
-                    // so we're at the last character and it's a tail character
-                    Some((index, _)) => {
-                        let need = 8 - remaining;
-                        let padding = TAIL_BITS - need as u32;
-                        if index.trailing_ones() >= padding {
-                            (need, index as u16 >> padding)
-                        } else {
-                            return None;
-                        }
-                    }
-                    None => return None,
+    while pos < bytes.len() {
+        let (c, len) = next_code_point(bytes, pos);
+        pos += len;
+        let last = pos == bytes.len();
+        match tables::table_index(c) {
+            Some(index) if !last => {
+                state.shift_in(index);
+                if state.remaining > 64 - BITS_PER_CHAR as u8 {
+                    state.flush(&mut push);
                 }
             }
-            new_bits => {
-                if chars.peek().is_none() {
-                    (11 - residue, new_bits >> residue)
-                } else {
-                    (11, new_bits)
+            _ => {
+                // hand over the bytes so far, even if `c` turns out to be an error
+                state.flush(&mut push);
+                let c = char::from_u32(c).expect("a str holds characters");
+                state
+                    .push(c, last, &mut push)
+                    .map_err(diagnostics::decode_error)?;
+            }
+        }
+    }
+    state.flush(&mut push);
+    state.finish_checked(push);
+
+    Ok(())
+}
+
+/// The code point starting at `bytes[pos]` and how many bytes it takes, for valid UTF-8.
+#[inline(always)]
+fn next_code_point(bytes: &[u8], pos: usize) -> (u32, usize) {
+    let cont = |i: usize| (bytes[pos + i] & 0x3F) as u32;
+    match bytes[pos] {
+        b @ 0..=0x7F => (b as u32, 1),
+        b @ 0xC0..=0xDF => (((b & 0x1F) as u32) << 6 | cont(1), 2),
+        b @ 0xE0..=0xEF => (((b & 0x0F) as u32) << 12 | cont(1) << 6 | cont(2), 3),
+        b => (
+            ((b & 0x07) as u32) << 18 | cont(1) << 12 | cont(2) << 6 | cont(3),
+            4,
+        ),
+    }
+}
+
+/// Like [`decode_with`] but for characters that don't come from a `str`.
+pub(crate) fn decode_chars_with(
+    chars: impl Iterator<Item = char>,
+    push: impl FnMut(u8),
+) -> Result<(), DecodeError> {
+    decode_chars_from(DecodeState::default(), chars, push)
+}
+
+/// Like [`decode_chars_with`] but carries on from `state`, which must be between bytes.
+///
+/// Also the reference [`decode_str_from`] is tested against.
+fn decode_chars_from(
+    mut state: DecodeState,
+    chars: impl Iterator<Item = char>,
+    mut push: impl FnMut(u8),
+) -> Result<(), DecodeError> {
+    let mut chars = chars.peekable();
+
+    while let Some(c) = chars.next() {
+        let last = chars.peek().is_none();
+        match Symbol::from_char(c) {
+            Some(Symbol::Table(index)) if !last => {
+                state.shift_in(index);
+                if state.remaining > 64 - BITS_PER_CHAR as u8 {
+                    state.flush(&mut push);
                 }
             }
+            _ => {
+                // hand over the bytes so far, even if `c` turns out to be an error
+                state.flush(&mut push);
+                state
+                    .push(c, last, &mut push)
+                    .map_err(diagnostics::decode_error)?;
+            }
+        }
+    }
+    state.flush(&mut push);
+    state.finish_checked(push);
+
+    Ok(())
+}
+
+#[cold]
+#[inline(never)]
+fn invalid_char(c: char, char_index: usize) -> DecodeError {
+    DecodeError::InvalidChar { c, char_index }
+}
+
+/// The decoded bits that don't make up a whole byte yet.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct DecodeState {
+    pub(crate) remaining: u8,
+    /// The bits, in the lowest `remaining` bits; the rest are clear between calls.
+    pub(crate) stage: u64,
+    pub(crate) residue: u8,
+    /// The number of characters taken in so far.
+    pub(crate) chars: usize,
+    /// Whether the last character was a table character with padding bits that aren't all set.
+    /// The encoder never produces that but it is accepted all the same, unless `strict`.
+    pub(crate) non_canonical_padding: bool,
+    /// Whether a last table character with padding bits that aren't all set is an error.
+    pub(crate) strict: bool,
+}
+
+impl DecodeState {
+    /// Adds a character, handing the (at most two) bytes it completes to `push`.
+    ///
+    /// Whether `c` is the `last` character has to be known up front since the last character is
+    /// interpreted differently.
+    pub(crate) fn push(
+        &mut self,
+        c: char,
+        last: bool,
+        push: impl FnMut(u8),
+    ) -> Result<(), DecodeError> {
+        match Symbol::from_char(c) {
+            Some(symbol) => self.push_symbol(symbol, last, push),
+            None => Err(invalid_char(c, self.chars)),
+        }
+    }
+
+    /// Like [`push`](Self::push) but takes the character as a [`Symbol`].
+    pub(crate) fn push_symbol(
+        &mut self,
+        symbol: Symbol,
+        last: bool,
+        push: impl FnMut(u8),
+    ) -> Result<(), DecodeError> {
+        let char_index = self.chars;
+        self.chars += 1;
+        // keep track of the misalignment between byte boundary.  This is useful when we get to the
+        // last character and it's NOT a tail character.
+        self.residue = (self.residue + 11) % 8;
+        let (n_new_bits, new_bits) = match symbol {
+            Symbol::Table(new_bits) if !last => (11, new_bits),
+            _ => self.last_bits(symbol, last, char_index)?,
         };
 
-        remaining += n_new_bits;
-        stage = (stage << n_new_bits) | new_bits as u32;
-        while remaining >= 8 {
-            //NOTE: This loop runs at most twice
-            remaining -= 8;
-            ret.push((stage >> remaining) as u8);
-            stage &= (1 << remaining) - 1
+        self.remaining += n_new_bits;
+        self.stage = (self.stage << n_new_bits) | new_bits as u64;
+        self.flush(push);
+
+        Ok(())
+    }
+
+    /// Adds a table character that isn't the last one, keeping the bytes it completes until
+    /// [`flush`](Self::flush).
+    ///
+    /// Up to five of these fit in the stage after a flush, so several characters can be taken in
+    /// before any bytes are handed over.
+    fn shift_in(&mut self, index: u16) {
+        self.chars += 1;
+        self.residue = (self.residue + 11) % 8;
+        self.remaining += 11;
+        self.stage = (self.stage << 11) | index as u64;
+    }
+
+    /// Hands every whole byte in the stage to `push`.
+    fn flush(&mut self, mut push: impl FnMut(u8)) {
+        while self.remaining >= 8 {
+            self.remaining -= 8;
+            push((self.stage >> self.remaining) as u8);
         }
+        self.stage &= (1 << self.remaining) - 1;
     }
 
-    if remaining > 0 {
-        let data = (stage >> (8 - remaining)) as u8;
-        // data &= !0 << BITS_PER_CHAR;
+    /// The bits a tail character, or the last character of the string, adds.
+    ///
+    /// Outlined from [`push_symbol`](Self::push_symbol) since it is reached at most once per
+    /// string.
+    #[cold]
+    #[inline(never)]
+    fn last_bits(
+        &mut self,
+        symbol: Symbol,
+        last: bool,
+        char_index: usize,
+    ) -> Result<(u8, u16), DecodeError> {
+        match symbol {
+            Symbol::Tail(_) if !last => Err(DecodeError::TrailingData { char_index }),
+            // so we're at the last character and it's a tail character
+            Symbol::Tail(index) => {
+                let need = 8 - self.remaining;
+                if need as u32 > TAIL_BITS {
+                    // a tail character can't complete this many bits
+                    return Err(DecodeError::UnexpectedTail { char_index });
+                }
+                let padding = TAIL_BITS - need as u32;
+                if index.trailing_ones() >= padding {
+                    Ok((need, index as u16 >> padding))
+                } else {
+                    Err(DecodeError::InvalidPadding { char_index })
+                }
+            }
+            Symbol::Table(new_bits) => {
+                let padding = !(!0 << self.residue);
+                self.non_canonical_padding = new_bits & padding != padding;
+                if self.strict && self.non_canonical_padding {
+                    return Err(DecodeError::InvalidPadding { char_index });
+                }
+                Ok((11 - self.residue, new_bits >> self.residue))
+            }
+        }
+    }
 
-        ret.push(data)
+    /// Like [`finish`](Self::finish), but also logs a final character with non-canonical padding
+    /// (with the `log` feature).
+    pub(crate) fn finish_checked(self, push: impl FnMut(u8)) {
+        if self.non_canonical_padding {
+            diagnostics::fixup(DecodeWarning::NonCanonicalPadding {
+                char_index: self.chars - 1,
+            });
+        }
+        self.finish(push)
     }
 
-    Some(ret)
+    /// Hands any final partial byte to `push`.
+    pub(crate) fn finish(self, mut push: impl FnMut(u8)) {
+        if self.remaining > 0 {
+            let data = (self.stage >> (8 - self.remaining)) as u8;
+            // data &= !0 << BITS_PER_CHAR;
+
+            push(data)
+        }
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    #[test]
+    fn decoded_len_is_exact() {
+        for len in 0..40 {
+            let bytes = (0..len).map(|i| (i * 37) as u8).collect::<Vec<_>>();
+            assert_eq!(decoded_str_len(&encode(&bytes)), len);
+        }
+    }
+
+    #[test]
+    fn tables_match_the_character_list() {
+        let listed = include_str!("../base2048.txt")
+            .lines()
+            .map(|line| line.parse::<char>().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(listed, ENC_TABLE);
+        for (index, c) in ENC_TABLE.iter().enumerate() {
+            assert_eq!(DEC_TABLE[*c as usize], index as u16);
+        }
+        assert_eq!(DEC_TABLE.iter().filter(|i| **i != 0xFFFF).count(), 2048);
+    }
+
+    #[test]
+    fn max_char_len() {
+        let longest = ENC_TABLE.iter().chain(TAIL).map(|c| c.len_utf8()).max();
+        assert_eq!(longest, Some(MAX_CHAR_LEN));
+    }
+
+    #[test]
+    fn lengths() {
+        let shapes = (0..300)
+            .map(|len| {
+                let encoded = encode(vec![0xAB; len]);
+                let ends_in_tail = encoded.ends_with(|c| TAIL.contains(&c));
+                assert_eq!(encoded.chars().count(), encoded_len(len));
+                (encoded.chars().count(), ends_in_tail, len)
+            })
+            .collect::<Vec<_>>();
+        for n_chars in 0..encoded_len(300) {
+            for ends_in_tail in [false, true] {
+                let expected = shapes
+                    .iter()
+                    .find(|shape| (shape.0, shape.1) == (n_chars, ends_in_tail))
+                    .map(|shape| shape.2);
+                assert_eq!(decoded_len(n_chars, ends_in_tail), expected);
+                assert!(expected.unwrap_or(0) <= max_decoded_len(n_chars));
+            }
+        }
+    }
+
+    #[test]
+    fn lengths_near_usize_max() {
+        // 11 * 8 bits is a whole number of characters and bytes
+        let n_bytes = usize::MAX - usize::MAX % 11;
+        let n_chars = n_bytes / 11 * 8;
+        assert_eq!(encoded_len(n_bytes), n_chars);
+        assert_eq!(
+            encoded_len(usize::MAX),
+            n_chars + (usize::MAX % 11 * 8).div_ceil(11)
+        );
+        assert_eq!(max_decoded_len(n_chars), n_bytes);
+        assert_eq!(decoded_len(n_chars, false), Some(n_bytes));
+        assert_eq!(max_decoded_len(usize::MAX), usize::MAX);
+        assert_eq!(decoded_len(usize::MAX, false), None);
+        assert_eq!(decoded_len(usize::MAX, true), None);
+    }
+
     #[test]
     fn encode_decode_roundtrip() {
         for tv in &[
@@ -187,6 +1113,234 @@ mod test {
         }
     }
 
+    #[test]
+    fn encode_to_vec_matches_encode() {
+        for len in 0..40 {
+            let bytes = (0..len).map(|i| (i * 37) as u8).collect::<Vec<_>>();
+            assert_eq!(encode_to_vec(&bytes), encode(&bytes).into_bytes());
+        }
+    }
+
+    #[test]
+    fn push_slice_matches_push_index() {
+        let bytes = (0..100).map(|i| (i * 37) as u8).collect::<Vec<_>>();
+        let mut expected = vec![];
+        let mut state = EncodeState::default();
+        for byte in &bytes {
+            expected.extend(state.push_index(*byte));
+        }
+        for split in 0..bytes.len() {
+            let mut indices = vec![];
+            let mut state = EncodeState::default();
+            state.push_slice(&bytes[..split], |index| indices.push(index));
+            state.push_slice(&bytes[split..], |index| indices.push(index));
+            assert_eq!(indices, expected);
+        }
+    }
+
+    #[test]
+    fn blocks() {
+        let block = [0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC, 0xDE, 0xF0, 0x0F, 0xED, 0xCB];
+        let indices = encode_block(&block);
+        let mut state = EncodeState::default();
+        let expected = block
+            .iter()
+            .filter_map(|byte| state.push_index(*byte))
+            .collect::<Vec<_>>();
+        assert_eq!(indices[..], expected[..]);
+        assert_eq!(decode_block(indices), block);
+    }
+
+    #[test]
+    fn block_decoding_matches_char_decoding() {
+        let encoded = encode((0..200).map(|i| (i * 37) as u8).collect::<Vec<_>>());
+        let chars = encoded.chars().collect::<Vec<_>>();
+        let mut cases = (0..chars.len())
+            .map(|len| chars[..len].iter().collect::<String>())
+            .collect::<Vec<_>>();
+        for at in [0, 7, 8, 9, 100, chars.len() - 1] {
+            for bad in ['€', TAIL[1]] {
+                let mut chars = chars.clone();
+                chars[at] = bad;
+                cases.push(chars.into_iter().collect());
+            }
+        }
+        for string in &cases {
+            let (mut by_block, mut by_char) = (vec![], vec![]);
+            let result = decode_with(string, |byte| by_block.push(byte));
+            let expected = decode_chars_with(string.chars(), |byte| by_char.push(byte));
+            assert_eq!((result, by_block), (expected, by_char), "{:?}", string);
+        }
+    }
+
+    #[test]
+    fn utf8_decoding_matches_char_decoding() {
+        // table characters of each length, tail characters, and ones of neither
+        let mut pool = vec![ENC_TABLE[0], ENC_TABLE[100], ENC_TABLE[2047], TAIL[0], TAIL[7]];
+        pool.extend(['a', 'é', '€', '😀', '\u{7FF}', '\u{800}', '\u{FFFF}', '\u{10000}']);
+        let mut x = 0x2545_f491_u32;
+        for _ in 0..2000 {
+            x ^= x << 13;
+            x ^= x >> 17;
+            x ^= x << 5;
+            let len = x as usize % 20;
+            let mut string = encode(x.to_le_bytes().repeat(x as usize % 7));
+            for i in 0..len {
+                let pick = (x as usize >> (i % 16)) % (pool.len() * 4);
+                string.push(pool.get(pick).copied().unwrap_or(ENC_TABLE[pick]));
+            }
+            let (mut by_byte, mut by_char) = (vec![], vec![]);
+            let result = decode_with(&string, |byte| by_byte.push(byte));
+            let expected = decode_chars_with(string.chars(), |byte| by_char.push(byte));
+            assert_eq!((result, by_byte), (expected, by_char), "{:?}", string);
+        }
+    }
+
+    #[test]
+    fn decode_extend_keeps_bytes_before_an_error() {
+        let mut chars = encode([0xA5; 100]).chars().collect::<Vec<_>>();
+        chars[20] = '€';
+        let mut out = vec![];
+        let result = decode_extend(&chars.into_iter().collect::<String>(), &mut out);
+        assert_eq!(
+            result,
+            Err(DecodeError::InvalidChar {
+                c: '€',
+                char_index: 20
+            })
+        );
+        // the 20 characters before it make 27 whole bytes
+        assert_eq!(out, [0xA5; 27]);
+    }
+
+    #[test]
+    fn decode_extend_appends() {
+        let mut out = alloc::collections::VecDeque::from(vec![1, 2]);
+        decode_extend(&encode(b"abc"), &mut out).unwrap();
+        assert_eq!(out, b"\x01\x02abc");
+        assert!(decode_extend("ետћζы༎X", &mut out).is_err());
+    }
+
+    #[test]
+    fn strict_extend_and_into() {
+        let loose = encode(b"hi").replace('Ո', "Ԉ");
+        let padding = Err(DecodeError::InvalidPadding { char_index: 1 });
+        let mut out = alloc::collections::VecDeque::new();
+        decode_extend_strict(&encode(b"hi"), &mut out).unwrap();
+        assert_eq!(out, b"hi");
+        decode_extend(&loose, &mut out).unwrap();
+        assert_eq!(out, b"hihi");
+        assert_eq!(decode_extend_strict(&loose, &mut out), padding);
+
+        let mut out = vec![1];
+        assert_eq!(decode_into_strict(&encode(b"hi"), &mut out), Ok(2));
+        assert_eq!(decode_into(&loose, &mut out), Ok(2));
+        assert_eq!(decode_into_strict(&loose, &mut out), padding.map(|()| 0));
+        assert_eq!(out, b"\x01hihi");
+    }
+
+    #[test]
+    fn encode_extend_appends() {
+        let mut out = alloc::collections::VecDeque::from(vec!['>']);
+        encode_extend(b"abc", &mut out);
+        assert!(out
+            .iter()
+            .copied()
+            .eq(">".chars().chain(encode(b"abc").chars())));
+    }
+
+    #[test]
+    fn try_decode_errors() {
+        assert_eq!(try_decode(""), Ok(vec![]));
+        assert_eq!(
+            try_decode("0a"),
+            Err(DecodeError::TrailingData { char_index: 0 })
+        );
+        assert_eq!(
+            try_decode(encode([1; 11]) + "0"),
+            Err(DecodeError::UnexpectedTail { char_index: 8 })
+        );
+    }
+
+    #[test]
+    fn try_decode_strict_rejects_loose_padding() {
+        for len in 0..40 {
+            let bytes = (0..len).map(|i| (i * 37) as u8).collect::<Vec<_>>();
+            let mut chars = encode(&bytes).chars().collect::<Vec<_>>();
+            assert_eq!(
+                try_decode_strict(chars.iter().collect::<String>()),
+                Ok(bytes.clone())
+            );
+            // the lowest bit of a final table character is padding unless it ends on a byte
+            let last = match chars.last().copied().and_then(Symbol::from_char) {
+                Some(Symbol::Table(_)) if chars.len() * 11 % 8 != 0 => chars.len() - 1,
+                _ => continue,
+            };
+            chars[last] = ENC_TABLE[tables::table_index(chars[last] as u32).unwrap() as usize - 1];
+            let loose = chars.iter().collect::<String>();
+            assert_eq!(try_decode(&loose), Ok(bytes));
+            assert_eq!(
+                try_decode_strict(&loose),
+                Err(DecodeError::InvalidPadding { char_index: last })
+            );
+        }
+        // earlier errors come first
+        assert_eq!(
+            try_decode_strict("ե!Ԉ"),
+            Err(DecodeError::InvalidChar {
+                c: '!',
+                char_index: 1
+            })
+        );
+    }
+
+    #[test]
+    fn decode_into_appends() {
+        let mut out = vec![1];
+        for len in 0..40 {
+            let bytes = (0..len).map(|i| (i * 37) as u8).collect::<Vec<_>>();
+            out.truncate(1);
+            assert_eq!(decode_into(&encode(&bytes), &mut out), Ok(len));
+            assert_eq!(out[1..], bytes[..]);
+        }
+        assert!(decode_into("ետћζы༎X", &mut out).is_err());
+        assert_eq!(out.len(), 40);
+    }
+
+    #[test]
+    fn encode_to_fmt_stops_at_errors() {
+        struct Limited(usize);
+
+        impl core::fmt::Write for Limited {
+            fn write_str(&mut self, s: &str) -> core::fmt::Result {
+                self.0 = self
+                    .0
+                    .checked_sub(s.chars().count())
+                    .ok_or(core::fmt::Error)?;
+                Ok(())
+            }
+        }
+
+        let mut out = String::new();
+        encode_to_fmt(b"abc", &mut out).unwrap();
+        assert_eq!(out, encode(b"abc"));
+        assert!(encode_to_fmt(b"abc", &mut Limited(3)).is_ok());
+        assert!(encode_to_fmt(b"abc", &mut Limited(2)).is_err());
+    }
+
+    #[test]
+    fn encode_into_appends() {
+        let mut out = String::from(">");
+        encode_into(b"abc", &mut out);
+        assert_eq!(out, format!(">{}", encode(b"abc")));
+        for len in 0..40 {
+            let bytes = (0..len).map(|i| (i * 37) as u8).collect::<Vec<_>>();
+            out.clear();
+            encode_into(&bytes, &mut out);
+            assert_eq!(out, encode(&bytes));
+        }
+    }
+
     #[test]
     fn test_all_characters() {
         for i in 0..=u16::MAX {
@@ -0,0 +1,224 @@
+//! Kotlin and Swift bindings (the `uniffi` feature), generated by UniFFI from the library the
+//! `mobile` directory builds.
+//!
+//! Byte arrays cross as `ByteArray`/`Data`, text as `String`, and positions as `ULong`/`UInt64`.
+//! The streaming types are shared objects whose methods take `&self`, as UniFFI requires, so their
+//! state is behind a mutex.
+use crate::DecodeError as CoreDecodeError;
+use alloc::{string::String, sync::Arc, vec::Vec};
+use std::sync::Mutex;
+
+/// Why text couldn't be decoded, thrown as `DecodeException` in Kotlin and `DecodeError` in
+/// Swift.
+#[derive(Clone, Debug, PartialEq, Eq, uniffi::Error)]
+pub enum DecodeError {
+    /// The character is neither in the encoding table nor a tail character.
+    InvalidChar {
+        /// The offending character.
+        c: String,
+        /// Its position, counted in characters.
+        char_index: u64,
+    },
+    /// A tail character was followed by more characters.
+    TrailingData {
+        /// The position of the tail character, counted in characters.
+        char_index: u64,
+    },
+    /// The text ends in a tail character but the characters before it leave no room for one.
+    UnexpectedTail {
+        /// The position of the tail character, counted in characters.
+        char_index: u64,
+    },
+    /// The padding bits of the final character aren't all set.
+    InvalidPadding {
+        /// The position of the final character, counted in characters.
+        char_index: u64,
+    },
+}
+
+impl From<CoreDecodeError> for DecodeError {
+    fn from(e: CoreDecodeError) -> Self {
+        match e {
+            CoreDecodeError::InvalidChar { c, char_index } => DecodeError::InvalidChar {
+                c: c.into(),
+                char_index: char_index as u64,
+            },
+            CoreDecodeError::TrailingData { char_index } => DecodeError::TrailingData {
+                char_index: char_index as u64,
+            },
+            CoreDecodeError::UnexpectedTail { char_index } => DecodeError::UnexpectedTail {
+                char_index: char_index as u64,
+            },
+            CoreDecodeError::InvalidPadding { char_index } => DecodeError::InvalidPadding {
+                char_index: char_index as u64,
+            },
+        }
+    }
+}
+
+impl core::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let e = match self {
+            DecodeError::InvalidChar { c, char_index } => CoreDecodeError::InvalidChar {
+                c: c.chars().next().unwrap_or(char::REPLACEMENT_CHARACTER),
+                char_index: *char_index as usize,
+            },
+            DecodeError::TrailingData { char_index } => CoreDecodeError::TrailingData {
+                char_index: *char_index as usize,
+            },
+            DecodeError::UnexpectedTail { char_index } => CoreDecodeError::UnexpectedTail {
+                char_index: *char_index as usize,
+            },
+            DecodeError::InvalidPadding { char_index } => CoreDecodeError::InvalidPadding {
+                char_index: *char_index as usize,
+            },
+        };
+        e.fmt(f)
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// Encodes bytes as base2048 text.
+#[uniffi::export]
+pub fn encode(bytes: Vec<u8>) -> String {
+    crate::encode(bytes)
+}
+
+/// Decodes base2048 text.
+#[uniffi::export]
+pub fn decode(text: String) -> Result<Vec<u8>, DecodeError> {
+    Ok(crate::try_decode(text)?)
+}
+
+/// Encodes bytes that arrive in chunks, as [`crate::Encoder`] does.
+#[derive(Debug, Default, uniffi::Object)]
+pub struct StreamEncoder {
+    inner: Mutex<crate::Encoder>,
+}
+
+#[uniffi::export]
+impl StreamEncoder {
+    /// An encoder that hasn't been given anything yet.
+    #[uniffi::constructor]
+    pub fn new() -> Arc<Self> {
+        Arc::default()
+    }
+
+    /// Encodes `bytes`, returning the text they complete.
+    pub fn push(&self, bytes: Vec<u8>) -> String {
+        let mut text = String::new();
+        self.lock().push(&bytes, &mut text);
+        text
+    }
+
+    /// Returns the final character, if the input so far needs one, and resets the encoder so it can
+    /// start another encoding.
+    pub fn finish(&self) -> String {
+        let mut text = String::new();
+        core::mem::take(&mut *self.lock()).finish(&mut text);
+        text
+    }
+}
+
+impl StreamEncoder {
+    fn lock(&self) -> std::sync::MutexGuard<'_, crate::Encoder> {
+        // the encoder is never left half updated, so a panic elsewhere doesn't matter
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Decodes text that arrives in chunks, as [`crate::Decoder`] does.
+#[derive(Debug, Default, uniffi::Object)]
+pub struct StreamDecoder {
+    inner: Mutex<crate::Decoder>,
+}
+
+#[uniffi::export]
+impl StreamDecoder {
+    /// A decoder that hasn't been given anything yet.
+    #[uniffi::constructor]
+    pub fn new() -> Arc<Self> {
+        Arc::default()
+    }
+
+    /// Decodes `text`, returning the bytes it completes.
+    ///
+    /// Once an error has been thrown, every later call throws it again.
+    pub fn push(&self, text: String) -> Result<Vec<u8>, DecodeError> {
+        let mut bytes = Vec::new();
+        self.lock().push(&text, &mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// Decodes the character held back, returning the last bytes, and resets the decoder so it can
+    /// start another decoding.
+    pub fn finish(&self) -> Result<Vec<u8>, DecodeError> {
+        let mut bytes = Vec::new();
+        core::mem::take(&mut *self.lock()).finish(&mut bytes)?;
+        Ok(bytes)
+    }
+}
+
+impl StreamDecoder {
+    fn lock(&self) -> std::sync::MutexGuard<'_, crate::Decoder> {
+        // as for `StreamEncoder::lock`
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let bytes = (0..100).map(|i| (i * 37) as u8).collect::<Vec<_>>();
+        let encoded = encode(bytes.clone());
+        assert_eq!(encoded, crate::encode(&bytes));
+        assert_eq!(decode(encoded.clone()), Ok(bytes.clone()));
+
+        let encoder = StreamEncoder::new();
+        let mut text = String::new();
+        for chunk in bytes.chunks(7) {
+            text += &encoder.push(chunk.to_vec());
+        }
+        text += &encoder.finish();
+        assert_eq!(text, encoded);
+
+        let decoder = StreamDecoder::new();
+        let mut decoded = Vec::new();
+        for chunk in encoded.chars().collect::<Vec<_>>().chunks(3) {
+            decoded.extend(decoder.push(chunk.iter().collect()).unwrap());
+        }
+        decoded.extend(decoder.finish().unwrap());
+        assert_eq!(decoded, bytes);
+    }
+
+    #[test]
+    fn errors() {
+        let e = decode("ետћζы༎X".into()).unwrap_err();
+        assert_eq!(
+            e,
+            DecodeError::InvalidChar {
+                c: "༎".into(),
+                char_index: 5
+            }
+        );
+        assert_eq!(
+            alloc::string::ToString::to_string(&e),
+            alloc::string::ToString::to_string(&CoreDecodeError::InvalidChar {
+                c: '༎',
+                char_index: 5
+            })
+        );
+
+        let decoder = StreamDecoder::new();
+        assert!(decoder.push("ետћζы༎X".into()).is_err());
+        assert!(decoder.push("X".into()).is_err());
+        // finishing resets it
+        assert!(decoder.finish().is_err());
+        assert_eq!(decoder.push(crate::encode(b"hi")).unwrap(), b"h");
+        assert_eq!(decoder.finish().unwrap(), b"i");
+    }
+}
@@ -0,0 +1,200 @@
+//! Self-describing text in the style of [multibase](https://github.com/multiformats/multibase).
+use crate::{encode_into, try_decode, DecodeError};
+use alloc::{string::String, vec::Vec};
+use core::fmt;
+
+/// The character [`encode_multibase`] puts in front of base2048 text.
+///
+/// Multibase has no registered code for base2048, so this is the one this crate agrees on. It is
+/// in none of the encoding tables and has no code in the multibase table.
+pub const MULTIBASE_PREFIX: char = '§';
+
+/// The encodings in the multibase table, by prefix.
+const MULTIBASE_TABLE: [(char, &str); 25] = [
+    ('\0', "identity"),
+    ('0', "base2"),
+    ('7', "base8"),
+    ('9', "base10"),
+    ('f', "base16"),
+    ('F', "base16upper"),
+    ('v', "base32hex"),
+    ('V', "base32hexupper"),
+    ('t', "base32hexpad"),
+    ('T', "base32hexpadupper"),
+    ('b', "base32"),
+    ('B', "base32upper"),
+    ('c', "base32pad"),
+    ('C', "base32padupper"),
+    ('h', "base32z"),
+    ('k', "base36"),
+    ('K', "base36upper"),
+    ('z', "base58btc"),
+    ('Z', "base58flickr"),
+    ('m', "base64"),
+    ('M', "base64pad"),
+    ('u', "base64url"),
+    ('U', "base64urlpad"),
+    ('p', "proquint"),
+    ('🚀', "base256emoji"),
+];
+
+/// Why a string couldn't be decoded by [`decode_multibase`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MultibaseError {
+    /// The string is empty, so there is no prefix.
+    Empty,
+    /// The prefix is a multibase code, but for an encoding other than base2048.
+    Unsupported {
+        /// The prefix.
+        prefix: char,
+        /// The encoding's name in the multibase table, such as `"base64"`.
+        name: &'static str,
+    },
+    /// The prefix is neither [`MULTIBASE_PREFIX`] nor a multibase code.
+    UnknownPrefix {
+        /// The prefix.
+        prefix: char,
+    },
+    /// The text after the prefix isn't valid base2048. Positions count the prefix.
+    Decode(DecodeError),
+}
+
+#[cfg(not(feature = "compact-errors"))]
+impl fmt::Display for MultibaseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MultibaseError::Empty => f.write_str("the string has no multibase prefix"),
+            MultibaseError::Unsupported { prefix, name } => write!(
+                f,
+                "multibase prefix {:?} is for {}, not base2048",
+                prefix, name
+            ),
+            MultibaseError::UnknownPrefix { prefix } => {
+                write!(f, "{:?} is not a multibase prefix", prefix)
+            }
+            MultibaseError::Decode(e) => e.fmt(f),
+        }
+    }
+}
+
+#[cfg(feature = "compact-errors")]
+impl fmt::Display for MultibaseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MultibaseError::Empty => f.write_str("base2048 multibase error 1: empty"),
+            MultibaseError::Unsupported { .. } => {
+                f.write_str("base2048 multibase error 2: unsupported encoding")
+            }
+            MultibaseError::UnknownPrefix { .. } => {
+                f.write_str("base2048 multibase error 3: unknown prefix")
+            }
+            MultibaseError::Decode(e) => e.fmt(f),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for MultibaseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            MultibaseError::Decode(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Encode some bytes using base2048 encoding, with [`MULTIBASE_PREFIX`] in front
+///
+/// # Example
+/// ```
+/// let encoded = base2048::encode_multibase(b"hello");
+/// assert_eq!(encoded, format!("§{}", base2048::encode(b"hello")));
+/// ```
+pub fn encode_multibase(bytes: impl AsRef<[u8]>) -> String {
+    let mut ret = String::from(MULTIBASE_PREFIX);
+    encode_into(bytes.as_ref(), &mut ret);
+    ret
+}
+
+/// Decode a string made by [`encode_multibase`], going by its prefix
+///
+/// A prefix for another multibase encoding gives [`MultibaseError::Unsupported`] with the
+/// encoding's name, so the caller can hand the string to a decoder for it.
+///
+/// # Example
+/// ```
+/// use base2048::{decode_multibase, encode_multibase, MultibaseError};
+/// assert_eq!(decode_multibase(encode_multibase(b"hello")), Ok(b"hello".to_vec()));
+/// assert_eq!(
+///     decode_multibase("maGVsbG8"),
+///     Err(MultibaseError::Unsupported { prefix: 'm', name: "base64" })
+/// );
+/// ```
+pub fn decode_multibase(string: impl AsRef<str>) -> Result<Vec<u8>, MultibaseError> {
+    let string = string.as_ref();
+    let prefix = string.chars().next().ok_or(MultibaseError::Empty)?;
+    if prefix != MULTIBASE_PREFIX {
+        return Err(match MULTIBASE_TABLE.iter().find(|(p, _)| *p == prefix) {
+            Some(&(prefix, name)) => MultibaseError::Unsupported { prefix, name },
+            None => MultibaseError::UnknownPrefix { prefix },
+        });
+    }
+    try_decode(&string[prefix.len_utf8()..])
+        .map_err(|e| MultibaseError::Decode(e.with_char_index(e.char_index() + 1)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{encode, tables::table_index, TAIL};
+
+    #[test]
+    fn round_trips() {
+        for len in 0..30 {
+            let bytes = (0..len).map(|i| (i * 37) as u8).collect::<Vec<_>>();
+            let encoded = encode_multibase(&bytes);
+            assert_eq!(
+                encoded.strip_prefix(MULTIBASE_PREFIX),
+                Some(&*encode(&bytes))
+            );
+            assert_eq!(decode_multibase(&encoded), Ok(bytes));
+        }
+    }
+
+    #[test]
+    fn prefix() {
+        assert_eq!(table_index(MULTIBASE_PREFIX as u32), None);
+        assert!(!TAIL.contains(&MULTIBASE_PREFIX));
+        assert!(MULTIBASE_TABLE.iter().all(|(p, _)| *p != MULTIBASE_PREFIX));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(decode_multibase(""), Err(MultibaseError::Empty));
+        assert_eq!(
+            decode_multibase("zQmfoo"),
+            Err(MultibaseError::Unsupported {
+                prefix: 'z',
+                name: "base58btc"
+            })
+        );
+        assert_eq!(
+            decode_multibase("🚀"),
+            Err(MultibaseError::Unsupported {
+                prefix: '🚀',
+                name: "base256emoji"
+            })
+        );
+        assert_eq!(
+            decode_multibase(encode(b"hello")),
+            Err(MultibaseError::UnknownPrefix { prefix: 'ڵ' })
+        );
+        assert_eq!(
+            decode_multibase("§ետћζы༎X"),
+            Err(MultibaseError::Decode(DecodeError::InvalidChar {
+                c: '༎',
+                char_index: 6
+            }))
+        );
+    }
+}
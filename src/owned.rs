@@ -0,0 +1,123 @@
+use crate::{diagnostics, DecodeError, DecodeState};
+use alloc::{string::String, vec::Vec};
+
+/// Decode a base2048 encoded string, reusing its allocation for the output
+///
+/// The decoded bytes are written over the text as it is read, so large messages are decoded
+/// without a second allocation. Only long runs of ascii characters, which take less room than
+/// they decode to, make the text move along first and perhaps grow its allocation.
+///
+/// # Example
+/// ```
+/// let encoded = base2048::encode(b"some utf8 bytes to encode more compactly");
+/// assert_eq!(
+///     base2048::decode_owned(encoded),
+///     Ok(b"some utf8 bytes to encode more compactly".to_vec())
+/// );
+/// ```
+pub fn decode_owned(string: String) -> Result<Vec<u8>, DecodeError> {
+    // Each character yields at most 11 bits and all but the ascii ones take up at least 16 bits of
+    // UTF-8, so the output can only catch up with the input where ascii characters bunch together.
+    // The input is moved along by the most the output would otherwise overtake it, which for most
+    // text is nothing.
+    let mut text_len = 0;
+    let head_start = string
+        .chars()
+        .enumerate()
+        .map(|(i, c)| {
+            text_len += c.len_utf8();
+            ((i + 1) * 11 / 8).saturating_sub(text_len)
+        })
+        .max()
+        .unwrap_or(0);
+    let mut buf = string.into_bytes();
+    if head_start > 0 {
+        let len = buf.len();
+        buf.resize(len + head_start, 0);
+        buf.copy_within(..len, head_start);
+    }
+
+    let next_char = |buf: &[u8], read: usize| {
+        // SAFETY: `buf[head_start..]` was a `String` and only bytes before `read` have been
+        // overwritten since. `read` always falls on a character boundary of that string.
+        unsafe { core::str::from_utf8_unchecked(&buf[read..]) }
+            .chars()
+            .next()
+    };
+
+    let mut state = DecodeState::default();
+    let mut read = head_start;
+    let mut written = 0;
+    let mut next = next_char(&buf, read);
+    while let Some(c) = next {
+        read += c.len_utf8();
+        next = next_char(&buf, read);
+        state
+            .push(c, next.is_none(), |byte| {
+                buf[written] = byte;
+                written += 1;
+            })
+            .map_err(diagnostics::decode_error)?;
+        debug_assert!(written <= read);
+    }
+    state.finish_checked(|byte| {
+        buf[written] = byte;
+        written += 1;
+    });
+
+    buf.truncate(written);
+    Ok(buf)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{decode, encode, ENC_TABLE};
+    use alloc::string::ToString;
+
+    #[test]
+    fn matches_decode() {
+        for len in 0..64 {
+            let bytes = (0..len).map(|i| (i * 71 + 5) as u8).collect::<Vec<_>>();
+            let encoded = encode(&bytes);
+            assert_eq!(decode_owned(encoded.clone()).ok(), decode(&encoded));
+        }
+    }
+
+    #[test]
+    fn reuses_allocation() {
+        let bytes = (0..1000).map(|i| (i * 71 + 5) as u8).collect::<Vec<_>>();
+        let encoded = encode(&bytes);
+        let (ptr, capacity) = (encoded.as_ptr(), encoded.capacity());
+        let decoded = decode_owned(encoded).unwrap();
+        assert_eq!(decoded, bytes);
+        assert_eq!((decoded.as_ptr(), decoded.capacity()), (ptr, capacity));
+    }
+
+    #[test]
+    fn all_ascii_input() {
+        let ascii = ENC_TABLE
+            .iter()
+            .filter(|c| c.is_ascii())
+            .cycle()
+            .take(300)
+            .collect::<String>();
+        assert_eq!(decode_owned(ascii.clone()).ok(), decode(&ascii));
+        let ending_in_tail = ascii + "7";
+        assert_eq!(
+            decode_owned(ending_in_tail.clone()).ok(),
+            decode(&ending_in_tail)
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            decode_owned("88€8".to_string()),
+            Err(DecodeError::InvalidChar {
+                c: '€',
+                char_index: 2
+            })
+        );
+    }
+}
@@ -0,0 +1,221 @@
+//! Decode errors as [`miette`] diagnostics (the `miette` feature).
+use crate::{decode_with, Alphabet, DecodeError, Symbol};
+use alloc::{
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt;
+use miette::{Diagnostic, LabeledSpan, SourceCode};
+use unicode_normalization::UnicodeNormalization;
+
+/// How many characters to show on either side of the offending one.
+const CONTEXT: usize = 30;
+
+/// A [`DecodeError`] together with the text around it, for showing to people.
+///
+/// As a [`Diagnostic`], a `miette` report shows the offending character with a caret under it and
+/// a hint at what went wrong, such as a letter from another script that looks like one in the
+/// table. Only the characters near the error are kept, so a huge input makes a short report.
+///
+/// # Example
+/// ```
+/// use miette::Diagnostic;
+/// let error = base2048::decode_diagnostic("ետţζы").unwrap_err();
+/// assert_eq!(error.error().char_index(), 2);
+/// assert_eq!(error.help().unwrap().to_string(), "this looks like a confusable for 't'");
+/// ```
+#[derive(Clone, Debug)]
+pub struct DecodeDiagnostic {
+    error: DecodeError,
+    snippet: String,
+    /// The byte offset and length of the offending character in `snippet`.
+    span: (usize, usize),
+    help: String,
+}
+
+impl DecodeDiagnostic {
+    /// Makes a diagnostic for `error`, which `source` failed to decode with the standard
+    /// alphabet.
+    pub fn new(source: &str, error: DecodeError) -> Self {
+        DecodeDiagnostic::with_alphabet(source, error, &Alphabet::standard())
+    }
+
+    /// Like [`new`](Self::new) for text encoded with `alphabet`.
+    pub fn with_alphabet(source: &str, error: DecodeError, alphabet: &Alphabet) -> Self {
+        DecodeDiagnostic::with_offset(source, 0, error, alphabet)
+    }
+
+    /// Like [`with_alphabet`](Self::with_alphabet) for a piece of a longer text that starts
+    /// `offset` characters in, as when decoding a stream. `error` counts from the start of
+    /// `source`; the diagnostic counts from the start of the whole text.
+    pub fn with_offset(
+        source: &str,
+        offset: usize,
+        error: DecodeError,
+        alphabet: &Alphabet,
+    ) -> Self {
+        let char_index = error.char_index();
+        let start = char_index.saturating_sub(CONTEXT);
+        let mut snippet = String::new();
+        if offset + start > 0 {
+            snippet.push('…');
+        }
+        let mut span = (0, 0);
+        let mut chars = source.chars().skip(start);
+        for (i, c) in chars.by_ref().take(2 * CONTEXT + 1).enumerate() {
+            if start + i == char_index {
+                span = (snippet.len(), c.len_utf8());
+            }
+            snippet.push(c);
+        }
+        if chars.next().is_some() {
+            snippet.push('…');
+        }
+
+        DecodeDiagnostic {
+            error: error.with_char_index(offset + char_index),
+            snippet,
+            span,
+            help: help(error, alphabet),
+        }
+    }
+
+    /// The underlying error.
+    pub fn error(&self) -> DecodeError {
+        self.error
+    }
+}
+
+fn help(error: DecodeError, alphabet: &Alphabet) -> String {
+    let tail = alphabet.tail();
+    match error {
+        DecodeError::InvalidChar { c, .. } => {
+            let in_table = |c: char| matches!(alphabet.symbol(c), Some(Symbol::Table(_)));
+            // the table leaves out letters with accents and compatibility forms such as
+            // fullwidth letters, but usually has the letter they are built on
+            let lookalike = c
+                .to_string()
+                .nfkd()
+                .take(1)
+                .chain(c.to_lowercase().chain(c.to_uppercase()))
+                .find(|other| *other != c && in_table(*other));
+            if let Some(other) = lookalike {
+                format!("this looks like a confusable for '{}'", other)
+            } else if c.is_whitespace() || crate::warnings::ZERO_WIDTH.contains(&c) {
+                "remove spaces, line breaks and invisible characters before decoding".to_string()
+            } else {
+                "this is not a base2048 character".to_string()
+            }
+        }
+        DecodeError::TrailingData { .. } => format!(
+            "'{}' to '{}' may only end the text; was something added after it?",
+            tail[0], tail[7]
+        ),
+        DecodeError::UnexpectedTail { .. } | DecodeError::InvalidPadding { .. } => {
+            "the text may have been cut short or its last character changed".to_string()
+        }
+    }
+}
+
+impl fmt::Display for DecodeDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.error.fmt(f)
+    }
+}
+
+impl std::error::Error for DecodeDiagnostic {}
+
+impl Diagnostic for DecodeDiagnostic {
+    fn code<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        Some(Box::new(match self.error {
+            DecodeError::InvalidChar { .. } => "base2048::invalid_char",
+            DecodeError::TrailingData { .. } => "base2048::trailing_data",
+            DecodeError::UnexpectedTail { .. } => "base2048::unexpected_tail",
+            DecodeError::InvalidPadding { .. } => "base2048::invalid_padding",
+        }))
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        Some(Box::new(&self.help))
+    }
+
+    fn source_code(&self) -> Option<&dyn SourceCode> {
+        Some(&self.snippet)
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        let label = match self.error {
+            DecodeError::InvalidChar { .. } => "not in the alphabet",
+            DecodeError::TrailingData { .. } => "tail character before the end",
+            DecodeError::UnexpectedTail { .. } => "no tail character fits here",
+            DecodeError::InvalidPadding { .. } => "padding bits aren't all set",
+        };
+        let span = LabeledSpan::new(Some(label.to_string()), self.span.0, self.span.1);
+        Some(Box::new(core::iter::once(span)))
+    }
+}
+
+/// Decode a base2048 encoded string, with errors ready to show to people
+///
+/// See [`DecodeDiagnostic`].
+pub fn decode_diagnostic(string: &str) -> Result<Vec<u8>, DecodeDiagnostic> {
+    let mut ret = Vec::new();
+    decode_with(string, |byte| ret.push(byte)).map_err(|e| DecodeDiagnostic::new(string, e))?;
+    Ok(ret)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::encode;
+
+    fn help_for(string: &str) -> String {
+        decode_diagnostic(string).unwrap_err().help
+    }
+
+    #[test]
+    fn snippet() {
+        let encoded = encode([7; 200]);
+        let mut broken = encoded.chars().collect::<Vec<_>>();
+        broken[100] = '€';
+        let broken = broken.into_iter().collect::<String>();
+        let e = decode_diagnostic(&broken).unwrap_err();
+        assert_eq!(e.snippet.chars().count(), 2 * CONTEXT + 3);
+        assert!(e.snippet.starts_with('…') && e.snippet.ends_with('…'));
+        assert_eq!(&e.snippet[e.span.0..e.span.0 + e.span.1], "€");
+
+        let e = decode_diagnostic("€").unwrap_err();
+        assert_eq!((e.snippet.as_str(), e.span), ("€", (0, 3)));
+
+        let error = crate::try_decode("€").unwrap_err();
+        let e = DecodeDiagnostic::with_offset("€", 1000, error, &Alphabet::standard());
+        assert_eq!(e.error().char_index(), 1000);
+        assert_eq!((e.snippet.as_str(), e.span), ("…€", ("…".len(), 3)));
+    }
+
+    #[test]
+    fn hints() {
+        for (bad, good) in [('é', 'e'), ('ţ', 't'), ('й', 'и'), ('Ａ', 'A')] {
+            assert_eq!(
+                help_for(&bad.to_string()),
+                format!("this looks like a confusable for '{}'", good)
+            );
+        }
+        assert!(help_for("a b").starts_with("remove spaces"));
+        assert!(help_for("\u{200B}").starts_with("remove spaces"));
+        assert!(help_for("0a").contains("'0' to '7'"));
+        assert_eq!(help_for("€"), "this is not a base2048 character");
+    }
+
+    #[test]
+    fn diagnostic() {
+        let e = decode_diagnostic("€").unwrap_err();
+        assert_eq!(e.code().unwrap().to_string(), "base2048::invalid_char");
+        assert_eq!(e.to_string(), e.error().to_string());
+        let labels = e.labels().unwrap().collect::<Vec<_>>();
+        assert_eq!(labels.len(), 1);
+        assert_eq!((labels[0].offset(), labels[0].len()), (0, 3));
+    }
+}
@@ -0,0 +1,132 @@
+//! Encoding and decoding that report how far along they are, for progress bars on large inputs.
+use crate::{diagnostics, DecodeError, DecodeState, EncodeState, Symbol};
+use alloc::{string::String, vec::Vec};
+
+/// How much input is processed between progress reports, in bytes.
+const INTERVAL: usize = 64 * 1024;
+
+/// Encode some bytes using base2048 encoding, reporting progress along the way
+///
+/// `progress` is called with the number of input bytes encoded so far: every 64KiB and once at
+/// the end.
+///
+/// # Example
+/// ```
+/// let bytes = vec![0u8; 200_000];
+/// let mut reports = vec![];
+/// let encoded = base2048::encode_with_progress(&bytes, |done| reports.push(done));
+/// assert_eq!(encoded, base2048::encode(&bytes));
+/// assert_eq!(reports, [65536, 131072, 196608, 200_000]);
+/// ```
+pub fn encode_with_progress(bytes: &[u8], progress: impl FnMut(usize)) -> String {
+    encode(bytes, Symbol::to_char, progress)
+}
+
+/// Decode a base2048 encoded string, reporting progress along the way
+///
+/// `progress` is called with the number of bytes of `string` decoded so far: about every 64KiB and
+/// once at the end.
+pub fn decode_with_progress(
+    string: &str,
+    progress: impl FnMut(usize),
+) -> Result<Vec<u8>, DecodeError> {
+    decode(string, Symbol::from_char, progress)
+}
+
+pub(crate) fn encode(
+    bytes: &[u8],
+    char_of: impl Fn(Symbol) -> char,
+    progress: impl FnMut(usize),
+) -> String {
+    let mut ret = String::new();
+    encode_with(bytes, char_of, progress, |c| ret.push(c));
+    ret
+}
+
+/// Like [`encode`] but hands each character to `push`.
+pub(crate) fn encode_with(
+    bytes: &[u8],
+    char_of: impl Fn(Symbol) -> char,
+    mut progress: impl FnMut(usize),
+    mut push: impl FnMut(char),
+) {
+    let mut state = EncodeState::default();
+    let mut done = 0;
+    for chunk in bytes.chunks(INTERVAL) {
+        state.push_slice(chunk, |index| push(char_of(Symbol::Table(index))));
+        done += chunk.len();
+        progress(done);
+    }
+    if let Some(symbol) = state.finish_symbol() {
+        push(char_of(symbol));
+    }
+    if bytes.is_empty() {
+        progress(0);
+    }
+}
+
+pub(crate) fn decode(
+    string: &str,
+    symbol_of: impl Fn(char) -> Option<Symbol>,
+    progress: impl FnMut(usize),
+) -> Result<Vec<u8>, DecodeError> {
+    let mut ret = vec![];
+    decode_with(string, symbol_of, progress, |byte| ret.push(byte))?;
+    Ok(ret)
+}
+
+/// Like [`decode`] but hands each byte to `push`.
+pub(crate) fn decode_with(
+    string: &str,
+    symbol_of: impl Fn(char) -> Option<Symbol>,
+    mut progress: impl FnMut(usize),
+    mut push: impl FnMut(u8),
+) -> Result<(), DecodeError> {
+    let mut state = DecodeState::default();
+    let mut chars = string.char_indices().peekable();
+    let mut next_report = INTERVAL;
+    while let Some((offset, c)) = chars.next() {
+        let last = chars.peek().is_none();
+        symbol_of(c)
+            .ok_or(DecodeError::InvalidChar {
+                c,
+                char_index: state.chars,
+            })
+            .and_then(|symbol| state.push_symbol(symbol, last, &mut push))
+            .map_err(diagnostics::decode_error)?;
+        if offset >= next_report {
+            progress(offset);
+            next_report += INTERVAL;
+        }
+    }
+    state.finish_checked(push);
+    progress(string.len());
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{decode, encode};
+
+    #[test]
+    fn same_results() {
+        let bytes = (0..300_000u32).map(|i| (i % 253) as u8).collect::<Vec<_>>();
+        let mut last = 0;
+        let encoded = encode_with_progress(&bytes, |done| {
+            assert!(done > last);
+            last = done;
+        });
+        assert_eq!(last, bytes.len());
+        assert_eq!(encoded, encode(&bytes));
+
+        let mut reports = 0;
+        let decoded = decode_with_progress(&encoded, |done| {
+            reports += 1;
+            last = done
+        });
+        assert_eq!(decoded.ok(), decode(&encoded));
+        assert_eq!(last, encoded.len());
+        assert!(reports > encoded.len() / INTERVAL);
+    }
+}
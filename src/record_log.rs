@@ -0,0 +1,321 @@
+//! An append-only log of records, one line of base2048 per record (the `std` feature).
+//!
+//! Each line encodes the record's length and a CRC-32 of it ahead of the data, so a reader can tell
+//! a good record from a damaged one. Lines are written with a single `write_all`, so a crash can
+//! only leave the last line half written; the reader leaves such a line alone until it is
+//! finished, which also makes it safe to follow a log another process is still appending to.
+//!
+//! ```
+//! use base2048::record_log::{ReadError, RecordReader, RecordWriter};
+//!
+//! let mut writer = RecordWriter::new(Vec::new());
+//! writer.append(b"first").unwrap();
+//! writer.append(b"second").unwrap();
+//! let mut log = writer.into_inner();
+//! log.splice(3..5, *b"??"); // damage the first line
+//!
+//! let mut reader = RecordReader::new(&log[..]);
+//! assert!(matches!(reader.next(), Some(Err(ReadError::Corrupt { offset: 0 }))));
+//! let second = reader.next().unwrap().unwrap();
+//! assert_eq!(second.data, b"second");
+//! assert!(reader.next().is_none());
+//! assert_eq!(reader.position(), log.len() as u64);
+//! ```
+use crate::{crc32::Crc32, decode_with, encode_with};
+use alloc::{string::String, vec::Vec};
+use core::{convert::TryFrom, fmt};
+use std::io::{self, BufRead, Write};
+
+/// The record length and checksum at the start of each line, both big-endian `u32`s.
+const HEADER_LEN: usize = 8;
+
+/// Appends records to a log.
+#[derive(Debug)]
+pub struct RecordWriter<W: Write> {
+    inner: W,
+    line: String,
+    bytes: Vec<u8>,
+}
+
+impl<W: Write> RecordWriter<W> {
+    /// Writes records to `inner`, which should be positioned at the end of the log.
+    pub fn new(inner: W) -> Self {
+        RecordWriter {
+            inner,
+            line: String::new(),
+            bytes: Vec::new(),
+        }
+    }
+
+    /// Appends one record.
+    ///
+    /// Fails with [`io::ErrorKind::InvalidInput`] if `data` is 4GiB or more.
+    pub fn append(&mut self, data: &[u8]) -> io::Result<()> {
+        let len = u32::try_from(data.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "record too long"))?;
+        self.bytes.clear();
+        self.bytes.extend_from_slice(&len.to_be_bytes());
+        self.bytes
+            .extend_from_slice(&checksum(len, data).to_be_bytes());
+        self.bytes.extend_from_slice(data);
+        let line = &mut self.line;
+        line.clear();
+        encode_with(&self.bytes, |c| line.push(c));
+        line.push('\n');
+        self.inner.write_all(self.line.as_bytes())
+    }
+
+    /// Flushes the underlying writer.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+
+    /// The underlying writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Gives back the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+/// A record read from a log.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Record {
+    /// Where its line starts, in bytes from the start of the log.
+    pub offset: u64,
+    /// The record itself.
+    pub data: Vec<u8>,
+}
+
+/// Why a record couldn't be read.
+#[derive(Debug)]
+pub enum ReadError {
+    /// Reading the log failed.
+    Io(io::Error),
+    /// The line isn't a valid record. Reading can go on with the next line.
+    Corrupt {
+        /// Where the line starts, in bytes from the start of the log.
+        offset: u64,
+    },
+}
+
+#[cfg(not(feature = "compact-errors"))]
+impl fmt::Display for ReadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReadError::Io(e) => e.fmt(f),
+            ReadError::Corrupt { offset } => write!(f, "corrupt record at byte {}", offset),
+        }
+    }
+}
+
+#[cfg(feature = "compact-errors")]
+impl fmt::Display for ReadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReadError::Io(e) => e.fmt(f),
+            ReadError::Corrupt { .. } => f.write_str("base2048 record error 1: corrupt record"),
+        }
+    }
+}
+
+impl std::error::Error for ReadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ReadError::Io(e) => Some(e),
+            ReadError::Corrupt { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for ReadError {
+    fn from(e: io::Error) -> Self {
+        ReadError::Io(e)
+    }
+}
+
+/// Reads the records of a log, as an iterator.
+///
+/// A damaged line comes out as [`ReadError::Corrupt`] and the next call carries on after it, so
+/// recovering what is left of a damaged log is a matter of skipping those. The iterator ends at the
+/// end of the input, but a later call picks up anything appended since.
+#[derive(Debug)]
+pub struct RecordReader<R: BufRead> {
+    inner: R,
+    offset: u64,
+    line: Vec<u8>,
+}
+
+impl<R: BufRead> RecordReader<R> {
+    /// Reads a log from the start.
+    pub fn new(inner: R) -> Self {
+        RecordReader::resume(inner, 0)
+    }
+
+    /// Reads a log from `offset`, a [`position`](Self::position) saved earlier. `inner` must
+    /// already be at that offset.
+    pub fn resume(inner: R, offset: u64) -> Self {
+        RecordReader {
+            inner,
+            offset,
+            line: Vec::new(),
+        }
+    }
+
+    /// The offset just past the last complete line read, where a later reader can
+    /// [`resume`](Self::resume).
+    pub fn position(&self) -> u64 {
+        self.offset
+    }
+
+    /// The underlying reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// The underlying reader.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Gives back the underlying reader.
+    ///
+    /// Any half-written line read so far is lost.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: BufRead> Iterator for RecordReader<R> {
+    type Item = Result<Record, ReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // a half-written line stays in `self.line` and the next call reads on from there
+        if let Err(e) = self.inner.read_until(b'\n', &mut self.line) {
+            return Some(Err(e.into()));
+        }
+        if self.line.last() != Some(&b'\n') {
+            return None;
+        }
+        let offset = self.offset;
+        self.offset += self.line.len() as u64;
+        let data = parse(&self.line[..self.line.len() - 1]);
+        self.line.clear();
+        Some(
+            data.map(|data| Record { offset, data })
+                .ok_or(ReadError::Corrupt { offset }),
+        )
+    }
+}
+
+fn parse(line: &[u8]) -> Option<Vec<u8>> {
+    let line = core::str::from_utf8(line).ok()?;
+    let mut bytes = Vec::new();
+    decode_with(line, |byte| bytes.push(byte)).ok()?;
+    if bytes.len() < HEADER_LEN {
+        return None;
+    }
+    let data = bytes.split_off(HEADER_LEN);
+    let len = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    let crc = u32::from_be_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]);
+    (len as usize == data.len() && crc == checksum(len, &data)).then_some(data)
+}
+
+/// The CRC-32 of a record's length followed by its data.
+fn checksum(len: u32, data: &[u8]) -> u32 {
+    let mut crc = Crc32::new();
+    crc.update(&len.to_be_bytes());
+    crc.update(data);
+    crc.finish()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::Cursor;
+
+    fn log(records: &[&[u8]]) -> Vec<u8> {
+        let mut writer = RecordWriter::new(Vec::new());
+        for record in records {
+            writer.append(record).unwrap();
+        }
+        writer.into_inner()
+    }
+
+    fn data(reader: &mut RecordReader<impl BufRead>) -> Vec<Result<Vec<u8>, u64>> {
+        reader
+            .map(|record| match record {
+                Ok(record) => Ok(record.data),
+                Err(ReadError::Corrupt { offset }) => Err(offset),
+                Err(ReadError::Io(e)) => panic!("{}", e),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn roundtrip() {
+        let records: [&[u8]; 4] = [b"", b"a", &[0xFF; 300], b"hello"];
+        let log = log(&records);
+        let mut reader = RecordReader::new(&log[..]);
+        let read = data(&mut reader);
+        assert_eq!(
+            read,
+            records.iter().map(|r| Ok(r.to_vec())).collect::<Vec<_>>()
+        );
+        assert_eq!(reader.position(), log.len() as u64);
+
+        let offsets = RecordReader::new(&log[..])
+            .map(|record| record.unwrap().offset)
+            .collect::<Vec<_>>();
+        let mut resumed = RecordReader::resume(&log[offsets[2] as usize..], offsets[2]);
+        assert_eq!(resumed.next().unwrap().unwrap().offset, offsets[2]);
+    }
+
+    #[test]
+    fn skips_corruption() {
+        let mut log = log(&[b"one", b"two", b"three"]);
+        let second = log.iter().position(|b| *b == b'\n').unwrap() + 1;
+        // garble the start of the second line
+        log.swap(second, second + 3);
+        log.swap(second + 1, second + 4);
+        log.swap(second + 2, second + 5);
+        let mut reader = RecordReader::new(&log[..]);
+        assert_eq!(
+            data(&mut reader),
+            [
+                Ok(b"one".to_vec()),
+                Err(second as u64),
+                Ok(b"three".to_vec())
+            ]
+        );
+
+        let junk = b"not a record\n\n"
+            .iter()
+            .chain(&log)
+            .copied()
+            .collect::<Vec<_>>();
+        let read = data(&mut RecordReader::new(&junk[..]));
+        assert_eq!(read[..2], [Err(0), Err(13)]);
+        assert_eq!(read.len(), 5);
+    }
+
+    #[test]
+    fn follows_a_growing_log() {
+        let full = log(&[b"one", b"two"]);
+        let cut = full.len() - 5;
+        let mut reader = RecordReader::new(Cursor::new(full[..cut].to_vec()));
+        assert_eq!(data(&mut reader), [Ok(b"one".to_vec())]);
+        let position = reader.position();
+
+        reader.get_mut().get_mut().extend_from_slice(&full[cut..]);
+        assert_eq!(data(&mut reader), [Ok(b"two".to_vec())]);
+
+        let mut reopened = Cursor::new(full);
+        reopened.set_position(position);
+        let mut resumed = RecordReader::resume(reopened, position);
+        assert_eq!(data(&mut resumed), [Ok(b"two".to_vec())]);
+    }
+}
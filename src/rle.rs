@@ -0,0 +1,142 @@
+//! Run-length encoding to shrink repetitive data before encoding it.
+//!
+//! The format is PackBits, the one TIFF and Apple's MacPaint use, so other tools can read it.
+//! Each run starts with a header byte `n`: `0..=127` means the next `n + 1` bytes are copied as
+//! they are, `129..=255` means the next byte is repeated `257 - n` times, and `128` is ignored.
+//!
+//! ```
+//! let board = [0u8; 200];
+//! let packed = base2048::rle::pack(&board);
+//! assert_eq!(packed, [0x81, 0, 0xB9, 0]);
+//! assert_eq!(base2048::rle::unpack(&packed).unwrap(), board);
+//! ```
+use crate::{decode_str, encode};
+use alloc::{string::String, vec::Vec};
+
+/// The longest run a header can describe.
+const MAX_RUN: usize = 128;
+
+/// Run-length encodes `bytes`.
+///
+/// Runs of three or more equal bytes are stored as a repeat, anything else as literals.
+pub fn pack(bytes: &[u8]) -> Vec<u8> {
+    let mut ret = Vec::with_capacity(bytes.len() + bytes.len() / MAX_RUN + 1);
+    let mut i = 0;
+    while i < bytes.len() {
+        let run = bytes[i..]
+            .iter()
+            .take(MAX_RUN)
+            .take_while(|byte| **byte == bytes[i])
+            .count();
+        if run >= 3 {
+            ret.push((257 - run) as u8);
+            ret.push(bytes[i]);
+            i += run;
+            continue;
+        }
+
+        let start = i;
+        while i < bytes.len() && i - start < MAX_RUN && !starts_run(&bytes[i..]) {
+            i += 1;
+        }
+        ret.push((i - start - 1) as u8);
+        ret.extend_from_slice(&bytes[start..i]);
+    }
+    ret
+}
+
+/// Whether `bytes` starts with three equal bytes.
+fn starts_run(bytes: &[u8]) -> bool {
+    matches!(bytes, [a, b, c, ..] if a == b && b == c)
+}
+
+/// Undoes [`pack`], or returns `None` if `packed` ends in the middle of a run.
+pub fn unpack(packed: &[u8]) -> Option<Vec<u8>> {
+    let mut ret = Vec::with_capacity(packed.len());
+    let mut packed = packed.iter();
+    while let Some(header) = packed.next() {
+        match *header {
+            0..=127 => {
+                let literal = packed.as_slice().get(..*header as usize + 1)?;
+                ret.extend_from_slice(literal);
+                packed.nth(*header as usize);
+            }
+            128 => {}
+            _ => {
+                let byte = *packed.next()?;
+                ret.resize(ret.len() + 257 - *header as usize, byte);
+            }
+        }
+    }
+    Some(ret)
+}
+
+/// Run-length encode some bytes with [`rle::pack`](pack), then encode them with base2048
+///
+/// # Example
+/// ```
+/// let sparse = [[0u8; 100], [1; 100]].concat();
+/// let encoded = base2048::encode_rle(&sparse);
+/// assert!(encoded.chars().count() < base2048::encode(&sparse).chars().count() / 10);
+/// assert_eq!(base2048::decode_rle(&encoded).unwrap(), sparse);
+/// ```
+pub fn encode_rle(bytes: &[u8]) -> String {
+    encode(pack(bytes))
+}
+
+/// Decode a string made by [`encode_rle`]
+pub fn decode_rle(string: &str) -> Option<Vec<u8>> {
+    unpack(&decode_str(string)?)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn packbits_example() {
+        // from Apple's Technical Note TN1023
+        let unpacked = [
+            0xAA, 0xAA, 0xAA, 0x80, 0x00, 0x2A, 0xAA, 0xAA, 0xAA, 0xAA, 0x80, 0x00, 0x2A, 0x22,
+            0xAA, 0xAA, 0xAA, 0xAA, 0xAA, 0xAA, 0xAA, 0xAA, 0xAA, 0xAA,
+        ];
+        let packed = [
+            0xFE, 0xAA, 0x02, 0x80, 0x00, 0x2A, 0xFD, 0xAA, 0x03, 0x80, 0x00, 0x2A, 0x22, 0xF7,
+            0xAA,
+        ];
+        assert_eq!(pack(&unpacked), packed);
+        assert_eq!(unpack(&packed).unwrap(), unpacked);
+    }
+
+    #[test]
+    fn roundtrip() {
+        let mut x = 1u32;
+        for len in [0, 1, 2, 3, 127, 128, 129, 300, 1000] {
+            // runs of random lengths, some long, some of one byte
+            let mut bytes = Vec::new();
+            while bytes.len() < len {
+                x ^= x << 13;
+                x ^= x >> 17;
+                x ^= x << 5;
+                let run = (x % 7).pow(3) as usize + 1;
+                bytes.extend(core::iter::repeat_n(
+                    x as u8 >> 6,
+                    run.min(len - bytes.len()),
+                ));
+            }
+            assert_eq!(unpack(&pack(&bytes)).unwrap(), bytes, "{}", len);
+            assert_eq!(decode_rle(&encode_rle(&bytes)).unwrap(), bytes);
+        }
+        let distinct = (0..=255).collect::<Vec<u8>>();
+        assert_eq!(pack(&distinct).len(), 258);
+        assert_eq!(unpack(&pack(&distinct)).unwrap(), distinct);
+    }
+
+    #[test]
+    fn truncated() {
+        assert_eq!(unpack(&[0x80]), Some(vec![]));
+        assert_eq!(unpack(&[0x02, 1, 2]), None);
+        assert_eq!(unpack(&[0xFE]), None);
+        assert!(decode_rle(&encode([0x05])).is_none());
+    }
+}
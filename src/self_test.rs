@@ -0,0 +1,168 @@
+//! A known-answer test of the codec and its tables, for checking a device at startup.
+use crate::{decode_with, encode_with, tables, TAIL};
+use core::fmt;
+
+/// Inputs with their encodings: every length up to 11 bytes, so every number of leftover bits and
+/// every kind of final character, of all-zero and all-one bytes, which use the first and last
+/// table characters.
+const VECTORS: &[(&[u8], &str)] = &[
+    (&[0x00; 1], "F"),
+    (&[0xFF; 1], "ၕ"),
+    (&[0x00; 2], "8Đ"),
+    (&[0xFF; 2], "ၕၕ"),
+    (&[0x00; 3], "881"),
+    (&[0xFF; 3], "ၕၕ7"),
+    (&[0x00; 4], "889"),
+    (&[0xFF; 4], "ၕၕၕ"),
+    (&[0x00; 5], "888N"),
+    (&[0xFF; 5], "ၕၕၕၕ"),
+    (&[0x00; 6], "8888ƶ"),
+    (&[0xFF; 6], "ၕၕၕၕၕ"),
+    (&[0x00; 7], "888883"),
+    (&[0xFF; 7], "ၕၕၕၕၕ7"),
+    (&[0x00; 8], "88888B"),
+    (&[0xFF; 8], "ၕၕၕၕၕၕ"),
+    (&[0x00; 9], "888888d"),
+    (&[0xFF; 9], "ၕၕၕၕၕၕၕ"),
+    (&[0x00; 10], "88888880"),
+    (&[0xFF; 10], "ၕၕၕၕၕၕၕ7"),
+    (&[0x00; 11], "88888888"),
+    (&[0xFF; 11], "ၕၕၕၕၕၕၕၕ"),
+    (
+        b"some utf8 bytes to encode more compactly",
+        "ݙޙצҭזЬශƕމਦعҭӿचॳಽܜͳԈඌཥШߣۿ۹ࠄעแಐ7",
+    ),
+];
+
+/// Why [`self_test`] failed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SelfTestError {
+    /// The tables disagree about this table position.
+    Table {
+        /// The position in [`ENC_TABLE`](crate::ENC_TABLE).
+        index: u16,
+    },
+    /// The decoding table has an entry for a character that isn't in the encoding table.
+    DecodeTable {
+        /// The character's code point.
+        code_point: u32,
+    },
+    /// The decoding table has an entry for a tail character.
+    Tail {
+        /// The position in [`TAIL`].
+        index: u8,
+    },
+    /// A known input encoded or decoded to something else.
+    Vector {
+        /// The number of the test vector.
+        index: usize,
+    },
+}
+
+#[cfg(not(feature = "compact-errors"))]
+impl fmt::Display for SelfTestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SelfTestError::Table { index } => {
+                write!(f, "the tables disagree about entry {}", index)
+            }
+            SelfTestError::DecodeTable { code_point } => write!(
+                f,
+                "decoding table has a stray entry for U+{:04X}",
+                code_point
+            ),
+            SelfTestError::Tail { index } => {
+                write!(f, "tail character {} is in the decoding table", index)
+            }
+            SelfTestError::Vector { index } => write!(f, "test vector {} failed", index),
+        }
+    }
+}
+
+#[cfg(feature = "compact-errors")]
+impl fmt::Display for SelfTestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SelfTestError::Table { .. } => "base2048 self test error 1: table",
+            SelfTestError::DecodeTable { .. } => "base2048 self test error 2: decoding table",
+            SelfTestError::Tail { .. } => "base2048 self test error 3: tail",
+            SelfTestError::Vector { .. } => "base2048 self test error 4: test vector",
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SelfTestError {}
+
+/// Checks the codec and its tables against known answers
+///
+/// Meant for firmware that wants to know at power-on that the code and the tables in flash are
+/// intact. The tables are checked in the form the codec reads them, so with `small-tables` that is
+/// the table of runs rather than [`DEC_TABLE`](crate::DEC_TABLE): every table character must
+/// decode to its position, no other code point may decode, and neither may a tail character. Then
+/// a set of known inputs is encoded and decoded covering every kind of final character. With
+/// `runtime-tables` the tables are built first if nothing has used them yet, and what was built is
+/// checked, including the UTF-8 of each character; otherwise nothing is allocated.
+///
+/// # Example
+/// ```
+/// base2048::self_test().expect("base2048 tables are corrupt");
+/// ```
+pub fn self_test() -> Result<(), SelfTestError> {
+    let table = tables::enc_table();
+    for ((index, c), utf8) in table.iter().enumerate().zip(tables::enc_utf8()) {
+        let mut buf = [0; 4];
+        let bytes = c.encode_utf8(&mut buf).as_bytes();
+        // the bytes first byte lowest, and the length in the high byte
+        let packed = bytes.iter().rev().fold(0, |acc, b| acc << 8 | *b as u32);
+        let packed = packed | (bytes.len() as u32) << 24;
+        if packed != *utf8 || tables::table_index(*c as u32) != Some(index as u16) {
+            return Err(SelfTestError::Table {
+                index: index as u16,
+            });
+        }
+    }
+    // every code point up to U+FFFF, the highest a table character may be
+    for code_point in 0..=0xFFFF {
+        if let Some(index) = tables::table_index(code_point) {
+            if table.get(index as usize).map(|c| *c as u32) != Some(code_point) {
+                return Err(SelfTestError::DecodeTable { code_point });
+            }
+        }
+    }
+    for (index, c) in TAIL.iter().enumerate() {
+        if tables::table_index(*c as u32).is_some() {
+            return Err(SelfTestError::Tail { index: index as u8 });
+        }
+    }
+
+    for (index, (bytes, encoded)) in VECTORS.iter().enumerate() {
+        let mut ok = true;
+        let mut expected = encoded.chars();
+        encode_with(bytes, |c| ok &= expected.next() == Some(c));
+        ok &= expected.next().is_none();
+
+        let mut expected = bytes.iter();
+        let decoded = decode_with(encoded, |byte| ok &= expected.next() == Some(&byte));
+        ok &= decoded.is_ok() && expected.next().is_none();
+        if !ok {
+            return Err(SelfTestError::Vector { index });
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{decode, encode};
+
+    #[test]
+    fn passes() {
+        assert_eq!(self_test(), Ok(()));
+        for (bytes, encoded) in VECTORS {
+            assert_eq!(encode(bytes), *encoded);
+            assert_eq!(decode(encoded).unwrap(), *bytes);
+        }
+    }
+}
@@ -0,0 +1,379 @@
+//! Serde helpers for byte fields written as base2048 strings (the `serde` feature).
+//!
+//! Use them with `#[serde(with = "base2048::serde")]` on `Vec<u8>` or `[u8; N]` fields. Formats
+//! that are meant to be read by people, like JSON or TOML, get a base2048 string; binary formats
+//! get the raw bytes, which are smaller there.
+//!
+//! ```
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize, Debug, PartialEq)]
+//! struct Message {
+//!     #[serde(with = "base2048::serde")]
+//!     payload: Vec<u8>,
+//! }
+//!
+//! let message = Message { payload: b"hello".to_vec() };
+//! let json = serde_json::to_string(&message).unwrap();
+//! assert_eq!(json, format!(r#"{{"payload":"{}"}}"#, base2048::encode(b"hello")));
+//! assert_eq!(serde_json::from_str::<Message>(&json).unwrap(), message);
+//!
+//! // plain byte arrays, from before the field was encoded, still load
+//! let old = serde_json::from_str::<Message>(r#"{"payload":[104,101,108,108,111]}"#).unwrap();
+//! assert_eq!(old, message);
+//! ```
+use crate::{decode_with, decoded_str_len};
+use ::serde::de::{self, Deserialize, Deserializer, SeqAccess, Visitor};
+use ::serde::ser::{Serialize, Serializer};
+use alloc::vec::Vec;
+use core::{
+    convert::TryFrom,
+    fmt,
+    ops::{Deref, DerefMut},
+};
+
+/// Serializes `bytes` as a base2048 string, or as raw bytes in binary formats.
+pub fn serialize<T, S>(bytes: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: AsRef<[u8]> + ?Sized,
+    S: Serializer,
+{
+    if serializer.is_human_readable() {
+        serializer.collect_str(&crate::Base2048Display::new(bytes.as_ref()))
+    } else {
+        serializer.serialize_bytes(bytes.as_ref())
+    }
+}
+
+/// Deserializes bytes from a base2048 string, or from raw bytes or a sequence of bytes.
+///
+/// Accepting raw bytes in human-readable formats lets data written before a field was switched to
+/// base2048 load without migrating it; the format has to be self-describing, like JSON, for the
+/// deserializer to tell which one it got. Binary formats are asked for bytes.
+///
+/// `T` is usually `Vec<u8>`, or `[u8; N]` which fails for any other number of bytes.
+pub fn deserialize<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: TryFrom<Vec<u8>>,
+{
+    let bytes = if deserializer.is_human_readable() {
+        deserializer.deserialize_any(BytesVisitor)?
+    } else {
+        deserializer.deserialize_byte_buf(BytesVisitor)?
+    };
+    let len = bytes.len();
+    T::try_from(bytes).map_err(|_| de::Error::invalid_length(len, &"a fixed number of bytes"))
+}
+
+struct BytesVisitor;
+
+impl<'de> Visitor<'de> for BytesVisitor {
+    type Value = Vec<u8>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a base2048 string or bytes")
+    }
+
+    fn visit_str<E: de::Error>(self, s: &str) -> Result<Vec<u8>, E> {
+        let mut ret = Vec::with_capacity(decoded_str_len(s));
+        decode_with(s, |byte| ret.push(byte)).map_err(E::custom)?;
+        Ok(ret)
+    }
+
+    fn visit_bytes<E: de::Error>(self, bytes: &[u8]) -> Result<Vec<u8>, E> {
+        Ok(bytes.to_vec())
+    }
+
+    fn visit_byte_buf<E: de::Error>(self, bytes: Vec<u8>) -> Result<Vec<u8>, E> {
+        Ok(bytes)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<u8>, A::Error> {
+        let mut ret = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+        while let Some(byte) = seq.next_element()? {
+            ret.push(byte);
+        }
+        Ok(ret)
+    }
+}
+
+/// Bytes that serialize like a field with `#[serde(with = "base2048::serde")]`, for where an
+/// attribute can't go, such as inside a `HashMap` or `Option`.
+///
+/// `T` is usually `Vec<u8>` (the default) or `[u8; N]`. An invalid string fails deserialization
+/// with the [`DecodeError`](crate::DecodeError) message, giving the character and its position.
+///
+/// # Example
+/// ```
+/// use base2048::serde::Base2048Bytes;
+/// use std::collections::HashMap;
+///
+/// let mut keys = HashMap::new();
+/// keys.insert("alice", Base2048Bytes(b"hello".to_vec()));
+/// let json = serde_json::to_string(&keys).unwrap();
+/// assert_eq!(json, format!(r#"{{"alice":"{}"}}"#, base2048::encode(b"hello")));
+/// assert_eq!(serde_json::from_str::<HashMap<&str, Base2048Bytes>>(&json).unwrap(), keys);
+///
+/// assert!(serde_json::from_str::<Base2048Bytes>(r#""ետћζы༎X""#).is_err());
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Base2048Bytes<T = Vec<u8>>(pub T);
+
+impl<T> Base2048Bytes<T> {
+    /// The wrapped bytes.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> From<T> for Base2048Bytes<T> {
+    fn from(bytes: T) -> Self {
+        Base2048Bytes(bytes)
+    }
+}
+
+impl<T> Deref for Base2048Bytes<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Base2048Bytes<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: AsRef<[u8]>> AsRef<[u8]> for Base2048Bytes<T> {
+    fn as_ref(&self) -> &[u8] {
+        self.0.as_ref()
+    }
+}
+
+impl<T: AsRef<[u8]>> Serialize for Base2048Bytes<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize(&self.0, serializer)
+    }
+}
+
+impl<'de, T: TryFrom<Vec<u8>>> Deserialize<'de> for Base2048Bytes<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize(deserializer).map(Base2048Bytes)
+    }
+}
+
+pub mod chunked {
+    //! Like the parent module, but writes the bytes as a sequence of encoded chunks.
+    //!
+    //! A field holding hundreds of megabytes becomes a string too long for many JSON parsers to
+    //! accept. Here each chunk of at most [`CHUNK_LEN`] bytes is encoded on its own, so no string
+    //! is longer than 32768 characters:
+    //!
+    //! ```
+    //! use serde::{Deserialize, Serialize};
+    //!
+    //! #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    //! struct Upload {
+    //!     #[serde(with = "base2048::serde::chunked")]
+    //!     data: Vec<u8>,
+    //! }
+    //!
+    //! let upload = Upload { data: vec![7; 100_000] };
+    //! let json = serde_json::to_value(&upload).unwrap();
+    //! assert_eq!(json["data"].as_array().unwrap().len(), 3);
+    //! assert_eq!(serde_json::from_value::<Upload>(json).unwrap(), upload);
+    //! ```
+    use crate::{decode_with, Base2048Display};
+    use ::serde::de::{self, DeserializeSeed, Deserializer, SeqAccess, Visitor};
+    use ::serde::ser::{Serialize, SerializeSeq, Serializer};
+    use alloc::vec::Vec;
+    use core::fmt;
+
+    /// The most bytes in one chunk.
+    ///
+    /// A multiple of 11 bytes encodes without a tail, to exactly 32768 characters.
+    pub const CHUNK_LEN: usize = 11 * 4096;
+
+    /// Serializes `bytes` as a sequence of base2048 strings.
+    pub fn serialize<T, S>(bytes: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: AsRef<[u8]> + ?Sized,
+        S: Serializer,
+    {
+        let bytes = bytes.as_ref();
+        let mut seq = serializer.serialize_seq(Some(bytes.len().div_ceil(CHUNK_LEN)))?;
+        for chunk in bytes.chunks(CHUNK_LEN) {
+            seq.serialize_element(&Chunk(chunk))?;
+        }
+        seq.end()
+    }
+
+    struct Chunk<'a>(&'a [u8]);
+
+    impl Serialize for Chunk<'_> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.collect_str(&Base2048Display::new(self.0))
+        }
+    }
+
+    /// Deserializes bytes from a sequence of base2048 strings, or from a single one.
+    ///
+    /// Accepting a single string lets fields written by the parent module load unchanged.
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let mut ret = Vec::new();
+        deserializer.deserialize_any(ChunksVisitor(&mut ret))?;
+        Ok(ret)
+    }
+
+    /// Decodes a string, or each string of a sequence, onto the end of the vector.
+    struct ChunksVisitor<'a>(&'a mut Vec<u8>);
+
+    impl<'de> Visitor<'de> for ChunksVisitor<'_> {
+        type Value = ();
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("a sequence of base2048 strings")
+        }
+
+        fn visit_str<E: de::Error>(self, s: &str) -> Result<(), E> {
+            decode_with(s, |byte| self.0.push(byte)).map_err(E::custom)
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+            while seq.next_element_seed(ChunkSeed(self.0))?.is_some() {}
+            Ok(())
+        }
+    }
+
+    struct ChunkSeed<'a>(&'a mut Vec<u8>);
+
+    impl<'de> DeserializeSeed<'de> for ChunkSeed<'_> {
+        type Value = ();
+
+        fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+            deserializer.deserialize_str(ChunksVisitor(self.0))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::encode;
+    use alloc::{string::ToString, vec::Vec};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Field(#[serde(with = "crate::serde")] Vec<u8>);
+
+    #[test]
+    fn string_or_bytes() {
+        let bytes = (0..40).map(|i| (i * 37) as u8).collect::<Vec<_>>();
+        let json = serde_json::to_string(&Field(bytes.clone())).unwrap();
+        assert_eq!(json, serde_json::to_string(&encode(&bytes)).unwrap());
+        assert_eq!(serde_json::from_str::<Field>(&json).unwrap().0, bytes);
+
+        let array = serde_json::to_string(&bytes).unwrap();
+        assert_eq!(serde_json::from_str::<Field>(&array).unwrap().0, bytes);
+    }
+
+    #[test]
+    fn invalid() {
+        let e = serde_json::from_str::<Field>(r#""ետћζы༎X""#).unwrap_err();
+        #[cfg(not(feature = "compact-errors"))]
+        assert!(e
+            .to_string()
+            .starts_with("invalid character '༎' (U+0F0E) at position 5"));
+        #[cfg(feature = "compact-errors")]
+        assert!(e.to_string().starts_with("base2048 error 1"));
+        assert!(serde_json::from_str::<Field>("[256]").is_err());
+        assert!(serde_json::from_str::<Field>("7").is_err());
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Array(#[serde(with = "crate::serde")] [u8; 4]);
+
+    #[test]
+    fn arrays() {
+        let json = serde_json::to_string(&Array(*b"abcd")).unwrap();
+        assert_eq!(json, serde_json::to_string(&encode(b"abcd")).unwrap());
+        assert_eq!(serde_json::from_str::<Array>(&json).unwrap().0, *b"abcd");
+        let e = serde_json::from_str::<Array>(&serde_json::to_string(&encode(b"abc")).unwrap());
+        assert!(e.is_err());
+    }
+
+    #[test]
+    fn binary_formats_get_bytes() {
+        use serde_test::{assert_tokens, Configure, Token};
+        assert_tokens(
+            &Field(b"hello".to_vec()).compact(),
+            &[
+                Token::NewtypeStruct { name: "Field" },
+                Token::Bytes(b"hello"),
+            ],
+        );
+        assert_tokens(
+            &Array(*b"abcd").compact(),
+            &[
+                Token::NewtypeStruct { name: "Array" },
+                Token::Bytes(b"abcd"),
+            ],
+        );
+        assert_eq!(encode(b"hello"), "ڵϠɲඹ");
+        assert_tokens(
+            &Field(b"hello".to_vec()).readable(),
+            &[Token::NewtypeStruct { name: "Field" }, Token::Str("ڵϠɲඹ")],
+        );
+    }
+
+    #[test]
+    fn wrapper() {
+        use super::Base2048Bytes;
+        let wrapped = Some(Base2048Bytes(*b"abcd"));
+        let json = serde_json::to_string(&wrapped).unwrap();
+        assert_eq!(json, serde_json::to_string(&encode(b"abcd")).unwrap());
+        assert_eq!(
+            serde_json::from_str::<Option<Base2048Bytes<[u8; 4]>>>(&json).unwrap(),
+            wrapped
+        );
+        assert_eq!(
+            serde_json::to_string(&Base2048Bytes(b"abcd".to_vec())).unwrap(),
+            json
+        );
+        let e = serde_json::from_str::<Base2048Bytes>(r#""ետћζы༎X""#).unwrap_err();
+        #[cfg(not(feature = "compact-errors"))]
+        assert!(e
+            .to_string()
+            .starts_with("invalid character '༎' (U+0F0E) at position 5"));
+        #[cfg(feature = "compact-errors")]
+        assert!(e.to_string().starts_with("base2048 error 1"));
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Chunked(#[serde(with = "crate::serde::chunked")] Vec<u8>);
+
+    #[test]
+    fn chunked() {
+        use crate::serde::chunked::CHUNK_LEN;
+        for len in [0, 1, CHUNK_LEN, CHUNK_LEN + 1, 3 * CHUNK_LEN - 5] {
+            let bytes = (0..len).map(|i| (i * 37) as u8).collect::<Vec<_>>();
+            let json = serde_json::to_value(Chunked(bytes.clone())).unwrap();
+            let chunks = json.as_array().unwrap();
+            assert_eq!(chunks.len(), len.div_ceil(CHUNK_LEN));
+            assert!(chunks
+                .iter()
+                .all(|c| c.as_str().unwrap().chars().count() <= 32768));
+            assert_eq!(serde_json::from_value::<Chunked>(json).unwrap().0, bytes);
+
+            let single = serde_json::to_string(&Field(bytes.clone())).unwrap();
+            assert_eq!(serde_json::from_str::<Chunked>(&single).unwrap().0, bytes);
+        }
+        assert!(serde_json::from_str::<Chunked>(r#"["ետћζы༎X"]"#).is_err());
+        assert!(serde_json::from_str::<Chunked>("[7]").is_err());
+    }
+}
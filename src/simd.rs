@@ -0,0 +1,347 @@
+//! Encoding and decoding whole blocks with SIMD instructions (the `simd` feature).
+//!
+//! Every 11 bytes make exactly 8 characters, so both work in blocks of 11 bytes and 8 characters,
+//! and whatever is left after the last block starts on a character boundary for the scalar code
+//! to finish as if it were the whole input. Encoding is accelerated on x86 with AVX2 or SSE4.1
+//! and decoding on AArch64 with NEON; other targets and processors leave it all to the scalar
+//! code.
+use crate::tables::table_index;
+use alloc::vec::Vec;
+
+/// Encodes as many whole blocks of `bytes` as it can, appending the UTF-8 text to `out`, and
+/// returns how many bytes that was.
+pub(crate) fn encode_blocks(bytes: &[u8], out: &mut Vec<u8>) -> usize {
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    {
+        if std::is_x86_feature_detected!("avx2") {
+            // SAFETY: the processor supports AVX2
+            return unsafe { x86::encode_avx2(bytes, out) };
+        }
+        if std::is_x86_feature_detected!("ssse3") && std::is_x86_feature_detected!("sse4.1") {
+            // SAFETY: the processor supports SSSE3 and SSE4.1
+            return unsafe { x86::encode_sse41(bytes, out) };
+        }
+    }
+    let _ = (bytes, out);
+    0
+}
+
+/// Decodes as many whole blocks of `string` as it can, appending the bytes to `out`, and returns
+/// how many bytes of text and how many characters that was.
+///
+/// Blocks with anything but table characters, and the last character of the text, are left for
+/// the scalar decoder, which knows how to report errors and take a tail.
+pub(crate) fn decode_blocks(string: &str, out: &mut Vec<u8>) -> (usize, usize) {
+    #[cfg(target_arch = "aarch64")]
+    {
+        if std::arch::is_aarch64_feature_detected!("neon") {
+            // SAFETY: the processor supports NEON
+            return unsafe { aarch64::decode_neon(string, out) };
+        }
+    }
+    let _ = (string, out);
+    (0, 0)
+}
+
+/// Puts the table positions of the 8 characters of `text` from `pos` into `indices`, `0xFFFF` for
+/// those not in the table, and returns where the next character starts.
+///
+/// `text` must be valid UTF-8 and `pos` on a character boundary.
+#[cfg_attr(not(target_arch = "aarch64"), allow(dead_code))]
+fn table_indices(text: &[u8], mut pos: usize, indices: &mut [u16; 8]) -> Option<usize> {
+    let continuation = |pos: usize| (text[pos] & 0x3F) as usize;
+    for index in indices.iter_mut() {
+        let lead = *text.get(pos)? as usize;
+        let (c, len) = match lead {
+            0..=0x7F => (lead, 1),
+            0xC0..=0xDF => ((lead & 0x1F) << 6 | continuation(pos + 1), 2),
+            0xE0..=0xEF => {
+                let c = (lead & 0x0F) << 12 | continuation(pos + 1) << 6 | continuation(pos + 2);
+                (c, 3)
+            }
+            // none of the table is outside the basic multilingual plane
+            _ => (usize::MAX, 4),
+        };
+        *index = table_index(c as u32).unwrap_or(0xFFFF);
+        pos += len;
+    }
+    Some(pos)
+}
+
+#[cfg(target_arch = "aarch64")]
+mod aarch64 {
+    use super::table_indices;
+    use alloc::vec::Vec;
+    use core::arch::aarch64::*;
+
+    /// # Safety
+    /// The processor must support NEON.
+    #[target_feature(enable = "neon")]
+    pub(super) unsafe fn decode_neon(string: &str, out: &mut Vec<u8>) -> (usize, usize) {
+        let text = string.as_bytes();
+        // the first and second character of each pair are shifted this far, so that the four
+        // characters of each half of a block fill 44 bits
+        let first = vld1q_s64([33, 11].as_ptr());
+        let second = vld1q_s64([22, 0].as_ptr());
+        let mut indices = [0; 8];
+        let (mut pos, mut chars) = (0, 0);
+        while let Some(end) = table_indices(text, pos, &mut indices) {
+            let block = vld1q_u16(indices.as_ptr());
+            if end == text.len() || vmaxvq_u16(block) > 0x7FF {
+                break;
+            }
+            // the pairs are 0 and 1, 2 and 3 in the first half and 4 and 5, 6 and 7 in the second
+            let evens = vmovl_u16(vget_low_u16(vuzp1q_u16(block, block)));
+            let odds = vmovl_u16(vget_low_u16(vuzp2q_u16(block, block)));
+            let halves = [
+                (
+                    vmovl_u32(vget_low_u32(evens)),
+                    vmovl_u32(vget_low_u32(odds)),
+                ),
+                (vmovl_high_u32(evens), vmovl_high_u32(odds)),
+            ];
+            let mut bits = [0u64; 2];
+            for (bits, (evens, odds)) in bits.iter_mut().zip(halves) {
+                // the shifted characters don't overlap, so adding them up puts them together
+                *bits = vaddvq_u64(vorrq_u64(vshlq_u64(evens, first), vshlq_u64(odds, second)));
+            }
+            let block = ((bits[0] as u128) << 44 | bits[1] as u128).to_be_bytes();
+            out.extend_from_slice(&block[5..]);
+            pos = end;
+            chars += 8;
+        }
+        (pos, chars)
+    }
+
+    #[cfg(test)]
+    mod test {
+        use super::*;
+        use crate::{decode_with, encode, TAIL};
+        use alloc::string::String;
+
+        fn check(string: &str) {
+            let mut expected = Vec::new();
+            let expected = decode_with(string, |byte| expected.push(byte)).map(|()| expected);
+            let mut out = vec![1, 2];
+            // SAFETY: the tests only run on AArch64, which always has NEON
+            let (n_bytes, chars) = unsafe { decode_neon(string, &mut out) };
+            assert_eq!(chars % 8, 0);
+            assert_eq!(string[..n_bytes].chars().count(), chars);
+            assert!(n_bytes < string.len() || string.is_empty());
+            assert_eq!(&out[..2], [1, 2]);
+            let state = crate::DecodeState {
+                chars,
+                ..Default::default()
+            };
+            let rest =
+                crate::decode_chars_from(state, string[n_bytes..].chars(), |byte| out.push(byte));
+            assert_eq!(rest.map(|()| out[2..].to_vec()), expected, "{:?}", string);
+        }
+
+        #[test]
+        fn matches_scalar() {
+            let bytes = (0..600)
+                .map(|i| (i * 37 + i / 256) as u8)
+                .collect::<Vec<_>>();
+            for len in 0..bytes.len() {
+                check(&encode(&bytes[..len]));
+            }
+        }
+
+        #[test]
+        fn errors() {
+            let encoded = encode([0xA5; 100]).chars().collect::<Vec<_>>();
+            for at in [0, 7, 8, 30, encoded.len() - 1] {
+                for bad in ['€', 'a', TAIL[3], '\u{1F600}'] {
+                    let mut chars = encoded.clone();
+                    chars[at] = bad;
+                    check(&chars.into_iter().collect::<String>());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+mod x86 {
+    use crate::tables::enc_utf8;
+    use alloc::vec::Vec;
+    #[cfg(target_arch = "x86")]
+    use core::arch::x86::*;
+    #[cfg(target_arch = "x86_64")]
+    use core::arch::x86_64::*;
+
+    // Character `k` of a block takes bits 11k to 11k + 10, counting from the most significant bit
+    // of the first byte. They all lie within the three bytes from byte 11k / 8, which the shuffles
+    // put together into a big-endian 24-bit word, so the position is that word shifted right by
+    // 13 - 11k % 8.
+
+    /// How many blocks are safe to encode, given each is read 16 bytes at a time.
+    fn n_blocks(bytes: &[u8]) -> usize {
+        match bytes.len() {
+            len if len >= 16 => (len - 16) / 11 + 1,
+            _ => 0,
+        }
+    }
+
+    /// Writes the character `utf8` (from [`enc_utf8`]) to `dst`, returning where the next one
+    /// goes.
+    ///
+    /// # Safety
+    /// There must be room for four bytes at `dst`; the ones past the character are overwritten by
+    /// the next one or left beyond the end of the text.
+    #[inline(always)]
+    unsafe fn put(dst: *mut u8, utf8: u32) -> *mut u8 {
+        (dst as *mut u32).write_unaligned(utf8);
+        dst.add((utf8 >> 24) as usize)
+    }
+
+    /// Runs `encode_block` over each block, writing its characters to the end of `out`.
+    ///
+    /// # Safety
+    /// `encode_block` must write at most 8 characters, as [`put`] does.
+    #[inline(always)]
+    unsafe fn encode(
+        bytes: &[u8],
+        out: &mut Vec<u8>,
+        mut encode_block: impl FnMut(*const u8, *mut u8) -> *mut u8,
+    ) -> usize {
+        let n_blocks = n_blocks(bytes);
+        // a character takes 3 bytes at most, and the last write runs 1 past that
+        out.reserve(n_blocks * 8 * 3 + 1);
+        let start = out.as_mut_ptr().add(out.len());
+        let mut dst = start;
+        for block in 0..n_blocks {
+            dst = encode_block(bytes.as_ptr().add(block * 11), dst);
+        }
+        out.set_len(out.len() + dst.offset_from(start) as usize);
+        n_blocks * 11
+    }
+
+    /// # Safety
+    /// The processor must support AVX2.
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn encode_avx2(bytes: &[u8], out: &mut Vec<u8>) -> usize {
+        let shuffle = _mm256_setr_epi8(
+            2, 1, 0, -1, 3, 2, 1, -1, 4, 3, 2, -1, 6, 5, 4, -1, //
+            7, 6, 5, -1, 8, 7, 6, -1, 10, 9, 8, -1, 11, 10, 9, -1,
+        );
+        let shifts = _mm256_setr_epi32(13, 10, 7, 12, 9, 6, 11, 8);
+        let mask = _mm256_set1_epi32(0x7FF);
+        let enc_utf8 = enc_utf8();
+        encode(bytes, out, |src, mut dst| {
+            let block = _mm256_broadcastsi128_si256(_mm_loadu_si128(src as *const __m128i));
+            let words = _mm256_shuffle_epi8(block, shuffle);
+            let indices = _mm256_and_si256(_mm256_srlv_epi32(words, shifts), mask);
+            let utf8 = _mm256_i32gather_epi32::<4>(enc_utf8.as_ptr() as *const i32, indices);
+            let mut chars = [0u32; 8];
+            _mm256_storeu_si256(chars.as_mut_ptr() as *mut __m256i, utf8);
+            for c in chars {
+                dst = put(dst, c);
+            }
+            dst
+        })
+    }
+
+    /// # Safety
+    /// The processor must support SSSE3 and SSE4.1.
+    #[target_feature(enable = "ssse3,sse4.1")]
+    pub(super) unsafe fn encode_sse41(bytes: &[u8], out: &mut Vec<u8>) -> usize {
+        let shuffles = [
+            _mm_setr_epi8(2, 1, 0, -1, 3, 2, 1, -1, 4, 3, 2, -1, 6, 5, 4, -1),
+            _mm_setr_epi8(7, 6, 5, -1, 8, 7, 6, -1, 10, 9, 8, -1, 11, 10, 9, -1),
+        ];
+        // there's no shift by a different amount in each lane, so shift left by 11k % 8 with a
+        // multiplication and then right by 13 for all of them
+        let multipliers = [
+            _mm_setr_epi32(1, 1 << 3, 1 << 6, 1 << 1),
+            _mm_setr_epi32(1 << 4, 1 << 7, 1 << 2, 1 << 5),
+        ];
+        let mask = _mm_set1_epi32(0x7FF);
+        let enc_utf8 = enc_utf8();
+        encode(bytes, out, |src, mut dst| {
+            let block = _mm_loadu_si128(src as *const __m128i);
+            for half in 0..2 {
+                let words = _mm_shuffle_epi8(block, shuffles[half]);
+                let words = _mm_mullo_epi32(words, multipliers[half]);
+                let indices = _mm_and_si128(_mm_srli_epi32::<13>(words), mask);
+                let mut chunk = [0u32; 4];
+                _mm_storeu_si128(chunk.as_mut_ptr() as *mut __m128i, indices);
+                for index in chunk {
+                    dst = put(dst, enc_utf8[index as usize]);
+                }
+            }
+            dst
+        })
+    }
+
+    #[cfg(test)]
+    mod test {
+        use super::*;
+        use crate::encode_with;
+
+        fn scalar(bytes: &[u8]) -> Vec<u8> {
+            let mut out = Vec::new();
+            encode_with(bytes, |c| {
+                out.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes())
+            });
+            out
+        }
+
+        fn check(encode_blocks: unsafe fn(&[u8], &mut Vec<u8>) -> usize) {
+            let mut x = 0x2545_f491_u32;
+            let bytes = (0..500)
+                .map(|_| {
+                    x ^= x << 13;
+                    x ^= x >> 17;
+                    x ^= x << 5;
+                    x as u8
+                })
+                .chain((0..=255).chain((0..=255).rev()))
+                .collect::<Vec<_>>();
+            for len in 0..bytes.len() {
+                let bytes = &bytes[..len];
+                let mut out = b"prefix".to_vec();
+                // SAFETY: only called for extensions the processor has
+                let done = unsafe { encode_blocks(bytes, &mut out) };
+                assert_eq!(done, n_blocks(bytes) * 11);
+                assert!(len - done < 16);
+                out.extend(scalar(&bytes[done..]));
+                assert_eq!(&out[..6], b"prefix");
+                assert_eq!(out[6..], scalar(bytes)[..], "{} bytes", len);
+            }
+        }
+
+        #[test]
+        fn avx2() {
+            if std::is_x86_feature_detected!("avx2") {
+                check(encode_avx2);
+            }
+        }
+
+        #[test]
+        fn sse41() {
+            if std::is_x86_feature_detected!("ssse3") && std::is_x86_feature_detected!("sse4.1") {
+                check(encode_sse41);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn matches_scalar() {
+        let bytes = (0..10_000)
+            .map(|i| (i * 37 + i / 256) as u8)
+            .collect::<Vec<_>>();
+        let mut out = Vec::new();
+        let done = encode_blocks(&bytes, &mut out);
+        crate::encode_with(&bytes[done..], |c| {
+            out.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes())
+        });
+        assert_eq!(out, crate::encode(&bytes).into_bytes());
+    }
+}
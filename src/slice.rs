@@ -0,0 +1,223 @@
+//! Encoding and decoding into fixed buffers, for targets without a heap.
+use crate::{decode_with_strict, encode_with, DecodeError};
+use core::fmt;
+
+/// The output buffer can't hold the result.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BufferTooSmall {
+    /// How long the buffer needs to be, in bytes.
+    pub needed: usize,
+}
+
+#[cfg(not(feature = "compact-errors"))]
+impl fmt::Display for BufferTooSmall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the output needs a buffer of {} bytes", self.needed)
+    }
+}
+
+#[cfg(feature = "compact-errors")]
+impl fmt::Display for BufferTooSmall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("base2048 buffer error 1: too small")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BufferTooSmall {}
+
+/// Why [`decode_to_slice`] failed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DecodeSliceError {
+    /// The string is valid but the output buffer can't hold what it decodes to.
+    BufferTooSmall(BufferTooSmall),
+    /// The string isn't valid base2048.
+    Decode(DecodeError),
+}
+
+impl From<BufferTooSmall> for DecodeSliceError {
+    fn from(e: BufferTooSmall) -> Self {
+        DecodeSliceError::BufferTooSmall(e)
+    }
+}
+
+impl From<DecodeError> for DecodeSliceError {
+    fn from(e: DecodeError) -> Self {
+        DecodeSliceError::Decode(e)
+    }
+}
+
+impl fmt::Display for DecodeSliceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeSliceError::BufferTooSmall(e) => e.fmt(f),
+            DecodeSliceError::Decode(e) => e.fmt(f),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DecodeSliceError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DecodeSliceError::BufferTooSmall(e) => Some(e),
+            DecodeSliceError::Decode(e) => Some(e),
+        }
+    }
+}
+
+/// Encode some bytes using base2048 encoding, writing the UTF-8 of the text into `out` and
+/// returning its length
+///
+/// Nothing is allocated. If `out` is too short, the error says how long it needs to be and what
+/// has been written to `out` is unspecified.
+///
+/// # Example
+/// ```
+/// let mut buf = [0; 16];
+/// let len = base2048::encode_to_slice(b"hello", &mut buf).unwrap();
+/// assert_eq!(&buf[..len], base2048::encode(b"hello").as_bytes());
+///
+/// let e = base2048::encode_to_slice(b"hello world", &mut buf).unwrap_err();
+/// assert_eq!(e.needed, base2048::encode(b"hello world").len());
+/// ```
+pub fn encode_to_slice(bytes: &[u8], out: &mut [u8]) -> Result<usize, BufferTooSmall> {
+    let mut len = 0;
+    encode_with(bytes, |c| {
+        // keep counting once it doesn't fit, to find out how much is needed
+        if let Some(dest) = out.get_mut(len..len + c.len_utf8()) {
+            c.encode_utf8(dest);
+        }
+        len += c.len_utf8();
+    });
+    if len <= out.len() {
+        Ok(len)
+    } else {
+        Err(BufferTooSmall { needed: len })
+    }
+}
+
+/// Decode a base2048 encoded string into `out`, returning the number of bytes written
+///
+/// Nothing is allocated. An invalid string is reported as such whatever the size of `out`; if it
+/// is valid but `out` is too short, the error says how long it needs to be. Either way, what has
+/// been written to `out` is unspecified.
+///
+/// # Example
+/// ```
+/// use base2048::{BufferTooSmall, DecodeSliceError};
+/// let mut buf = [0; 8];
+/// let len = base2048::decode_to_slice(&base2048::encode(b"hello"), &mut buf).unwrap();
+/// assert_eq!(&buf[..len], b"hello");
+/// assert_eq!(
+///     base2048::decode_to_slice(&base2048::encode(b"hello world"), &mut buf),
+///     Err(DecodeSliceError::BufferTooSmall(BufferTooSmall { needed: 11 }))
+/// );
+/// ```
+pub fn decode_to_slice(string: &str, out: &mut [u8]) -> Result<usize, DecodeSliceError> {
+    decode_to_slice_with(string, out, false)
+}
+
+/// Like [`decode_to_slice`], but rejecting non-canonical padding as
+/// [`try_decode_strict`](crate::try_decode_strict) does
+///
+/// # Example
+/// ```
+/// use base2048::{DecodeError, DecodeSliceError};
+/// let loose = base2048::encode(b"hi").replace('Ո', "Ԉ");
+/// assert_eq!(
+///     base2048::decode_to_slice_strict(&loose, &mut [0; 2]),
+///     Err(DecodeSliceError::Decode(DecodeError::InvalidPadding { char_index: 1 }))
+/// );
+/// ```
+pub fn decode_to_slice_strict(string: &str, out: &mut [u8]) -> Result<usize, DecodeSliceError> {
+    decode_to_slice_with(string, out, true)
+}
+
+fn decode_to_slice_with(
+    string: &str,
+    out: &mut [u8],
+    strict: bool,
+) -> Result<usize, DecodeSliceError> {
+    let mut len = 0;
+    decode_with_strict(string, strict, |byte| {
+        if let Some(dest) = out.get_mut(len) {
+            *dest = byte;
+        }
+        len += 1;
+    })?;
+    if len <= out.len() {
+        Ok(len)
+    } else {
+        Err(BufferTooSmall { needed: len }.into())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::encode;
+    use alloc::vec::Vec;
+
+    fn bytes(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i * 37) as u8).collect()
+    }
+
+    #[test]
+    fn exact_buffers() {
+        for len in 0..40 {
+            let bytes = bytes(len);
+            let encoded = encode(&bytes);
+            let mut buf = vec![0; encoded.len()];
+            assert_eq!(encode_to_slice(&bytes, &mut buf), Ok(encoded.len()));
+            assert_eq!(buf, encoded.as_bytes());
+            if !encoded.is_empty() {
+                assert_eq!(
+                    encode_to_slice(&bytes, &mut buf[1..]),
+                    Err(BufferTooSmall {
+                        needed: encoded.len()
+                    })
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn decode_exact_buffers() {
+        for len in 0..40 {
+            let encoded = encode(bytes(len));
+            let mut buf = vec![0; len];
+            assert_eq!(decode_to_slice(&encoded, &mut buf), Ok(len));
+            assert_eq!(buf, bytes(len));
+            if len > 0 {
+                assert_eq!(
+                    decode_to_slice(&encoded, &mut buf[1..]),
+                    Err(DecodeSliceError::BufferTooSmall(BufferTooSmall {
+                        needed: len
+                    }))
+                );
+            }
+        }
+        // invalid input is reported even when the buffer is too small as well
+        assert_eq!(
+            decode_to_slice("ետћζы༎X", &mut []),
+            Err(DecodeSliceError::Decode(DecodeError::InvalidChar {
+                c: '༎',
+                char_index: 5
+            }))
+        );
+    }
+
+    #[test]
+    fn decode_strict() {
+        let loose = encode(b"hi").replace('Ո', "Ԉ");
+        let mut buf = [0; 2];
+        assert_eq!(decode_to_slice_strict(&encode(b"hi"), &mut buf), Ok(2));
+        assert_eq!(decode_to_slice(&loose, &mut buf), Ok(2));
+        assert_eq!(&buf, b"hi");
+        let padding = DecodeSliceError::Decode(DecodeError::InvalidPadding { char_index: 1 });
+        assert_eq!(decode_to_slice_strict(&loose, &mut buf), Err(padding));
+        // the padding is checked whatever the size of the buffer
+        assert_eq!(decode_to_slice_strict(&loose, &mut []), Err(padding));
+    }
+}
@@ -0,0 +1,270 @@
+//! Splitting a payload across several length-limited messages, such as tweets.
+use crate::{decode_str, encode, encoded_len, BITS_PER_CHAR};
+use alloc::{string::String, vec::Vec};
+use core::fmt;
+
+/// The number of characters in a tweet.
+pub const TWEET_CHARS: usize = 280;
+
+/// The message index, message count and payload length at the start of each message.
+const HEADER_LEN: usize = 8;
+
+/// Split a payload into base2048 messages of at most `max_chars` characters each
+///
+/// Every message carries a small header so [`join`] can put them back together in any order.
+///
+/// # Panics
+///
+/// If `max_chars` is too small for a message to carry any of the payload, or the payload needs more
+/// than 65535 messages.
+///
+/// # Example
+/// ```
+/// let payload = vec![7; 1000];
+/// let messages = base2048::split(&payload, base2048::TWEET_CHARS);
+/// assert_eq!(messages.len(), 3);
+/// assert!(messages.iter().all(|m| m.chars().count() <= 280));
+/// assert_eq!(base2048::join(&messages), Ok(payload));
+/// ```
+pub fn split(bytes: &[u8], max_chars: usize) -> Vec<String> {
+    split_inner(bytes, max_chars, false)
+}
+
+/// Like [`split`] but adds a parity message, so the payload survives losing any one message
+///
+/// The parity message is the sum over GF(2¹¹) of the 11-bit symbols of the other messages, which
+/// for this field is their XOR. Since every message body has the same length, that is the same as
+/// encoding the XOR of the payload chunks.
+///
+/// # Panics
+///
+/// As for [`split`].
+///
+/// # Example
+/// ```
+/// let payload = vec![7; 1000];
+/// let mut messages = base2048::split_with_parity(&payload, base2048::TWEET_CHARS);
+/// assert_eq!(messages.len(), 4);
+/// messages.remove(1);
+/// assert_eq!(base2048::join(&messages), Ok(payload));
+/// ```
+pub fn split_with_parity(bytes: &[u8], max_chars: usize) -> Vec<String> {
+    split_inner(bytes, max_chars, true)
+}
+
+fn split_inner(bytes: &[u8], max_chars: usize, parity: bool) -> Vec<String> {
+    let max_chunk = (max_chars * BITS_PER_CHAR as usize / 8).saturating_sub(HEADER_LEN);
+    assert!(
+        max_chunk > 0,
+        "{} characters can't hold a message header and any of the payload",
+        max_chars
+    );
+    let count = bytes.len().div_ceil(max_chunk).max(1);
+    let chunk_len = bytes.len().div_ceil(count);
+    assert!(
+        count < u16::MAX as usize,
+        "the payload needs too many messages"
+    );
+    debug_assert!(encoded_len(HEADER_LEN + chunk_len) <= max_chars);
+
+    let mut messages = Vec::with_capacity(count + parity as usize);
+    let mut parity_chunk = vec![0; chunk_len];
+    let mut message = Vec::with_capacity(HEADER_LEN + chunk_len);
+    for index in 0..count {
+        let chunk = bytes.chunks(chunk_len.max(1)).nth(index).unwrap_or(&[]);
+        header(&mut message, index, count, bytes.len());
+        message.extend_from_slice(chunk);
+        // the last chunk is padded with zeros so every message is the same length
+        message.resize(HEADER_LEN + chunk_len, 0);
+        for (p, byte) in parity_chunk.iter_mut().zip(&message[HEADER_LEN..]) {
+            *p ^= byte;
+        }
+        messages.push(encode(&message));
+    }
+    if parity {
+        header(&mut message, count, count, bytes.len());
+        message.extend_from_slice(&parity_chunk);
+        messages.push(encode(&message));
+    }
+    messages
+}
+
+fn header(message: &mut Vec<u8>, index: usize, count: usize, len: usize) {
+    message.clear();
+    message.extend_from_slice(&(index as u16).to_be_bytes());
+    message.extend_from_slice(&(count as u16).to_be_bytes());
+    message.extend_from_slice(&(len as u32).to_be_bytes());
+}
+
+/// Why messages couldn't be joined back into a payload.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum JoinError {
+    /// The message isn't one made by [`split`].
+    Invalid {
+        /// Its position in the messages passed to [`join`].
+        message: usize,
+    },
+    /// The message is from a different payload than the ones before it.
+    Mismatched {
+        /// Its position in the messages passed to [`join`].
+        message: usize,
+    },
+    /// A message is missing and there is no parity message to recover it with, or more than one
+    /// is missing.
+    Missing {
+        /// The index of the first missing message.
+        index: u16,
+    },
+}
+
+#[cfg(not(feature = "compact-errors"))]
+impl fmt::Display for JoinError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JoinError::Invalid { message } => {
+                write!(f, "message {} is not a split message", message)
+            }
+            JoinError::Mismatched { message } => {
+                write!(f, "message {} belongs to a different payload", message)
+            }
+            JoinError::Missing { index } => write!(f, "message {} is missing", index),
+        }
+    }
+}
+
+#[cfg(feature = "compact-errors")]
+impl fmt::Display for JoinError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            JoinError::Invalid { .. } => "base2048 join error 1: invalid message",
+            JoinError::Mismatched { .. } => "base2048 join error 2: mismatched message",
+            JoinError::Missing { .. } => "base2048 join error 3: missing message",
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for JoinError {}
+
+/// Put a payload split by [`split`] or [`split_with_parity`] back together
+///
+/// The messages may come in any order and repeats are ignored. With a parity message, any one
+/// message can be missing.
+pub fn join<S: AsRef<str>>(messages: &[S]) -> Result<Vec<u8>, JoinError> {
+    // (count, len, chunk length) of the payload and its chunks, by index
+    let mut payload: Option<(u16, u32, usize)> = None;
+    let mut chunks: Vec<Option<Vec<u8>>> = Vec::new();
+    for (i, message) in messages.iter().enumerate() {
+        let bytes = decode_str(message.as_ref())
+            .filter(|bytes| bytes.len() >= HEADER_LEN)
+            .ok_or(JoinError::Invalid { message: i })?;
+        let index = u16::from_be_bytes([bytes[0], bytes[1]]);
+        let count = u16::from_be_bytes([bytes[2], bytes[3]]);
+        let len = u32::from_be_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]);
+        let chunk = &bytes[HEADER_LEN..];
+        if count == 0 || index > count || (chunk.len() * count as usize) < len as usize {
+            return Err(JoinError::Invalid { message: i });
+        }
+
+        let this = (count, len, chunk.len());
+        if *payload.get_or_insert(this) != this {
+            return Err(JoinError::Mismatched { message: i });
+        }
+        if chunks.is_empty() {
+            chunks.resize(count as usize + 1, None);
+        }
+        match &chunks[index as usize] {
+            Some(existing) if existing[..] != *chunk => {
+                return Err(JoinError::Mismatched { message: i })
+            }
+            _ => chunks[index as usize] = Some(chunk.to_vec()),
+        }
+    }
+
+    let (count, len, _) = payload.ok_or(JoinError::Missing { index: 0 })?;
+    let parity = chunks.pop().flatten();
+    if let Some(index) = chunks.iter().position(Option::is_none) {
+        let more_missing = chunks[index + 1..].iter().any(Option::is_none);
+        match parity {
+            Some(mut recovered) if !more_missing => {
+                for chunk in chunks.iter().flatten() {
+                    for (r, byte) in recovered.iter_mut().zip(chunk) {
+                        *r ^= byte;
+                    }
+                }
+                chunks[index] = Some(recovered);
+            }
+            _ => {
+                return Err(JoinError::Missing {
+                    index: index as u16,
+                })
+            }
+        }
+    }
+    debug_assert_eq!(chunks.len(), count as usize);
+
+    let mut ret = chunks.into_iter().flatten().flatten().collect::<Vec<_>>();
+    ret.truncate(len as usize);
+    Ok(ret)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn payload(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i * 37) as u8).collect()
+    }
+
+    #[test]
+    fn split_join_roundtrip() {
+        for len in [0, 1, 10, 377, 378, 379, 1000, 5000] {
+            let bytes = payload(len);
+            let messages = split(&bytes, TWEET_CHARS);
+            assert!(messages.iter().all(|m| m.chars().count() <= TWEET_CHARS));
+            assert_eq!(join(&messages), Ok(bytes.clone()));
+
+            let mut reversed = messages.clone();
+            reversed.reverse();
+            reversed.push(messages[0].clone());
+            assert_eq!(join(&reversed), Ok(bytes));
+        }
+    }
+
+    #[test]
+    fn parity_recovers_any_one_message() {
+        for len in [0, 10, 1000, 5000] {
+            let bytes = payload(len);
+            let messages = split_with_parity(&bytes, 100);
+            assert!(messages.iter().all(|m| m.chars().count() <= 100));
+            for lost in 0..messages.len() {
+                let mut received = messages.clone();
+                received.remove(lost);
+                if received.is_empty() {
+                    continue;
+                }
+                assert_eq!(join(&received), Ok(bytes.clone()));
+            }
+        }
+    }
+
+    #[test]
+    fn errors() {
+        let messages = split(&payload(1000), TWEET_CHARS);
+        assert_eq!(join(&messages[1..]), Err(JoinError::Missing { index: 0 }));
+        let mut parity = split_with_parity(&payload(1000), TWEET_CHARS);
+        parity.drain(..2);
+        assert_eq!(join(&parity), Err(JoinError::Missing { index: 0 }));
+
+        let other = split(&payload(999), TWEET_CHARS);
+        assert_eq!(
+            join(&[&messages[0], &other[1]]),
+            Err(JoinError::Mismatched { message: 1 })
+        );
+        assert_eq!(
+            join(&[&messages[0], "hello"]),
+            Err(JoinError::Invalid { message: 1 })
+        );
+        assert_eq!(join::<&str>(&[]), Err(JoinError::Missing { index: 0 }));
+    }
+}
@@ -0,0 +1,165 @@
+//! [`abi_stable`] wrappers for loading the codec across Rust dylib boundaries (the `abi_stable`
+//! feature).
+//!
+//! Plain Rust types have no stable layout between compiler versions, so a plugin can't hand a
+//! `String` or [`DecodeError`] to a host built separately. These functions use `abi_stable`'s types
+//! instead and can go straight into a plugin's module struct:
+//!
+//! ```
+//! use abi_stable::{
+//!     std_types::{RResult, RSlice, RStr, RString, RVec},
+//!     StableAbi,
+//! };
+//! use base2048::stable_abi::{self, StableDecodeError};
+//!
+//! #[repr(C)]
+//! #[derive(StableAbi)]
+//! pub struct CodecModule {
+//!     pub encode: extern "C" fn(RSlice<'_, u8>) -> RString,
+//!     pub decode: extern "C" fn(RStr<'_>) -> RResult<RVec<u8>, StableDecodeError>,
+//! }
+//!
+//! let module = CodecModule {
+//!     encode: stable_abi::encode,
+//!     decode: stable_abi::decode,
+//! };
+//! let encoded = (module.encode)(RSlice::from_slice(b"hello"));
+//! assert_eq!((module.decode)(encoded.as_rstr()).unwrap().as_slice(), b"hello");
+//! ```
+use crate::{decode_with, decoded_str_len, encode_bytes, DecodeError};
+use abi_stable::{
+    std_types::{RErr, ROk, RResult, RSlice, RStr, RString, RVec},
+    StableAbi,
+};
+use alloc::vec::Vec;
+use core::{convert::TryFrom, fmt};
+
+/// [`DecodeError`] with a stable layout.
+///
+/// The character of `InvalidChar` is kept as its code point since `char` isn't a `StableAbi` type.
+#[repr(u8)]
+#[derive(StableAbi, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StableDecodeError {
+    /// See [`DecodeError::InvalidChar`].
+    InvalidChar {
+        /// The offending character's code point.
+        c: u32,
+        /// Its position, counted in characters.
+        char_index: usize,
+    },
+    /// See [`DecodeError::TrailingData`].
+    TrailingData {
+        /// The position of the tail character, counted in characters.
+        char_index: usize,
+    },
+    /// See [`DecodeError::UnexpectedTail`].
+    UnexpectedTail {
+        /// The position of the tail character, counted in characters.
+        char_index: usize,
+    },
+    /// See [`DecodeError::InvalidPadding`].
+    InvalidPadding {
+        /// The position of the final character, counted in characters.
+        char_index: usize,
+    },
+}
+
+impl From<DecodeError> for StableDecodeError {
+    fn from(e: DecodeError) -> Self {
+        match e {
+            DecodeError::InvalidChar { c, char_index } => StableDecodeError::InvalidChar {
+                c: c as u32,
+                char_index,
+            },
+            DecodeError::TrailingData { char_index } => {
+                StableDecodeError::TrailingData { char_index }
+            }
+            DecodeError::UnexpectedTail { char_index } => {
+                StableDecodeError::UnexpectedTail { char_index }
+            }
+            DecodeError::InvalidPadding { char_index } => {
+                StableDecodeError::InvalidPadding { char_index }
+            }
+        }
+    }
+}
+
+/// Fails only for an `InvalidChar` whose code point isn't a `char`, which can't come from this
+/// crate.
+impl TryFrom<StableDecodeError> for DecodeError {
+    type Error = StableDecodeError;
+
+    fn try_from(e: StableDecodeError) -> Result<Self, StableDecodeError> {
+        Ok(match e {
+            StableDecodeError::InvalidChar { c, char_index } => DecodeError::InvalidChar {
+                c: char::from_u32(c).ok_or(e)?,
+                char_index,
+            },
+            StableDecodeError::TrailingData { char_index } => {
+                DecodeError::TrailingData { char_index }
+            }
+            StableDecodeError::UnexpectedTail { char_index } => {
+                DecodeError::UnexpectedTail { char_index }
+            }
+            StableDecodeError::InvalidPadding { char_index } => {
+                DecodeError::InvalidPadding { char_index }
+            }
+        })
+    }
+}
+
+impl fmt::Display for StableDecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match DecodeError::try_from(*self) {
+            Ok(e) => e.fmt(f),
+            Err(_) => f.write_str("invalid character"),
+        }
+    }
+}
+
+impl std::error::Error for StableDecodeError {}
+
+/// Encode some bytes using base2048 encoding, with `abi_stable` types.
+pub extern "C" fn encode(bytes: RSlice<'_, u8>) -> RString {
+    encode_bytes(bytes.as_slice()).into()
+}
+
+/// Decode a base2048 encoded string, with `abi_stable` types.
+pub extern "C" fn decode(string: RStr<'_>) -> RResult<RVec<u8>, StableDecodeError> {
+    let mut ret = Vec::with_capacity(decoded_str_len(string.as_str()));
+    match decode_with(string.as_str(), |byte| ret.push(byte)) {
+        Ok(()) => ROk(ret.into()),
+        Err(e) => RErr(e.into()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::string::ToString;
+
+    #[test]
+    fn roundtrip() {
+        let bytes = (0..40).map(|i| (i * 37) as u8).collect::<Vec<_>>();
+        let encoded = encode(RSlice::from_slice(&bytes));
+        assert_eq!(encoded.as_str(), crate::encode(&bytes));
+        assert_eq!(decode(encoded.as_rstr()).unwrap().as_slice(), &bytes[..]);
+    }
+
+    #[test]
+    fn errors() {
+        let e = decode(RStr::from_str("ետћζы༎X")).unwrap_err();
+        let original = DecodeError::InvalidChar {
+            c: '༎',
+            char_index: 5,
+        };
+        assert_eq!(e, original.into());
+        assert_eq!(DecodeError::try_from(e), Ok(original));
+        assert_eq!(e.to_string(), original.to_string());
+        let bad = StableDecodeError::InvalidChar {
+            c: 0xD800,
+            char_index: 0,
+        };
+        assert_eq!(DecodeError::try_from(bad), Err(bad));
+    }
+}
@@ -0,0 +1,564 @@
+//! Adapters between [`std::io`] and base2048 text.
+use crate::{
+    decoder::CharDecoder,
+    stream::{StreamError, Utf8Chunks},
+    DecodeError, EncodeState,
+};
+use alloc::vec::Vec;
+use core::fmt::Debug;
+use std::io::{self, Read, Write};
+
+pub(crate) const BUF_LEN: usize = 8 * 1024;
+
+/// An encoding the adapters can work with, a byte or a character at a time.
+///
+/// The adapters for base2048 and [`base32768`](crate::base32768) share everything else.
+pub(crate) trait Scheme: Debug + Default {
+    type Encode: Debug + Default;
+    type Decode: Debug + Default;
+
+    /// Adds a byte, returning the character it completes (if any).
+    fn push_byte(state: &mut Self::Encode, byte: u8) -> Option<char>;
+
+    /// The final character, if the input so far needs one.
+    fn finish_bytes(state: Self::Encode) -> Option<char>;
+
+    /// Adds a character, handing the bytes it completes to `push`.
+    fn push_char(
+        state: &mut Self::Decode,
+        c: char,
+        push: impl FnMut(u8),
+    ) -> Result<(), DecodeError>;
+
+    /// Ends the text, handing the last bytes to `push`.
+    fn finish_chars(state: Self::Decode, push: impl FnMut(u8)) -> Result<(), DecodeError>;
+}
+
+/// The standard base2048 [`Scheme`].
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct Base2048;
+
+impl Scheme for Base2048 {
+    type Encode = EncodeState;
+    type Decode = CharDecoder;
+
+    fn push_byte(state: &mut EncodeState, byte: u8) -> Option<char> {
+        state.push(byte)
+    }
+
+    fn finish_bytes(state: EncodeState) -> Option<char> {
+        state.finish()
+    }
+
+    fn push_char(
+        state: &mut CharDecoder,
+        c: char,
+        push: impl FnMut(u8),
+    ) -> Result<(), DecodeError> {
+        state.push(c, push)
+    }
+
+    fn finish_chars(state: CharDecoder, push: impl FnMut(u8)) -> Result<(), DecodeError> {
+        state.finish(push)
+    }
+}
+
+/// Encoded text waiting to be written, shared by the blocking and async writers.
+#[derive(Debug, Default)]
+pub(crate) struct EncodeBuf<S: Scheme = Base2048> {
+    state: S::Encode,
+    buf: Vec<u8>,
+    written: usize,
+}
+
+impl<S: Scheme> EncodeBuf<S> {
+    /// Whether the text should be written out before encoding more.
+    pub(crate) fn is_full(&self) -> bool {
+        self.buf.len() >= BUF_LEN
+    }
+
+    /// Encodes as much of `bytes` as the buffer takes at once, returning how much that was.
+    pub(crate) fn encode(&mut self, bytes: &[u8]) -> usize {
+        let bytes = &bytes[..bytes.len().min(BUF_LEN)];
+        let mut utf8 = [0; 4];
+        for byte in bytes {
+            if let Some(c) = S::push_byte(&mut self.state, *byte) {
+                self.buf
+                    .extend_from_slice(c.encode_utf8(&mut utf8).as_bytes());
+            }
+        }
+        bytes.len()
+    }
+
+    /// Encodes the final character, if the input so far needs one.
+    pub(crate) fn finish(&mut self) {
+        let mut utf8 = [0; 4];
+        if let Some(c) = S::finish_bytes(core::mem::take(&mut self.state)) {
+            self.buf
+                .extend_from_slice(c.encode_utf8(&mut utf8).as_bytes());
+        }
+    }
+
+    /// The text not written yet.
+    pub(crate) fn pending(&self) -> &[u8] {
+        &self.buf[self.written..]
+    }
+
+    /// Marks `n` more bytes of the text as written.
+    pub(crate) fn consume(&mut self, n: usize) {
+        self.written += n;
+        if self.written == self.buf.len() {
+            self.buf.clear();
+            self.written = 0;
+        }
+    }
+}
+
+/// Decoded bytes waiting to be read, shared by the blocking and async readers.
+#[derive(Debug)]
+pub(crate) struct DecodeBuf<S: Scheme = Base2048> {
+    chunks: Utf8Chunks,
+    // `None` once the end of the text has been decoded
+    decoder: Option<S::Decode>,
+    error: Option<StreamError>,
+    out: Vec<u8>,
+    pos: usize,
+}
+
+impl<S: Scheme> Default for DecodeBuf<S> {
+    fn default() -> Self {
+        DecodeBuf {
+            chunks: Utf8Chunks::default(),
+            decoder: Some(S::Decode::default()),
+            error: None,
+            out: Vec::new(),
+            pos: 0,
+        }
+    }
+}
+
+impl<S: Scheme> DecodeBuf<S> {
+    /// Reads decoded bytes into `dest`, or `None` if more text is needed first.
+    ///
+    /// The bytes decoded before an error are read before it is returned, and then it is returned
+    /// every time.
+    pub(crate) fn read(&mut self, dest: &mut [u8]) -> Option<io::Result<usize>> {
+        if self.pos < self.out.len() {
+            let n = dest.len().min(self.out.len() - self.pos);
+            dest[..n].copy_from_slice(&self.out[self.pos..self.pos + n]);
+            self.pos += n;
+            Some(Ok(n))
+        } else if let Some(e) = self.error {
+            Some(Err(e.into()))
+        } else if self.decoder.is_none() || dest.is_empty() {
+            Some(Ok(0))
+        } else {
+            None
+        }
+    }
+
+    /// Decodes the next block of text, where an empty one means the end of it.
+    pub(crate) fn push(&mut self, text: &[u8]) {
+        self.out.clear();
+        self.pos = 0;
+        let out = &mut self.out;
+        let result = if text.is_empty() {
+            let decoder = match self.decoder.take() {
+                Some(decoder) => decoder,
+                None => return,
+            };
+            self.chunks.finish().and_then(|()| {
+                S::finish_chars(decoder, |byte| out.push(byte)).map_err(StreamError::from)
+            })
+        } else {
+            let decoder = match &mut self.decoder {
+                Some(decoder) => decoder,
+                None => return,
+            };
+            self.chunks.push(text, |c| {
+                S::push_char(decoder, c, |byte| out.push(byte)).map_err(StreamError::from)
+            })
+        };
+        self.error = result.err();
+    }
+}
+
+impl DecodeBuf {
+    /// Rejects a final character with non-canonical padding if `strict`.
+    pub(crate) fn set_strict(&mut self, strict: bool) {
+        if let Some(decoder) = &mut self.decoder {
+            decoder.set_strict(strict);
+        }
+    }
+}
+
+/// The workings of [`EncodeWriter`], for any [`Scheme`].
+#[derive(Debug)]
+pub(crate) struct Writer<W: Write, S: Scheme> {
+    // `None` once finished
+    inner: Option<W>,
+    buf: EncodeBuf<S>,
+}
+
+impl<W: Write, S: Scheme> Writer<W, S> {
+    pub(crate) fn new(inner: W) -> Self {
+        Writer {
+            inner: Some(inner),
+            buf: EncodeBuf::default(),
+        }
+    }
+
+    pub(crate) fn get_ref(&self) -> &W {
+        self.inner.as_ref().expect("writer is only taken by finish")
+    }
+
+    pub(crate) fn get_mut(&mut self) -> &mut W {
+        self.inner.as_mut().expect("writer is only taken by finish")
+    }
+
+    pub(crate) fn finish(mut self) -> io::Result<W> {
+        self.buf.finish();
+        self.write_buf()?;
+        let mut inner = self.inner.take().expect("writer is only taken by finish");
+        inner.flush()?;
+        Ok(inner)
+    }
+
+    /// Writes out the buffered text, keeping whatever couldn't be written.
+    fn write_buf(&mut self) -> io::Result<()> {
+        let inner = self.inner.as_mut().expect("writer is only taken by finish");
+        while !self.buf.pending().is_empty() {
+            match inner.write(self.buf.pending()) {
+                Ok(0) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::WriteZero,
+                        "failed to write the encoded text",
+                    ))
+                }
+                Ok(n) => self.buf.consume(n),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write, S: Scheme> Write for Writer<W, S> {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        if self.buf.is_full() {
+            self.write_buf()?;
+        }
+        Ok(self.buf.encode(bytes))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.write_buf()?;
+        self.get_mut().flush()
+    }
+}
+
+impl<W: Write, S: Scheme> Drop for Writer<W, S> {
+    fn drop(&mut self) {
+        // don't write after a panic in the underlying writer, as `BufWriter` doesn't
+        if self.inner.is_some() && !std::thread::panicking() {
+            self.buf.finish();
+            let _ = self.write_buf();
+        }
+    }
+}
+
+/// The workings of [`DecodeReader`], for any [`Scheme`].
+#[derive(Debug)]
+pub(crate) struct Reader<R: Read, S: Scheme> {
+    inner: R,
+    text: Vec<u8>,
+    buf: DecodeBuf<S>,
+}
+
+impl<R: Read, S: Scheme> Reader<R, S> {
+    pub(crate) fn new(inner: R) -> Self {
+        Reader {
+            inner,
+            text: vec![0; BUF_LEN],
+            buf: DecodeBuf::default(),
+        }
+    }
+
+    pub(crate) fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    pub(crate) fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    pub(crate) fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read, S: Scheme> Read for Reader<R, S> {
+    fn read(&mut self, dest: &mut [u8]) -> io::Result<usize> {
+        loop {
+            if let Some(result) = self.buf.read(dest) {
+                return result;
+            }
+            let n = self.inner.read(&mut self.text)?;
+            self.buf.push(&self.text[..n]);
+        }
+    }
+}
+
+/// Encodes everything written to it, writing the UTF-8 base2048 text to an inner writer.
+///
+/// The encoded text is buffered and written out in blocks, as by [`io::BufWriter`]. The bits left
+/// over at the end of the input are only written by [`finish`](Self::finish), since whether they
+/// need a tail character depends on there being no more input. Dropping the writer finishes it
+/// too, but ignores any errors doing so.
+///
+/// # Example
+/// ```
+/// use std::io::Write;
+/// let mut writer = base2048::EncodeWriter::new(Vec::new());
+/// writer.write_all(b"hel")?;
+/// writer.write_all(b"lo")?;
+/// let encoded = writer.finish()?;
+/// assert_eq!(encoded, base2048::encode(b"hello").into_bytes());
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct EncodeWriter<W: Write>(Writer<W, Base2048>);
+
+impl<W: Write> EncodeWriter<W> {
+    /// Writes the encoded text to `inner`.
+    pub fn new(inner: W) -> Self {
+        EncodeWriter(Writer::new(inner))
+    }
+
+    /// The underlying writer.
+    pub fn get_ref(&self) -> &W {
+        self.0.get_ref()
+    }
+
+    /// The underlying writer.
+    ///
+    /// Writing to it directly puts the text out of order with what is still buffered.
+    pub fn get_mut(&mut self) -> &mut W {
+        self.0.get_mut()
+    }
+
+    /// Writes out the final character and everything still buffered, then gives back the
+    /// underlying writer.
+    pub fn finish(self) -> io::Result<W> {
+        self.0.finish()
+    }
+}
+
+impl<W: Write> Write for EncodeWriter<W> {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        self.0.write(bytes)
+    }
+
+    /// Writes out the buffered text and flushes the underlying writer.
+    ///
+    /// The bits left over from the input so far stay pending, since the next write may complete
+    /// them.
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+/// Decodes UTF-8 base2048 text read from an inner reader, giving the decoded bytes.
+///
+/// The text is read in blocks, and characters split between them are put back together. Invalid
+/// input is reported as an [`io::ErrorKind::InvalidData`] error, carrying a
+/// [`DecodeError`](crate::DecodeError) when the text isn't valid base2048, once the bytes decoded
+/// before it have been read. The same error is returned by every read after that.
+///
+/// # Example
+/// ```
+/// use std::io::Read;
+/// let encoded = base2048::encode(b"hello");
+/// let mut reader = base2048::DecodeReader::new(encoded.as_bytes());
+/// let mut decoded = Vec::new();
+/// reader.read_to_end(&mut decoded)?;
+/// assert_eq!(decoded, b"hello");
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct DecodeReader<R: Read>(Reader<R, Base2048>);
+
+impl<R: Read> DecodeReader<R> {
+    /// Reads the encoded text from `inner`.
+    pub fn new(inner: R) -> Self {
+        DecodeReader(Reader::new(inner))
+    }
+
+    /// Whether a final character whose padding bits aren't all set is rejected, as
+    /// [`try_decode_strict`](crate::try_decode_strict) rejects it.
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.0.buf.set_strict(strict);
+        self
+    }
+
+    /// The underlying reader.
+    pub fn get_ref(&self) -> &R {
+        self.0.get_ref()
+    }
+
+    /// The underlying reader.
+    ///
+    /// Reading from it directly skips text this reader would have decoded.
+    pub fn get_mut(&mut self) -> &mut R {
+        self.0.get_mut()
+    }
+
+    /// Gives back the underlying reader, dropping anything read from it but not yet decoded.
+    pub fn into_inner(self) -> R {
+        self.0.into_inner()
+    }
+}
+
+impl<R: Read> Read for DecodeReader<R> {
+    fn read(&mut self, dest: &mut [u8]) -> io::Result<usize> {
+        self.0.read(dest)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{encode, DecodeError};
+
+    fn bytes(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i * 37) as u8).collect()
+    }
+
+    #[test]
+    fn chunks_match_encode() {
+        let bytes = bytes(100);
+        for chunk_len in 1..25 {
+            let mut writer = EncodeWriter::new(Vec::new());
+            for chunk in bytes.chunks(chunk_len) {
+                writer.write_all(chunk).unwrap();
+            }
+            assert_eq!(writer.finish().unwrap(), encode(&bytes).into_bytes());
+        }
+    }
+
+    #[test]
+    fn copy() {
+        let bytes = bytes(100_000);
+        let mut writer = EncodeWriter::new(Vec::new());
+        io::copy(&mut &bytes[..], &mut writer).unwrap();
+        assert_eq!(writer.finish().unwrap(), encode(&bytes).into_bytes());
+    }
+
+    #[test]
+    fn drop_finishes() {
+        let mut out = Vec::new();
+        {
+            let mut writer = EncodeWriter::new(&mut out);
+            writer.write_all(b"hello").unwrap();
+        }
+        assert_eq!(out, encode(b"hello").into_bytes());
+    }
+
+    /// Accepts `room` bytes, then fails.
+    struct Limited {
+        out: Vec<u8>,
+        room: usize,
+    }
+
+    impl Write for Limited {
+        fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+            if self.room == 0 {
+                return Err(io::Error::other("full"));
+            }
+            let n = bytes.len().min(self.room);
+            self.out.extend_from_slice(&bytes[..n]);
+            self.room -= n;
+            Ok(n)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn errors_keep_the_text() {
+        let mut writer = EncodeWriter::new(Limited {
+            out: Vec::new(),
+            room: 5,
+        });
+        writer.write_all(b"hello world").unwrap();
+        assert!(writer.flush().is_err());
+        writer.get_mut().room = usize::MAX;
+        let out = writer.finish().unwrap().out;
+        assert_eq!(out, encode(b"hello world").into_bytes());
+    }
+
+    #[test]
+    fn read_matches_decode() {
+        let bytes = bytes(100_000);
+        let encoded = encode(&bytes);
+        let mut decoded = Vec::new();
+        DecodeReader::new(encoded.as_bytes())
+            .read_to_end(&mut decoded)
+            .unwrap();
+        assert_eq!(decoded, bytes);
+
+        // one byte at a time splits every character but the ASCII ones
+        let mut reader = DecodeReader::new(io::BufReader::with_capacity(1, encoded.as_bytes()));
+        let mut decoded = Vec::new();
+        let mut byte = [0];
+        while reader.read(&mut byte).unwrap() == 1 {
+            decoded.push(byte[0]);
+        }
+        assert_eq!(decoded, bytes);
+    }
+
+    #[test]
+    fn invalid_input() {
+        let mut reader = DecodeReader::new("ետћζы༎X".as_bytes());
+        let mut decoded = Vec::new();
+        let e = reader.read_to_end(&mut decoded).unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(
+            e.into_inner().unwrap().downcast_ref::<DecodeError>(),
+            Some(&DecodeError::InvalidChar {
+                c: '༎',
+                char_index: 5
+            })
+        );
+        // what decoded before it is kept, and the error sticks
+        assert_eq!(decoded.len(), 5 * 11 / 8);
+        assert!(reader.read(&mut [0; 8]).is_err());
+
+        let e = DecodeReader::new(&[0xE0, 0xA4][..])
+            .read_to_end(&mut Vec::new())
+            .unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn strict() {
+        let loose = encode(b"hi").replace('Ո', "Ԉ");
+        let mut decoded = Vec::new();
+        DecodeReader::new(loose.as_bytes())
+            .read_to_end(&mut decoded)
+            .unwrap();
+        assert_eq!(decoded, b"hi");
+
+        let e = DecodeReader::new(loose.as_bytes())
+            .with_strict(true)
+            .read_to_end(&mut Vec::new())
+            .unwrap_err();
+        assert_eq!(
+            e.into_inner().unwrap().downcast_ref::<DecodeError>(),
+            Some(&DecodeError::InvalidPadding { char_index: 1 })
+        );
+    }
+}
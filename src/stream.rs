@@ -0,0 +1,154 @@
+//! Building blocks for decoding input that arrives in pieces.
+use crate::DecodeError;
+
+/// Why a stream of UTF-8 bytes couldn't be decoded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum StreamError {
+    /// The bytes weren't valid UTF-8.
+    Utf8,
+    /// The text wasn't valid base2048.
+    Decode(DecodeError),
+}
+
+impl From<DecodeError> for StreamError {
+    fn from(e: DecodeError) -> Self {
+        StreamError::Decode(e)
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<StreamError> for std::io::Error {
+    fn from(e: StreamError) -> Self {
+        match e {
+            StreamError::Utf8 => std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "stream did not contain valid UTF-8",
+            ),
+            StreamError::Decode(e) => e.into(),
+        }
+    }
+}
+
+/// Reassembles characters whose UTF-8 encoding is split between chunks.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct Utf8Chunks {
+    partial: [u8; 4],
+    len: usize,
+}
+
+impl Utf8Chunks {
+    /// Hands every complete character in `bytes` (and any started in earlier chunks) to `f`.
+    pub(crate) fn push<E: From<StreamError>>(
+        &mut self,
+        mut bytes: &[u8],
+        mut f: impl FnMut(char) -> Result<(), E>,
+    ) -> Result<(), E> {
+        if self.len > 0 {
+            let width = utf8_width(self.partial[0]);
+            let take = (width - self.len).min(bytes.len());
+            self.partial[self.len..self.len + take].copy_from_slice(&bytes[..take]);
+            self.len += take;
+            bytes = &bytes[take..];
+            if self.len < width {
+                return Ok(());
+            }
+            let c = core::str::from_utf8(&self.partial[..width])
+                .map_err(|_| StreamError::Utf8)?
+                .chars()
+                .next()
+                .unwrap();
+            self.len = 0;
+            f(c)?;
+        }
+
+        let text = match core::str::from_utf8(bytes) {
+            Ok(text) => text,
+            Err(e) => {
+                let (valid, rest) = bytes.split_at(e.valid_up_to());
+                if e.error_len().is_some() || utf8_width(rest[0]) == 0 {
+                    return Err(StreamError::Utf8.into());
+                }
+                // the chunk ends part way through a character
+                self.partial[..rest.len()].copy_from_slice(rest);
+                self.len = rest.len();
+                // SAFETY: `from_utf8` just checked this much
+                unsafe { core::str::from_utf8_unchecked(valid) }
+            }
+        };
+        for c in text.chars() {
+            f(c)?;
+        }
+        Ok(())
+    }
+
+    /// Checks that the input didn't stop part way through a character.
+    pub(crate) fn finish(&self) -> Result<(), StreamError> {
+        if self.len > 0 {
+            Err(StreamError::Utf8)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// The length of a UTF-8 sequence given its first byte, 0 if it can't start one.
+fn utf8_width(first: u8) -> usize {
+    match first {
+        0x00..=0x7F => 1,
+        0xC2..=0xDF => 2,
+        0xE0..=0xEF => 3,
+        0xF0..=0xF4 => 4,
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{decode, decoder::CharDecoder, encode};
+    use alloc::vec::Vec;
+
+    fn decode_in_chunks(bytes: &[u8], chunk_len: usize) -> Result<Vec<u8>, StreamError> {
+        let mut ret = Vec::new();
+        let mut chunks = Utf8Chunks::default();
+        let mut decoder = CharDecoder::default();
+        for chunk in bytes.chunks(chunk_len) {
+            chunks.push(chunk, |c| {
+                decoder
+                    .push(c, |byte| ret.push(byte))
+                    .map_err(StreamError::from)
+            })?;
+        }
+        chunks.finish()?;
+        decoder.finish(|byte| ret.push(byte))?;
+        Ok(ret)
+    }
+
+    #[test]
+    fn any_chunking_decodes_the_same() {
+        let bytes = (0..100u32).map(|i| (i * 23) as u8).collect::<Vec<_>>();
+        let encoded = encode(&bytes);
+        for chunk_len in 1..10 {
+            assert_eq!(
+                decode_in_chunks(encoded.as_bytes(), chunk_len).ok(),
+                decode(&encoded)
+            );
+        }
+    }
+
+    #[test]
+    fn bad_utf8() {
+        // the second character is cut short
+        let truncated = &"8ၕ".as_bytes()[..3];
+        for chunk_len in 1..4 {
+            assert_eq!(
+                decode_in_chunks(truncated, chunk_len),
+                Err(StreamError::Utf8)
+            );
+            assert_eq!(
+                decode_in_chunks(b"88\xFF8", chunk_len),
+                Err(StreamError::Utf8)
+            );
+        }
+    }
+}
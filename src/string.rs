@@ -0,0 +1,265 @@
+//! A string known to hold valid base2048.
+use crate::{decode, decode_with, encode, DecodeError, EncodeState};
+use alloc::{string::String, vec::Vec};
+use core::{borrow::Borrow, convert::TryFrom, fmt, iter::FromIterator, ops::Deref, str::FromStr};
+
+/// A `String` holding the base2048 encoding of some bytes.
+///
+/// Made by encoding, or from text that is checked when parsed, so it always decodes.
+///
+/// # Example
+/// ```
+/// use base2048::Base2048String;
+/// let encoded = b"hello".iter().copied().collect::<Base2048String>();
+/// assert_eq!(encoded, base2048::encode(b"hello"));
+/// assert_eq!(encoded.decode(), b"hello");
+///
+/// let parsed = base2048::encode(b"hello").parse::<Base2048String>().unwrap();
+/// assert_eq!(parsed, encoded);
+/// assert!("ետћζы༎X".parse::<Base2048String>().is_err());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Base2048String(String);
+
+impl Base2048String {
+    /// Encodes `bytes`.
+    pub fn new(bytes: &[u8]) -> Self {
+        Base2048String(encode(bytes))
+    }
+
+    /// The encoded text.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The encoded text, giving up the guarantee that it is valid.
+    pub fn into_string(self) -> String {
+        self.0
+    }
+
+    /// The bytes this string encodes.
+    pub fn decode(&self) -> Vec<u8> {
+        decode(&self.0).expect("a Base2048String is always valid base2048")
+    }
+
+    /// Borrows the text as a [`Base2048Str`].
+    pub fn as_base2048_str(&self) -> &Base2048Str {
+        Base2048Str::from_str_unchecked(&self.0)
+    }
+}
+
+/// A `str` holding valid base2048, the borrowed form of [`Base2048String`].
+///
+/// # Example
+/// ```
+/// use base2048::Base2048Str;
+/// let encoded = base2048::encode(b"hello");
+/// let checked = Base2048Str::new(&encoded).unwrap();
+/// assert_eq!(checked.decode(), b"hello");
+/// assert!(Base2048Str::new("ետћζы༎X").is_err());
+/// ```
+#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[repr(transparent)]
+pub struct Base2048Str(str);
+
+impl Base2048Str {
+    /// Checks that `string` is valid base2048.
+    pub fn new(string: &str) -> Result<&Base2048Str, DecodeError> {
+        decode_with(string, |_| ())?;
+        Ok(Base2048Str::from_str_unchecked(string))
+    }
+
+    fn from_str_unchecked(string: &str) -> &Base2048Str {
+        // SAFETY: Base2048Str is a repr(transparent) wrapper around str
+        unsafe { &*(string as *const str as *const Base2048Str) }
+    }
+
+    /// The encoded text.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The bytes this string encodes.
+    pub fn decode(&self) -> Vec<u8> {
+        decode(&self.0).expect("a Base2048Str is always valid base2048")
+    }
+}
+
+impl Deref for Base2048Str {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for Base2048Str {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Base2048Str {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl alloc::borrow::ToOwned for Base2048Str {
+    type Owned = Base2048String;
+
+    fn to_owned(&self) -> Base2048String {
+        Base2048String(String::from(&self.0))
+    }
+}
+
+impl Borrow<Base2048Str> for Base2048String {
+    fn borrow(&self) -> &Base2048Str {
+        self.as_base2048_str()
+    }
+}
+
+impl AsRef<Base2048Str> for Base2048String {
+    fn as_ref(&self) -> &Base2048Str {
+        self.as_base2048_str()
+    }
+}
+
+impl FromIterator<u8> for Base2048String {
+    fn from_iter<I: IntoIterator<Item = u8>>(iter: I) -> Self {
+        let mut ret = String::new();
+        let mut state = EncodeState::default();
+        for byte in iter {
+            if let Some(c) = state.push(byte) {
+                ret.push(c);
+            }
+        }
+        if let Some(c) = state.finish() {
+            ret.push(c);
+        }
+        Base2048String(ret)
+    }
+}
+
+impl<'a> FromIterator<&'a u8> for Base2048String {
+    fn from_iter<I: IntoIterator<Item = &'a u8>>(iter: I) -> Self {
+        iter.into_iter().copied().collect()
+    }
+}
+
+impl Deref for Base2048String {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for Base2048String {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Base2048String {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<Base2048String> for String {
+    fn from(s: Base2048String) -> Self {
+        s.0
+    }
+}
+
+impl FromStr for Base2048String {
+    type Err = DecodeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Base2048String(Base2048Str::new(s)?.as_str().into()))
+    }
+}
+
+impl TryFrom<&str> for Base2048String {
+    type Error = DecodeError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl TryFrom<String> for Base2048String {
+    type Error = DecodeError;
+
+    /// Keeps the allocation of `s` if it is valid.
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        Base2048Str::new(&s)?;
+        Ok(Base2048String(s))
+    }
+}
+
+impl PartialEq<str> for Base2048String {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
+    }
+}
+
+impl PartialEq<&str> for Base2048String {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == *other
+    }
+}
+
+impl PartialEq<String> for Base2048String {
+    fn eq(&self, other: &String) -> bool {
+        &self.0 == other
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn collect_matches_encode() {
+        for len in 0..40 {
+            let bytes = (0..len).map(|i| (i * 37) as u8).collect::<Vec<_>>();
+            let encoded = bytes.iter().collect::<Base2048String>();
+            assert_eq!(encoded, encode(&bytes));
+            assert_eq!(encoded, Base2048String::new(&bytes));
+            assert_eq!(encoded.decode(), bytes);
+        }
+    }
+
+    #[test]
+    fn borrowed() {
+        let encoded = Base2048String::new(b"hello");
+        let borrowed = Base2048Str::new(&encoded).unwrap();
+        assert_eq!(borrowed, encoded.as_base2048_str());
+        assert_eq!(borrowed.decode(), b"hello");
+        assert_eq!(alloc::borrow::ToOwned::to_owned(borrowed), encoded);
+        assert_eq!(
+            Base2048Str::new("ետћζы༎X"),
+            Err(DecodeError::InvalidChar {
+                c: '༎',
+                char_index: 5
+            })
+        );
+    }
+
+    #[test]
+    fn parse() {
+        let encoded = encode(b"hello");
+        let expected = Base2048String::new(b"hello");
+        assert_eq!(encoded.parse::<Base2048String>(), Ok(expected.clone()));
+        assert_eq!(
+            Base2048String::try_from(encoded.as_str()),
+            Ok(expected.clone())
+        );
+        assert_eq!(Base2048String::try_from(encoded), Ok(expected));
+        let invalid = Err(DecodeError::TrailingData { char_index: 0 });
+        assert_eq!("0a".parse::<Base2048String>(), invalid);
+        assert_eq!(Base2048String::try_from(String::from("0a")), invalid);
+    }
+}
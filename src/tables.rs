@@ -0,0 +1,299 @@
+//! The tables encoding and decoding read, in whichever form the features ask for.
+//!
+//! By default these are [`ENC_TABLE`], [`DEC_TABLE`] and the UTF-8 of each table character, all
+//! built at compile time: about 24 KiB. [`ENC_TABLE`] is in code point order and mostly made of
+//! runs of consecutive code points, so it can be described in under 1 KiB by the first code point
+//! and position of each run, and two features use that instead:
+//!
+//! - `small-tables` looks characters up for decoding in a two-level table: each page of 64 code
+//!   points points at the first run that could hold its characters.
+//! - `runtime-tables` builds the encoding tables (and, without `small-tables`, [`DEC_TABLE`]) on
+//!   the heap from the runs the first time they are needed.
+//!
+//! All of them give the same answers.
+#[cfg(not(any(feature = "small-tables", feature = "runtime-tables")))]
+use crate::DEC_TABLE;
+use crate::ENC_TABLE;
+#[cfg(feature = "runtime-tables")]
+use alloc::boxed::Box;
+#[cfg(feature = "runtime-tables")]
+use core::{
+    convert::TryInto,
+    ptr,
+    sync::atomic::{AtomicPtr, Ordering},
+};
+
+/// The characters of the standard table, in order.
+#[cfg(not(feature = "runtime-tables"))]
+#[inline(always)]
+pub(crate) fn enc_table() -> &'static [char; 2048] {
+    ENC_TABLE
+}
+
+/// The characters of the standard table, in order.
+#[cfg(feature = "runtime-tables")]
+#[inline(always)]
+pub(crate) fn enc_table() -> &'static [char; 2048] {
+    static TABLE: Lazy<[char; 2048]> = Lazy::new(|| {
+        let mut table = vec!['\0'; 2048];
+        for (run, next) in RUNS.iter().zip(&RUNS[1..]) {
+            for index in run.1..next.1 {
+                let c = (run.0 + index - run.1) as u32;
+                table[index as usize] = char::from_u32(c).expect("runs hold characters");
+            }
+        }
+        boxed_array(table)
+    });
+    TABLE.get()
+}
+
+/// The UTF-8 of each character in [`enc_table`] in the low three bytes, first byte lowest, and its
+/// length in the high byte.
+#[cfg(not(feature = "runtime-tables"))]
+#[inline(always)]
+pub(crate) fn enc_utf8() -> &'static [u32; 2048] {
+    static TABLE: [u32; 2048] = {
+        let mut table = [0; 2048];
+        let mut i = 0;
+        while i < 2048 {
+            table[i] = utf8(ENC_TABLE[i]);
+            i += 1;
+        }
+        table
+    };
+    &TABLE
+}
+
+/// The UTF-8 of each character in [`enc_table`] in the low three bytes, first byte lowest, and its
+/// length in the high byte.
+#[cfg(feature = "runtime-tables")]
+#[inline(always)]
+pub(crate) fn enc_utf8() -> &'static [u32; 2048] {
+    static TABLE: Lazy<[u32; 2048]> =
+        Lazy::new(|| boxed_array(enc_table().iter().map(|c| utf8(*c)).collect()));
+    TABLE.get()
+}
+
+/// `c` as it goes in [`enc_utf8`].
+const fn utf8(c: char) -> u32 {
+    let c = c as u32;
+    if c < 0x80 {
+        c | 1 << 24
+    } else if c < 0x800 {
+        (0xC0 | c >> 6) | (0x80 | (c & 0x3F)) << 8 | 2 << 24
+    } else {
+        (0xE0 | c >> 12) | (0x80 | (c >> 6 & 0x3F)) << 8 | (0x80 | (c & 0x3F)) << 16 | 3 << 24
+    }
+}
+
+/// The table position of the code point `c`, if it is a table character.
+#[cfg(not(any(feature = "small-tables", feature = "runtime-tables")))]
+#[inline(always)]
+pub(crate) fn table_index(c: u32) -> Option<u16> {
+    match DEC_TABLE.get(c as usize) {
+        Some(&index) if index != 0xFFFF => Some(index),
+        _ => None,
+    }
+}
+
+/// The table position of the code point `c`, if it is a table character.
+#[cfg(all(feature = "runtime-tables", not(feature = "small-tables")))]
+#[inline(always)]
+pub(crate) fn table_index(c: u32) -> Option<u16> {
+    static TABLE: Lazy<[u16; N_CODE_POINTS]> = Lazy::new(|| {
+        let mut table = vec![0xFFFF; N_CODE_POINTS];
+        for (index, c) in enc_table().iter().enumerate() {
+            table[*c as usize] = index as u16;
+        }
+        boxed_array(table)
+    });
+    match TABLE.get().get(c as usize) {
+        Some(&index) if index != 0xFFFF => Some(index),
+        _ => None,
+    }
+}
+
+/// The table position of the code point `c`, if it is a table character.
+#[cfg(feature = "small-tables")]
+#[inline(always)]
+pub(crate) fn table_index(c: u32) -> Option<u16> {
+    let mut run = *PAGES.get((c >> PAGE_BITS) as usize)? as usize;
+    // the last run ends with a sentinel that starts past every page
+    while RUNS[run + 1].0 as u32 <= c {
+        run += 1;
+    }
+    let (start, first) = RUNS[run];
+    let offset = c.checked_sub(start as u32)?;
+    match offset < (RUNS[run + 1].1 - first) as u32 {
+        true => Some(first + offset as u16),
+        false => None,
+    }
+}
+
+/// One past the highest code point in the table.
+#[cfg(all(feature = "runtime-tables", not(feature = "small-tables")))]
+const N_CODE_POINTS: usize = ENC_TABLE[2047] as usize + 1;
+
+#[cfg(any(feature = "small-tables", feature = "runtime-tables"))]
+const N_RUNS: usize = count_runs();
+
+/// The first code point and table position of each run, then a sentinel of `(u16::MAX, 2048)` so
+/// every run's length is the difference to the next position.
+#[cfg(any(feature = "small-tables", feature = "runtime-tables"))]
+static RUNS: [(u16, u16); N_RUNS + 1] = runs();
+
+#[cfg(any(feature = "small-tables", feature = "runtime-tables"))]
+const fn count_runs() -> usize {
+    let mut n = 1;
+    let mut i = 1;
+    while i < 2048 {
+        if ENC_TABLE[i] as u32 != ENC_TABLE[i - 1] as u32 + 1 {
+            n += 1;
+        }
+        i += 1;
+    }
+    n
+}
+
+#[cfg(any(feature = "small-tables", feature = "runtime-tables"))]
+const fn runs() -> [(u16, u16); N_RUNS + 1] {
+    let mut runs = [(u16::MAX, 2048); N_RUNS + 1];
+    runs[0] = (ENC_TABLE[0] as u16, 0);
+    let mut n = 1;
+    let mut i = 1;
+    while i < 2048 {
+        if ENC_TABLE[i] as u32 != ENC_TABLE[i - 1] as u32 + 1 {
+            runs[n] = (ENC_TABLE[i] as u16, i as u16);
+            n += 1;
+        }
+        i += 1;
+    }
+    runs
+}
+
+#[cfg(feature = "small-tables")]
+const PAGE_BITS: u32 = 6;
+
+// every run number has to fit in a byte of `PAGES`
+#[cfg(feature = "small-tables")]
+const _: () = assert!(N_RUNS <= 256);
+
+/// For each page, the last run that starts at or before the page does (or the first run).
+#[cfg(feature = "small-tables")]
+static PAGES: [u8; (ENC_TABLE[2047] as usize >> PAGE_BITS) + 1] = pages();
+
+#[cfg(feature = "small-tables")]
+const fn pages() -> [u8; (ENC_TABLE[2047] as usize >> PAGE_BITS) + 1] {
+    let runs = runs();
+    let mut pages = [0; (ENC_TABLE[2047] as usize >> PAGE_BITS) + 1];
+    let mut page = 0;
+    let mut run = 0;
+    while page < pages.len() {
+        while run + 1 < N_RUNS && runs[run + 1].0 as usize <= page << PAGE_BITS {
+            run += 1;
+        }
+        pages[page] = run as u8;
+        page += 1;
+    }
+    pages
+}
+
+/// A table built on the heap the first time it is asked for, and kept for good.
+///
+/// Threads that ask at the same time may each build it, but only one is kept and the rest are
+/// dropped, so this needs no lock, only a pointer-sized compare and swap.
+#[cfg(feature = "runtime-tables")]
+struct Lazy<T> {
+    table: AtomicPtr<T>,
+    build: fn() -> Box<T>,
+}
+
+#[cfg(feature = "runtime-tables")]
+impl<T> Lazy<T> {
+    const fn new(build: fn() -> Box<T>) -> Self {
+        Lazy {
+            table: AtomicPtr::new(ptr::null_mut()),
+            build,
+        }
+    }
+
+    #[inline(always)]
+    fn get(&'static self) -> &'static T {
+        let table = self.table.load(Ordering::Acquire);
+        if table.is_null() {
+            return self.init();
+        }
+        // SAFETY: a non-null pointer came from `Box::into_raw` in `init` and is never freed
+        unsafe { &*table }
+    }
+
+    #[cold]
+    #[inline(never)]
+    fn init(&'static self) -> &'static T {
+        let built = Box::into_raw((self.build)());
+        let table = match self.table.compare_exchange(
+            ptr::null_mut(),
+            built,
+            Ordering::AcqRel,
+            Ordering::Acquire,
+        ) {
+            Ok(_) => built,
+            Err(other) => {
+                // SAFETY: `built` came from `Box::into_raw` above and nothing else has seen it
+                drop(unsafe { Box::from_raw(built) });
+                other
+            }
+        };
+        // SAFETY: as in `get`
+        unsafe { &*table }
+    }
+}
+
+/// `table` as a boxed array, without building the array on the stack first.
+#[cfg(feature = "runtime-tables")]
+fn boxed_array<T, const N: usize>(table: alloc::vec::Vec<T>) -> Box<[T; N]> {
+    table
+        .into_boxed_slice()
+        .try_into()
+        .unwrap_or_else(|_| panic!("tables have {} entries", N))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn matches_enc_table() {
+        let mut expected = [None; 0x1_1000];
+        for (index, c) in ENC_TABLE.iter().enumerate() {
+            expected[*c as usize] = Some(index as u16);
+        }
+        for (c, expected) in expected.iter().enumerate() {
+            assert_eq!(table_index(c as u32), *expected, "{:#x}", c);
+        }
+        assert_eq!(table_index(0x10_FFFF), None);
+        assert_eq!(table_index(u32::MAX), None);
+
+        assert_eq!(enc_table(), ENC_TABLE);
+        for (c, utf8) in ENC_TABLE.iter().zip(enc_utf8().iter()) {
+            let len = (utf8 >> 24) as usize;
+            assert_eq!(
+                &utf8.to_le_bytes()[..len],
+                c.encode_utf8(&mut [0; 4]).as_bytes()
+            );
+        }
+    }
+
+    #[cfg(feature = "small-tables")]
+    #[test]
+    fn small() {
+        assert!(core::mem::size_of_val(&RUNS) + core::mem::size_of_val(&PAGES) < 1024);
+    }
+
+    #[cfg(feature = "runtime-tables")]
+    #[test]
+    fn built_once() {
+        assert!(core::ptr::eq(enc_table(), enc_table()));
+        assert!(core::ptr::eq(enc_utf8(), enc_utf8()));
+    }
+}
@@ -0,0 +1,226 @@
+//! Newline-delimited base2048 frames for [`tokio_util::codec`].
+use crate::{decode_into, decode_into_strict, encode_with, encoded_len, MAX_CHAR_LEN};
+use alloc::vec::Vec;
+use bytes::{BufMut, BytesMut};
+use std::io;
+use tokio_util::codec::{Decoder, Encoder};
+
+/// Encodes each frame as a line of base2048 text, and decodes lines back into frames.
+///
+/// Lines end with `\n`; a `\r` before it is ignored when decoding, as is a last line without
+/// one at the end of the stream. A line that isn't valid base2048 is reported as an
+/// [`io::ErrorKind::InvalidData`] error, carrying a [`DecodeError`](crate::DecodeError) when the
+/// text is valid UTF-8.
+///
+/// # Example
+/// ```
+/// use base2048::Base2048Codec;
+/// use bytes::BytesMut;
+/// use tokio_util::codec::{Decoder, Encoder};
+/// let mut codec = Base2048Codec::new();
+/// let mut buf = BytesMut::new();
+/// codec.encode(b"hello", &mut buf)?;
+/// assert_eq!(&buf[..], "ڵϠɲඹ\n".as_bytes());
+/// assert_eq!(codec.decode(&mut buf)?, Some(b"hello".to_vec()));
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Base2048Codec {
+    // how far the buffer has been searched for a newline
+    next_index: usize,
+    max_length: Option<usize>,
+    strict: bool,
+}
+
+impl Base2048Codec {
+    /// A codec for lines of any length.
+    pub fn new() -> Self {
+        Base2048Codec::default()
+    }
+
+    /// A codec that gives up on lines longer than `max_length` bytes, not counting the newline.
+    ///
+    /// Without a limit, a peer that never sends a newline makes the buffer grow without bound.
+    pub fn with_max_length(max_length: usize) -> Self {
+        Base2048Codec {
+            max_length: Some(max_length),
+            ..Base2048Codec::default()
+        }
+    }
+
+    /// Whether a line ending in a character whose padding bits aren't all set is rejected, as
+    /// [`try_decode_strict`](crate::try_decode_strict) rejects it.
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// The longest line this codec decodes, if there is a limit.
+    pub fn max_length(&self) -> Option<usize> {
+        self.max_length
+    }
+}
+
+/// Decodes one line, without its newline.
+fn decode_line(mut line: &[u8], strict: bool) -> io::Result<Vec<u8>> {
+    if let Some((b'\r', rest)) = line.split_last() {
+        line = rest;
+    }
+    let text =
+        core::str::from_utf8(line).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    let mut out = Vec::new();
+    let decode = if strict { decode_into_strict } else { decode_into };
+    decode(text, &mut out)?;
+    Ok(out)
+}
+
+impl Decoder for Base2048Codec {
+    type Item = Vec<u8>;
+    type Error = io::Error;
+
+    fn decode(&mut self, buf: &mut BytesMut) -> io::Result<Option<Vec<u8>>> {
+        let newline = buf[self.next_index..].iter().position(|&b| b == b'\n');
+        match newline {
+            Some(offset) => {
+                let line = buf.split_to(self.next_index + offset + 1);
+                self.next_index = 0;
+                if let Some(max_length) = self.max_length {
+                    if line.len() - 1 > max_length {
+                        return Err(line_too_long());
+                    }
+                }
+                decode_line(&line[..line.len() - 1], self.strict).map(Some)
+            }
+            None => {
+                if let Some(max_length) = self.max_length {
+                    if buf.len() > max_length {
+                        return Err(line_too_long());
+                    }
+                }
+                self.next_index = buf.len();
+                Ok(None)
+            }
+        }
+    }
+
+    fn decode_eof(&mut self, buf: &mut BytesMut) -> io::Result<Option<Vec<u8>>> {
+        match self.decode(buf)? {
+            Some(frame) => Ok(Some(frame)),
+            None if buf.is_empty() => Ok(None),
+            None => {
+                let line = buf.split();
+                self.next_index = 0;
+                decode_line(&line, self.strict).map(Some)
+            }
+        }
+    }
+}
+
+fn line_too_long() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "base2048 line is too long")
+}
+
+impl<T: AsRef<[u8]>> Encoder<T> for Base2048Codec {
+    type Error = io::Error;
+
+    fn encode(&mut self, frame: T, buf: &mut BytesMut) -> io::Result<()> {
+        let bytes = frame.as_ref();
+        buf.reserve(encoded_len(bytes.len()) * MAX_CHAR_LEN + 1);
+        let mut utf8 = [0; 4];
+        encode_with(bytes, |c| {
+            buf.put_slice(c.encode_utf8(&mut utf8).as_bytes())
+        });
+        buf.put_u8(b'\n');
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{encode, DecodeError};
+
+    fn bytes(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i * 37) as u8).collect()
+    }
+
+    #[test]
+    fn frames_round_trip() {
+        let frames = (0..40).map(bytes).collect::<Vec<_>>();
+        let mut codec = Base2048Codec::new();
+        let mut buf = BytesMut::new();
+        for frame in &frames {
+            codec.encode(frame, &mut buf).unwrap();
+        }
+        assert_eq!(buf.iter().filter(|&&b| b == b'\n').count(), frames.len());
+
+        // feed it back a byte at a time, so lines and characters are split
+        let all = buf.split();
+        let mut decoded = Vec::new();
+        for byte in all.iter() {
+            buf.put_u8(*byte);
+            if let Some(frame) = codec.decode(&mut buf).unwrap() {
+                decoded.push(frame);
+            }
+        }
+        assert_eq!(codec.decode_eof(&mut buf).unwrap(), None);
+        assert_eq!(decoded, frames);
+    }
+
+    #[test]
+    fn line_endings() {
+        let mut codec = Base2048Codec::new();
+        let mut buf = BytesMut::from(&*alloc::format!("{}\r\n{}", encode(b"hi"), encode(b"yo")));
+        assert_eq!(codec.decode(&mut buf).unwrap(), Some(b"hi".to_vec()));
+        assert_eq!(codec.decode(&mut buf).unwrap(), None);
+        assert_eq!(codec.decode_eof(&mut buf).unwrap(), Some(b"yo".to_vec()));
+        assert_eq!(codec.decode_eof(&mut buf).unwrap(), None);
+    }
+
+    #[test]
+    fn invalid_lines() {
+        let mut codec = Base2048Codec::new();
+        let mut buf = BytesMut::from("ետћζы༎X\n".as_bytes());
+        let e = codec.decode(&mut buf).unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(
+            e.into_inner().unwrap().downcast_ref::<DecodeError>(),
+            Some(&DecodeError::InvalidChar {
+                c: '༎',
+                char_index: 5
+            })
+        );
+
+        let mut buf = BytesMut::from(&[0xFF, b'\n'][..]);
+        let e = codec.decode(&mut buf).unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn strict() {
+        let loose = alloc::format!("{}\n", encode(b"hi").replace('Ո', "Ԉ"));
+        let mut buf = BytesMut::from(loose.as_bytes());
+        let mut codec = Base2048Codec::new();
+        assert_eq!(codec.decode(&mut buf).unwrap(), Some(b"hi".to_vec()));
+        let mut buf = BytesMut::from(loose.as_bytes());
+        let mut codec = Base2048Codec::new().with_strict(true);
+        let e = codec.decode(&mut buf).unwrap_err();
+        assert_eq!(
+            e.into_inner().unwrap().downcast_ref::<DecodeError>(),
+            Some(&DecodeError::InvalidPadding { char_index: 1 })
+        );
+    }
+
+    #[test]
+    fn max_length() {
+        let max_length = encode(b"hello").len();
+        let mut codec = Base2048Codec::with_max_length(max_length);
+        let mut buf = BytesMut::new();
+        codec.encode(b"hello", &mut buf).unwrap();
+        assert_eq!(codec.decode(&mut buf).unwrap(), Some(b"hello".to_vec()));
+        codec.encode(b"hello world", &mut buf).unwrap();
+        assert!(codec.decode(&mut buf).is_err());
+        let mut buf = BytesMut::from(&encode(b"hello world").as_bytes()[..max_length + 1]);
+        assert!(codec.decode(&mut buf).is_err());
+    }
+}
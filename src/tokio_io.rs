@@ -0,0 +1,160 @@
+use crate::{decoder::CharDecoder, stream::Utf8Chunks, EncodeState};
+use alloc::{string::String, vec::Vec};
+use std::io;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+const BUF_LEN: usize = 8 * 1024;
+
+/// Encode everything `reader` produces into `writer` as UTF-8 base2048 text
+///
+/// Like [`tokio::io::copy`], but the data is encoded on the way through. Only a fixed amount of it
+/// is buffered at a time. Returns the number of bytes written to `writer`.
+///
+/// # Example
+/// ```
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() -> std::io::Result<()> {
+/// let mut reader: &[u8] = b"some utf8 bytes to encode more compactly";
+/// let mut writer = vec![];
+/// base2048::copy_encode(&mut reader, &mut writer).await?;
+/// assert_eq!(writer, base2048::encode(b"some utf8 bytes to encode more compactly").into_bytes());
+/// # Ok(())
+/// # }
+/// ```
+pub async fn copy_encode<R, W>(reader: &mut R, writer: &mut W) -> io::Result<u64>
+where
+    R: AsyncRead + Unpin + ?Sized,
+    W: AsyncWrite + Unpin + ?Sized,
+{
+    let mut buf = vec![0u8; BUF_LEN];
+    let mut out = String::new();
+    let mut state = EncodeState::default();
+    let mut written = 0;
+
+    loop {
+        let n = reader.read(&mut buf).await?;
+        if n == 0 {
+            break;
+        }
+        out.clear();
+        out.extend(buf[..n].iter().filter_map(|byte| state.push(*byte)));
+        writer.write_all(out.as_bytes()).await?;
+        written += out.len() as u64;
+    }
+
+    out.clear();
+    out.extend(state.finish());
+    writer.write_all(out.as_bytes()).await?;
+    writer.flush().await?;
+    Ok(written + out.len() as u64)
+}
+
+/// Decode UTF-8 base2048 text from `reader` into `writer`
+///
+/// The decoding twin of [`copy_encode`]. Invalid input is reported as an
+/// [`io::ErrorKind::InvalidData`] error, carrying a [`DecodeError`](crate::DecodeError) when the
+/// text isn't valid base2048. Returns the number of bytes written to `writer`.
+pub async fn copy_decode<R, W>(reader: &mut R, writer: &mut W) -> io::Result<u64>
+where
+    R: AsyncRead + Unpin + ?Sized,
+    W: AsyncWrite + Unpin + ?Sized,
+{
+    copy_decode_with(reader, writer, false).await
+}
+
+/// Like [`copy_decode`], but rejecting non-canonical padding as
+/// [`try_decode_strict`](crate::try_decode_strict) does
+pub async fn copy_decode_strict<R, W>(reader: &mut R, writer: &mut W) -> io::Result<u64>
+where
+    R: AsyncRead + Unpin + ?Sized,
+    W: AsyncWrite + Unpin + ?Sized,
+{
+    copy_decode_with(reader, writer, true).await
+}
+
+async fn copy_decode_with<R, W>(reader: &mut R, writer: &mut W, strict: bool) -> io::Result<u64>
+where
+    R: AsyncRead + Unpin + ?Sized,
+    W: AsyncWrite + Unpin + ?Sized,
+{
+    let mut buf = vec![0u8; BUF_LEN];
+    let mut out = Vec::new();
+    let mut chunks = Utf8Chunks::default();
+    let mut decoder = CharDecoder::default();
+    decoder.set_strict(strict);
+    let mut written = 0;
+
+    loop {
+        let n = reader.read(&mut buf).await?;
+        if n == 0 {
+            break;
+        }
+        out.clear();
+        chunks.push(&buf[..n], |c| {
+            decoder
+                .push(c, |byte| out.push(byte))
+                .map_err(io::Error::from)
+        })?;
+        writer.write_all(&out).await?;
+        written += out.len() as u64;
+    }
+
+    chunks.finish()?;
+    out.clear();
+    decoder.finish(|byte| out.push(byte))?;
+    writer.write_all(&out).await?;
+    writer.flush().await?;
+    Ok(written + out.len() as u64)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{encode, DecodeError};
+
+    #[tokio::test]
+    async fn roundtrip() {
+        let bytes = (0..100_000u32)
+            .map(|i| (i * 7 % 251) as u8)
+            .collect::<Vec<_>>();
+        let mut encoded = vec![];
+        copy_encode(&mut &bytes[..], &mut encoded).await.unwrap();
+        assert_eq!(encoded, encode(&bytes).into_bytes());
+
+        let mut decoded = vec![];
+        copy_decode(&mut &encoded[..], &mut decoded).await.unwrap();
+        assert_eq!(decoded, bytes);
+    }
+
+    #[tokio::test]
+    async fn invalid_input() {
+        let e = copy_decode(&mut &"88€8".as_bytes()[..], &mut vec![])
+            .await
+            .unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(
+            e.into_inner().unwrap().downcast_ref::<DecodeError>(),
+            Some(&DecodeError::InvalidChar {
+                c: '€',
+                char_index: 2
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn strict() {
+        let loose = encode(b"hi").replace('Ո', "Ԉ");
+        let mut decoded = vec![];
+        copy_decode(&mut loose.as_bytes(), &mut decoded)
+            .await
+            .unwrap();
+        assert_eq!(decoded, b"hi");
+        let e = copy_decode_strict(&mut loose.as_bytes(), &mut vec![])
+            .await
+            .unwrap_err();
+        assert_eq!(
+            e.into_inner().unwrap().downcast_ref::<DecodeError>(),
+            Some(&DecodeError::InvalidPadding { char_index: 1 })
+        );
+    }
+}
@@ -0,0 +1,83 @@
+//! Encoding into `ufmt` writers (the `ufmt` feature).
+use crate::EncodeState;
+use ufmt::uWrite;
+
+/// Encode some bytes using base2048 encoding, writing the characters to a [`uWrite`] sink
+///
+/// For targets that avoid `core::fmt` to save code size. Stops at, and returns, the first error
+/// from `w`.
+///
+/// # Example
+/// ```
+/// struct Uart(String);
+///
+/// impl ufmt::uWrite for Uart {
+///     type Error = core::convert::Infallible;
+///
+///     fn write_str(&mut self, s: &str) -> Result<(), Self::Error> {
+///         self.0.push_str(s);
+///         Ok(())
+///     }
+/// }
+///
+/// let mut uart = Uart(String::new());
+/// base2048::encode_to_uwrite(b"hello", &mut uart).unwrap();
+/// assert_eq!(uart.0, base2048::encode(b"hello"));
+/// ```
+pub fn encode_to_uwrite<W: uWrite + ?Sized>(bytes: &[u8], w: &mut W) -> Result<(), W::Error> {
+    let mut state = EncodeState::default();
+    for byte in bytes {
+        if let Some(c) = state.push(*byte) {
+            w.write_char(c)?;
+        }
+    }
+    if let Some(c) = state.finish() {
+        w.write_char(c)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::encode;
+    use alloc::{string::String, vec::Vec};
+
+    struct Collect(String);
+
+    impl uWrite for Collect {
+        type Error = core::convert::Infallible;
+
+        fn write_str(&mut self, s: &str) -> Result<(), Self::Error> {
+            self.0.push_str(s);
+            Ok(())
+        }
+    }
+
+    struct Full(usize);
+
+    impl uWrite for Full {
+        type Error = ();
+
+        fn write_str(&mut self, s: &str) -> Result<(), ()> {
+            self.0 = self.0.checked_sub(s.chars().count()).ok_or(())?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn matches_encode() {
+        for len in 0..40 {
+            let bytes = (0..len).map(|i| (i * 37) as u8).collect::<Vec<_>>();
+            let mut encoded = Collect(String::new());
+            encode_to_uwrite(&bytes, &mut encoded).unwrap();
+            assert_eq!(encoded.0, encode(&bytes));
+        }
+    }
+
+    #[test]
+    fn stops_at_error() {
+        assert_eq!(encode_to_uwrite(&[0; 10], &mut Full(8)), Ok(()));
+        assert_eq!(encode_to_uwrite(&[0; 10], &mut Full(7)), Err(()));
+    }
+}
@@ -0,0 +1,165 @@
+use crate::{diagnostics, DecodeError, DecodeState};
+use alloc::vec::Vec;
+use core::fmt;
+
+/// Characters that messengers and editors insert invisibly. None of them are part of the
+/// encoding so they can be dropped without changing the meaning of the text.
+pub(crate) const ZERO_WIDTH: &[char] =
+    &['\u{200B}', '\u{200C}', '\u{200D}', '\u{2060}', '\u{FEFF}'];
+
+/// Something odd about an input that was decoded anyway, see [`decode_with_warnings`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DecodeWarning {
+    /// An invisible zero-width character was dropped.
+    ZeroWidthStripped {
+        /// The dropped character.
+        c: char,
+        /// Its position in the input, counted in characters.
+        char_index: usize,
+    },
+    /// The padding bits of the final character weren't all set, so some other string decodes to
+    /// the same bytes. The encoder never produces this.
+    NonCanonicalPadding {
+        /// The position of the final character, counted in characters.
+        char_index: usize,
+    },
+}
+
+#[cfg(not(feature = "compact-errors"))]
+impl fmt::Display for DecodeWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeWarning::ZeroWidthStripped { c, char_index } => write!(
+                f,
+                "zero-width character U+{:04X} at position {} was ignored",
+                *c as u32, char_index
+            ),
+            DecodeWarning::NonCanonicalPadding { char_index } => write!(
+                f,
+                "the final character at position {} has non-canonical padding",
+                char_index
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "compact-errors")]
+impl fmt::Display for DecodeWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            DecodeWarning::ZeroWidthStripped { .. } => "base2048 warning 1: zero-width character",
+            DecodeWarning::NonCanonicalPadding { .. } => {
+                "base2048 warning 2: non-canonical padding"
+            }
+        })
+    }
+}
+
+/// The result of [`decode_with_warnings`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DecodeReport {
+    /// The decoded bytes.
+    pub bytes: Vec<u8>,
+    /// Everything that was questionable about the input, in the order it was found.
+    pub warnings: Vec<DecodeWarning>,
+}
+
+/// Decode a base2048 encoded string, reporting the data-hygiene issues that were tolerated
+///
+/// Zero-width characters are skipped instead of failing the decode, and a final character with
+/// non-canonical padding is accepted (as [`decode`](crate::decode) does); both are reported as
+/// [`DecodeWarning`]s so tools can point them out. Errors and warnings give positions in the
+/// original string.
+///
+/// # Example
+/// ```
+/// use base2048::DecodeWarning;
+/// let pasted = format!("\u{200B}{}", base2048::encode(b"hi"));
+/// let report = base2048::decode_with_warnings(&pasted).unwrap();
+/// assert_eq!(report.bytes, b"hi");
+/// assert_eq!(
+///     report.warnings,
+///     [DecodeWarning::ZeroWidthStripped { c: '\u{200B}', char_index: 0 }]
+/// );
+/// ```
+pub fn decode_with_warnings(string: &str) -> Result<DecodeReport, DecodeError> {
+    let mut bytes = vec![];
+    let mut warnings = vec![];
+    let mut state = DecodeState::default();
+    let mut chars = string.chars().enumerate().filter(|(char_index, c)| {
+        let zero_width = ZERO_WIDTH.contains(c);
+        if zero_width {
+            warnings.push(DecodeWarning::ZeroWidthStripped {
+                c: *c,
+                char_index: *char_index,
+            });
+        }
+        !zero_width
+    });
+
+    let mut next = chars.next();
+    let mut last_index = 0;
+    while let Some((char_index, c)) = next {
+        next = chars.next();
+        last_index = char_index;
+        state
+            .push(c, next.is_none(), |byte| bytes.push(byte))
+            .map_err(|e| diagnostics::decode_error(e.with_char_index(char_index)))?;
+    }
+    state.finish(|byte| bytes.push(byte));
+
+    if state.non_canonical_padding {
+        warnings.push(DecodeWarning::NonCanonicalPadding {
+            char_index: last_index,
+        });
+    }
+    for warning in &warnings {
+        diagnostics::fixup(*warning);
+    }
+    Ok(DecodeReport { bytes, warnings })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{decode, encode, ENC_TABLE};
+    use alloc::string::String;
+
+    #[test]
+    fn clean_input_has_no_warnings() {
+        for len in 0..30 {
+            let bytes = (0..len).map(|i| (i * 17) as u8).collect::<Vec<_>>();
+            let report = decode_with_warnings(&encode(&bytes)).unwrap();
+            assert_eq!(report.bytes, bytes);
+            assert!(report.warnings.is_empty(), "{:?}", report.warnings);
+        }
+    }
+
+    #[test]
+    fn non_canonical_padding() {
+        // one byte is 8 bits in a table character with 3 padding bits
+        let mut encoded = encode([0xAB]).chars().collect::<Vec<_>>();
+        let index = crate::DEC_TABLE[encoded[0] as usize];
+        encoded[0] = ENC_TABLE[(index & !0b111) as usize];
+        let encoded = encoded.into_iter().collect::<String>();
+
+        assert_eq!(decode(&encoded), Some(vec![0xAB]));
+        let report = decode_with_warnings(&encoded).unwrap();
+        assert_eq!(report.bytes, [0xAB]);
+        assert_eq!(
+            report.warnings,
+            [DecodeWarning::NonCanonicalPadding { char_index: 0 }]
+        );
+    }
+
+    #[test]
+    fn errors_point_into_the_original() {
+        assert_eq!(
+            decode_with_warnings("\u{200B}\u{200B}8€"),
+            Err(DecodeError::InvalidChar {
+                c: '€',
+                char_index: 3
+            })
+        );
+    }
+}
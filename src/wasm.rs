@@ -0,0 +1,123 @@
+//! JavaScript bindings (the `wasm` feature), packaged for npm by the `wasm` directory.
+//!
+//! Bytes are passed in as `&[u8]`, which wasm-bindgen copies straight out of the caller's
+//! `Uint8Array` (or a `subarray` view of one) into wasm memory, with no JS array or Rust `Vec` in
+//! between. [`decode_into`] writes into a `Uint8Array` the caller already has, so decoding in a loop
+//! needs no allocation on either side.
+//!
+//! Invalid input throws an `Error` named `Base2048Error`, with the [`DecodeError::code`] of the
+//! error as its `code` and the position of the offending character as its `charIndex`.
+use crate::{encode_with, DecodeError, DecodeState};
+use alloc::{string::String, string::ToString, vec::Vec};
+use js_sys::{Reflect, Uint8Array};
+use wasm_bindgen::prelude::*;
+
+/// How much [`decode_into`] decodes before copying it out.
+const CHUNK: usize = 4096;
+
+fn js_error(e: DecodeError) -> JsValue {
+    let error = js_sys::Error::new(&e.to_string());
+    error.set_name("Base2048Error");
+    // setting a property on a fresh `Error` can't fail
+    let _ = Reflect::set(&error, &"code".into(), &e.code().into());
+    let _ = Reflect::set(&error, &"charIndex".into(), &(e.char_index() as f64).into());
+    error.into()
+}
+
+/// Encodes the bytes of a `Uint8Array`.
+#[wasm_bindgen]
+pub fn encode(bytes: &[u8]) -> String {
+    let mut ret = String::with_capacity(crate::encoded_len(bytes.len()) * 2);
+    encode_with(bytes, |c| ret.push(c));
+    ret
+}
+
+/// Decodes a string into a new `Uint8Array`, throwing if it isn't valid base2048.
+#[wasm_bindgen]
+pub fn decode(string: &str) -> Result<Vec<u8>, JsValue> {
+    let mut ret = Vec::with_capacity(crate::decoded_str_len(string));
+    crate::decode_with(string, |byte| ret.push(byte)).map_err(js_error)?;
+    Ok(ret)
+}
+
+/// The most bytes `string` can decode to: how big the array given to [`decode_into`] must be.
+#[wasm_bindgen(js_name = maxDecodedLength)]
+pub fn max_decoded_len(string: &str) -> usize {
+    crate::max_decoded_len(string.chars().count())
+}
+
+/// Decodes a string into `out`, returning the number of bytes written.
+///
+/// Throws if the string isn't valid base2048 or `out` is too small, in which case `out` may have
+/// been partly written.
+#[wasm_bindgen(js_name = decodeInto)]
+pub fn decode_into(string: &str, out: &Uint8Array) -> Result<usize, JsValue> {
+    let capacity = out.length() as usize;
+    let mut buf = [0; CHUNK];
+    let (mut len, mut written) = (0, 0);
+    let mut too_small = false;
+    let mut flush = |buf: &[u8], written: &mut usize| {
+        if *written + buf.len() > capacity {
+            too_small = true;
+        } else {
+            let end = *written + buf.len();
+            out.subarray(*written as u32, end as u32).copy_from(buf);
+            *written = end;
+        }
+    };
+
+    let mut state = DecodeState::default();
+    let mut chars = string.chars().peekable();
+    while let Some(c) = chars.next() {
+        let last = chars.peek().is_none();
+        state
+            .push(c, last, |byte| {
+                buf[len] = byte;
+                len += 1;
+            })
+            .map_err(js_error)?;
+        // a character completes at most two bytes
+        if len + 2 > CHUNK {
+            flush(&buf[..len], &mut written);
+            len = 0;
+        }
+    }
+    state.finish_checked(|byte| {
+        buf[len] = byte;
+        len += 1;
+    });
+    flush(&buf[..len], &mut written);
+
+    if too_small {
+        return Err(JsError::new("output array is too small").into());
+    }
+    Ok(written)
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod test {
+    use super::*;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    #[wasm_bindgen_test]
+    fn decode_into_matches_decode() {
+        for len in [0, 1, 10, 5000, 10000] {
+            let bytes = (0..len).map(|i| (i * 37) as u8).collect::<Vec<_>>();
+            let encoded = encode(&bytes);
+            let out = Uint8Array::new_with_length(max_decoded_len(&encoded) as u32);
+            let written = decode_into(&encoded, &out).unwrap();
+            assert_eq!(out.subarray(0, written as u32).to_vec(), bytes);
+            assert_eq!(decode(&encoded).unwrap(), bytes);
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn errors() {
+        let e = decode("ետћζы༎X").unwrap_err();
+        assert_eq!(js_sys::Error::from(e.clone()).name(), "Base2048Error");
+        assert_eq!(Reflect::get(&e, &"code".into()).unwrap(), 1);
+        assert_eq!(Reflect::get(&e, &"charIndex".into()).unwrap(), 5);
+        let encoded = encode(&[1; 100]);
+        assert!(decode_into(&encoded, &Uint8Array::new_with_length(99)).is_err());
+    }
+}
@@ -0,0 +1,92 @@
+//! Whitening: scrambling the encoded characters with a fixed pseudorandom sequence.
+//!
+//! Repetitive input (long runs of zeros, say) encodes to long runs of the same character, which
+//! some chat platforms flag as spam. XORing every character's 11 bits with a fixed sequence breaks
+//! those runs up. This is not encryption: anyone can undo it.
+use crate::{
+    decode_chars_with, encode_with,
+    tables::{enc_table, table_index},
+};
+use alloc::{string::String, vec::Vec};
+
+/// A fixed xorshift32 sequence, 11 bits at a time.
+struct Whitener(u32);
+
+impl Whitener {
+    fn new() -> Self {
+        Whitener(0x9E37_79B9)
+    }
+
+    /// XORs `c` with the next 11 bits of the sequence. Tail characters are left alone and don't
+    /// advance the sequence.
+    fn apply(&mut self, c: char) -> char {
+        match table_index(c as u32) {
+            Some(index) => {
+                self.0 ^= self.0 << 13;
+                self.0 ^= self.0 >> 17;
+                self.0 ^= self.0 << 5;
+                enc_table()[(index ^ (self.0 >> 21) as u16) as usize]
+            }
+            None => c,
+        }
+    }
+}
+
+/// Encode some bytes using base2048 encoding, whitened so repetitive input doesn't produce
+/// repetitive output
+///
+/// The result has the same length as [`encode`](crate::encode)'s but can only be decoded with
+/// [`decode_whitened`].
+///
+/// # Example
+/// ```
+/// assert_eq!(base2048::encode(&[0; 11]), "88888888");
+/// let whitened = base2048::encode_whitened(&[0; 11]);
+/// assert_eq!(whitened, "ՑරޛKพဇறဢ");
+/// assert_eq!(base2048::decode_whitened(&whitened), Some(vec![0; 11]));
+/// ```
+pub fn encode_whitened(bytes: &[u8]) -> String {
+    let mut ret = String::new();
+    let mut whitener = Whitener::new();
+    encode_with(bytes, |c| ret.push(whitener.apply(c)));
+    ret
+}
+
+/// Decode a string produced by [`encode_whitened`]
+pub fn decode_whitened(string: &str) -> Option<Vec<u8>> {
+    let mut ret = vec![];
+    let mut whitener = Whitener::new();
+    decode_chars_with(string.chars().map(|c| whitener.apply(c)), |byte| {
+        ret.push(byte)
+    })
+    .ok()?;
+    Some(ret)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        for len in 0..40 {
+            for fill in &[0x00, 0xFF, 0x5A] {
+                let bytes = vec![*fill; len];
+                let whitened = encode_whitened(&bytes);
+                assert_eq!(
+                    whitened.chars().count(),
+                    crate::encode(&bytes).chars().count()
+                );
+                assert_eq!(decode_whitened(&whitened), Some(bytes));
+            }
+        }
+    }
+
+    #[test]
+    fn breaks_up_runs() {
+        let mut whitened = encode_whitened(&[0; 110]).chars().collect::<Vec<_>>();
+        whitened.sort_unstable();
+        whitened.dedup();
+        assert!(whitened.len() > 70);
+    }
+}
@@ -0,0 +1,69 @@
+//! The records the `log` feature emits.
+#![cfg(feature = "log")]
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::sync::Mutex;
+
+struct Capture(Mutex<Vec<(Level, String)>>);
+
+impl Log for Capture {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.target() == "base2048"
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            let message = (record.level(), record.args().to_string());
+            self.0.lock().unwrap().push(message);
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: Capture = Capture(Mutex::new(Vec::new()));
+
+/// Runs `f` and returns what it logged. There is only one test so nothing else logs meanwhile.
+fn captured(f: impl FnOnce()) -> Vec<(Level, String)> {
+    LOGGER.0.lock().unwrap().clear();
+    f();
+    LOGGER.0.lock().unwrap().drain(..).collect()
+}
+
+#[test]
+fn errors_and_fixups() {
+    log::set_logger(&LOGGER).unwrap();
+    log::set_max_level(LevelFilter::Trace);
+
+    assert!(captured(|| assert!(base2048::decode(base2048::encode(b"hi")).is_some())).is_empty());
+
+    let records = captured(|| assert!(base2048::decode("ետћζы༎X").is_none()));
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].0, Level::Debug);
+    assert!(records[0].1.starts_with("decode failed"), "{:?}", records);
+
+    // the first table character leaves the padding bits of a final character unset
+    let non_canonical = base2048::ENC_TABLE[0].to_string();
+    for decode in [
+        |s: &str| base2048::decode(s).is_some(),
+        |s: &str| base2048::Codec::new().decode(s).is_ok(),
+        |s: &str| base2048::Alphabet::standard().decode(s).is_ok(),
+        |s: &str| base2048::decode_owned(s.to_owned()).is_ok(),
+        |s: &str| {
+            let mut decoder = base2048::Decoder::new();
+            let mut bytes = vec![];
+            decoder.push(s, &mut bytes).is_ok() && decoder.finish(&mut bytes).is_ok()
+        },
+    ] {
+        let records = captured(|| assert!(decode(&non_canonical)));
+        assert_eq!(records.len(), 1, "{:?}", records);
+        assert_eq!(records[0].0, Level::Warn);
+    }
+    #[cfg(feature = "std")]
+    {
+        let mut reader = base2048::DecodeReader::new(non_canonical.as_bytes());
+        let records =
+            captured(|| assert!(std::io::copy(&mut reader, &mut std::io::sink()).is_ok()));
+        assert_eq!(records.len(), 1, "{:?}", records);
+        assert_eq!(records[0].0, Level::Warn);
+    }
+}
@@ -0,0 +1,86 @@
+//! Golden-file tests for the encoding itself.
+//!
+//! Each payload is encoded and the result is compared against a snapshot in `tests/snapshots/`.
+//! Anything that changes the output (the table, the tail characters, padding) shows up as a diff
+//! there. After an intentional change, review and accept the new snapshots with
+//! `cargo insta review` (or re-run with `INSTA_UPDATE=always`).
+use std::fmt::Write;
+
+/// A deterministic pseudorandom payload so the snapshots don't depend on a `rand` version.
+fn pseudorandom(len: usize) -> Vec<u8> {
+    let mut x = 0x2545_f491_u32;
+    (0..len)
+        .map(|_| {
+            x ^= x << 13;
+            x ^= x >> 17;
+            x ^= x << 5;
+            x as u8
+        })
+        .collect()
+}
+
+fn render(payloads: &[Vec<u8>]) -> String {
+    let mut out = String::new();
+    for bytes in payloads {
+        let encoded = base2048::encode(bytes);
+        assert_eq!(
+            base2048::decode(&encoded).as_deref(),
+            Some(&bytes[..]),
+            "{:02x?} did not survive a round trip",
+            bytes
+        );
+        writeln!(out, "{:02x?}", bytes).unwrap();
+        writeln!(out, "  {}", encoded).unwrap();
+    }
+    out
+}
+
+#[test]
+fn tail_residues() {
+    // 8 * n mod 11 takes every value as n goes through 0..11
+    let payloads = (0..=11)
+        .flat_map(|n| vec![vec![0x00; n], vec![0xFF; n], vec![0xA5; n]])
+        .collect::<Vec<_>>();
+    insta::assert_snapshot!(render(&payloads));
+}
+
+#[test]
+fn table_boundaries() {
+    // 11 bytes are exactly 8 characters, so these hit the first and last table entries
+    let payloads = vec![
+        vec![0x00; 11],
+        vec![0xFF; 11],
+        vec![
+            0x00, 0x1F, 0xFC, 0x00, 0x7F, 0xF0, 0x01, 0xFF, 0xC0, 0x07, 0xFF,
+        ],
+        vec![
+            0xFF, 0xE0, 0x03, 0xFF, 0x80, 0x0F, 0xFE, 0x00, 0x3F, 0xF8, 0x00,
+        ],
+    ];
+    insta::assert_snapshot!(render(&payloads));
+}
+
+#[test]
+fn every_byte() {
+    let payloads = (0..=255u8).map(|byte| vec![byte]).collect::<Vec<_>>();
+    insta::assert_snapshot!(render(&payloads));
+}
+
+#[test]
+fn text() {
+    let payloads = vec![
+        b"hello, world".to_vec(),
+        b"some utf8 bytes to encode more compactly".to_vec(),
+        "ünïcödé ✓".as_bytes().to_vec(),
+    ];
+    insta::assert_snapshot!(render(&payloads));
+}
+
+#[test]
+fn pseudorandom_lengths() {
+    let payloads = [1, 2, 3, 10, 11, 12, 21, 22, 23, 64, 100, 385]
+        .iter()
+        .map(|len| pseudorandom(*len))
+        .collect::<Vec<_>>();
+    insta::assert_snapshot!(render(&payloads));
+}
@@ -0,0 +1,37 @@
+// Compares the bindings' zero-copy entry points with the naive way of calling them.
+//
+// Build the bindings for node first, from the repository root:
+//
+//     wasm-pack build wasm --target nodejs
+//     node wasm/bench.mjs
+import { createRequire } from "node:module";
+
+const { encode, decode, decodeInto, maxDecodedLength } = createRequire(import.meta.url)(
+  "./pkg/base2048_wasm.js",
+);
+
+const SIZE = 1 << 20;
+const ROUNDS = 50;
+
+function time(name, f) {
+  f(); // warm up
+  const start = performance.now();
+  for (let i = 0; i < ROUNDS; i++) f();
+  const ms = (performance.now() - start) / ROUNDS;
+  console.log(`${name.padEnd(40)} ${ms.toFixed(2).padStart(8)} ms ${(SIZE / 1048576 / (ms / 1000)).toFixed(1).padStart(8)} MiB/s`);
+}
+
+// a payload in the middle of a bigger buffer, as it would be when read from a socket
+const buffer = new Uint8Array(SIZE + 1024);
+for (let i = 0; i < buffer.length; i++) buffer[i] = (i * 37) & 0xff;
+const payload = buffer.subarray(512, 512 + SIZE);
+const encoded = encode(payload);
+
+time("encode(Array.from(payload)) [naive]", () => encode(Uint8Array.from(Array.from(payload))));
+time("encode(payload.slice()) [copy]", () => encode(payload.slice()));
+time("encode(payload)", () => encode(payload));
+
+const out = new Uint8Array(maxDecodedLength(encoded));
+time("Array.from(decode(encoded)) [naive]", () => Array.from(decode(encoded)));
+time("decode(encoded)", () => decode(encoded));
+time("decodeInto(encoded, out)", () => decodeInto(encoded, out));
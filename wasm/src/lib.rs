@@ -0,0 +1,2 @@
+//! Links the bindings from `base2048::wasm` into a module wasm-pack can package.
+pub use base2048::wasm::*;
@@ -0,0 +1,116 @@
+//! Round trips inputs through every encoder and decoder in the crate and the reference.
+use crate::reference;
+use base2048::{Alphabet, Base2048String, IterEncodeExt, TAIL};
+
+/// The number of inputs checked and the failures found.
+#[derive(Default)]
+pub struct Report {
+    pub checked: u64,
+    pub failures: Vec<String>,
+}
+
+impl Report {
+    fn fail(&mut self, what: &str, input: &[u8], detail: impl core::fmt::Debug) {
+        if self.failures.len() < 20 {
+            self.failures.push(format!(
+                "{} disagrees on {:02x?}: {:?}",
+                what, input, detail
+            ));
+        }
+    }
+}
+
+pub struct Runner {
+    alphabet: Alphabet,
+    pub report: Report,
+}
+
+impl Runner {
+    pub fn new() -> Self {
+        Runner {
+            alphabet: Alphabet::standard(),
+            report: Report::default(),
+        }
+    }
+
+    /// Checks that every encoder agrees with the reference on `bytes` and every decoder brings it
+    /// back.
+    pub fn check(&mut self, bytes: &[u8]) {
+        self.report.checked += 1;
+        let expected = reference::encode(bytes);
+        let encoders: [(&str, String); 5] = [
+            ("encode", base2048::encode(bytes)),
+            (
+                "encode_to_vec",
+                String::from_utf8(base2048::encode_to_vec(bytes)).unwrap(),
+            ),
+            ("Alphabet::encode", self.alphabet.encode(bytes)),
+            (
+                "base2048_chars",
+                bytes.iter().copied().base2048_chars().collect(),
+            ),
+            (
+                "Base2048String",
+                bytes.iter().collect::<Base2048String>().into_string(),
+            ),
+        ];
+        for (name, encoded) in &encoders {
+            if *encoded != expected {
+                self.report.fail(name, bytes, (encoded, &expected));
+            }
+        }
+
+        self.check_decode(bytes, &expected, Some(bytes));
+    }
+
+    /// Checks that every decoder agrees with the reference on `string`, which should decode to
+    /// `expected`.
+    fn check_decode(&mut self, input: &[u8], string: &str, expected: Option<&[u8]>) {
+        let reference = reference::decode(string);
+        if reference.as_deref() != expected {
+            self.report
+                .fail("reference::decode", input, (string, &reference));
+        }
+        let decoders: [(&str, Option<Vec<u8>>); 5] = [
+            ("decode", base2048::decode(string)),
+            (
+                "decode_owned",
+                base2048::decode_owned(string.to_owned()).ok(),
+            ),
+            ("Alphabet::decode", self.alphabet.decode(string).ok()),
+            (
+                "base2048_bytes",
+                string
+                    .chars()
+                    .base2048_bytes()
+                    .collect::<Result<_, _>>()
+                    .ok(),
+            ),
+            (
+                "decode_aligned",
+                base2048::decode_aligned::<base2048::Align8>(string).map(|b| b.to_vec()),
+            ),
+        ];
+        for (name, decoded) in &decoders {
+            if decoded.as_deref() != expected {
+                self.report.fail(name, input, (string, decoded, expected));
+            }
+        }
+    }
+
+    /// Replaces the last character of the encoding of `bytes` with each tail character and checks
+    /// the decoders agree with the reference on whether (and to what) that decodes.
+    pub fn check_tails(&mut self, bytes: &[u8]) {
+        let encoded = base2048::encode(bytes);
+        let mut prefix = encoded.clone();
+        if prefix.pop().is_none() {
+            return;
+        }
+        for tail in TAIL {
+            let mut string = prefix.clone();
+            string.push(*tail);
+            let expected = reference::decode(&string);
+            self.check_decode(bytes, &string, expected.as_deref());
+        }
+    }
+}
@@ -0,0 +1,145 @@
+//! Measures how often corrupted text is caught when decoding.
+use crate::rng::Rng;
+use base2048::{ENC_TABLE, TAIL};
+
+/// A way of encoding and decoding whose error detection is measured.
+pub struct Mode {
+    pub name: &'static str,
+    pub encode: fn(&[u8]) -> String,
+    /// Returns `None` when the text is found to be corrupted.
+    pub decode: fn(&str) -> Option<Decoded>,
+}
+
+/// What a [`Mode`] decoded a text to.
+pub struct Decoded {
+    pub bytes: Vec<u8>,
+    /// How many characters the decoder said it corrected to find the bytes.
+    pub corrected: usize,
+}
+
+impl Decoded {
+    fn uncorrected(bytes: Vec<u8>) -> Self {
+        Decoded {
+            bytes,
+            corrected: 0,
+        }
+    }
+}
+
+/// How many wrong characters a block can correct in the "ecc" mode.
+#[cfg(feature = "ecc")]
+const ECC_CORRECTIONS: usize = 2;
+
+pub const MODES: &[Mode] = &[
+    Mode {
+        name: "plain",
+        encode: base2048::encode_bytes,
+        decode: |text| base2048::decode_str(text).map(Decoded::uncorrected),
+    },
+    Mode {
+        name: "checked",
+        encode: |bytes| base2048::encode_checked(bytes),
+        decode: |text| {
+            base2048::decode_checked(text)
+                .ok()
+                .map(Decoded::uncorrected)
+        },
+    },
+    Mode {
+        name: "crc",
+        encode: |bytes| base2048::encode_with_crc(bytes),
+        decode: |text| {
+            base2048::decode_with_crc(text)
+                .ok()
+                .map(Decoded::uncorrected)
+        },
+    },
+    #[cfg(feature = "ecc")]
+    Mode {
+        name: "ecc",
+        encode: |bytes| base2048::ecc::encode(bytes, ECC_CORRECTIONS),
+        decode: |text| {
+            let decoded = base2048::ecc::decode(text, ECC_CORRECTIONS).ok()?;
+            Some(Decoded {
+                bytes: decoded.bytes,
+                corrected: decoded.corrected,
+            })
+        },
+    },
+];
+
+/// What happens to the encoded text.
+#[derive(Clone, Copy)]
+pub enum Corruption {
+    /// One character is replaced by a different character of the alphabet.
+    Substitution,
+    /// One character is removed.
+    Deletion,
+}
+
+impl Corruption {
+    pub fn name(self) -> &'static str {
+        match self {
+            Corruption::Substitution => "substitution",
+            Corruption::Deletion => "deletion",
+        }
+    }
+
+    fn apply(self, rng: &mut Rng, chars: &mut Vec<char>) {
+        let at = rng.next() as usize % chars.len();
+        match self {
+            Corruption::Substitution => loop {
+                let n = rng.next() as usize % (ENC_TABLE.len() + TAIL.len());
+                let c = ENC_TABLE
+                    .get(n)
+                    .unwrap_or_else(|| &TAIL[n - ENC_TABLE.len()]);
+                if *c != chars[at] {
+                    chars[at] = *c;
+                    break;
+                }
+            },
+            Corruption::Deletion => {
+                chars.remove(at);
+            }
+        }
+    }
+}
+
+/// How the decoder reacted to the corrupted texts.
+#[derive(Default)]
+pub struct Tally {
+    /// Decoding failed.
+    pub detected: u64,
+    /// Decoding corrected characters and gave back the original bytes.
+    pub corrected: u64,
+    /// Decoding gave back the original bytes anyway.
+    pub harmless: u64,
+    /// Decoding gave different bytes without complaint.
+    pub undetected: u64,
+}
+
+impl Tally {
+    pub fn percent(&self, n: u64) -> f64 {
+        let total = self.detected + self.corrected + self.harmless + self.undetected;
+        100.0 * n as f64 / total.max(1) as f64
+    }
+}
+
+/// Corrupts the encodings of `trials` random `size` byte payloads and tallies the outcomes.
+pub fn measure(mode: &Mode, size: usize, corruption: Corruption, trials: u64) -> Tally {
+    let mut rng = Rng::new(size as u64 ^ 0xD1B5_4A32_D192_ED03);
+    let mut bytes = vec![0; size];
+    let mut tally = Tally::default();
+    for _ in 0..trials {
+        rng.fill(&mut bytes);
+        let mut chars = (mode.encode)(&bytes).chars().collect::<Vec<_>>();
+        corruption.apply(&mut rng, &mut chars);
+        match (mode.decode)(&chars.into_iter().collect::<String>()) {
+            None => tally.detected += 1,
+            Some(decoded) if decoded.bytes != bytes => tally.undetected += 1,
+            Some(decoded) if decoded.corrected > 0 => tally.corrected += 1,
+            Some(_) => tally.harmless += 1,
+        }
+    }
+    tally
+}
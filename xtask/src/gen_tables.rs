@@ -0,0 +1,125 @@
+//! Generating the character tables from a list of code point ranges and checking them.
+//!
+//! The build script makes the crate's own tables the same way; this is for trying out other
+//! alphabets, such as a restricted one, and being sure the decoder agrees with them.
+#[path = "../../build/ranges.rs"]
+mod ranges;
+
+use base2048::Alphabet;
+use std::{fs, path::Path};
+
+/// What the tables for `spec` came to.
+pub struct Summary {
+    pub ranges: usize,
+    pub first: char,
+    pub last: char,
+    pub dec_len: usize,
+    /// Whether they are the crate's own tables.
+    pub standard: bool,
+}
+
+/// Reads the ranges in `spec`, checks the tables they make with `tail` and writes them to `out`
+/// as `enc_table.src` and `dec_table.src`, if given.
+pub fn run(spec: &Path, tail: &[char], out: Option<&Path>) -> Result<Summary, String> {
+    let text =
+        fs::read_to_string(spec).map_err(|e| format!("reading {}: {}", spec.display(), e))?;
+    let table = ranges::parse(&text, tail).map_err(|e| format!("{}: {}", spec.display(), e))?;
+    let dec = ranges::dec_table(&table);
+    check(&table, &dec, tail)?;
+
+    if let Some(out) = out {
+        let write = |name: &str, src: String| {
+            let path = out.join(name);
+            fs::write(&path, src).map_err(|e| format!("writing {}: {}", path.display(), e))
+        };
+        write("enc_table.src", ranges::enc_src(&table))?;
+        write("dec_table.src", ranges::dec_src(&table))?;
+    }
+
+    Ok(Summary {
+        ranges: text
+            .lines()
+            .filter(|line| !line.split('#').next().unwrap_or("").trim().is_empty())
+            .count(),
+        first: table[0],
+        last: table[2047],
+        dec_len: dec.len(),
+        standard: table == base2048::ENC_TABLE[..]
+            && dec == base2048::DEC_TABLE[..]
+            && tail == base2048::TAIL,
+    })
+}
+
+/// Checks that `dec` undoes `table` exactly and that the crate's decoder, given the same
+/// characters, agrees.
+fn check(table: &[char], dec: &[u16], tail: &[char]) -> Result<(), String> {
+    for (index, c) in table.iter().enumerate() {
+        if dec[*c as usize] as usize != index {
+            return Err(format!(
+                "{:?} decodes to {} instead of {}",
+                c, dec[*c as usize], index
+            ));
+        }
+    }
+    for (code_point, index) in dec.iter().enumerate() {
+        if *index != 0xFFFF && table[*index as usize] as usize != code_point {
+            return Err(format!(
+                "U+{:04X} decodes to {} but isn't there",
+                code_point, index
+            ));
+        }
+    }
+
+    let alphabet = Alphabet::from_chars(table, tail).map_err(|e| e.to_string())?;
+    // every position in turn, 11 bits each, comes out as the table in order
+    let mut bytes = vec![0; 2048 * 11 / 8];
+    for index in 0..2048 {
+        for bit in 0..11 {
+            if index >> (10 - bit) & 1 == 1 {
+                let at = index * 11 + bit;
+                bytes[at / 8] |= 0x80 >> (at % 8);
+            }
+        }
+    }
+    let text = alphabet.encode(&bytes);
+    if !text.chars().eq(table.iter().copied()) {
+        return Err("encoding every position doesn't give the table in order".into());
+    }
+    if alphabet.decode(&text).as_deref() != Ok(&bytes[..]) {
+        return Err("the table in order doesn't decode to every position".into());
+    }
+    // and every length, so each tail character and final table character is used
+    for len in 0..=2 * 11 {
+        let bytes = &bytes[bytes.len() - len..];
+        if alphabet.decode(&alphabet.encode(bytes)).as_deref() != Ok(bytes) {
+            return Err(format!("{} bytes don't survive encoding and decoding", len));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn standard() {
+        let spec = Path::new(env!("CARGO_MANIFEST_DIR")).join("../base2048.ranges");
+        let summary = run(&spec, base2048::TAIL, None).unwrap();
+        assert!(summary.standard);
+        assert_eq!((summary.first, summary.dec_len), ('8', 4182));
+    }
+
+    #[test]
+    fn restricted() {
+        // the 2048 code points after the tail, but nothing under U+0100 but letters and digits
+        let spec = "0038..0039\n0041..005A\n0061..007A\n0100..08C9\n";
+        let table = ranges::parse(spec, base2048::TAIL).unwrap();
+        check(&table, &ranges::dec_table(&table), base2048::TAIL).unwrap();
+
+        let missing = "0038..0039\n0041..005A\n0061..007A\n0100..08C8\n";
+        assert!(ranges::parse(missing, base2048::TAIL).is_err());
+        let tail = "0030..0039\n0041..005A\n0061..007A\n0100..08C1\n";
+        assert!(ranges::parse(tail, base2048::TAIL).is_err());
+    }
+}
@@ -0,0 +1,191 @@
+//! Tasks too slow for `cargo test`.
+//!
+//! ```text
+//! cargo xtask conformance [--samples N] [--max-len N]
+//! cargo xtask detection [--trials N] [--sizes N,N,...]
+//! cargo xtask gen-tables [--spec FILE] [--tail CHARS] [--out DIR]
+//! ```
+mod conformance;
+mod detection;
+mod gen_tables;
+mod reference;
+mod rng;
+
+use conformance::Runner;
+use rng::Rng;
+use std::{
+    env,
+    path::{Path, PathBuf},
+    process,
+    time::Instant,
+};
+
+const USAGE: &str = "usage: cargo xtask conformance [--samples N] [--max-len N]
+       cargo xtask detection [--trials N] [--sizes N,N,...]
+       cargo xtask gen-tables [--spec FILE] [--tail CHARS] [--out DIR]";
+
+fn main() {
+    let mut args = env::args().skip(1);
+    match args.next().as_deref() {
+        Some("conformance") => conformance(args),
+        Some("detection") => detection(args),
+        Some("gen-tables") => gen_tables(args),
+        _ => usage(),
+    }
+}
+
+fn usage() -> ! {
+    eprintln!("{}", USAGE);
+    process::exit(2);
+}
+
+/// Hands each `--name value` pair to `set`, which returns `None` for names or values it doesn't
+/// accept.
+fn options(mut args: impl Iterator<Item = String>, mut set: impl FnMut(&str, &str) -> Option<()>) {
+    while let Some(name) = args.next() {
+        if args.next().and_then(|value| set(&name, &value)).is_none() {
+            usage();
+        }
+    }
+}
+
+fn conformance(args: impl Iterator<Item = String>) {
+    let mut samples = 10_000;
+    let mut max_len = 256;
+    options(args, |name, value| {
+        match name {
+            "--samples" => samples = value.parse().ok()?,
+            "--max-len" => max_len = value.parse::<usize>().ok()?.max(4),
+            _ => return None,
+        }
+        Some(())
+    });
+
+    let start = Instant::now();
+    let mut runner = Runner::new();
+
+    runner.check(&[]);
+    for len in 1..=3u32 {
+        eprintln!("every {}-byte input", len);
+        for n in 0..1u32 << (8 * len) {
+            let bytes = &n.to_be_bytes()[4 - len as usize..];
+            runner.check(bytes);
+            if len < 3 {
+                runner.check_tails(bytes);
+            }
+        }
+    }
+
+    // 8 * len bits leave len % 11 different remainders after the full characters, so sample every
+    // remainder evenly.
+    eprintln!("{} random inputs of 4 to {} bytes", samples, max_len);
+    let mut rng = Rng::new(0x9E37_79B9_7F4A_7C15);
+    let mut bytes = vec![0; max_len];
+    for i in 0..samples {
+        let residue = i % 11;
+        let len = 4 + (rng.next() as usize % (max_len - 3));
+        let len = (len - len % 11 + residue).clamp(4, max_len);
+        rng.fill(&mut bytes[..len]);
+        runner.check(&bytes[..len]);
+        runner.check_tails(&bytes[..len]);
+    }
+
+    let report = runner.report;
+    eprintln!(
+        "checked {} inputs in {:.1?}",
+        report.checked,
+        start.elapsed()
+    );
+    if !report.failures.is_empty() {
+        for failure in &report.failures {
+            eprintln!("{}", failure);
+        }
+        eprintln!("conformance failed");
+        process::exit(1);
+    }
+}
+
+fn detection(args: impl Iterator<Item = String>) {
+    let mut trials = 10_000;
+    let mut sizes = vec![16, 64, 256, 1024];
+    options(args, |name, value| {
+        match name {
+            "--trials" => trials = value.parse().ok()?,
+            "--sizes" => {
+                sizes = value
+                    .split(',')
+                    .map(|size| size.parse().ok().filter(|size| *size > 0))
+                    .collect::<Option<_>>()?
+            }
+            _ => return None,
+        }
+        Some(())
+    });
+
+    println!(
+        "{:<8} {:>6}  {:<12} {:>9} {:>10} {:>9} {:>11}",
+        "mode", "bytes", "corruption", "detected", "corrected", "harmless", "undetected"
+    );
+    for mode in detection::MODES {
+        for size in &sizes {
+            for corruption in &[
+                detection::Corruption::Substitution,
+                detection::Corruption::Deletion,
+            ] {
+                let tally = detection::measure(mode, *size, *corruption, trials);
+                println!(
+                    "{:<8} {:>6}  {:<12} {:>8.2}% {:>9.2}% {:>8.2}% {:>10.2}%",
+                    mode.name,
+                    size,
+                    corruption.name(),
+                    tally.percent(tally.detected),
+                    tally.percent(tally.corrected),
+                    tally.percent(tally.harmless),
+                    tally.percent(tally.undetected),
+                );
+            }
+        }
+    }
+}
+
+fn gen_tables(args: impl Iterator<Item = String>) {
+    let mut spec = Path::new(env!("CARGO_MANIFEST_DIR")).join("../base2048.ranges");
+    let mut tail = base2048::TAIL.to_vec();
+    let mut out = None;
+    options(args, |name, value| {
+        match name {
+            "--spec" => spec = PathBuf::from(value),
+            "--tail" => tail = value.chars().collect(),
+            "--out" => out = Some(PathBuf::from(value)),
+            _ => return None,
+        }
+        Some(())
+    });
+
+    match gen_tables::run(&spec, &tail, out.as_deref()) {
+        Ok(summary) => {
+            println!(
+                "2048 characters in {} ranges from {:?} (U+{:04X}) to {:?} (U+{:04X})",
+                summary.ranges,
+                summary.first,
+                summary.first as u32,
+                summary.last,
+                summary.last as u32
+            );
+            println!(
+                "decoding table of {} entries undoes the encoding table, and the decoder agrees",
+                summary.dec_len
+            );
+            if summary.standard {
+                println!("these are the crate's own tables");
+            }
+            if let Some(out) = out {
+                println!("wrote enc_table.src and dec_table.src to {}", out.display());
+            }
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,72 @@
+//! A deliberately simple base2048 implementation to check the real one against.
+//!
+//! It works on one bit at a time and shares nothing with the crate but the character tables.
+use base2048::{BITS_PER_CHAR, DEC_TABLE, ENC_TABLE, TAIL, TAIL_BITS};
+
+fn bits_of(bytes: &[u8]) -> Vec<bool> {
+    bytes
+        .iter()
+        .flat_map(|byte| (0..8).rev().map(move |i| byte >> i & 1 == 1))
+        .collect()
+}
+
+fn number(bits: &[bool]) -> usize {
+    bits.iter().fold(0, |n, bit| n << 1 | *bit as usize)
+}
+
+pub fn encode(bytes: &[u8]) -> String {
+    let mut bits = bits_of(bytes);
+    let full = bits.len() / BITS_PER_CHAR as usize * BITS_PER_CHAR as usize;
+    let rest = bits.len() - full;
+    let tail = rest > 0 && rest <= TAIL_BITS as usize;
+    // the last character is padded with ones
+    if rest > 0 {
+        let width = if tail { TAIL_BITS } else { BITS_PER_CHAR } as usize;
+        bits.resize(full + width, true);
+    }
+
+    let mut ret = String::new();
+    for chunk in bits[..full].chunks(BITS_PER_CHAR as usize) {
+        ret.push(ENC_TABLE[number(chunk)]);
+    }
+    if rest > 0 {
+        let last = number(&bits[full..]);
+        ret.push(if tail { TAIL[last] } else { ENC_TABLE[last] });
+    }
+    ret
+}
+
+pub fn decode(string: &str) -> Option<Vec<u8>> {
+    let chars = string.chars().collect::<Vec<_>>();
+    let mut bits = vec![];
+    for (i, c) in chars.iter().enumerate() {
+        let last = i + 1 == chars.len();
+        match DEC_TABLE.get(*c as usize) {
+            Some(&index) if index != 0xFFFF => {
+                bits.extend((0..BITS_PER_CHAR).rev().map(|i| index >> i & 1 == 1));
+                if last {
+                    // whatever doesn't make up a whole byte is padding
+                    bits.truncate(bits.len() / 8 * 8);
+                }
+            }
+            _ => {
+                let index = TAIL.iter().position(|t| t == c)?;
+                if !last {
+                    return None;
+                }
+                let need = (8 - bits.len() % 8) as u32;
+                if need > TAIL_BITS {
+                    return None;
+                }
+                let tail_bits = (0..TAIL_BITS).rev().map(|i| index >> i & 1 == 1);
+                let (data, padding): (Vec<_>, Vec<_>) =
+                    tail_bits.enumerate().partition(|(i, _)| (*i as u32) < need);
+                if !padding.iter().all(|(_, bit)| *bit) {
+                    return None;
+                }
+                bits.extend(data.into_iter().map(|(_, bit)| bit));
+            }
+        }
+    }
+    Some(bits.chunks(8).map(|byte| number(byte) as u8).collect())
+}